@@ -1,6 +1,6 @@
 use std::io::{IsTerminal, Write};
 
-use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Shell};
 use clap_complete_nushell::Nushell;
 
@@ -14,17 +14,25 @@ pub struct Cli {
     pub verbose: bool,
 
     /// Show only packages with updates
-    #[arg(short, long)]
+    #[arg(short, long, overrides_with = "no_updates_only")]
     pub updates_only: bool,
 
+    /// Show every package, overriding `updates-only` in the config file
+    #[arg(long, overrides_with = "updates_only")]
+    pub no_updates_only: bool,
+
     /// Non-interactive mode (default is interactive mode)
     #[arg(long)]
     pub no_interactive: bool,
 
     /// Include prerelease versions (alpha, beta, rc, etc.)
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_include_prerelease")]
     pub include_prerelease: bool,
 
+    /// Skip prerelease versions, overriding `include-prerelease` in the config file
+    #[arg(long, overrides_with = "include_prerelease")]
+    pub no_include_prerelease: bool,
+
     /// Batch mode - automatically update all packages without confirmation
     #[arg(long)]
     pub batch: bool,
@@ -33,7 +41,8 @@ pub struct Cli {
     #[arg(long)]
     pub filter: Option<String>,
 
-    /// Exclude packages by glob pattern (repeatable)
+    /// Exclude packages by glob pattern (repeatable). Replaces `exclude`
+    /// from `[defaults]` in the config file
     #[arg(long, action = clap::ArgAction::Append)]
     pub exclude: Vec<String>,
 
@@ -60,18 +69,42 @@ pub struct Cli {
     /// Scope: this only disables the version-check fallback (sparse index →
     /// `cargo search`). It does NOT touch the install-path fallback
     /// (downloader → `cargo install`), which is separate and always active.
-    #[arg(long)]
+    #[arg(long, overrides_with = "cargo_search_fallback")]
     pub no_cargo_search_fallback: bool,
 
+    /// Keep the `cargo search` fallback, overriding `no-cargo-search-fallback`
+    /// in the config file
+    #[arg(long, overrides_with = "no_cargo_search_fallback")]
+    pub cargo_search_fallback: bool,
+
     /// Probe each update candidate with cargo-fresh's own downloader to mark
     /// whether prebuilt binaries are available (fast) or it'd fall back to
     /// compiling from source (slow). Replaces the older `--check-binstall`
     /// flag — same intent, but uses the same HEAD-probe logic as the actual
     /// update path so the verdict matches what update would do. Off by
     /// default — each candidate does a few HEAD requests.
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_check_prebuilt")]
     pub check_prebuilt: bool,
 
+    /// Don't probe for prebuilt binaries, overriding `check-prebuilt` in the
+    /// config file
+    #[arg(long, overrides_with = "check_prebuilt")]
+    pub no_check_prebuilt: bool,
+
+    /// When the newest version has no prebuilt binary (still uploading, or
+    /// shipped without assets), offer the newest version that does and is
+    /// still newer than the installed one, shown as
+    /// "15.1.0 (15.2.0 source-only)". Probes the newest version like
+    /// `--check-prebuilt`, then up to 4 older ones. Per package:
+    /// `prefer-prebuilt = true` under `[packages."<glob>"]` in the config file.
+    #[arg(long, overrides_with = "no_prefer_prebuilt")]
+    pub prefer_prebuilt: bool,
+
+    /// Always offer the newest version, overriding `prefer-prebuilt` under
+    /// `[defaults]` in the config file
+    #[arg(long, overrides_with = "prefer_prebuilt")]
+    pub no_prefer_prebuilt: bool,
+
     /// Emit internal trace lines (downloader path decisions, GitHub Releases
     /// API tag attempts, token discovery source, candidate counts) to stderr.
     /// Off by default. Intended for filing actionable issues — paste the
@@ -92,6 +125,33 @@ pub struct Cli {
     #[arg(short = 'j', long, default_value_t = 4, value_name = "N")]
     pub jobs: u32,
 
+    /// Cap the combined download bandwidth of all concurrent downloads, in
+    /// bytes per second (`500K`, `2M`, `1G`; 1024-based). Overrides
+    /// `[network] limit-rate` in the config file. Default: unlimited.
+    #[arg(long, value_name = "RATE", value_parser = crate::downloader::cache::parse_size)]
+    pub limit_rate: Option<u64>,
+
+    /// Install updates from an offline bundle written by `cargo fresh bundle`
+    /// (directory or `.tar.gz`) instead of the network. Every archive is
+    /// checked against the bundle manifest's SHA-256 before install; only
+    /// packages that are installed and older than the bundled version are
+    /// offered.
+    #[arg(long, value_name = "PATH")]
+    pub from_bundle: Option<std::path::PathBuf>,
+
+    /// Never compile: install only prebuilt binaries and skip any package
+    /// that has none (or needs custom features / a git or path source).
+    /// Skipped packages are reported with reason code `prebuilt_unavailable`.
+    /// Per package: `install = "prebuilt-only"` under `[packages."<glob>"]`.
+    #[arg(long, conflicts_with = "source_only")]
+    pub prebuilt_only: bool,
+
+    /// Never download third-party binaries: always build from source with
+    /// `cargo install`, even for default-feature crates.io packages.
+    /// Per package: `install = "source-only"` under `[packages."<glob>"]`.
+    #[arg(long, conflicts_with = "from_bundle")]
+    pub source_only: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -127,6 +187,251 @@ pub enum Commands {
     /// (`cargo fresh man > ~/.local/share/man/man1/cargo-fresh.1`) or pipe to
     /// `mandoc` / `groff -Tutf8 -man`.
     Man,
+    /// Inspect or prune the download cache of prebuilt archives
+    ///
+    /// Archives are cached by URL, version and SHA-256 under `[cache] dir`
+    /// (default `$XDG_CACHE_HOME/cargo-fresh/archives`). A hit skips the
+    /// archive's HEAD / GET requests; finding the candidate URLs (crate
+    /// metadata, release API) still goes to the network. The cache is capped
+    /// at `[cache] max-size` (default 1GiB).
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    /// Download prebuilt archives of the selected updates into an offline bundle
+    ///
+    /// Runs the normal update check, then fetches each selected package's
+    /// prebuilt archive through the downloader (cache, mirrors and signature
+    /// checks included) and writes them together with a `manifest.json` and
+    /// `SHA256SUMS`. OUTPUT ending in `.tar.gz`/`.tgz` produces a tarball,
+    /// anything else a directory. Install it on an offline machine with
+    /// `cargo fresh --from-bundle OUTPUT`. Global filters (`--filter`,
+    /// `--exclude`, `--batch`, `--include-prerelease`) apply.
+    ///
+    /// With `--target`, archives are resolved for another target triple
+    /// (e.g. provisioning ARM boards from an x86 laptop); nothing is installed
+    /// locally, and packages without a prebuilt for that target are reported.
+    Bundle {
+        /// Bundle directory or `.tar.gz` path to write
+        #[arg(value_name = "OUTPUT")]
+        output: std::path::PathBuf,
+        /// Fetch archives for this target triple instead of the host
+        /// (`aarch64-unknown-linux-gnu`, `x86_64-unknown-linux-musl`, ...)
+        #[arg(long, value_name = "TRIPLE")]
+        target: Option<String>,
+        /// Bundle every installed crates.io package at its latest version, not
+        /// only the ones with an update
+        #[arg(long)]
+        all: bool,
+    },
+    /// List installed packages without checking for updates
+    ///
+    /// Shows each package's version, source, binaries, saved features, install
+    /// method (`prebuilt` / `source`, known when cargo-fresh installed that
+    /// version), binary size on disk and last-modified date. `--filter` and
+    /// `--exclude` narrow the list; `--format json` prints it as JSON and
+    /// `--csv` as CSV.
+    List {
+        /// Sort by this column
+        #[arg(long, value_enum, default_value_t = crate::package::inventory::SortKey::Name)]
+        sort: crate::package::inventory::SortKey,
+        /// Reverse the sort order
+        #[arg(long)]
+        reverse: bool,
+        /// Print CSV instead of a table
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Install a crate that is not installed yet
+    ///
+    /// Resolves the newest version (or the newest matching `CRATE@VERSION`;
+    /// a bare version means exactly that version) from the sparse index, then
+    /// installs it the same way updates are applied: the prebuilt downloader
+    /// first, `cargo install` as the fallback. `--prebuilt-only`,
+    /// `--source-only`, `--dry-run` and `--registry-url` apply. Prebuilt
+    /// installs get their own `.crates.toml` / `.crates2.json` entries, so
+    /// `cargo install --list` and later updates see them.
+    Install {
+        /// Crate to install, optionally with a version or requirement
+        /// (`ripgrep`, `ripgrep@14.1.1`, `bevy_cli@0.21`)
+        #[arg(value_name = "CRATE[@VERSION]")]
+        crate_spec: String,
+        /// Reinstall even if the crate is already installed
+        #[arg(long)]
+        force: bool,
+        /// Comma-separated features to enable (builds from source)
+        #[arg(long, value_delimiter = ',', value_name = "FEATURES")]
+        features: Vec<String>,
+        /// Enable all features (builds from source)
+        #[arg(long)]
+        all_features: bool,
+        /// Disable the default features (builds from source)
+        #[arg(long)]
+        no_default_features: bool,
+    },
+    /// Switch an installed package to a specific version, older or newer
+    ///
+    /// Resolves `PACKAGE@VERSION` (a bare version means exactly that version,
+    /// a requirement picks the newest match) and installs it through the same
+    /// downloader / `cargo install` pipeline as updates, keeping the recorded
+    /// features. Afterwards the package is held at that version so the next
+    /// run does not offer to upgrade it back; `cargo fresh unhold PACKAGE`
+    /// releases it.
+    Use {
+        /// Installed package and the version to switch to
+        /// (`ripgrep@14.0.3`, `bevy_cli@0.20`)
+        #[arg(value_name = "PACKAGE@VERSION")]
+        spec: String,
+        /// Do not hold the package at the chosen version
+        #[arg(long)]
+        no_hold: bool,
+    },
+    /// Uninstall packages without going through `cargo uninstall`
+    ///
+    /// Deletes every binary the package recorded in `$CARGO_HOME/bin` and
+    /// removes its `.crates.toml` / `.crates2.json` entries, the same
+    /// bookkeeping `cargo uninstall` does. `--dry-run` lists the binaries
+    /// that would be removed.
+    Uninstall {
+        /// Installed packages to remove
+        #[arg(required = true, value_name = "PACKAGE")]
+        packages: Vec<String>,
+    },
+    /// Offer to uninstall packages that have not been used for a while
+    ///
+    /// "Used" is the newest access time of the package's binaries, so this is
+    /// only as accurate as the filesystem's atime (`relatime` updates it at
+    /// most once a day; on `noatime` mounts it is the install time). With
+    /// both `--unused-for` and `--match`, a package must match both. The
+    /// picker starts with nothing selected; `--batch` removes every candidate,
+    /// `--dry-run` and `--no-interactive` only list them.
+    ///
+    /// `--orphans` instead offers the files in `$CARGO_HOME/bin` that no
+    /// installed package owns (rustup's proxies excepted), plus
+    /// `.cargo-fresh-*.tmp` files left over by an interrupted install.
+    #[command(group(
+        clap::ArgGroup::new("criteria")
+            .required(true)
+            .multiple(true)
+            .args(["unused_for", "patterns", "orphans"])
+    ))]
+    Prune {
+        /// Packages whose binaries were not used for at least this long
+        /// (e.g. `90d`, `12w`)
+        #[arg(long, value_name = "AGE", value_parser = crate::downloader::cache::parse_duration)]
+        unused_for: Option<std::time::Duration>,
+        /// Packages whose name matches this glob, same rules as `--exclude`
+        /// (repeatable)
+        #[arg(long = "match", value_name = "GLOB")]
+        patterns: Vec<String>,
+        /// Remove orphaned files and stale temp files from `$CARGO_HOME/bin`
+        /// instead of packages
+        #[arg(long, conflicts_with_all = ["unused_for", "patterns"])]
+        orphans: bool,
+    },
+    /// Write a TOML manifest of every installed package
+    ///
+    /// Each package is listed with its version (or its hold requirement,
+    /// marked `pinned`), its source (crates.io, `git` + `rev`, or `path`) and
+    /// its features. Check the manifest into a repository and run
+    /// `cargo fresh sync` on other machines to reproduce the toolset.
+    Export {
+        /// File to write; prints to stdout when omitted
+        #[arg(value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Make the installed packages match a manifest written by `export`
+    ///
+    /// Installs missing packages, updates or downgrades packages whose version
+    /// does not satisfy the manifest, and reinstalls packages whose source or
+    /// features differ. A bare version means exactly that version; a
+    /// requirement like `0.21` keeps any matching installed version. Packages
+    /// marked `pinned` are held at their manifest version. Packages missing
+    /// from the manifest are reported and only removed with
+    /// `--uninstall-extras`. `--dry-run` prints the plan without changing
+    /// anything.
+    Sync {
+        /// Manifest to apply
+        #[arg(value_name = "MANIFEST")]
+        manifest: std::path::PathBuf,
+        /// Uninstall packages that are not listed in the manifest
+        #[arg(long)]
+        uninstall_extras: bool,
+    },
+    /// Inspect the layered configuration
+    ///
+    /// Settings are merged from `[fresh]` in `$CARGO_HOME/config.toml`, then
+    /// `$XDG_CONFIG_HOME/cargo-fresh/config.toml`, then `CARGO_FRESH_<KEY>`
+    /// environment variables (later wins). Flags given on the command line
+    /// override all of them.
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Hold a package at its installed version or a version requirement
+    ///
+    /// Held packages are still checked, but an update outside the hold is
+    /// shown as "held at 0.21, 0.22 available", never selected by `--batch`
+    /// and not preselected in the interactive picker. Holds are stored in
+    /// `$XDG_STATE_HOME/cargo-fresh/state.json`.
+    Hold {
+        /// Installed package to hold
+        #[arg(value_name = "PACKAGE")]
+        package: String,
+        /// SemVer requirement to allow, e.g. `0.21` (any 0.21.x) or `~1.4.2`.
+        /// Defaults to exactly the installed version
+        #[arg(long, value_name = "REQ")]
+        at: Option<String>,
+    },
+    /// Remove a hold set by `cargo fresh hold`
+    Unhold {
+        #[arg(value_name = "PACKAGE")]
+        package: String,
+    },
+    /// Diagnose the environment cargo-fresh runs in
+    ///
+    /// Checks the cargo / rustc toolchain, the `$CARGO_HOME` layout and PATH,
+    /// whether `.crates.toml` and `.crates2.json` agree, HTTP / TLS setup, the
+    /// sparse index (honouring `--registry-url`), the GitHub token and API rate
+    /// limit, and the prebuilt target aliases for this host. Each check is
+    /// reported as pass / warn / fail with a suggested fix; `--format json`
+    /// prints the same report as JSON. Exits with 2 when any check fails.
+    Doctor,
+    /// Update cargo-fresh itself to the latest release
+    ///
+    /// Uses the same pipeline as a regular update (prebuilt download first,
+    /// `cargo install` as fallback) and honours `--dry-run`,
+    /// `--prebuilt-only` / `--source-only` and `--include-prerelease`. Only
+    /// works for a cargo-fresh installed with `cargo install`. The running
+    /// process keeps the old binary; re-run to use the new one.
+    SelfUpdate,
+}
+
+/// `cargo fresh config <action>`
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Print the effective merged settings and where each value came from
+    Show,
+}
+
+/// `cargo fresh cache <action>`
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum CacheCommand {
+    /// List cached archives, most recently used first
+    List,
+    /// Remove cached archives
+    ///
+    /// Without flags, evicts least-recently-used archives down to the
+    /// configured `max-size` and deletes unreferenced files.
+    Prune {
+        /// Remove archives not used for this long (e.g. `30d`, `12h`, `2w`)
+        #[arg(long, value_name = "AGE", value_parser = crate::downloader::cache::parse_duration)]
+        older_than: Option<std::time::Duration>,
+        /// Evict least-recently-used archives until the cache fits (e.g. `500MiB`).
+        /// Defaults to the configured `[cache] max-size`
+        #[arg(long, value_name = "SIZE", value_parser = crate::downloader::cache::parse_size)]
+        max_size: Option<u64>,
+    },
 }
 
 /// 输出格式。Human 是 cargo 风格的状态行；Json 用于 CI / 脚本消费。
@@ -189,6 +494,79 @@ impl ShellType {
 }
 
 impl Cli {
+    /// 解析命令行，同时保留 `ArgMatches`——[`Cli::apply_defaults`] 要靠它
+    /// 分辨 `--jobs 4` 是用户传的还是 clap 的默认值。
+    pub fn parse_with_matches<I, T>(args: I) -> (Self, clap::ArgMatches)
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = Self::command().get_matches_from(args);
+        let cli = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        (cli, matches)
+    }
+
+    /// 用配置里的 `[defaults]` 补上命令行没给的标志。命令行给了的 (包括
+    /// `--no-…` 反向开关和 `--exclude`) 一律以命令行为准。
+    pub fn apply_defaults(&mut self, defaults: &crate::config::DefaultsConfig, matches: &clap::ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(clap::parser::ValueSource::CommandLine);
+        // 开关和它的 `--no-…` 都没出现在命令行上时才用配置值
+        let flag = |on: &str, off: &str, current: bool, default: Option<bool>| {
+            if from_cli(on) || from_cli(off) {
+                current
+            } else {
+                default.unwrap_or(current)
+            }
+        };
+        if !from_cli("exclude") {
+            self.exclude.extend(defaults.exclude.iter().cloned());
+        }
+        if !from_cli("jobs") {
+            if let Some(jobs) = defaults.jobs {
+                self.jobs = jobs;
+            }
+        }
+        if self.registry_url.is_none() {
+            self.registry_url.clone_from(&defaults.registry_url);
+        }
+        self.include_prerelease = flag(
+            "include_prerelease",
+            "no_include_prerelease",
+            self.include_prerelease,
+            defaults.include_prerelease,
+        );
+        self.updates_only = flag("updates_only", "no_updates_only", self.updates_only, defaults.updates_only);
+        self.check_prebuilt = flag(
+            "check_prebuilt",
+            "no_check_prebuilt",
+            self.check_prebuilt,
+            defaults.check_prebuilt,
+        );
+        self.prefer_prebuilt = flag(
+            "prefer_prebuilt",
+            "no_prefer_prebuilt",
+            self.prefer_prebuilt,
+            defaults.prefer_prebuilt,
+        );
+        self.no_cargo_search_fallback = flag(
+            "no_cargo_search_fallback",
+            "cargo_search_fallback",
+            self.no_cargo_search_fallback,
+            defaults.no_cargo_search_fallback,
+        );
+    }
+
+    /// `--prebuilt-only` / `--source-only` 给出的策略；都没传时 None（按配置）。
+    pub fn install_policy_override(&self) -> Option<crate::config::InstallPolicy> {
+        if self.prebuilt_only {
+            Some(crate::config::InstallPolicy::PrebuiltOnly)
+        } else if self.source_only {
+            Some(crate::config::InstallPolicy::SourceOnly)
+        } else {
+            None
+        }
+    }
+
     /// 生成补全脚本的通用方法。`out` 让调用者决定写到 stdout 还是缓冲区。
     fn render_completion_into(shell: &ShellType, cmd: &mut clap::Command, name: &str, out: &mut dyn Write) {
         let shell_type = match shell {
@@ -538,6 +916,348 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cli_bundle_and_from_bundle_parse() {
+        let cli =
+            Cli::try_parse_from(["cargo-fresh", "--batch", "bundle", "tools.tar.gz"]).expect("parse");
+        assert!(cli.batch);
+        match cli.command {
+            Some(Commands::Bundle { output, target, all }) => {
+                assert_eq!(output, std::path::PathBuf::from("tools.tar.gz"));
+                assert_eq!(target, None);
+                assert!(!all);
+            }
+            _ => panic!("expected bundle subcommand"),
+        }
+        let cli = Cli::try_parse_from([
+            "cargo-fresh",
+            "bundle",
+            "--target",
+            "aarch64-unknown-linux-gnu",
+            "--all",
+            "out",
+        ])
+        .expect("parse");
+        match cli.command {
+            Some(Commands::Bundle { target, all, .. }) => {
+                assert_eq!(target.as_deref(), Some("aarch64-unknown-linux-gnu"));
+                assert!(all);
+            }
+            _ => panic!("expected bundle subcommand"),
+        }
+        let cli = Cli::try_parse_from(["cargo-fresh", "--from-bundle", "/mnt/usb/tools"])
+            .expect("parse");
+        assert_eq!(cli.from_bundle, Some(std::path::PathBuf::from("/mnt/usb/tools")));
+    }
+
+    #[test]
+    fn cli_install_policy_flags() {
+        use crate::config::InstallPolicy;
+        let cli = Cli::try_parse_from(["cargo-fresh", "--prebuilt-only"]).expect("parse");
+        assert_eq!(cli.install_policy_override(), Some(InstallPolicy::PrebuiltOnly));
+        let cli = Cli::try_parse_from(["cargo-fresh", "--source-only"]).expect("parse");
+        assert_eq!(cli.install_policy_override(), Some(InstallPolicy::SourceOnly));
+        assert_eq!(Cli::try_parse_from(["cargo-fresh"]).unwrap().install_policy_override(), None);
+        assert!(Cli::try_parse_from(["cargo-fresh", "--prebuilt-only", "--source-only"]).is_err());
+        assert!(Cli::try_parse_from(["cargo-fresh", "--source-only", "--from-bundle", "b"]).is_err());
+    }
+
+    #[test]
+    fn cli_apply_defaults_keeps_explicit_flags() {
+        let defaults = crate::config::DefaultsConfig {
+            exclude: vec!["cargo-fresh".into()],
+            jobs: Some(8),
+            include_prerelease: Some(true),
+            registry_url: Some("https://mirror.example/".into()),
+            ..Default::default()
+        };
+        let (mut cli, matches) = Cli::parse_with_matches(["cargo-fresh"]);
+        cli.apply_defaults(&defaults, &matches);
+        assert_eq!(cli.exclude, ["cargo-fresh"]);
+        assert_eq!(cli.jobs, 8);
+        assert!(cli.include_prerelease);
+        assert_eq!(cli.registry_url.as_deref(), Some("https://mirror.example/"));
+
+        // 命令行显式给的值 (哪怕等于 clap 默认值) 不被配置覆盖
+        let (mut cli, matches) =
+            Cli::parse_with_matches(["cargo-fresh", "--jobs", "4", "--registry-url", "https://x/"]);
+        cli.apply_defaults(&defaults, &matches);
+        assert_eq!(cli.jobs, 4);
+        assert_eq!(cli.registry_url.as_deref(), Some("https://x/"));
+
+        // --exclude 替换而不是并上配置里的 exclude; --no-… 压过配置里的 true
+        let (mut cli, matches) =
+            Cli::parse_with_matches(["cargo-fresh", "--exclude", "rg", "--no-include-prerelease"]);
+        cli.apply_defaults(&defaults, &matches);
+        assert_eq!(cli.exclude, ["rg"]);
+        assert!(!cli.include_prerelease);
+    }
+
+    #[test]
+    fn cli_boolean_defaults_respect_negations() {
+        let defaults = crate::config::DefaultsConfig {
+            updates_only: Some(true),
+            check_prebuilt: Some(true),
+            prefer_prebuilt: Some(false),
+            no_cargo_search_fallback: Some(true),
+            ..Default::default()
+        };
+        let (mut cli, matches) = Cli::parse_with_matches([
+            "cargo-fresh",
+            "--no-updates-only",
+            "--no-check-prebuilt",
+            "--prefer-prebuilt",
+            "--cargo-search-fallback",
+        ]);
+        cli.apply_defaults(&defaults, &matches);
+        assert!(!cli.updates_only);
+        assert!(!cli.check_prebuilt);
+        assert!(cli.prefer_prebuilt);
+        assert!(!cli.no_cargo_search_fallback);
+
+        let (mut cli, matches) = Cli::parse_with_matches(["cargo-fresh"]);
+        cli.apply_defaults(&defaults, &matches);
+        assert!(cli.updates_only && cli.check_prebuilt && cli.no_cargo_search_fallback);
+        assert!(!cli.prefer_prebuilt);
+
+        // 同一开关正反都给时后出现的生效
+        let cli = Cli::try_parse_from(["cargo-fresh", "--check-prebuilt", "--no-check-prebuilt"]).unwrap();
+        assert!(!cli.check_prebuilt);
+        let cli = Cli::try_parse_from(["cargo-fresh", "--no-check-prebuilt", "--check-prebuilt"]).unwrap();
+        assert!(cli.check_prebuilt);
+    }
+
+    #[test]
+    fn cli_hold_and_unhold_parse() {
+        let cli = Cli::try_parse_from(["cargo-fresh", "hold", "bevy_cli", "--at", "0.21"]).expect("parse");
+        match cli.command {
+            Some(Commands::Hold { package, at }) => {
+                assert_eq!(package, "bevy_cli");
+                assert_eq!(at.as_deref(), Some("0.21"));
+            }
+            _ => panic!("expected hold"),
+        }
+        let cli = Cli::try_parse_from(["cargo-fresh", "unhold", "bevy_cli"]).expect("parse");
+        assert!(matches!(cli.command, Some(Commands::Unhold { .. })));
+        assert!(Cli::try_parse_from(["cargo-fresh", "hold"]).is_err());
+    }
+
+    #[test]
+    fn cli_doctor_parses() {
+        let cli =
+            Cli::try_parse_from(["cargo-fresh", "--format", "json", "doctor"]).expect("parse");
+        assert!(matches!(cli.command, Some(Commands::Doctor)));
+        assert_eq!(cli.format, OutputFormat::Json);
+        assert!(Cli::try_parse_from(["cargo-fresh", "doctor", "extra"]).is_err());
+    }
+
+    #[test]
+    fn cli_self_update_parses() {
+        let cli = Cli::try_parse_from(["cargo-fresh", "--dry-run", "self-update"]).expect("parse");
+        assert!(matches!(cli.command, Some(Commands::SelfUpdate)));
+        assert!(cli.dry_run);
+        assert!(Cli::try_parse_from(["cargo-fresh", "self-update", "ripgrep"]).is_err());
+    }
+
+    #[test]
+    fn cli_install_parses_spec_and_features() {
+        let cli = Cli::try_parse_from([
+            "cargo-fresh",
+            "--prebuilt-only",
+            "install",
+            "ripgrep@14.1.1",
+            "--features",
+            "pcre2,simd",
+            "--force",
+        ])
+        .expect("parse");
+        assert!(cli.install_policy_override().is_some());
+        match cli.command {
+            Some(Commands::Install {
+                crate_spec,
+                force,
+                features,
+                all_features,
+                no_default_features,
+            }) => {
+                assert_eq!(crate_spec, "ripgrep@14.1.1");
+                assert!(force);
+                assert_eq!(features, ["pcre2", "simd"]);
+                assert!(!all_features && !no_default_features);
+            }
+            _ => panic!("expected install"),
+        }
+        assert!(Cli::try_parse_from(["cargo-fresh", "install"]).is_err());
+    }
+
+    #[test]
+    fn cli_list_parses_sort_options() {
+        use crate::package::inventory::SortKey;
+
+        let cli = Cli::try_parse_from(["cargo-fresh", "list"]).expect("parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List {
+                sort: SortKey::Name,
+                reverse: false,
+                csv: false
+            })
+        ));
+        let cli = Cli::try_parse_from([
+            "cargo-fresh",
+            "--exclude",
+            "cargo-*",
+            "list",
+            "--sort",
+            "size",
+            "--reverse",
+            "--csv",
+        ])
+        .expect("parse");
+        assert_eq!(cli.exclude, ["cargo-*"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List {
+                sort: SortKey::Size,
+                reverse: true,
+                csv: true
+            })
+        ));
+        assert!(Cli::try_parse_from(["cargo-fresh", "list", "--sort", "age"]).is_err());
+    }
+
+    #[test]
+    fn cli_use_parses_spec() {
+        let cli = Cli::try_parse_from(["cargo-fresh", "use", "ripgrep@14.0.3", "--no-hold"])
+            .expect("parse");
+        match cli.command {
+            Some(Commands::Use { spec, no_hold }) => {
+                assert_eq!(spec, "ripgrep@14.0.3");
+                assert!(no_hold);
+            }
+            _ => panic!("expected use"),
+        }
+        assert!(Cli::try_parse_from(["cargo-fresh", "use"]).is_err());
+    }
+
+    #[test]
+    fn cli_uninstall_and_prune_parse() {
+        let cli =
+            Cli::try_parse_from(["cargo-fresh", "uninstall", "tokei", "just"]).expect("parse");
+        match cli.command {
+            Some(Commands::Uninstall { packages }) => assert_eq!(packages, ["tokei", "just"]),
+            _ => panic!("expected uninstall"),
+        }
+        assert!(Cli::try_parse_from(["cargo-fresh", "uninstall"]).is_err());
+
+        let cli = Cli::try_parse_from([
+            "cargo-fresh",
+            "prune",
+            "--unused-for",
+            "90d",
+            "--match",
+            "cargo-*",
+        ])
+        .expect("parse");
+        match cli.command {
+            Some(Commands::Prune {
+                unused_for,
+                patterns,
+                orphans,
+            }) => {
+                assert_eq!(
+                    unused_for,
+                    Some(std::time::Duration::from_secs(90 * 86_400))
+                );
+                assert_eq!(patterns, ["cargo-*"]);
+                assert!(!orphans);
+            }
+            _ => panic!("expected prune"),
+        }
+        assert!(Cli::try_parse_from(["cargo-fresh", "prune"]).is_err());
+
+        let cli = Cli::try_parse_from(["cargo-fresh", "prune", "--orphans"]).expect("parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Prune { orphans: true, .. })
+        ));
+        assert!(
+            Cli::try_parse_from(["cargo-fresh", "prune", "--orphans", "--unused-for", "90d"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn cli_export_and_sync_parse() {
+        let cli = Cli::try_parse_from(["cargo-fresh", "export"]).expect("parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export { output: None })
+        ));
+        let cli = Cli::try_parse_from([
+            "cargo-fresh",
+            "--dry-run",
+            "sync",
+            "tools.toml",
+            "--uninstall-extras",
+        ])
+        .expect("parse");
+        assert!(cli.dry_run);
+        match cli.command {
+            Some(Commands::Sync {
+                manifest,
+                uninstall_extras,
+            }) => {
+                assert_eq!(manifest, std::path::PathBuf::from("tools.toml"));
+                assert!(uninstall_extras);
+            }
+            _ => panic!("expected sync"),
+        }
+        assert!(Cli::try_parse_from(["cargo-fresh", "sync"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_show_parses() {
+        let cli = Cli::try_parse_from(["cargo-fresh", "config", "show"]).expect("parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config {
+                action: ConfigCommand::Show
+            })
+        ));
+    }
+
+    #[test]
+    fn cli_limit_rate_accepts_units() {
+        let cli = Cli::try_parse_from(["cargo-fresh", "--limit-rate", "2M"]).expect("parse");
+        assert_eq!(cli.limit_rate, Some(2 << 20));
+        assert!(Cli::try_parse_from(["cargo-fresh", "--limit-rate", "fast"]).is_err());
+    }
+
+    #[test]
+    fn cli_cache_prune_parses_age_and_size() {
+        let cli = Cli::try_parse_from([
+            "cargo-fresh",
+            "cache",
+            "prune",
+            "--older-than",
+            "30d",
+            "--max-size",
+            "500MiB",
+        ])
+        .expect("parse");
+        match cli.command {
+            Some(Commands::Cache {
+                action: CacheCommand::Prune { older_than, max_size },
+            }) => {
+                assert_eq!(older_than, Some(std::time::Duration::from_secs(30 * 86_400)));
+                assert_eq!(max_size, Some(500 << 20));
+            }
+            _ => panic!("expected cache prune subcommand"),
+        }
+        assert!(Cli::try_parse_from(["cargo-fresh", "cache", "prune", "--older-than", "soon"]).is_err());
+    }
+
     #[test]
     fn install_target_path_fish_top_level() {
         let path = Cli::install_target_path(&ShellType::Fish, InstallTarget::TopLevel)
@@ -627,6 +1347,13 @@ mod tests {
         assert!(cli.check_prebuilt);
     }
 
+    #[test]
+    fn parses_prefer_prebuilt() {
+        let cli = Cli::try_parse_from(["cargo-fresh", "--prefer-prebuilt"]).unwrap();
+        assert!(cli.prefer_prebuilt);
+        assert!(!cli.check_prebuilt);
+    }
+
     #[test]
     fn parses_debug_flag() {
         let cli = Cli::try_parse_from(["cargo-fresh", "--debug"]).unwrap();
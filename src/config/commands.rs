@@ -0,0 +1,39 @@
+//! `cargo fresh config` 子命令的执行流程：展示合并后的配置及每个值的出处。
+
+use anyhow::Result;
+
+use crate::cli::ConfigCommand;
+use crate::models::EXIT_OK;
+
+/// `cargo fresh config show`：合并后的配置 + 每个值的出处。TOML 形式写 stdout，
+/// 可以直接重定向成一份配置文件；JSON 模式每个叶子值一条 `{key, value, source}`。
+pub fn run_config_command(action: &ConfigCommand, json_mode: bool) -> Result<i32> {
+    match action {
+        ConfigCommand::Show => {
+            let layered = super::layers::load()?;
+            if json_mode {
+                let layers: Vec<serde_json::Value> = layered
+                    .layers
+                    .iter()
+                    .map(|(source, found)| {
+                        serde_json::json!({ "source": source.to_string(), "found": found })
+                    })
+                    .collect();
+                let values: Vec<serde_json::Value> = layered
+                    .entries()
+                    .into_iter()
+                    .map(|(key, value, source)| {
+                        serde_json::json!({ "key": key, "value": value, "source": source })
+                    })
+                    .collect();
+                anstream::println!(
+                    "{}",
+                    serde_json::json!({ "layers": layers, "values": values })
+                );
+            } else {
+                anstream::print!("{}", layered.render());
+            }
+            Ok(EXIT_OK)
+        }
+    }
+}
@@ -0,0 +1,469 @@
+//! 分层配置：把几处来源按优先级合并成一张表，并记下每个值来自哪一层。
+//!
+//! 从低到高（后者覆盖前者）：
+//! 1. `$CARGO_HOME/config.toml` 里的 `[fresh]` 表——和团队共享的 cargo 配置放一起
+//! 2. `$XDG_CONFIG_HOME/cargo-fresh/config.toml`
+//! 3. 环境变量 `CARGO_FRESH_<KEY>`，只覆盖 `[defaults]` 里的键
+//!
+//! 合并按键递归：表与表逐键合并，其余值（包括数组、`[[rewrite]]`）整体替换。
+//! 每层先单独解析成 [`Config`] 校验一遍，出错时能指到具体文件 / 变量。
+//! `cargo fresh config show` 用 [`Layered::render`] 打印合并结果和出处。
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use super::{from_table, Config};
+
+/// 一个配置值的出处。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// cargo 配置文件里的 `[fresh]` 表。
+    CargoConfig(PathBuf),
+    /// cargo-fresh 自己的配置文件。
+    File(PathBuf),
+    /// 环境变量。
+    Env(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::CargoConfig(path) => write!(f, "{} [fresh]", path.display()),
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            ConfigSource::Env(var) => write!(f, "env {var}"),
+        }
+    }
+}
+
+/// `[defaults]` 里某个键的取值类型，决定环境变量怎么解析。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Bool,
+    Int,
+    Str,
+    /// 逗号分隔的列表。
+    List,
+}
+
+/// `[defaults]` 支持的键、类型与内置默认值（`config show` 显示用）。
+/// 与 [`super::DefaultsConfig`] 的字段一一对应。
+const DEFAULT_KEYS: &[(&str, Kind, &str)] = &[
+    ("exclude", Kind::List, "[]"),
+    ("jobs", Kind::Int, "4"),
+    ("include-prerelease", Kind::Bool, "false"),
+    ("updates-only", Kind::Bool, "false"),
+    ("check-prebuilt", Kind::Bool, "false"),
+    ("prefer-prebuilt", Kind::Bool, "false"),
+    ("no-cargo-search-fallback", Kind::Bool, "false"),
+    ("registry-url", Kind::Str, "(from cargo config)"),
+    ("install", Kind::Str, "\"auto\""),
+];
+
+/// 键 → 环境变量名：`include-prerelease` → `CARGO_FRESH_INCLUDE_PRERELEASE`。
+pub fn env_var_name(key: &str) -> String {
+    format!("CARGO_FRESH_{}", key.to_ascii_uppercase().replace('-', "_"))
+}
+
+/// 合并后的配置、合并前的原始表，以及每个叶子值的出处。
+#[derive(Debug, Clone, Default)]
+pub struct Layered {
+    pub config: Config,
+    pub table: toml::Table,
+    /// 渲染后的点分键（`packages."corp-*".install`）→ 出处。
+    pub origins: BTreeMap<String, ConfigSource>,
+    /// 参与合并的每一层及其是否存在，按优先级从低到高。
+    pub layers: Vec<(ConfigSource, bool)>,
+}
+
+impl Layered {
+    /// 把一层叠到已有结果上。`layer` 先单独校验，错误带上出处。
+    pub fn push(&mut self, source: ConfigSource, layer: toml::Table) -> Result<()> {
+        from_table(layer.clone()).with_context(|| format!("invalid configuration in {source}"))?;
+        merge(&mut self.table, layer, &source, &mut self.origins, &[]);
+        self.layers.push((source, true));
+        Ok(())
+    }
+
+    /// 记一层不存在的来源（`config show` 列出来，方便排查"为什么没生效"）。
+    pub fn push_missing(&mut self, source: ConfigSource) {
+        self.layers.push((source, false));
+    }
+
+    /// 所有层叠完后解析出最终的 [`Config`]。
+    pub fn finish(mut self) -> Result<Self> {
+        self.config = from_table(self.table.clone()).context("invalid merged configuration")?;
+        Ok(self)
+    }
+
+    /// 按 TOML 形式渲染合并结果，每个值后面注释出处；`[defaults]` 里没设的
+    /// 键也列出来，标成内置默认值。
+    pub fn render(&self) -> String {
+        let mut out = String::from("# layers, later wins:\n");
+        for (source, present) in &self.layers {
+            let note = if *present { "" } else { "  (not found)" };
+            out.push_str(&format!("#   {source}{note}\n"));
+        }
+        let mut table = self.table.clone();
+        let defaults = table
+            .remove("defaults")
+            .and_then(|v| v.as_table().cloned())
+            .unwrap_or_default();
+        // 顶层的非表值 (`[[rewrite]]`) 必须写在第一个 `[table]` 头之前
+        let (top, nested): (toml::Table, toml::Table) =
+            table.into_iter().partition(|(_, v)| !v.is_table());
+        self.render_table(&mut out, &top, &[]);
+        out.push_str("\n[defaults]\n");
+        for (key, _, builtin) in DEFAULT_KEYS {
+            match defaults.get(*key) {
+                Some(value) => {
+                    let origin = self.origin(&[*key], "defaults");
+                    out.push_str(&format!("{key} = {}  # {origin}\n", render_value(value)));
+                }
+                None => out.push_str(&format!("# {key} = {builtin}  (default)\n")),
+            }
+        }
+        self.render_table(&mut out, &nested, &[]);
+        out
+    }
+
+    fn origin(&self, path: &[&str], prefix: &str) -> String {
+        let mut segments = vec![prefix.to_string()];
+        segments.extend(path.iter().map(|s| s.to_string()));
+        self.origins
+            .get(&dotted(&segments))
+            .map_or_else(|| "?".to_string(), ToString::to_string)
+    }
+
+    fn render_table(&self, out: &mut String, table: &toml::Table, path: &[String]) {
+        let (leaves, subtables): (Vec<_>, Vec<_>) = table.iter().partition(|(_, v)| !v.is_table());
+        if !leaves.is_empty() && !path.is_empty() {
+            out.push_str(&format!("\n[{}]\n", dotted(path)));
+        }
+        for (key, value) in leaves {
+            let mut full = path.to_vec();
+            full.push(key.clone());
+            let origin = self
+                .origins
+                .get(&dotted(&full))
+                .map_or_else(|| "?".to_string(), ToString::to_string);
+            out.push_str(&format!(
+                "{} = {}  # {origin}\n",
+                key_segment(key),
+                render_value(value)
+            ));
+        }
+        for (key, value) in subtables {
+            let mut full = path.to_vec();
+            full.push(key.clone());
+            if let Some(sub) = value.as_table() {
+                self.render_table(out, sub, &full);
+            }
+        }
+    }
+
+    /// `config show --format=json` 用：每个叶子值一条 `{key, value, source}`。
+    pub fn entries(&self) -> Vec<(String, toml::Value, String)> {
+        let mut out = Vec::new();
+        collect_leaves(&self.table, &mut Vec::new(), &mut |path, value| {
+            let key = dotted(path);
+            let source = self
+                .origins
+                .get(&key)
+                .map_or_else(|| "?".to_string(), ToString::to_string);
+            out.push((key, value.clone(), source));
+        });
+        out
+    }
+}
+
+fn collect_leaves(
+    table: &toml::Table,
+    path: &mut Vec<String>,
+    f: &mut dyn FnMut(&[String], &toml::Value),
+) {
+    for (key, value) in table {
+        path.push(key.clone());
+        match value.as_table() {
+            Some(sub) => collect_leaves(sub, path, f),
+            None => f(path, value),
+        }
+        path.pop();
+    }
+}
+
+/// 递归合并 `layer` 到 `into`，同时刷新被覆盖叶子的出处。
+fn merge(
+    into: &mut toml::Table,
+    layer: toml::Table,
+    source: &ConfigSource,
+    origins: &mut BTreeMap<String, ConfigSource>,
+    path: &[String],
+) {
+    for (key, value) in layer {
+        let mut full = path.to_vec();
+        full.push(key.clone());
+        match (into.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(incoming)) => {
+                merge(existing, incoming, source, origins, &full);
+            }
+            (_, value) => {
+                // 被整体替换的子树: 旧出处全部作废
+                let stale = dotted(&full);
+                origins.retain(|k, _| k != &stale && !k.starts_with(&format!("{stale}.")));
+                if let toml::Value::Table(sub) = &value {
+                    collect_leaves(sub, &mut full.clone(), &mut |leaf, _| {
+                        origins.insert(dotted(leaf), source.clone());
+                    });
+                } else {
+                    origins.insert(stale, source.clone());
+                }
+                into.insert(key, value);
+            }
+        }
+    }
+}
+
+/// 纯函数：从环境变量里挑出 `CARGO_FRESH_<KEY>`，组装成 `{defaults = {...}}`
+/// 一层。空值视为没设；类型不对直接报错，免得 `CARGO_FRESH_JOBS=lots` 被静默忽略。
+pub fn env_layer<I>(vars: I) -> Result<Vec<(String, toml::Table)>>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let vars: BTreeMap<String, String> = vars.into_iter().collect();
+    let mut out = Vec::new();
+    for (key, kind, _) in DEFAULT_KEYS {
+        let var = env_var_name(key);
+        let Some(raw) = vars.get(&var).map(|s| s.trim()).filter(|s| !s.is_empty()) else {
+            continue;
+        };
+        let value = match kind {
+            Kind::Bool => toml::Value::Boolean(
+                parse_bool(raw)
+                    .with_context(|| format!("{var}={raw}: expected 1/0, true/false or yes/no"))?,
+            ),
+            Kind::Int => toml::Value::Integer(
+                raw.parse::<u32>()
+                    .map(i64::from)
+                    .with_context(|| format!("{var}={raw}: expected a non-negative integer"))?,
+            ),
+            Kind::Str => toml::Value::String(raw.to_string()),
+            Kind::List => toml::Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| toml::Value::String(s.to_string()))
+                    .collect(),
+            ),
+        };
+        let mut defaults = toml::Table::new();
+        defaults.insert((*key).to_string(), value);
+        let mut layer = toml::Table::new();
+        layer.insert("defaults".to_string(), toml::Value::Table(defaults));
+        out.push((var, layer));
+    }
+    Ok(out)
+}
+
+fn parse_bool(raw: &str) -> Option<bool> {
+    match raw.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// 读一个 TOML 文件；不存在返回 None。
+fn read_table(path: &std::path::Path) -> Result<Option<toml::Table>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let body = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let table =
+        toml::from_str(&body).with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(Some(table))
+}
+
+/// 按优先级读取并合并所有层。
+pub fn load() -> Result<Layered> {
+    let mut layered = Layered::default();
+
+    if let Some(path) = crate::package::registry::cargo_home().map(|h| h.join("config.toml")) {
+        let source = ConfigSource::CargoConfig(path.clone());
+        match read_table(&path)?.and_then(|mut t| t.remove("fresh")) {
+            Some(toml::Value::Table(fresh)) => layered.push(source, fresh)?,
+            Some(_) => anyhow::bail!("`fresh` in {} must be a table", path.display()),
+            None => layered.push_missing(source),
+        }
+    }
+
+    if let Some(path) = super::config_path() {
+        let source = ConfigSource::File(path.clone());
+        match read_table(&path)? {
+            Some(table) => layered.push(source, table)?,
+            None => layered.push_missing(source),
+        }
+    }
+
+    for (var, layer) in env_layer(std::env::vars())? {
+        layered.push(ConfigSource::Env(var), layer)?;
+    }
+
+    layered.finish()
+}
+
+/// 渲染点分键；不是 bare key 的段加引号（`packages."corp-*"`）。
+fn dotted(path: &[String]) -> String {
+    path.iter()
+        .map(|seg| key_segment(seg))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+pub(crate) fn key_segment(seg: &str) -> String {
+    let bare = !seg.is_empty()
+        && seg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        seg.to_string()
+    } else {
+        quote(seg)
+    }
+}
+
+pub(crate) fn quote(s: &str) -> String {
+    // JSON 字符串的转义是 TOML basic string 的子集
+    serde_json::to_string(s).unwrap_or_else(|_| format!("\"{s}\""))
+}
+
+/// 把单个值渲染成 TOML 字面量（行内表用于 `[[rewrite]]` 这类表数组）。
+fn render_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => quote(s),
+        toml::Value::Integer(n) => n.to_string(),
+        toml::Value::Float(n) => n.to_string(),
+        toml::Value::Boolean(b) => b.to_string(),
+        toml::Value::Datetime(d) => d.to_string(),
+        toml::Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(render_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        toml::Value::Table(t) => format!(
+            "{{ {} }}",
+            t.iter()
+                .map(|(k, v)| format!("{} = {}", key_segment(k), render_value(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(body: &str) -> toml::Table {
+        toml::from_str(body).unwrap()
+    }
+
+    fn file(name: &str) -> ConfigSource {
+        ConfigSource::File(PathBuf::from(name))
+    }
+
+    #[test]
+    fn later_layers_override_per_key_and_track_origins() {
+        let mut l = Layered::default();
+        l.push(
+            ConfigSource::CargoConfig(PathBuf::from("cargo.toml")),
+            table("[defaults]\njobs = 2\nexclude = [\"a\"]\n[packages.\"corp-*\"]\ninstall = \"source-only\"\n"),
+        )
+        .unwrap();
+        l.push(file("fresh.toml"), table("[defaults]\njobs = 8\n"))
+            .unwrap();
+        for (var, layer) in
+            env_layer([("CARGO_FRESH_INCLUDE_PRERELEASE".into(), "yes".into())]).unwrap()
+        {
+            l.push(ConfigSource::Env(var), layer).unwrap();
+        }
+        let l = l.finish().unwrap();
+
+        assert_eq!(l.config.defaults.jobs, Some(8));
+        assert_eq!(l.config.defaults.exclude, ["a"]);
+        assert_eq!(l.config.defaults.include_prerelease, Some(true));
+        assert_eq!(l.origins["defaults.jobs"], file("fresh.toml"));
+        assert_eq!(
+            l.origins["defaults.include-prerelease"],
+            ConfigSource::Env("CARGO_FRESH_INCLUDE_PRERELEASE".into())
+        );
+        assert_eq!(
+            l.origins["packages.\"corp-*\".install"],
+            ConfigSource::CargoConfig(PathBuf::from("cargo.toml"))
+        );
+
+        let shown = l.render();
+        assert!(shown.contains("jobs = 8  # fresh.toml"), "{shown}");
+        assert!(
+            shown.contains("# updates-only = false  (default)"),
+            "{shown}"
+        );
+        assert!(
+            shown
+                .contains("[packages.\"corp-*\"]\ninstall = \"source-only\"  # cargo.toml [fresh]"),
+            "{shown}"
+        );
+    }
+
+    #[test]
+    fn each_layer_is_validated_with_its_source() {
+        let mut l = Layered::default();
+        let err = l
+            .push(
+                file("bad.toml"),
+                table("[packages.\"x\"]\ninstall = \"never\"\n"),
+            )
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("bad.toml"), "{err:#}");
+    }
+
+    #[test]
+    fn env_values_are_typed() {
+        let layers = env_layer([
+            ("CARGO_FRESH_JOBS".into(), "6".into()),
+            ("CARGO_FRESH_EXCLUDE".into(), "cargo-fresh, ripgrep".into()),
+            ("CARGO_FRESH_CHECK_PREBUILT".into(), String::new()),
+            ("UNRELATED".into(), "1".into()),
+        ])
+        .unwrap();
+        let names: Vec<&str> = layers.iter().map(|(v, _)| v.as_str()).collect();
+        assert_eq!(names, ["CARGO_FRESH_EXCLUDE", "CARGO_FRESH_JOBS"]);
+        let exclude = &layers[0].1["defaults"]["exclude"];
+        assert_eq!(exclude.as_array().unwrap().len(), 2);
+
+        assert!(env_layer([("CARGO_FRESH_JOBS".into(), "lots".into())]).is_err());
+        assert!(env_layer([("CARGO_FRESH_BATCH".into(), "maybe".into())]).is_ok());
+        assert!(env_layer([("CARGO_FRESH_UPDATES_ONLY".into(), "maybe".into())]).is_err());
+    }
+
+    #[test]
+    fn replaced_subtree_drops_stale_origins() {
+        let mut l = Layered::default();
+        l.push(file("a"), table("[[rewrite]]\nfrom = \"x\"\nto = \"y\"\n"))
+            .unwrap();
+        l.push(file("b"), table("[[rewrite]]\nfrom = \"p\"\nto = \"q\"\n"))
+            .unwrap();
+        let l = l.finish().unwrap();
+        assert_eq!(l.config.rewrite.len(), 1);
+        assert_eq!(l.origins["rewrite"], file("b"));
+        assert!(l
+            .render()
+            .contains("rewrite = [{ from = \"p\", to = \"q\" }]  # b"));
+    }
+}
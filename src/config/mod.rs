@@ -0,0 +1,831 @@
+//! cargo-fresh 自身的配置文件：`$XDG_CONFIG_HOME/cargo-fresh/config.toml`
+//! （未设 XDG_CONFIG_HOME 时回退 `$HOME/.config/cargo-fresh/config.toml`）。
+//! 同样的内容也可以写在 `$CARGO_HOME/config.toml` 的 `[fresh]` 表里，
+//! `[defaults]` 的键还能用 `CARGO_FRESH_<KEY>` 环境变量覆盖——合并顺序见
+//! [`layers`]，`cargo fresh config show` 打印合并结果和每个值的出处。
+//!
+//! ```toml
+//! [defaults]            # CLI 标志的默认值; 命令行显式传入的优先
+//! exclude = ["cargo-fresh"]
+//! jobs = 8
+//! include-prerelease = false
+//!
+//! [signatures]
+//! verified = "allow"   # 签名校验通过
+//! invalid  = "source"  # 签名存在但校验失败
+//! missing  = "warn"    # 钉了公钥但 release 没带签名
+//!
+//! [packages."ripgrep"]
+//! minisign-key = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3"
+//!
+//! [packages."corp-*"]
+//! prebuilt-urls = ["https://artifacts.corp.example/{name}/{version}/{name}-{target}.{ext}"]
+//! prebuilt-source = "instead"   # 或 "before"（默认）：先试这些, 不中再走 GitHub
+//!
+//! [packages."typos-cli"]
+//! prefer-prebuilt = true   # 最新版只能源码构建时, 退到最新的有预编译产物的版本
+//!
+//! [packages."cargo-*"]
+//! install = "source-only"  # 或 "prebuilt-only"（没有预编译产物就跳过）/ "auto"（默认）
+//!
+//! [packages."nextest-*"]
+//! exclude = true           # 永远不检查 / 不更新
+//!
+//! [packages."bevy_cli"]
+//! prerelease = true        # 这个包允许升级到预发布版本
+//! features = ["wasm-opt"]  # 追加的 features (会改走 cargo install)
+//! registry = "https://index.corp.example/"   # 查这个包版本时用的 sparse index
+//!
+//! [[rewrite]]
+//! from = "https://github.com/"
+//! to   = "https://artifacts.corp.example/github/"
+//!
+//! [hosts."git.corp.example"]
+//! kind = "gitlab"
+//!
+//! [hosts."ghe.corp.example"]
+//! api-base = "https://ghe.corp.example/api/v3"
+//! token-env = "GHE_TOKEN"
+//!
+//! [cache]
+//! enabled = true                      # 默认关闭
+//! dir = "/shared/cargo-fresh-cache"   # 默认 $XDG_CACHE_HOME/cargo-fresh/archives
+//! max-size = "2GiB"                   # 默认 1GiB; 也可写字节数
+//!
+//! [network]
+//! limit-rate = "2M"          # 所有下载流合计的带宽上限 (字节/秒); --limit-rate 优先
+//! per-host-connections = 8   # 每个主机同时在飞的 HEAD / GET 上限, 默认 16
+//!
+//! [extract]
+//! max-size = "4GiB"     # 单个归档解压出的总字节数上限, 默认 2GiB
+//! max-entries = 50000   # 单个归档的条目数上限, 默认 10000
+//!
+//! [targets."armv7-unknown-linux-gnueabihf"]
+//! aliases = ["armv7l-linux"]          # 追加到内置别名之后
+//!
+//! [targets."loongarch64-unknown-linux-gnu"]
+//! arch = "loongarch64"                # 内置表没有的 triple: 本机 (arch, os) 匹配时使用
+//! os = "linux"
+//! aliases = ["linux-loong64"]
+//! ```
+//!
+//! 一个包命中多条 `[packages."<glob>"]` 时，越具体的规则越优先：不含通配符的
+//! 精确包名最先，glob 之间字面字符多的在前（`cargo-*` 先于 `*`），再按字典序。
+//! 每个键取第一条配置了它的规则，所以上例里 `[packages."*"]` 的设置不会盖过
+//! `[packages."ripgrep"]`。
+//!
+//! 文件不存在 = 全默认值（行为与没有配置文件时完全一致）；文件存在但解析
+//! 失败则直接报错退出——签名策略这类安全相关的配置，静默退回默认值比报错更危险。
+//!
+//! 生产路径由 `main` 在启动期调一次 [`init`]，之后各模块通过 [`get`] 读取
+//! 进程级快照（与 `registry::sparse_index_base` 同样的 OnceLock 模式）。
+//! 测试直接用 [`parse_config`] 构造 `Config`，不碰全局状态。
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::downloader::release_host::ForgeKind;
+use crate::models::InstallOpts;
+
+pub mod commands;
+pub mod layers;
+
+/// 签名校验三种结局各自对应的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignaturePolicy {
+    /// 照常安装预编译产物，不额外提示。
+    Allow,
+    /// 照常安装，但在该包的进度行上打一条警告。
+    Warn,
+    /// 放弃预编译产物，回退 `cargo install` 从源码构建。
+    Source,
+}
+
+/// `[signatures]` 表：签名校验结局 → 策略。
+///
+/// 只有给包钉了公钥（`[packages."<glob>"]` 里的 `minisign-key` /
+/// `cosign-key`）时才会校验；没钉公钥的包不受这些策略影响。拼错的键直接
+/// 报错，而不是悄悄退回默认策略。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SignatureConfig {
+    pub verified: SignaturePolicy,
+    pub invalid: SignaturePolicy,
+    pub missing: SignaturePolicy,
+}
+
+impl Default for SignatureConfig {
+    fn default() -> Self {
+        Self {
+            verified: SignaturePolicy::Allow,
+            invalid: SignaturePolicy::Source,
+            missing: SignaturePolicy::Warn,
+        }
+    }
+}
+
+/// 自定义预编译地址 (`prebuilt-urls`) 与默认猜测路径的关系。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrebuiltSource {
+    /// 先试自定义地址，都不中再走 crate 声明 / release API / 模板猜测。
+    #[default]
+    Before,
+    /// 只试自定义地址；不中直接回退 `cargo install`。
+    Instead,
+}
+
+/// 更新时允许走哪条安装路径。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstallPolicy {
+    /// 先试预编译产物，不行再 `cargo install` 从源码构建。
+    #[default]
+    Auto,
+    /// 只装预编译产物；拿不到就跳过这个包，绝不编译（低配 CI）。
+    PrebuiltOnly,
+    /// 一律 `cargo install` 从源码构建，不下载第三方二进制。
+    SourceOnly,
+}
+
+/// `[defaults]` 表：CLI 标志的全局默认值，由 `Cli::apply_defaults` 填进
+/// 命令行上没给出的标志。命令行显式传入的值优先：`--exclude` 替换这里的
+/// `exclude` 而不是取并集，布尔项可用对应的 `--no-…` 取反标志在单次运行中关掉。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct DefaultsConfig {
+    pub exclude: Vec<String>,
+    pub jobs: Option<u32>,
+    pub include_prerelease: Option<bool>,
+    pub updates_only: Option<bool>,
+    pub check_prebuilt: Option<bool>,
+    pub prefer_prebuilt: Option<bool>,
+    pub no_cargo_search_fallback: Option<bool>,
+    pub registry_url: Option<String>,
+    /// 没有包规则命中时的安装策略。
+    pub install: Option<InstallPolicy>,
+}
+
+/// `[packages."<glob>"]` 表：按包名 glob 匹配的单包设置。未知键是错误——
+/// `minisign_key` 这种拼写被忽略就等于悄悄关掉了签名校验。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PackageConfig {
+    /// minisign 公钥（`minisign.pub` 第二行的 base64）。
+    pub minisign_key: Option<String>,
+    /// cosign 公钥（PEM，`-----BEGIN PUBLIC KEY-----`），用于 `.sig` 与
+    /// `.sigstore.json`。
+    pub cosign_key: Option<String>,
+    /// 自定义预编译 URL 模板，占位符与 `resolve` 的文件名模板相同：
+    /// `{name}` `{version}` `{target}` `{ext}`（`tar.gz` / `zip`）。
+    pub prebuilt_urls: Vec<String>,
+    pub prebuilt_source: Option<PrebuiltSource>,
+    /// 同 `--prefer-prebuilt`，只作用于匹配的包。
+    pub prefer_prebuilt: Option<bool>,
+    /// 安装路径策略；CLI `--prebuilt-only` / `--source-only` 优先。
+    pub install: Option<InstallPolicy>,
+    /// 从检查和更新里排除，同 `--exclude`。
+    pub exclude: Option<bool>,
+    /// 允许升级到预发布版本，同 `--include-prerelease`。
+    pub prerelease: Option<bool>,
+    /// 追加到已安装 features 之后的 features。非空时这个包不走预编译产物。
+    pub features: Vec<String>,
+    /// 查这个包版本时用的 sparse index，优先于 `--registry-url`。
+    pub registry: Option<String>,
+}
+
+/// `[[rewrite]]`：下载 URL 的前缀改写（制品镜像）。按声明顺序，第一条
+/// 前缀匹配的规则生效。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RewriteRule {
+    pub from: String,
+    pub to: String,
+}
+
+/// `[hosts."<host>"]` 表：自托管 release 平台。github.com / gitlab.com /
+/// codeberg.org 内置识别，不用配置（也可以写同名表覆盖 API 根或 token 来源）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HostConfig {
+    /// `github` / `gitlab` / `gitea`（`forgejo` 为别名）。省略时内置主机取
+    /// 内置种类，其余主机按 GitHub Enterprise 处理。
+    pub kind: Option<ForgeKind>,
+    /// API 根，如 `https://ghe.corp.example/api/v3`。省略时按平台约定推导。
+    pub api_base: Option<String>,
+    /// 从哪个环境变量读该主机的 token；优先于平台默认的变量。自托管的
+    /// GitLab / Gitea 只认这一项，不会拿到 `GITLAB_TOKEN` 这类通用变量。
+    pub token_env: Option<String>,
+}
+
+/// `[cache]` 表：预编译归档的持久缓存（见 `downloader::cache`）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CacheConfig {
+    /// 默认关闭：写 `enabled = true` 才在下载时读写缓存，不会悄悄占用磁盘。
+    pub enabled: bool,
+    /// 缓存目录。多台机器 / 容器可以指向同一个共享卷。
+    pub dir: Option<PathBuf>,
+    /// 大小上限（字节）。配置里写数字或 `"500MiB"` / `"2G"` 这类字符串。
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_size: Option<u64>,
+}
+
+fn deserialize_size<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+    match Size::deserialize(deserializer)? {
+        Size::Bytes(n) => Ok(Some(n)),
+        Size::Text(s) => crate::downloader::cache::parse_size(&s)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// `[network]` 表：进程级下载节流（见 `downloader::throttle`）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NetworkConfig {
+    /// 全局带宽上限（字节/秒），写法同 `[cache] max-size`。
+    #[serde(deserialize_with = "deserialize_size")]
+    pub limit_rate: Option<u64>,
+    /// 每个主机的并发连接上限。
+    pub per_host_connections: Option<usize>,
+}
+
+/// `[extract]` 表：解压下载归档时的上限（见 `downloader::archive`）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ExtractConfig {
+    /// 解压总字节数上限，写法同 `[cache] max-size`。
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_size: Option<u64>,
+    /// 条目数上限（目录、链接也算）。
+    pub max_entries: Option<u64>,
+}
+
+/// `[targets."<triple>"]` 表：扩展 `resolve` 的 target 别名表。
+///
+/// triple 已在内置表里 (作为规范名或别名) 时只把 `aliases` 追加到那一行;
+/// 否则新增一行, 给了 `arch` / `os` (取 `std::env::consts` 的写法, 如
+/// `arm` / `linux`) 时本机匹配即使用这一行, 且优先于内置行。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TargetConfig {
+    pub arch: Option<String>,
+    pub os: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    pub defaults: DefaultsConfig,
+    pub signatures: SignatureConfig,
+    /// glob → 单包设置。匹配优先级见模块文档，不是这里的字典序。
+    pub packages: BTreeMap<String, PackageConfig>,
+    /// 小写主机名 → 平台种类 / API 根 / token 来源。
+    pub hosts: BTreeMap<String, HostConfig>,
+    pub rewrite: Vec<RewriteRule>,
+    pub cache: CacheConfig,
+    pub network: NetworkConfig,
+    pub extract: ExtractConfig,
+    /// target triple → 额外别名 / 本机匹配条件。
+    pub targets: BTreeMap<String, TargetConfig>,
+    /// `packages` 的 glob 编译结果，由 [`from_table`] 构建。
+    #[serde(skip)]
+    package_globs: PackageGlobs,
+}
+
+/// 编译好的 `[packages]` glob，按匹配优先级排好序。坏 glob 在 [`from_table`]
+/// 里就报错，这里不存在"编译失败就当没匹配"的路径。
+#[derive(Debug, Clone, Default)]
+struct PackageGlobs(Vec<(String, globset::GlobMatcher)>);
+
+impl PackageGlobs {
+    fn compile<'a>(patterns: impl Iterator<Item = &'a String>) -> Result<Self> {
+        let mut globs = patterns
+            .map(|pattern| {
+                let glob = globset::Glob::new(pattern)
+                    .with_context(|| format!("invalid package glob `{pattern}` in [packages]"))?;
+                Ok((pattern.clone(), glob.compile_matcher()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // 稳定排序：同优先级的保持字典序
+        globs.sort_by_key(|(pattern, _)| std::cmp::Reverse(glob_specificity(pattern)));
+        Ok(Self(globs))
+    }
+}
+
+// 只比较 glob 文本：matcher 由文本唯一确定
+impl PartialEq for PackageGlobs {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().map(|(p, _)| p).eq(other.0.iter().map(|(p, _)| p))
+    }
+}
+
+impl Eq for PackageGlobs {}
+
+/// 规则的具体程度：(是否精确包名, 字面字符数)，越大越优先。
+fn glob_specificity(pattern: &str) -> (bool, usize) {
+    const META: &[char] = &['*', '?', '[', ']', '{', '}', '\\'];
+    let literal = pattern.chars().filter(|c| !META.contains(c)).count();
+    (!pattern.contains(META), literal)
+}
+
+/// 给某个包钉住的公钥集合。两种都没配时 [`Config::pinned_keys`] 返回 None。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinnedKeys {
+    pub minisign: Option<String>,
+    pub cosign: Option<String>,
+}
+
+impl Config {
+    /// 按优先级（见模块文档）依次返回匹配的 `[packages."<glob>"]` 表。glob
+    /// 精确匹配整个包名（和 `--filter` 的"无通配符即子串"语义不同——钉公钥
+    /// 这类设置不能因为 `rg` 是 `cargo-rgx` 的子串就串到别的包上）。
+    pub fn package_rules<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a PackageConfig> {
+        self.package_globs
+            .0
+            .iter()
+            .filter(move |(_, glob)| glob.is_match(name))
+            .filter_map(|(pattern, _)| self.packages.get(pattern))
+    }
+
+    /// 合并所有匹配规则里的公钥：每种公钥取第一条配置了它的规则。
+    pub fn pinned_keys(&self, name: &str) -> Option<PinnedKeys> {
+        let mut keys = PinnedKeys::default();
+        for rule in self.package_rules(name) {
+            if keys.minisign.is_none() {
+                keys.minisign.clone_from(&rule.minisign_key);
+            }
+            if keys.cosign.is_none() {
+                keys.cosign.clone_from(&rule.cosign_key);
+            }
+        }
+        (keys.minisign.is_some() || keys.cosign.is_some()).then_some(keys)
+    }
+
+    /// 第一条配置了 `prebuilt-urls` 的匹配规则给出的模板及其策略。
+    pub fn prebuilt_sources<'a>(&'a self, name: &'a str) -> Option<(&'a [String], PrebuiltSource)> {
+        self.package_rules(name)
+            .find(|rule| !rule.prebuilt_urls.is_empty())
+            .map(|rule| {
+                (
+                    rule.prebuilt_urls.as_slice(),
+                    rule.prebuilt_source.unwrap_or_default(),
+                )
+            })
+    }
+
+    /// 第一条配置了 `prefer-prebuilt` 的匹配规则的取值，没有则 false。
+    pub fn prefers_prebuilt(&self, name: &str) -> bool {
+        self.package_rules(name)
+            .find_map(|rule| rule.prefer_prebuilt)
+            .unwrap_or(false)
+    }
+
+    /// 第一条配置了 `install` 的匹配规则给出的策略，没有则取
+    /// `[defaults] install`，再没有则 `Auto`。
+    pub fn install_policy(&self, name: &str) -> InstallPolicy {
+        self.package_rules(name)
+            .find_map(|rule| rule.install)
+            .or(self.defaults.install)
+            .unwrap_or_default()
+    }
+
+    /// 第一条配置了 `exclude` 的匹配规则的取值，没有则 false。
+    pub fn is_excluded(&self, name: &str) -> bool {
+        self.package_rules(name)
+            .find_map(|rule| rule.exclude)
+            .unwrap_or(false)
+    }
+
+    /// 第一条配置了 `prerelease` 的匹配规则的取值，没有则 false。
+    pub fn allows_prerelease(&self, name: &str) -> bool {
+        self.package_rules(name)
+            .find_map(|rule| rule.prerelease)
+            .unwrap_or(false)
+    }
+
+    /// 第一条配置了 `registry` 的匹配规则给出的 sparse index。
+    pub fn package_registry<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        self.package_rules(name).find_map(|rule| rule.registry.as_deref())
+    }
+
+    /// 把匹配规则里的 `features` 并进已安装记录的 features（去重、保序）。
+    /// 没有额外 features 时原样返回。
+    pub fn install_opts_for(&self, name: &str, recorded: Option<InstallOpts>) -> Option<InstallOpts> {
+        let extra: Vec<&String> = self
+            .package_rules(name)
+            .flat_map(|rule| rule.features.iter())
+            .collect();
+        if extra.is_empty() {
+            return recorded;
+        }
+        let mut opts = recorded.unwrap_or_default();
+        for feature in extra {
+            if !opts.features.contains(feature) {
+                opts.features.push(feature.clone());
+            }
+        }
+        Some(opts)
+    }
+
+    /// 按 `[[rewrite]]` 改写下载 URL；没有规则匹配时原样返回。
+    pub fn rewrite_url(&self, url: &str) -> String {
+        self.rewrite
+            .iter()
+            .find_map(|r| url.strip_prefix(r.from.as_str()).map(|rest| format!("{}{rest}", r.to)))
+            .unwrap_or_else(|| url.to_string())
+    }
+}
+
+/// 纯函数：解析配置正文并校验所有 glob。便于单元测试。
+pub fn parse_config(body: &str) -> Result<Config> {
+    from_table(toml::from_str(body)?)
+}
+
+/// 同 [`parse_config`]，输入是已经解析 / 合并好的 TOML 表。
+pub fn from_table(table: toml::Table) -> Result<Config> {
+    let mut config: Config = toml::Value::Table(table).try_into()?;
+    // 主机名大小写不敏感；统一小写后 `ReleaseRepo::parse` 直接查表
+    config.hosts = std::mem::take(&mut config.hosts)
+        .into_iter()
+        .map(|(host, cfg)| (host.to_ascii_lowercase(), cfg))
+        .collect();
+    config.package_globs = PackageGlobs::compile(config.packages.keys())?;
+    Ok(config)
+}
+
+/// 配置文件路径。`$HOME` 和 `$XDG_CONFIG_HOME` 都没有时返回 None。
+pub fn config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        .map(|dir| dir.join("cargo-fresh").join("config.toml"))
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// 启动期调用一次：按 [`layers::load`] 读取并合并所有层，存进进程级快照。
+/// 文件不存在不算错误；重复调用直接返回已加载的快照。
+pub fn init() -> Result<&'static Config> {
+    if let Some(config) = CONFIG.get() {
+        return Ok(config);
+    }
+    let config = layers::load()?.config;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// 读取进程级配置快照。`init` 没跑过（单元测试、库调用方）时返回默认配置。
+pub fn get() -> &'static Config {
+    static DEFAULT: OnceLock<Config> = OnceLock::new();
+    CONFIG
+        .get()
+        .unwrap_or_else(|| DEFAULT.get_or_init(Config::default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_and_package_rules_parse() {
+        let c = parse_config(
+            r#"
+[defaults]
+exclude = ["cargo-fresh"]
+jobs = 8
+install = "prebuilt-only"
+
+[packages."nextest-*"]
+exclude = true
+
+[packages."bevy_cli"]
+prerelease = true
+features = ["wasm-opt"]
+registry = "https://index.corp.example/"
+install = "auto"
+"#,
+        )
+        .unwrap();
+        assert_eq!(c.defaults.exclude, ["cargo-fresh"]);
+        assert_eq!(c.defaults.jobs, Some(8));
+        assert!(c.is_excluded("nextest-runner"));
+        assert!(!c.is_excluded("bevy_cli"));
+        assert!(c.allows_prerelease("bevy_cli"));
+        assert!(!c.allows_prerelease("ripgrep"));
+        assert_eq!(c.package_registry("bevy_cli"), Some("https://index.corp.example/"));
+        assert_eq!(c.package_registry("ripgrep"), None);
+        // 包规则优先, 其余取 [defaults] install
+        assert_eq!(c.install_policy("bevy_cli"), InstallPolicy::Auto);
+        assert_eq!(c.install_policy("ripgrep"), InstallPolicy::PrebuiltOnly);
+
+        let recorded = InstallOpts {
+            features: vec!["wasm-opt".into(), "x".into()],
+            ..Default::default()
+        };
+        let opts = c.install_opts_for("bevy_cli", Some(recorded)).unwrap();
+        assert_eq!(opts.features, ["wasm-opt", "x"]);
+        let opts = c.install_opts_for("bevy_cli", None).unwrap();
+        assert_eq!(opts.features, ["wasm-opt"]);
+        assert!(!opts.is_default());
+        assert_eq!(c.install_opts_for("ripgrep", None), None);
+    }
+
+    #[test]
+    fn empty_body_is_default() {
+        assert_eq!(parse_config("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn default_signature_policies() {
+        let s = SignatureConfig::default();
+        assert_eq!(s.verified, SignaturePolicy::Allow);
+        assert_eq!(s.invalid, SignaturePolicy::Source);
+        assert_eq!(s.missing, SignaturePolicy::Warn);
+    }
+
+    #[test]
+    fn parses_signature_policies_and_keys() {
+        let c = parse_config(
+            r#"
+[signatures]
+missing = "source"
+invalid = "warn"
+
+[packages."ripgrep"]
+minisign-key = "RWQ-test"
+"#,
+        )
+        .unwrap();
+        assert_eq!(c.signatures.verified, SignaturePolicy::Allow);
+        assert_eq!(c.signatures.invalid, SignaturePolicy::Warn);
+        assert_eq!(c.signatures.missing, SignaturePolicy::Source);
+        let keys = c.pinned_keys("ripgrep").unwrap();
+        assert_eq!(keys.minisign.as_deref(), Some("RWQ-test"));
+        assert_eq!(keys.cosign, None);
+    }
+
+    #[test]
+    fn unknown_policy_is_an_error() {
+        assert!(parse_config("[signatures]\nmissing = \"maybe\"\n").is_err());
+    }
+
+    #[test]
+    fn unknown_signature_and_package_keys_are_errors() {
+        let err =
+            parse_config("[packages.\"ripgrep\"]\nminisign_key = \"RWQ-test\"\n").unwrap_err();
+        assert!(format!("{err:#}").contains("minisign_key"), "got: {err:#}");
+        let err = parse_config("[signatures]\nmising = \"source\"\n").unwrap_err();
+        assert!(format!("{err:#}").contains("mising"), "got: {err:#}");
+    }
+
+    #[test]
+    fn pinned_key_for_one_package_beats_wildcard() {
+        let c = parse_config(
+            r#"
+[packages."*"]
+minisign-key = "RWQ-everyone"
+cosign-key = "PEM-everyone"
+
+[packages."ripgrep"]
+minisign-key = "RWQ-ripgrep"
+"#,
+        )
+        .unwrap();
+        let keys = c.pinned_keys("ripgrep").unwrap();
+        assert_eq!(keys.minisign.as_deref(), Some("RWQ-ripgrep"));
+        assert_eq!(keys.cosign.as_deref(), Some("PEM-everyone"));
+        let keys = c.pinned_keys("bat").unwrap();
+        assert_eq!(keys.minisign.as_deref(), Some("RWQ-everyone"));
+    }
+
+    #[test]
+    fn invalid_glob_is_an_error() {
+        let err = parse_config("[packages.\"[oops\"]\nminisign-key = \"k\"\n").unwrap_err();
+        assert!(format!("{err:#}").contains("[oops"), "got: {err:#}");
+    }
+
+    #[test]
+    fn unclosed_alternates_glob_is_an_error() {
+        let err = parse_config("[packages.\"cargo-{deny\"]\nexclude = true\n").unwrap_err();
+        assert!(format!("{err:#}").contains("cargo-{deny"), "got: {err:#}");
+    }
+
+    #[test]
+    fn specific_rules_beat_wildcards() {
+        let c = parse_config(
+            r#"
+[packages."*"]
+install = "prebuilt-only"
+prerelease = true
+
+[packages."cargo-*"]
+install = "auto"
+
+[packages."cargo-deny"]
+install = "source-only"
+
+[packages."ripgrep"]
+install = "source-only"
+"#,
+        )
+        .unwrap();
+        assert_eq!(c.install_policy("ripgrep"), InstallPolicy::SourceOnly);
+        assert_eq!(c.install_policy("cargo-deny"), InstallPolicy::SourceOnly);
+        assert_eq!(c.install_policy("cargo-audit"), InstallPolicy::Auto);
+        assert_eq!(c.install_policy("bat"), InstallPolicy::PrebuiltOnly);
+        // 具体规则没配的键仍然落到通配规则上
+        assert!(c.allows_prerelease("ripgrep"));
+    }
+
+    #[test]
+    fn parses_hosts_case_insensitively() {
+        let c = parse_config(
+            r#"
+[hosts."Git.Corp.Example"]
+kind = "forgejo"
+"#,
+        )
+        .unwrap();
+        assert_eq!(c.hosts["git.corp.example"].kind, Some(ForgeKind::Gitea));
+        assert!(parse_config("[hosts.\"x\"]\nkind = \"svn\"\n").is_err());
+    }
+
+    #[test]
+    fn parses_enterprise_host_overrides() {
+        let c = parse_config(
+            r#"
+[hosts."ghe.corp.example"]
+api-base = "https://ghe.corp.example/api/v3"
+token-env = "GHE_TOKEN"
+"#,
+        )
+        .unwrap();
+        let h = &c.hosts["ghe.corp.example"];
+        assert_eq!(h.kind, None);
+        assert_eq!(
+            h.api_base.as_deref(),
+            Some("https://ghe.corp.example/api/v3")
+        );
+        assert_eq!(h.token_env.as_deref(), Some("GHE_TOKEN"));
+    }
+
+    #[test]
+    fn package_globs_match_whole_name() {
+        let c = parse_config(
+            r#"
+[packages."rg"]
+minisign-key = "exact"
+"#,
+        )
+        .unwrap();
+        assert!(c.pinned_keys("rg").is_some());
+        // 不是子串匹配
+        assert!(c.pinned_keys("cargo-rgx").is_none());
+    }
+
+    #[test]
+    fn prebuilt_sources_take_first_rule_with_urls() {
+        let c = parse_config(
+            r#"
+[packages."corp-*"]
+prebuilt-urls = ["https://a.example/{name}-{target}.{ext}"]
+prebuilt-source = "instead"
+
+[packages."corp-tool"]
+minisign-key = "k"
+"#,
+        )
+        .unwrap();
+        let (urls, mode) = c.prebuilt_sources("corp-tool").unwrap();
+        assert_eq!(urls, ["https://a.example/{name}-{target}.{ext}"]);
+        assert_eq!(mode, PrebuiltSource::Instead);
+        assert!(c.prebuilt_sources("ripgrep").is_none());
+    }
+
+    #[test]
+    fn rewrite_uses_first_matching_prefix() {
+        let c = parse_config(
+            r#"
+[[rewrite]]
+from = "https://github.com/BurntSushi/"
+to = "https://mirror.example/bs/"
+
+[[rewrite]]
+from = "https://github.com/"
+to = "https://mirror.example/gh/"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            c.rewrite_url("https://github.com/BurntSushi/ripgrep/releases/download/x"),
+            "https://mirror.example/bs/ripgrep/releases/download/x"
+        );
+        assert_eq!(
+            c.rewrite_url("https://github.com/o/r/releases/download/x"),
+            "https://mirror.example/gh/o/r/releases/download/x"
+        );
+        assert_eq!(c.rewrite_url("https://gitlab.com/x"), "https://gitlab.com/x");
+    }
+
+    #[test]
+    fn pinned_keys_merge_across_rules() {
+        let c = parse_config(
+            r#"
+[packages."cargo-*"]
+cosign-key = "pem"
+
+[packages."cargo-deny"]
+minisign-key = "mini"
+"#,
+        )
+        .unwrap();
+        let keys = c.pinned_keys("cargo-deny").unwrap();
+        assert_eq!(keys.minisign.as_deref(), Some("mini"));
+        assert_eq!(keys.cosign.as_deref(), Some("pem"));
+        assert_eq!(c.pinned_keys("cargo-nextest").unwrap().minisign, None);
+        assert!(c.pinned_keys("ripgrep").is_none());
+    }
+
+    #[test]
+    fn cache_section_accepts_bytes_or_units() {
+        assert!(!parse_config("").unwrap().cache.enabled);
+        let c = parse_config("[cache]\ndir = \"/srv/cache\"\nmax-size = \"2GiB\"\n").unwrap();
+        assert!(!c.cache.enabled);
+        assert_eq!(c.cache.dir.as_deref(), Some(std::path::Path::new("/srv/cache")));
+        assert_eq!(c.cache.max_size, Some(2 << 30));
+        let c = parse_config("[cache]\nenabled = true\nmax-size = 4096\n").unwrap();
+        assert!(c.cache.enabled);
+        assert_eq!(c.cache.max_size, Some(4096));
+        assert!(parse_config("[cache]\nmax-size = \"lots\"\n").is_err());
+    }
+
+    #[test]
+    fn network_section_parses_rate_and_host_cap() {
+        let c = parse_config("[network]\nlimit-rate = \"500K\"\nper-host-connections = 4\n").unwrap();
+        assert_eq!(c.network.limit_rate, Some(500 << 10));
+        assert_eq!(c.network.per_host_connections, Some(4));
+        assert_eq!(parse_config("").unwrap().network, NetworkConfig::default());
+    }
+
+    #[test]
+    fn prefer_prebuilt_takes_first_rule_that_sets_it() {
+        let c = parse_config(
+            "[packages.\"*\"]\nminisign-key = \"k\"\n\n\
+             [packages.\"typos*\"]\nprefer-prebuilt = true\n",
+        )
+        .unwrap();
+        // "*" 排在前面但没配 prefer-prebuilt, 不挡住后面的规则
+        assert!(c.prefers_prebuilt("typos-cli"));
+        assert!(!c.prefers_prebuilt("ripgrep"));
+    }
+
+    #[test]
+    fn install_policy_per_glob() {
+        let c = parse_config(
+            "[packages.\"cargo-*\"]\ninstall = \"source-only\"\n\n             [packages.\"ripgrep\"]\ninstall = \"prebuilt-only\"\n",
+        )
+        .unwrap();
+        assert_eq!(c.install_policy("cargo-deny"), InstallPolicy::SourceOnly);
+        assert_eq!(c.install_policy("ripgrep"), InstallPolicy::PrebuiltOnly);
+        assert_eq!(c.install_policy("bat"), InstallPolicy::Auto);
+        assert!(parse_config("[packages.\"x\"]\ninstall = \"binary\"\n").is_err());
+    }
+
+    #[test]
+    fn extract_section_parses_limits() {
+        let c = parse_config("[extract]\nmax-size = \"4GiB\"\nmax-entries = 50000\n").unwrap();
+        assert_eq!(c.extract.max_size, Some(4 << 30));
+        assert_eq!(c.extract.max_entries, Some(50_000));
+        assert_eq!(parse_config("").unwrap().extract, ExtractConfig::default());
+    }
+
+    #[test]
+    fn targets_section_parses_aliases_and_host_match() {
+        let c = parse_config(
+            "[targets.\"armv7-unknown-linux-gnueabihf\"]\naliases = [\"armv7l\"]\n\n\
+             [targets.\"loongarch64-unknown-linux-gnu\"]\narch = \"loongarch64\"\nos = \"linux\"\n",
+        )
+        .unwrap();
+        assert_eq!(c.targets["armv7-unknown-linux-gnueabihf"].aliases, vec!["armv7l"]);
+        let loong = &c.targets["loongarch64-unknown-linux-gnu"];
+        assert_eq!(loong.arch.as_deref(), Some("loongarch64"));
+        assert_eq!(loong.os.as_deref(), Some("linux"));
+        assert!(loong.aliases.is_empty());
+    }
+}
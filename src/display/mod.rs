@@ -7,6 +7,8 @@ use indicatif::ProgressBar;
 use crate::locale::Language;
 use crate::models::{InstallMethod, PackageInfo, PrebuiltAvailability, UpdateResult};
 
+pub mod report;
+
 /// JSON mode 开关：在 main 早期被设置一次，之后所有 status* / print_* /
 /// dialoguer 调用都自动 no-op，避免污染 JSON 输出。
 static JSON_MODE: AtomicBool = AtomicBool::new(false);
@@ -172,17 +174,66 @@ pub fn package_transition(package: &PackageInfo, language: Language) -> String {
         None => String::new(),
     };
     format!(
-        "{} {} -> {}{}{}",
+        "{} {} -> {}{}{}{}",
         package.name.cyan(),
         current.red(),
         latest.green(),
+        source_only_note(package, language),
         suffix,
         prebuilt_suffix
     )
 }
 
+/// 被 hold 拦下的包："ripgrep 14.1.0 held at 14.1.0, 15.0.0 available"。
+pub fn held_line(package: &PackageInfo, language: Language) -> String {
+    let current = package
+        .current_version
+        .as_deref()
+        .unwrap_or(language.get_text("unknown"));
+    let latest = package
+        .latest_version
+        .as_deref()
+        .unwrap_or(language.get_text("unknown"));
+    format!(
+        "{} {} {}",
+        package.name.cyan(),
+        current.dimmed(),
+        language.format_text(
+            "held_at",
+            &[
+                ("at", package.hold.as_deref().unwrap_or("?")),
+                ("latest", &latest.yellow().to_string()),
+            ],
+        )
+    )
+}
+
+/// `--prefer-prebuilt` 退版本时跟在新版本后面的 "(15.2.0 source-only)"。
+pub fn source_only_note(package: &PackageInfo, language: Language) -> String {
+    match &package.source_only_latest {
+        Some(v) => format!(
+            " {}",
+            language
+                .format_text("source_only_newer", &[("version", v)])
+                .dimmed()
+        ),
+        None => String::new(),
+    }
+}
+
 /// 给 `--check-prebuilt` 探测标记上色：预编译绿（好消息）、源码构建黄（预警：
 /// 这次升级会慢）、无法判别 dim。挂在 `Updating` 行尾。
+/// `rg: /usr/bin/rg comes first in PATH ...`——更新后跑的仍是遮住它的那个。
+pub fn shadowed_line(shadowed: &crate::models::ShadowedBin, language: Language) -> String {
+    language.format_text(
+        "shadowed_binary",
+        &[
+            ("bin", &shadowed.bin.cyan().to_string()),
+            ("path", &shadowed.path.display().to_string()),
+        ],
+    )
+}
+
 fn prebuilt_marker(kind: PrebuiltAvailability) -> String {
     match kind {
         PrebuiltAvailability::Prebuilt => kind.marker().green().to_string(),
@@ -239,9 +290,14 @@ pub fn print_results(packages: &[PackageInfo], updates_only: bool, language: Lan
             continue;
         }
 
-        if package.has_update() {
+        if package.held {
+            status_dim("Held", &held_line(package, language));
+        } else if package.has_update() {
             has_updates = true;
             status("Updating", &package_transition(package, language));
+            for shadowed in &package.shadowed {
+                status_warn("Shadowed", &shadowed_line(shadowed, language));
+            }
         } else if !updates_only {
             let version = package.current_version.as_deref().unwrap_or("?");
             status_dim(
@@ -269,9 +325,12 @@ pub fn print_update_summary(update_results: &[UpdateResult], language: Language)
 
     let mut success_updates = Vec::new();
     let mut failed_updates = Vec::new();
+    let mut skipped_updates = Vec::new();
 
     for result in update_results {
-        if result.success {
+        if let Some(reason) = result.skipped {
+            skipped_updates.push((result, reason));
+        } else if result.success {
             success_updates.push(result);
         } else {
             failed_updates.push(result);
@@ -297,6 +356,17 @@ pub fn print_update_summary(update_results: &[UpdateResult], language: Language)
         }
     }
 
+    for (result, reason) in &skipped_updates {
+        status_warn(
+            "Skipped",
+            &format!(
+                "{} ({})",
+                result.package_name.cyan(),
+                language.get_text(reason.locale_key())
+            ),
+        );
+    }
+
     if !failed_updates.is_empty() {
         for result in &failed_updates {
             let detail = match &result.old_version {
@@ -347,17 +417,25 @@ pub fn print_update_selection(
     if !stable_updates.is_empty() {
         anstream::eprintln!("{}", language.get_text("stable_updates").dimmed());
         for package in stable_updates {
-            status("Updating", &package_transition(package, language));
+            if package.held {
+                status_dim("Held", &held_line(package, language));
+            } else {
+                status("Updating", &package_transition(package, language));
+            }
         }
     }
 
     if !prerelease_updates.is_empty() {
         anstream::eprintln!("{}", language.get_text("prerelease_updates").dimmed());
         for package in prerelease_updates {
+            if package.held {
+                status_dim("Held", &held_line(package, language));
+                continue;
+            }
             status_warn(
                 "Prerelease",
                 &format!(
-                    "{} {} -> {}",
+                    "{} {} -> {}{}",
                     package.name.cyan(),
                     package
                         .current_version
@@ -369,6 +447,7 @@ pub fn print_update_selection(
                         .as_deref()
                         .unwrap_or(language.get_text("unknown"))
                         .yellow(),
+                    source_only_note(package, language),
                 ),
             );
         }
@@ -421,20 +500,22 @@ pub fn print_update_selection(
         }
     }
 
-    // 让用户选择要更新的包
+    // 让用户选择要更新的包；被 hold 拦下的包列出来但不预选
     let package_names: Vec<String> = packages_to_update.iter().map(|p| p.name.clone()).collect();
+    let preselected: Vec<bool> = packages_to_update.iter().map(|p| !p.held).collect();
 
     let selections = match MultiSelect::new()
         .with_prompt(language.get_text("select_packages"))
         .items(&package_names)
+        .defaults(&preselected)
         .interact()
     {
         Ok(choices) => choices,
         Err(e) => {
-            // 如果不是终端环境，默认选择所有包
+            // 如果不是终端环境，默认选择所有没被 hold 的包
             if e.to_string().contains("not a terminal") {
                 anstream::eprintln!("{}", language.get_text("no_interactive_mode").yellow());
-                (0..package_names.len()).collect()
+                (0..package_names.len()).filter(|&i| preselected[i]).collect()
             } else {
                 return Err(e.into());
             }
@@ -443,3 +524,129 @@ pub fn print_update_selection(
 
     Ok(selections)
 }
+
+/// `prune` 的多选：默认一个都不选（删包不可撤销）。非终端时同样什么都不选。
+pub fn print_prune_selection(
+    items: &[String],
+    language: Language,
+) -> Result<Vec<usize>, anyhow::Error> {
+    match MultiSelect::new()
+        .with_prompt(language.get_text("prune_select"))
+        .items(items)
+        .interact()
+    {
+        Ok(choices) => Ok(choices),
+        Err(e) if e.to_string().contains("not a terminal") => {
+            status_warn("Note", language.get_text("no_interactive_mode"));
+            Ok(Vec::new())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `cargo fresh list` 的表格，写到 stdout（它就是这个命令的输出，和
+/// `export` 一样）。列宽按去色的文本算好再上色；中文表头按两列宽计。
+pub fn print_inventory(entries: &[crate::package::inventory::InventoryEntry], language: Language) {
+    use crate::package::inventory::format_date;
+
+    let headers = [
+        "list_col_package",
+        "list_col_version",
+        "list_col_method",
+        "list_col_size",
+        "list_col_modified",
+        "list_col_bins",
+        "list_col_features",
+    ]
+    .map(|key| language.get_text(key).to_string());
+    let rows: Vec<[String; 7]> = entries
+        .iter()
+        .map(|entry| {
+            let marker = entry.source.marker();
+            let name = if marker.is_empty() {
+                entry.name.clone()
+            } else {
+                format!("{} {marker}", entry.name)
+            };
+            let features = entry.features_label();
+            [
+                name,
+                entry.version.clone().unwrap_or_else(|| "?".into()),
+                entry.method.map_or("-", |m| m.as_str()).to_string(),
+                indicatif::HumanBytes(entry.size).to_string(),
+                entry.modified.map_or_else(|| "-".into(), format_date),
+                entry.bins.join(", "),
+                if features.is_empty() {
+                    "-".into()
+                } else {
+                    features
+                },
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.clone().map(|h| text_width(&h));
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(text_width(cell));
+        }
+    }
+    // 最后一列不补空格，免得行尾拖一串空白
+    let pad = |cell: &str, i: usize| {
+        let fill = if i + 1 == widths.len() {
+            0
+        } else {
+            widths[i].saturating_sub(text_width(cell))
+        };
+        format!("{cell}{}", " ".repeat(fill))
+    };
+    let line = |cells: Vec<String>| cells.join("  ");
+
+    let header: Vec<String> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| pad(h, i).bold().to_string())
+        .collect();
+    anstream::println!("{}", line(header));
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let padded = pad(cell, i);
+                match i {
+                    0 => padded.cyan().to_string(),
+                    2 | 4 | 6 => padded.dimmed().to_string(),
+                    _ => padded,
+                }
+            })
+            .collect();
+        anstream::println!("{}", line(cells));
+    }
+}
+
+/// 秒数 → `45s` / `12m` / `5h` / `3d`, 取最大的整单位。
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        s if s >= 86_400 => format!("{}d", s / 86_400),
+        s if s >= 3_600 => format!("{}h", s / 3_600),
+        s if s >= 60 => format!("{}m", s / 60),
+        s => format!("{s}s"),
+    }
+}
+
+/// 终端显示宽度：CJK 与全角字符算两列，其余一列。
+fn text_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
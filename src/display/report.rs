@@ -0,0 +1,379 @@
+//! `--format json` 下检查 / 更新流程的最终报告。
+
+use crate::cli::Cli;
+use crate::models::{
+    JsonCheckError, JsonHold, JsonReport, JsonResult, JsonSkipped, JsonSummary,
+    JsonUpdateCandidate, PackageInfo, UpdateResult,
+};
+
+/// 纯函数：把整次运行的快照组装成 JSON 报告结构。
+/// 不做 I/O，便于单元测试；`emit_report` 负责真正写 stdout。
+pub fn build_report<'a>(
+    cli: &'a Cli,
+    packages: &'a [PackageInfo],
+    all_updates: &[&'a PackageInfo],
+    update_results: &'a [UpdateResult],
+    aborted: bool,
+    start: std::time::Instant,
+    selected: usize,
+) -> JsonReport<'a> {
+    let updates_available: Vec<JsonUpdateCandidate> = all_updates
+        .iter()
+        .filter_map(|p| {
+            p.latest_version
+                .as_deref()
+                .map(|latest| JsonUpdateCandidate {
+                    name: p.name.as_str(),
+                    current: p.current_version.as_deref(),
+                    latest,
+                    source: p.source.kind_str(),
+                    prerelease: p.is_prerelease(),
+                    prebuilt: p.prebuilt.map(|k| k.kind_str()),
+                    source_only_latest: p.source_only_latest.as_deref(),
+                    shadowed: &p.shadowed,
+                })
+        })
+        .collect();
+
+    let holds: Vec<JsonHold> = packages
+        .iter()
+        .filter_map(|p| {
+            p.hold.as_deref().map(|at| JsonHold {
+                name: p.name.as_str(),
+                at,
+                current: p.current_version.as_deref(),
+                latest: p.latest_version.as_deref(),
+                held: p.held,
+            })
+        })
+        .collect();
+
+    let fresh: Vec<&str> = packages
+        .iter()
+        .filter(|p| !p.has_update() && p.source.is_crates() && p.check_error.is_none())
+        .map(|p| p.name.as_str())
+        .collect();
+
+    let mut skipped: Vec<JsonSkipped> = packages
+        .iter()
+        .filter(|p| !p.source.is_crates())
+        .map(|p| JsonSkipped {
+            name: p.name.as_str(),
+            source: p.source.kind_str(),
+            reason_code: p.source.skip_reason_code(),
+            reason: "non-crates source: version check skipped",
+        })
+        .collect();
+    skipped.extend(update_results.iter().filter_map(|r| {
+        let reason = r.skipped?;
+        Some(JsonSkipped {
+            name: r.package_name.as_str(),
+            source: packages
+                .iter()
+                .find(|p| p.name == r.package_name)
+                .map_or("crates", |p| p.source.kind_str()),
+            reason_code: reason.code(),
+            reason: reason.reason(),
+        })
+    }));
+
+    let version_check_errors: Vec<JsonCheckError> = packages
+        .iter()
+        .filter_map(|p| {
+            p.check_error.as_ref().map(|e| JsonCheckError {
+                name: p.name.as_str(),
+                kind: e.kind.kind_str(),
+                error: e.message.as_str(),
+            })
+        })
+        .collect();
+
+    let results: Vec<JsonResult> = update_results
+        .iter()
+        .filter(|r| r.skipped.is_none())
+        .map(|r| JsonResult {
+            name: r.package_name.as_str(),
+            old_version: r.old_version.as_deref(),
+            new_version: r.new_version.as_deref(),
+            success: r.success,
+            install_method: r.install_method.json_str(),
+        })
+        .collect();
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+    let failed = results.iter().filter(|r| !r.success).count();
+
+    let summary = JsonSummary {
+        checked: packages.len(),
+        available: updates_available.len(),
+        selected,
+        attempted: results.len(),
+        succeeded,
+        failed,
+        skipped: skipped.len(),
+        check_errors: version_check_errors.len(),
+        duration_ms: start.elapsed().as_millis(),
+    };
+
+    JsonReport {
+        schema_version: 2,
+        format: "cargo-fresh-v1",
+        version: env!("CARGO_PKG_VERSION"),
+        include_prerelease: cli.include_prerelease,
+        dry_run: cli.dry_run,
+        registry_url: cli.registry_url.as_deref(),
+        updates_available,
+        fresh,
+        skipped,
+        version_check_errors,
+        holds,
+        results,
+        summary,
+        aborted,
+    }
+}
+
+/// 组装报告并写到 stdout。
+pub fn emit_report(
+    cli: &Cli,
+    packages: &[PackageInfo],
+    all_updates: &[&PackageInfo],
+    update_results: &[UpdateResult],
+    aborted: bool,
+    start: std::time::Instant,
+    selected: usize,
+) {
+    print_json(&build_report(
+        cli,
+        packages,
+        all_updates,
+        update_results,
+        aborted,
+        start,
+        selected,
+    ));
+}
+
+fn print_json(report: &JsonReport) {
+    // JSON 报告永远不需要颜色，但仍走 anstream::println! 以保持 stdout 通道一致
+    match serde_json::to_string(report) {
+        Ok(s) => anstream::println!("{}", s),
+        Err(e) => {
+            anstream::eprintln!(
+                "{{\"schema_version\":2,\"error\":\"failed to serialize report: {}\"}}",
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PackageSource, SkipReason, UpdateResult};
+    use clap::Parser;
+
+    fn empty_cli() -> Cli {
+        Cli::parse_from(["cargo-fresh"])
+    }
+
+    #[test]
+    fn build_report_counts_packages_and_sets_format() {
+        let cli = empty_cli();
+        let packages = vec![PackageInfo::with_source(
+            "ripgrep".into(),
+            Some("14.1.1".into()),
+            PackageSource::Crates,
+        )];
+        let report = build_report(
+            &cli,
+            &packages,
+            &[],
+            &[],
+            false,
+            std::time::Instant::now(),
+            0,
+        );
+        assert_eq!(report.schema_version, 2);
+        assert_eq!(report.format, "cargo-fresh-v1");
+        assert_eq!(report.summary.checked, 1);
+        assert_eq!(report.fresh, vec!["ripgrep"]);
+    }
+
+    #[test]
+    fn build_report_sets_skip_reason_code() {
+        let cli = empty_cli();
+        let packages = vec![PackageInfo::with_source(
+            "my-tool".into(),
+            Some("0.1.0".into()),
+            PackageSource::Git {
+                url: "u".into(),
+                rev: None,
+            },
+        )];
+        let report = build_report(
+            &cli,
+            &packages,
+            &[],
+            &[],
+            false,
+            std::time::Instant::now(),
+            0,
+        );
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].reason_code, "git_source");
+    }
+
+    #[test]
+    fn build_report_lists_holds_separately() {
+        let cli = empty_cli();
+        let mut held = PackageInfo::with_source(
+            "bevy_cli".into(),
+            Some("0.21.0".into()),
+            PackageSource::Crates,
+        );
+        held.latest_version = Some("0.22.1".into());
+        held.hold = Some("0.21".into());
+        held.held = true;
+        let packages = vec![held];
+        let updates: Vec<&PackageInfo> = packages.iter().collect();
+        let report = build_report(
+            &cli,
+            &packages,
+            &updates,
+            &[],
+            false,
+            std::time::Instant::now(),
+            0,
+        );
+        assert_eq!(report.holds.len(), 1);
+        assert_eq!(report.holds[0].at, "0.21");
+        assert!(report.holds[0].held);
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.updates_available.len(), 1);
+        assert!(crate::updater::unheld(&updates).is_empty());
+    }
+
+    #[test]
+    fn build_report_moves_prebuilt_only_skips_out_of_results() {
+        let cli = empty_cli();
+        let packages = vec![PackageInfo::with_source(
+            "ripgrep".into(),
+            Some("14.1.0".into()),
+            PackageSource::Crates,
+        )];
+        let results = vec![UpdateResult::skipped(
+            "ripgrep".into(),
+            Some("14.1.0".into()),
+            SkipReason::PrebuiltUnavailable,
+        )];
+        let report = build_report(
+            &cli,
+            &packages,
+            &[],
+            &results,
+            false,
+            std::time::Instant::now(),
+            1,
+        );
+        assert!(report.results.is_empty());
+        assert_eq!(report.summary.failed, 0);
+        assert_eq!(report.summary.skipped, 1);
+        assert_eq!(report.skipped[0].reason_code, "prebuilt_unavailable");
+        assert_eq!(report.skipped[0].source, "crates");
+    }
+
+    #[test]
+    fn build_report_excludes_check_error_packages_from_fresh() {
+        use crate::models::{CheckError, CheckErrorKind};
+
+        let cli = empty_cli();
+        let mut errored =
+            PackageInfo::with_source("bat".into(), Some("0.24.0".into()), PackageSource::Crates);
+        errored.check_error = Some(CheckError {
+            kind: CheckErrorKind::Unavailable,
+            message: "sparse index HTTP 503".into(),
+        });
+        let fresh_pkg = PackageInfo::with_source(
+            "ripgrep".into(),
+            Some("14.1.1".into()),
+            PackageSource::Crates,
+        );
+        let packages = vec![errored, fresh_pkg];
+
+        let report = build_report(
+            &cli,
+            &packages,
+            &[],
+            &[],
+            false,
+            std::time::Instant::now(),
+            0,
+        );
+
+        assert_eq!(report.fresh, vec!["ripgrep"]);
+        assert_eq!(report.version_check_errors.len(), 1);
+        assert_eq!(report.version_check_errors[0].name, "bat");
+        assert_eq!(report.version_check_errors[0].kind, "unavailable");
+        assert_eq!(report.summary.check_errors, 1);
+    }
+
+    #[test]
+    fn build_report_maps_prebuilt_to_json() {
+        // --check-prebuilt 探测出的 PrebuiltAvailability 必须落到 updates_available[].prebuilt
+        use crate::models::PrebuiltAvailability;
+        let cli = empty_cli();
+        let mut pkg = PackageInfo::with_source(
+            "cargo-deny".into(),
+            Some("0.19.6".into()),
+            PackageSource::Crates,
+        );
+        pkg.latest_version = Some("0.19.7".into());
+        pkg.prebuilt = Some(PrebuiltAvailability::Source);
+        let packages = vec![pkg];
+        let all_updates: Vec<&PackageInfo> = packages.iter().collect();
+        let report = build_report(
+            &cli,
+            &packages,
+            &all_updates,
+            &[],
+            false,
+            std::time::Instant::now(),
+            0,
+        );
+        assert_eq!(report.updates_available.len(), 1);
+        assert_eq!(report.updates_available[0].prebuilt, Some("source"));
+    }
+
+    #[test]
+    fn build_report_prebuilt_is_null_when_not_probed() {
+        // 没跑 --check-prebuilt 时 pkg.prebuilt 为 None,JSON 里应是 null
+        let cli = empty_cli();
+        let mut pkg = PackageInfo::with_source(
+            "ripgrep".into(),
+            Some("14.1.0".into()),
+            PackageSource::Crates,
+        );
+        pkg.latest_version = Some("14.1.1".into());
+        let packages = vec![pkg];
+        let all_updates: Vec<&PackageInfo> = packages.iter().collect();
+        let report = build_report(
+            &cli,
+            &packages,
+            &all_updates,
+            &[],
+            false,
+            std::time::Instant::now(),
+            0,
+        );
+        assert_eq!(report.updates_available[0].prebuilt, None);
+    }
+
+    #[test]
+    fn build_report_summary_has_selection_counts() {
+        let cli = empty_cli();
+        let report = build_report(&cli, &[], &[], &[], false, std::time::Instant::now(), 3);
+        assert_eq!(report.summary.selected, 3);
+        assert_eq!(report.summary.attempted, 0);
+    }
+}
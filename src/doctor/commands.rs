@@ -0,0 +1,70 @@
+//! `cargo fresh doctor` 的执行流程：逐项运行检查并按文本或 JSON 输出。
+
+use anyhow::Result;
+
+use crate::cli::Cli;
+use crate::display::{status, status_dim, status_err, status_warn};
+use crate::locale::Language;
+use crate::models::{EXIT_FAILED, EXIT_OK};
+
+use super::CheckStatus;
+
+/// `cargo fresh doctor`：逐项打印检查结果，有 fail 时退出码为 2。
+pub async fn run_doctor(cli: &Cli, json_mode: bool, language: Language) -> Result<i32> {
+    let checks = super::run(cli.registry_url.as_deref(), language).await;
+    let count = |status: CheckStatus| checks.iter().filter(|c| c.status == status).count();
+    let (pass, warn, fail) = (
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail),
+    );
+
+    if json_mode {
+        let items: Vec<serde_json::Value> = checks
+            .iter()
+            .map(|check| {
+                serde_json::json!({
+                    "id": check.id,
+                    "status": check.status,
+                    "detail": check.detail,
+                    "hint": check.hint.map(|h| language.get_text(h.locale_key())),
+                })
+            })
+            .collect();
+        anstream::println!(
+            "{}",
+            serde_json::json!({
+                "schema_version": 2,
+                "doctor": {
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "checks": items,
+                    "summary": { "pass": pass, "warn": warn, "fail": fail },
+                }
+            })
+        );
+    } else {
+        for check in &checks {
+            let line = format!("{}: {}", check.id, check.detail);
+            match check.status {
+                CheckStatus::Pass => status("Pass", &line),
+                CheckStatus::Warn => status_warn("Warn", &line),
+                CheckStatus::Fail => status_err("Fail", &line),
+            }
+            if let Some(hint) = check.hint {
+                status_dim("Hint", language.get_text(hint.locale_key()));
+            }
+        }
+        status(
+            "Finished",
+            &language.format_text(
+                "doctor_summary",
+                &[
+                    ("pass", &pass.to_string()),
+                    ("warn", &warn.to_string()),
+                    ("fail", &fail.to_string()),
+                ],
+            ),
+        );
+    }
+    Ok(if fail > 0 { EXIT_FAILED } else { EXIT_OK })
+}
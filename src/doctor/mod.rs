@@ -0,0 +1,489 @@
+//! `cargo fresh doctor`：把 bug 报告里总要问的几件事一次查完——工具链版本、
+//! `$CARGO_HOME` 布局、两个安装元数据文件是否一致、`bin/` 里的孤儿文件、
+//! HTTP / TLS、sparse index、GitHub token 与 API 配额、本机的 target 别名。
+//!
+//! 每项检查给出 pass / warn / fail，没通过时附一条 [`Hint`]（和顶层错误的
+//! `Hint` 行同一套文案）。检查之间互不依赖，一项失败不影响其余项。
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::errors::Hint;
+use crate::locale::Language;
+use crate::package::{bins, crates2, crates_toml};
+
+pub mod commands;
+
+/// 一项检查的结论。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// 一项检查：`id` 是稳定的英文标识（JSON 里也用它），`detail` 是给人看的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub hint: Option<Hint>,
+}
+
+impl Check {
+    fn pass(id: &'static str, detail: String) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Pass,
+            detail,
+            hint: None,
+        }
+    }
+
+    fn warn(id: &'static str, detail: String, hint: Option<Hint>) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Warn,
+            detail,
+            hint,
+        }
+    }
+
+    fn fail(id: &'static str, detail: String, hint: Option<Hint>) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Fail,
+            detail,
+            hint,
+        }
+    }
+}
+
+/// 按顺序跑完所有检查。`registry_url` 是 `--registry-url`（没给时用 cargo
+/// 配置里的 source replacement 或 crates.io）。
+pub async fn run(registry_url: Option<&str>, language: Language) -> Vec<Check> {
+    let mut checks = vec![
+        toolchain("cargo", Hint::InstallToolchain, CheckStatus::Fail).await,
+        toolchain("rustc", Hint::InstallToolchain, CheckStatus::Warn).await,
+    ];
+    match crate::package::registry::cargo_home() {
+        Some(home) => {
+            checks.push(cargo_home(&home, language));
+            checks.push(metadata(&home, language));
+            checks.push(orphans(&home, language));
+        }
+        None => checks.push(Check::fail(
+            "cargo-home",
+            language.get_text("doctor_cargo_home_unknown").to_string(),
+            None,
+        )),
+    }
+    match crate::package::http_client() {
+        Ok(client) => {
+            checks.push(Check::pass(
+                "http",
+                language.get_text("doctor_http_ok").to_string(),
+            ));
+            checks.push(registry(client, registry_url, language).await);
+            checks.push(token(language));
+            checks.push(rate_limit(client, language).await);
+        }
+        Err(e) => checks.push(Check::fail("http", format!("{e:#}"), Some(Hint::TlsInit))),
+    }
+    checks.push(targets(language));
+    checks
+}
+
+/// `cargo --version` / `rustc --version`。
+async fn toolchain(program: &'static str, hint: Hint, missing: CheckStatus) -> Check {
+    let output = tokio::process::Command::new(program)
+        .arg("--version")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await;
+    match output {
+        Ok(out) if out.status.success() => Check::pass(
+            program,
+            String::from_utf8_lossy(&out.stdout).trim().to_string(),
+        ),
+        Ok(out) => Check {
+            id: program,
+            status: missing,
+            detail: String::from_utf8_lossy(&out.stderr).trim().to_string(),
+            hint: Some(hint),
+        },
+        Err(e) => Check {
+            id: program,
+            status: missing,
+            detail: format!("{program}: {e}"),
+            hint: Some(hint),
+        },
+    }
+}
+
+/// `$CARGO_HOME` 与 `bin/` 是否存在，`bin/` 是否在 PATH 里。
+fn cargo_home(home: &Path, language: Language) -> Check {
+    let shown = home.display().to_string();
+    if !home.is_dir() {
+        return Check::fail(
+            "cargo-home",
+            language.format_text("doctor_path_missing", &[("path", &shown)]),
+            Some(Hint::InstallToolchain),
+        );
+    }
+    let bin = home.join("bin");
+    let bin_shown = bin.display().to_string();
+    let Ok(read) = std::fs::read_dir(&bin) else {
+        return Check::warn(
+            "cargo-home",
+            language.format_text("doctor_path_missing", &[("path", &bin_shown)]),
+            None,
+        );
+    };
+    let count = read.filter_map(Result::ok).count().to_string();
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    if !dir_in_path(&bin, &path) {
+        return Check::warn(
+            "cargo-home",
+            language.format_text("doctor_bin_not_in_path", &[("path", &bin_shown)]),
+            Some(Hint::CargoBinNotInPath),
+        );
+    }
+    Check::pass(
+        "cargo-home",
+        language.format_text(
+            "doctor_cargo_home_ok",
+            &[("path", &shown), ("count", &count)],
+        ),
+    )
+}
+
+/// `dir` 是否是 `path`（PATH 格式）里的一项。
+pub fn dir_in_path(dir: &Path, path: &std::ffi::OsStr) -> bool {
+    std::env::split_paths(path).any(|entry| bins::same_dir(&entry, dir))
+}
+
+/// `.crates.toml` 与 `.crates2.json` 的差异（按包名）。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordDiff {
+    pub only_in_toml: Vec<String>,
+    pub only_in_json: Vec<String>,
+    /// 两边都有但 PackageId 不同（版本或来源对不上）
+    pub differing: Vec<String>,
+}
+
+impl RecordDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_in_toml.is_empty() && self.only_in_json.is_empty() && self.differing.is_empty()
+    }
+}
+
+/// 纯函数：按包名比较两个文件里的 PackageId。
+pub fn diff_records(toml_keys: &[String], json_keys: &[String]) -> RecordDiff {
+    use std::collections::BTreeMap;
+
+    let by_name = |keys: &[String]| -> BTreeMap<String, String> {
+        keys.iter()
+            .map(|k| (k.split(' ').next().unwrap_or(k).to_string(), k.clone()))
+            .collect()
+    };
+    let toml = by_name(toml_keys);
+    let json = by_name(json_keys);
+    let mut diff = RecordDiff::default();
+    for (name, key) in &toml {
+        match json.get(name) {
+            None => diff.only_in_toml.push(name.clone()),
+            Some(other) if other != key => diff.differing.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.only_in_json = json
+        .keys()
+        .filter(|name| !toml.contains_key(*name))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// 两个元数据文件能否解析、是否一致，记录的 binary 是否都还在 `bin/` 下。
+fn metadata(home: &Path, language: Language) -> Check {
+    let read = |name: &str| match std::fs::read_to_string(home.join(name)) {
+        Ok(body) => Ok(Some(body)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("{name}: {e}")),
+    };
+    let (toml, json) = match (read(".crates.toml"), read(".crates2.json")) {
+        (Ok(toml), Ok(json)) => (toml, json),
+        (Err(e), _) | (_, Err(e)) => {
+            return Check::fail("metadata", e, Some(Hint::RepairMetadata));
+        }
+    };
+    let toml_entries = toml
+        .as_deref()
+        .map(crates_toml::entries)
+        .unwrap_or_default();
+    let json_entries = match json.as_deref().map(crates2::entries).transpose() {
+        Ok(entries) => entries.unwrap_or_default(),
+        Err(e) => {
+            return Check::fail("metadata", format!("{e:#}"), Some(Hint::RepairMetadata));
+        }
+    };
+
+    let keys = |entries: &[(String, Vec<String>)]| -> Vec<String> {
+        entries.iter().map(|(k, _)| k.clone()).collect()
+    };
+    let diff = diff_records(&keys(&toml_entries), &keys(&json_entries));
+    let mut problems = Vec::new();
+    let mut push = |key: &str, names: &[String]| {
+        if !names.is_empty() {
+            problems.push(language.format_text(key, &[("names", &names.join(", "))]));
+        }
+    };
+    push("doctor_only_in_toml", &diff.only_in_toml);
+    push("doctor_only_in_json", &diff.only_in_json);
+    push("doctor_records_differ", &diff.differing);
+
+    let bin = home.join("bin");
+    let mut missing: Vec<String> = toml_entries
+        .iter()
+        .flat_map(|(_, bins)| bins)
+        .filter(|b| !b.contains(['/', '\\']) && bin.join(b).symlink_metadata().is_err())
+        .cloned()
+        .collect();
+    missing.sort();
+    missing.dedup();
+    push("doctor_bins_missing", &missing);
+
+    if problems.is_empty() {
+        let count = toml_entries.len().to_string();
+        Check::pass(
+            "metadata",
+            language.format_text("doctor_metadata_ok", &[("count", &count)]),
+        )
+    } else {
+        Check::warn("metadata", problems.join("; "), Some(Hint::RepairMetadata))
+    }
+}
+
+/// `bin/` 下没有安装记录认领的文件和中断安装留下的临时文件。
+fn orphans(home: &Path, language: Language) -> Check {
+    let found = match bins::find_orphans(home, std::time::SystemTime::now()) {
+        Ok(found) => found,
+        Err(e) => return Check::fail("orphans", format!("{e:#}"), Some(Hint::RepairMetadata)),
+    };
+    if found.is_empty() {
+        return Check::pass(
+            "orphans",
+            language.get_text("doctor_orphans_none").to_string(),
+        );
+    }
+    let names: Vec<String> = found.iter().map(bins::Orphan::file_name).collect();
+    let temps = found
+        .iter()
+        .filter(|o| o.kind == bins::OrphanKind::StaleTemp)
+        .count();
+    Check::warn(
+        "orphans",
+        language.format_text(
+            "doctor_orphans",
+            &[
+                ("unowned", &(found.len() - temps).to_string()),
+                ("temp", &temps.to_string()),
+                ("names", &names.join(", ")),
+            ],
+        ),
+        Some(Hint::PruneOrphans),
+    )
+}
+
+/// sparse index 的 `config.json` 能否取到。
+async fn registry(
+    client: &reqwest::Client,
+    registry_url: Option<&str>,
+    language: Language,
+) -> Check {
+    let base = crate::package::registry::sparse_index_base(registry_url);
+    let url = format!("{base}/config.json");
+    match client.get(&url).send().await {
+        Ok(resp) if resp.status().is_success() => Check::pass(
+            "registry",
+            language.format_text("doctor_registry_ok", &[("url", &base)]),
+        ),
+        Ok(resp) => Check::fail(
+            "registry",
+            format!("{url}: HTTP {}", resp.status().as_u16()),
+            None,
+        ),
+        Err(e) => Check::fail(
+            "registry",
+            format!("{base}: {e}"),
+            Some(Hint::NetworkConnectTimeout),
+        ),
+    }
+}
+
+/// GitHub token 的来源（`env:GITHUB_TOKEN` / `env:GH_TOKEN` / `gh` / `none`）。
+fn token(language: Language) -> Check {
+    match crate::downloader::token::discover_token_source() {
+        "none" => Check::warn(
+            "token",
+            language.get_text("doctor_token_none").to_string(),
+            Some(Hint::GithubToken),
+        ),
+        source => Check::pass(
+            "token",
+            language.format_text("doctor_token_ok", &[("source", source)]),
+        ),
+    }
+}
+
+/// GitHub API 配额（`core`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    /// 配额重置的 Unix 秒
+    pub reset: u64,
+}
+
+impl RateLimit {
+    /// 剩余不到一成算紧张。
+    pub fn is_low(&self) -> bool {
+        self.remaining * 10 < self.limit
+    }
+}
+
+/// 纯函数：解析 `GET /rate_limit` 的响应体。
+pub fn parse_rate_limit(body: &str) -> Option<RateLimit> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let core = json.get("resources")?.get("core")?;
+    Some(RateLimit {
+        limit: core.get("limit")?.as_u64()?,
+        remaining: core.get("remaining")?.as_u64()?,
+        reset: core.get("reset")?.as_u64()?,
+    })
+}
+
+/// `GET https://api.github.com/rate_limit`（这个端点本身不消耗配额）。
+/// 连不上只算 warn：downloader 拿不到 release 时本来就会回退。
+async fn rate_limit(client: &reqwest::Client, language: Language) -> Check {
+    let mut req = client
+        .get("https://api.github.com/rate_limit")
+        .header("Accept", "application/vnd.github+json");
+    if let Some(token) = crate::downloader::token::discover_token() {
+        req = req.header("Authorization", format!("Bearer {token}"));
+    }
+    let body = match req.send().await {
+        Ok(resp) => resp.text().await.ok(),
+        Err(e) => {
+            return Check::warn("rate-limit", format!("api.github.com: {e}"), None);
+        }
+    };
+    let Some(limit) = body.as_deref().and_then(parse_rate_limit) else {
+        return Check::warn(
+            "rate-limit",
+            language.get_text("doctor_rate_unreadable").to_string(),
+            None,
+        );
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let minutes = limit.reset.saturating_sub(now).div_ceil(60).to_string();
+    let detail = language.format_text(
+        "doctor_rate_limit",
+        &[
+            ("remaining", &limit.remaining.to_string()),
+            ("limit", &limit.limit.to_string()),
+            ("minutes", &minutes),
+        ],
+    );
+    if limit.is_low() {
+        Check::warn("rate-limit", detail, Some(Hint::GithubToken))
+    } else {
+        Check::pass("rate-limit", detail)
+    }
+}
+
+/// 本机的 target 别名（含配置扩展）；为空时 downloader 一律回退源码构建。
+fn targets(language: Language) -> Check {
+    let targets = crate::downloader::resolve::current_targets();
+    if targets.is_empty() {
+        let host = format!(
+            "{}-{}",
+            crate::downloader::resolve::host_arch(),
+            std::env::consts::OS
+        );
+        return Check::warn(
+            "targets",
+            language.format_text("doctor_targets_none", &[("host", &host)]),
+            Some(Hint::UnknownTarget),
+        );
+    }
+    Check::pass("targets", targets.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_records_reports_each_kind_of_mismatch() {
+        let toml = [
+            "ripgrep 14.1.1 (registry+https://github.com/rust-lang/crates.io-index)".to_string(),
+            "just 1.30.0 (registry+https://github.com/rust-lang/crates.io-index)".to_string(),
+            "tokei 12.1.2 (registry+https://github.com/rust-lang/crates.io-index)".to_string(),
+        ];
+        let json = [
+            "ripgrep 14.1.1 (registry+https://github.com/rust-lang/crates.io-index)".to_string(),
+            "just 1.29.0 (registry+https://github.com/rust-lang/crates.io-index)".to_string(),
+            "bat 0.24.0 (registry+https://github.com/rust-lang/crates.io-index)".to_string(),
+        ];
+        let diff = diff_records(&toml, &json);
+        assert_eq!(diff.only_in_toml, ["tokei"]);
+        assert_eq!(diff.only_in_json, ["bat"]);
+        assert_eq!(diff.differing, ["just"]);
+        assert!(diff_records(&toml, &toml).is_empty());
+    }
+
+    #[test]
+    fn rate_limit_is_parsed_from_the_core_resource() {
+        let body = r#"{
+            "resources": {
+                "core": { "limit": 60, "remaining": 4, "reset": 1700000000, "used": 56 },
+                "search": { "limit": 10, "remaining": 10, "reset": 1700000000 }
+            },
+            "rate": { "limit": 60, "remaining": 4, "reset": 1700000000 }
+        }"#;
+        let limit = parse_rate_limit(body).unwrap();
+        assert_eq!(
+            limit,
+            RateLimit {
+                limit: 60,
+                remaining: 4,
+                reset: 1_700_000_000
+            }
+        );
+        assert!(limit.is_low());
+        assert!(!RateLimit {
+            remaining: 4_000,
+            limit: 5_000,
+            reset: 0
+        }
+        .is_low());
+        assert_eq!(parse_rate_limit(r#"{"message": "Bad credentials"}"#), None);
+    }
+
+    #[test]
+    fn cargo_bin_is_found_in_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let path = std::env::join_paths(["/usr/bin".as_ref(), bin.as_path()]).unwrap();
+        assert!(dir_in_path(&bin, &path));
+        assert!(!dir_in_path(&bin, std::ffi::OsStr::new("/usr/bin")));
+    }
+}
@@ -1,14 +1,55 @@
 //! 把下载的归档解压到临时目录, 定位 binary 路径。
 //!
-//! 支持 tar.gz / zip / 裸二进制三种格式。tar 流式读取避免一次性
+//! 支持 tar.gz / tar.xz / tar.zst / tar.bz2 / zip / 裸二进制。格式由
+//! [`detect_format`] 按文件头判断, 不信扩展名。tar 流式解压避免一次性
 //! 读入大文件内存。返回临时目录 + binary 在里面的相对路径。
+//!
+//! 归档来自网络, 按不可信输入处理: 只落盘名字 / 路径对得上 binary 候选的
+//! 条目, 不原样还原整棵树; 任何条目是绝对路径或含 `..`、链接指向解压目录
+//! 之外、总大小或条目数超过 [`ExtractLimits`], 整个归档都拒绝, 并以各自的
+//! `FailureKind` 失败 (`--debug` 可见)。需要的 binary 本身是链接时, 把链接
+//! 目标拷成普通文件, 磁盘上从不创建符号链接。
 
-use anyhow::{anyhow, Context, Result};
-use std::path::{Path, PathBuf};
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 
+use crate::config::ExtractConfig;
 use crate::downloader::events::{DownloaderError, FailureKind, UnsupportedReason};
 use crate::downloader::resolve::ArchiveFmt;
 
+/// 默认解压总字节数上限。最大的单 binary release 也就几百 MiB。
+pub const DEFAULT_MAX_SIZE: u64 = 2 << 30;
+/// 默认条目数上限。带文档 / 补全脚本的 release 也在几百条以内。
+pub const DEFAULT_MAX_ENTRIES: u64 = 10_000;
+
+/// 单个归档的解压上限, 防 zip / tar 炸弹。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    /// 所有条目 (含跳过不落盘的) 解压后的总字节数。
+    pub max_size: u64,
+    pub max_entries: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+impl ExtractLimits {
+    pub fn from_config(config: &ExtractConfig) -> Self {
+        Self {
+            max_size: config.max_size.unwrap_or(DEFAULT_MAX_SIZE),
+            max_entries: config.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES),
+        }
+    }
+}
+
 /// 解压结果。`_temp_dir` 通过 RAII 保活整段调用——caller 用完调 `Drop`
 /// (即丢弃 ExtractResult) 时整个临时目录会被 rm -rf。
 #[derive(Debug)]
@@ -24,6 +65,7 @@ pub struct ExtractResult {
 
 /// 解压 `archive_path` (本身在另一个临时目录里) 到一个新临时目录,
 /// 然后在里面挨个尝试 `bin_candidates` 里的 binary 名, 首个找到的胜出。
+/// 上限取自配置文件的 `[extract]`。
 ///
 /// 候选列表设计动机: 包名 != binary 名 (ripgrep -> rg, tauri-cli -> cargo-tauri)
 /// 时仅传 spec.name 会找不到文件 → 失败回 cargo install。
@@ -32,29 +74,58 @@ pub fn extract(
     archive_path: &Path,
     fmt: ArchiveFmt,
     bin_candidates: &[String],
+) -> Result<ExtractResult, DownloaderError> {
+    let limits = ExtractLimits::from_config(&crate::config::get().extract);
+    extract_with_bin_paths(archive_path, fmt, bin_candidates, &[], limits)
+}
+
+/// 同 [`extract`], 但先按 `bin_paths` (binary 名 → 归档内相对路径, 来自
+/// crate 声明的 `bin-dir`) 精确定位; 都不存在时再退回按名字搜索。
+pub fn extract_with_bin_paths(
+    archive_path: &Path,
+    fmt: ArchiveFmt,
+    bin_candidates: &[String],
+    bin_paths: &[(String, PathBuf)],
+    limits: ExtractLimits,
 ) -> Result<ExtractResult, DownloaderError> {
     if bin_candidates.is_empty() {
         return Err(DownloaderError::Unsupported(UnsupportedReason::UnknownArchiveFormat));
     }
     let temp_dir = tempfile::tempdir()
         .map_err(|e| failed_extract(anyhow!(e).context("mkdir tempdir for extract")))?;
+    let wanted = Wanted {
+        names: bin_candidates,
+        paths: bin_paths
+            .iter()
+            .filter_map(|(_, rel)| safe_rel_path(rel).ok())
+            .collect(),
+    };
 
     match fmt {
-        ArchiveFmt::TarGz => extract_targz(archive_path, temp_dir.path())
-            .map_err(|e| failed_extract(e.context("extract tar.gz")))?,
-        ArchiveFmt::Zip => extract_zip(archive_path, temp_dir.path())
+        ArchiveFmt::TarGz | ArchiveFmt::TarXz | ArchiveFmt::TarZst | ArchiveFmt::TarBz2 => {
+            extract_tar(archive_path, fmt, temp_dir.path(), &wanted, limits)
+                .map_err(|e| failed_extract(e.context(format!("extract {}", fmt.as_str()))))?;
+        }
+        ArchiveFmt::Zip => extract_zip(archive_path, temp_dir.path(), &wanted, limits)
             .map_err(|e| failed_extract(e.context("extract zip")))?,
         ArchiveFmt::Bin => {
             // 裸二进制: 直接拷过去, 第一个候选当文件名
             let dest = temp_dir.path().join(&bin_candidates[0]);
-            std::fs::copy(archive_path, &dest)
-                .map_err(|e| failed_extract(anyhow!(e).context("copy raw bin")))?;
-            #[cfg(unix)]
-            set_executable(&dest)
-                .map_err(|e| failed_extract(e.context("chmod +x bin")))?;
+            copy_raw_bin(archive_path, &dest, limits)
+                .map_err(|e| failed_extract(e.context("copy raw bin")))?;
         }
     }
 
+    for (name, rel) in bin_paths {
+        let binary_path = temp_dir.path().join(rel);
+        if binary_path.is_file() {
+            return Ok(ExtractResult {
+                temp_dir,
+                binary_path,
+                binary_name: name.clone(),
+            });
+        }
+    }
     for name in bin_candidates {
         if let Some(binary_path) = find_binary(temp_dir.path(), name) {
             return Ok(ExtractResult {
@@ -67,18 +138,332 @@ pub fn extract(
     Err(DownloaderError::Unsupported(UnsupportedReason::UnknownArchiveFormat))
 }
 
-fn extract_targz(archive: &Path, into: &Path) -> Result<()> {
-    let f = std::fs::File::open(archive).context("open archive")?;
-    let gz = flate2::read::GzDecoder::new(f);
-    let mut tar = tar::Archive::new(gz);
-    tar.unpack(into).context("tar.unpack")?;
+/// 按文件头的 magic bytes 判断格式。压缩 tar 按压缩算法认 (里面默认是
+/// tar), 可执行文件 (ELF / Mach-O / PE / shebang 脚本) 认作裸二进制;
+/// 认不出返回 None, 调用方退回声明或扩展名推断的格式。
+pub fn detect_format(path: &Path) -> std::io::Result<Option<ArchiveFmt>> {
+    let mut head = [0u8; 8];
+    let mut f = std::fs::File::open(path)?;
+    let mut n = 0;
+    while n < head.len() {
+        match f.read(&mut head[n..])? {
+            0 => break,
+            k => n += k,
+        }
+    }
+    Ok(sniff(&head[..n]))
+}
+
+fn sniff(head: &[u8]) -> Option<ArchiveFmt> {
+    match head {
+        [0x1f, 0x8b, ..] => Some(ArchiveFmt::TarGz),
+        [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(ArchiveFmt::TarXz),
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(ArchiveFmt::TarZst),
+        [b'B', b'Z', b'h', ..] => Some(ArchiveFmt::TarBz2),
+        [b'P', b'K', 0x03, 0x04, ..] | [b'P', b'K', 0x05, 0x06, ..] => Some(ArchiveFmt::Zip),
+        // ELF / Mach-O (32/64 位, 两种字节序, universal) / PE / shebang
+        [0x7f, b'E', b'L', b'F', ..]
+        | [0xcf | 0xce, 0xfa, 0xed, 0xfe, ..]
+        | [0xfe, 0xed, 0xfa, 0xcf | 0xce, ..]
+        | [0xca, 0xfe, 0xba, 0xbe, ..]
+        | [b'M', b'Z', ..]
+        | [b'#', b'!', ..] => Some(ArchiveFmt::Bin),
+        _ => None,
+    }
+}
+
+/// 哪些条目值得落盘: 文件名是 binary 候选之一, 或路径等于声明的 `bin-dir`。
+struct Wanted<'a> {
+    names: &'a [String],
+    paths: Vec<PathBuf>,
+}
+
+impl Wanted<'_> {
+    fn matches(&self, rel: &Path) -> bool {
+        self.paths.iter().any(|p| p == rel)
+            || rel
+                .file_name()
+                .and_then(|f| f.to_str())
+                .is_some_and(|f| self.names.iter().any(|n| n == f))
+    }
+}
+
+/// 拒绝原因。挂在 anyhow 链里, [`failed_extract`] 据此挑 `FailureKind`。
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+struct Rejected {
+    kind: FailureKind,
+    message: String,
+}
+
+fn reject(kind: FailureKind, message: String) -> anyhow::Error {
+    anyhow::Error::new(Rejected { kind, message })
+}
+
+/// 条目数与累计字节的记账, 超过 [`ExtractLimits`] 即拒绝。
+struct Budget {
+    limits: ExtractLimits,
+    entries: u64,
+    bytes: u64,
+}
+
+impl Budget {
+    fn new(limits: ExtractLimits) -> Self {
+        Self {
+            limits,
+            entries: 0,
+            bytes: 0,
+        }
+    }
+
+    fn entry(&mut self) -> Result<()> {
+        self.entries += 1;
+        if self.entries > self.limits.max_entries {
+            return Err(reject(
+                FailureKind::ArchiveTooManyEntries,
+                format!("archive has more than {} entries", self.limits.max_entries),
+            ));
+        }
+        Ok(())
+    }
+
+    fn charge(&mut self, bytes: u64) -> Result<()> {
+        self.bytes = self.bytes.saturating_add(bytes);
+        if self.bytes > self.limits.max_size {
+            return Err(reject(
+                FailureKind::ArchiveTooLarge,
+                format!("archive unpacks to more than {} bytes", self.limits.max_size),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 归档内路径 → 相对解压根的路径。绝对路径、盘符前缀与 `..` 一律拒绝,
+/// `./` 前缀去掉。
+fn safe_rel_path(raw: &Path) -> Result<PathBuf> {
+    let mut out = PathBuf::new();
+    for c in raw.components() {
+        match c {
+            Component::Normal(p) => out.push(p),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(reject(
+                    FailureKind::UnsafeArchivePath,
+                    format!("entry path escapes the extraction dir: {}", raw.display()),
+                ));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// 从 `base` (相对解压根) 出发解析链接目标; 绝对目标或 `..` 越过根即拒绝。
+/// 符号链接的 base 是链接所在目录, 硬链接是解压根。
+fn resolve_link(base: &Path, target: &Path) -> Result<PathBuf> {
+    let escape = || {
+        reject(
+            FailureKind::UnsafeArchiveLink,
+            format!(
+                "link {} -> {} points outside the extraction dir",
+                base.display(),
+                target.display()
+            ),
+        )
+    };
+    let mut out = base.to_path_buf();
+    for c in target.components() {
+        match c {
+            Component::Normal(p) => out.push(p),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return Err(escape());
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(escape()),
+        }
+    }
+    Ok(out)
+}
+
+/// 把 `from` 写到 `dest` (父目录按需创建), 最多 `max` 字节; 实际更长说明
+/// 条目头在撒谎, 按超限拒绝。
+fn write_entry(from: &mut impl Read, dest: &Path, max: u64, mode: Option<u32>) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("mkdir {}", parent.display()))?;
+    }
+    let mut out =
+        std::fs::File::create(dest).with_context(|| format!("create {}", dest.display()))?;
+    let written = std::io::copy(&mut from.take(max.saturating_add(1)), &mut out)
+        .with_context(|| format!("write {}", dest.display()))?;
+    if written > max {
+        return Err(reject(
+            FailureKind::ArchiveTooLarge,
+            format!("{} is larger than its header claims", dest.display()),
+        ));
+    }
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dest, std::fs::Permissions::from_mode(mode & 0o777))
+            .context("set_permissions")?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
     Ok(())
 }
 
-fn extract_zip(archive: &Path, into: &Path) -> Result<()> {
+/// 需要的 binary 是链接: 把目标文件拷到链接的位置。
+fn materialize_links(into: &Path, links: &[(PathBuf, PathBuf)]) -> Result<()> {
+    for (link, target) in links {
+        let target = into.join(target);
+        if !target.is_file() {
+            continue;
+        }
+        let dest = into.join(link);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).context("mkdir for link")?;
+        }
+        std::fs::copy(&target, &dest).with_context(|| format!("copy link {}", link.display()))?;
+    }
+    Ok(())
+}
+
+/// 压缩 tar 的解压流。
+fn tar_stream(archive: &Path, fmt: ArchiveFmt) -> Result<Box<dyn Read>> {
+    let f = std::io::BufReader::new(std::fs::File::open(archive).context("open archive")?);
+    Ok(match fmt {
+        ArchiveFmt::TarGz => Box::new(flate2::read::GzDecoder::new(f)),
+        ArchiveFmt::TarXz => Box::new(lzma_rust2::XzReader::new(f, true)),
+        ArchiveFmt::TarZst => Box::new(
+            ruzstd::decoding::StreamingDecoder::new(f).map_err(|e| anyhow!("zstd header: {e}"))?,
+        ),
+        ArchiveFmt::TarBz2 => Box::new(bzip2::read::MultiBzDecoder::new(f)),
+        ArchiveFmt::Zip | ArchiveFmt::Bin => bail!("{} is not a tar format", fmt.as_str()),
+    })
+}
+
+fn extract_tar(
+    archive: &Path,
+    fmt: ArchiveFmt,
+    into: &Path,
+    wanted: &Wanted,
+    limits: ExtractLimits,
+) -> Result<()> {
+    let links = tar_pass(archive, fmt, into, limits, |rel| wanted.matches(rel))?;
+    // 链接目标可能排在链接后面, 也可能本身不是候选: 没落盘的再扫一遍
+    let missing: Vec<&PathBuf> = links
+        .iter()
+        .map(|(_, target)| target)
+        .filter(|target| !into.join(target).is_file())
+        .collect();
+    if !missing.is_empty() {
+        tar_pass(archive, fmt, into, limits, |rel| missing.iter().any(|t| *t == rel))?;
+    }
+    materialize_links(into, &links)
+}
+
+/// 扫一遍 tar: 每个条目都校验路径 / 链接并记账, 只把 `want` 命中的普通
+/// 文件落盘。返回命中的链接 (链接路径, 解析后的目标)。
+fn tar_pass(
+    archive: &Path,
+    fmt: ArchiveFmt,
+    into: &Path,
+    limits: ExtractLimits,
+    want: impl Fn(&Path) -> bool,
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut budget = Budget::new(limits);
+    let mut tar = tar::Archive::new(tar_stream(archive, fmt)?);
+    let mut links = Vec::new();
+    for entry in tar.entries().context("read tar")? {
+        let mut entry = entry.context("read tar entry")?;
+        budget.entry()?;
+        let size = entry.size();
+        budget.charge(size)?;
+        let rel = safe_rel_path(&entry.path().context("tar entry path")?)?;
+        let kind = entry.header().entry_type();
+        if kind.is_symlink() || kind.is_hard_link() {
+            let target = entry
+                .link_name()
+                .context("tar link target")?
+                .ok_or_else(|| anyhow!("link {} has no target", rel.display()))?;
+            let base = if kind.is_symlink() {
+                rel.parent().unwrap_or(Path::new("")).to_path_buf()
+            } else {
+                PathBuf::new()
+            };
+            let resolved = resolve_link(&base, &target)?;
+            if want(&rel) {
+                links.push((rel, resolved));
+            }
+        } else if kind.is_file() && want(&rel) {
+            let mode = entry.header().mode().ok();
+            write_entry(&mut entry, &into.join(&rel), size, mode)?;
+        }
+    }
+    Ok(links)
+}
+
+fn extract_zip(archive: &Path, into: &Path, wanted: &Wanted, limits: ExtractLimits) -> Result<()> {
     let f = std::fs::File::open(archive).context("open archive")?;
     let mut z = zip::ZipArchive::new(f).context("read zip")?;
-    z.extract(into).context("zip.extract")?;
+    // 中央目录里就有条目数, 不用一条条数
+    if z.len() as u64 > limits.max_entries {
+        return Err(reject(
+            FailureKind::ArchiveTooManyEntries,
+            format!("archive has {} entries (limit {})", z.len(), limits.max_entries),
+        ));
+    }
+    let mut budget = Budget::new(limits);
+    let mut files: HashMap<PathBuf, usize> = HashMap::new();
+    let mut links = Vec::new();
+    for i in 0..z.len() {
+        let mut entry = z.by_index(i).context("read zip entry")?;
+        budget.entry()?;
+        let size = entry.size();
+        budget.charge(size)?;
+        let rel = safe_rel_path(Path::new(entry.name()))?;
+        if entry.is_symlink() {
+            // zip 的链接目标存在条目内容里
+            let mut target = String::new();
+            (&mut entry)
+                .take(4096)
+                .read_to_string(&mut target)
+                .context("read zip link target")?;
+            let base = rel.parent().unwrap_or(Path::new("")).to_path_buf();
+            let resolved = resolve_link(&base, Path::new(&target))?;
+            if wanted.matches(&rel) {
+                links.push((rel, resolved));
+            }
+        } else if entry.is_file() {
+            if wanted.matches(&rel) {
+                let mode = entry.unix_mode();
+                write_entry(&mut entry, &into.join(&rel), size, mode)?;
+            }
+            files.insert(rel, i);
+        }
+    }
+    for (_, target) in &links {
+        if into.join(target).is_file() {
+            continue;
+        }
+        if let Some(&i) = files.get(target) {
+            let mut entry = z.by_index(i).context("read zip entry")?;
+            let size = entry.size();
+            let mode = entry.unix_mode();
+            write_entry(&mut entry, &into.join(target), size, mode)?;
+        }
+    }
+    materialize_links(into, &links)
+}
+
+fn copy_raw_bin(archive: &Path, dest: &Path, limits: ExtractLimits) -> Result<()> {
+    let len = std::fs::metadata(archive).context("stat")?.len();
+    Budget::new(limits).charge(len)?;
+    std::fs::copy(archive, dest).context("copy")?;
+    #[cfg(unix)]
+    set_executable(dest).context("chmod +x bin")?;
     Ok(())
 }
 
@@ -119,11 +504,12 @@ fn find_binary(root: &Path, name: &str) -> Option<PathBuf> {
     walk(root, name, 0)
 }
 
+/// 拒绝类错误 ([`Rejected`]) 带着自己的 kind, 其余一律 `ExtractFailed`。
 fn failed_extract(e: anyhow::Error) -> DownloaderError {
-    DownloaderError::Failed {
-        kind: FailureKind::ExtractFailed,
-        source: e,
-    }
+    let kind = e
+        .downcast_ref::<Rejected>()
+        .map_or(FailureKind::ExtractFailed, |r| r.kind);
+    DownloaderError::Failed { kind, source: e }
 }
 
 #[cfg(test)]
@@ -178,6 +564,242 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn declared_bin_path_wins_over_search() {
+        let r = extract_with_bin_paths(
+            &fixture("ripgrep-like.tar.gz"),
+            ArchiveFmt::TarGz,
+            &["no-such-binary".into()],
+            &[("rg".into(), PathBuf::from("ripgrep-14.1.2-x86_64-apple-darwin/rg"))],
+            ExtractLimits::default(),
+        )
+        .expect("extract ok");
+        assert_eq!(r.binary_name, "rg");
+    }
+
+    /// 现场生成一个只含 `tool-1.0/tool` 的 tar, 再按 `fmt` 压缩。
+    fn tar_fixture(fmt: ArchiveFmt) -> tempfile::NamedTempFile {
+        let mut tar = tar::Builder::new(Vec::new());
+        let body = b"#!/bin/sh\necho tool\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(body.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        tar.append_data(&mut header, "tool-1.0/tool", &body[..]).unwrap();
+        let raw = tar.into_inner().unwrap();
+        let compressed = match fmt {
+            ArchiveFmt::TarGz => {
+                let mut enc =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                std::io::Write::write_all(&mut enc, &raw).unwrap();
+                enc.finish().unwrap()
+            }
+            ArchiveFmt::TarXz => {
+                let mut enc =
+                    lzma_rust2::XzWriter::new(Vec::new(), lzma_rust2::XzOptions::default()).unwrap();
+                std::io::Write::write_all(&mut enc, &raw).unwrap();
+                enc.finish().unwrap()
+            }
+            ArchiveFmt::TarZst => ruzstd::encoding::compress_to_vec(
+                &raw[..],
+                ruzstd::encoding::CompressionLevel::Fastest,
+            ),
+            ArchiveFmt::TarBz2 => {
+                let mut enc =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                std::io::Write::write_all(&mut enc, &raw).unwrap();
+                enc.finish().unwrap()
+            }
+            ArchiveFmt::Zip | ArchiveFmt::Bin => unreachable!(),
+        };
+        let f = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(f.path(), compressed).unwrap();
+        f
+    }
+
+    #[test]
+    fn extracts_every_compressed_tar_format() {
+        for fmt in [
+            ArchiveFmt::TarGz,
+            ArchiveFmt::TarXz,
+            ArchiveFmt::TarZst,
+            ArchiveFmt::TarBz2,
+        ] {
+            let archive = tar_fixture(fmt);
+            assert_eq!(detect_format(archive.path()).unwrap(), Some(fmt));
+            let r = extract(archive.path(), fmt, &["tool".into()])
+                .unwrap_or_else(|e| panic!("{}: {e}", fmt.as_str()));
+            assert!(r.binary_path.ends_with("tool-1.0/tool"));
+        }
+    }
+
+    #[test]
+    fn magic_bytes_identify_formats() {
+        assert_eq!(
+            detect_format(&fixture("cargo-deny-like.zip")).unwrap(),
+            Some(ArchiveFmt::Zip)
+        );
+        assert_eq!(
+            detect_format(&fixture("ripgrep-like.tar.gz")).unwrap(),
+            Some(ArchiveFmt::TarGz)
+        );
+        assert_eq!(sniff(b"\x7fELF\x02\x01\x01\x00"), Some(ArchiveFmt::Bin));
+        assert_eq!(sniff(&[0xcf, 0xfa, 0xed, 0xfe, 7, 0, 0, 1]), Some(ArchiveFmt::Bin));
+        assert_eq!(sniff(b"MZ\x90\x00"), Some(ArchiveFmt::Bin));
+        assert_eq!(sniff(b"#!/bin/sh"), Some(ArchiveFmt::Bin));
+        assert_eq!(sniff(b"<html>"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn raw_binary_is_installed_under_first_candidate() {
+        let f = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(f.path(), b"\x7fELF fake").unwrap();
+        let r = extract(f.path(), ArchiveFmt::Bin, &["tool".into()]).expect("extract ok");
+        assert_eq!(r.binary_name, "tool");
+        assert_eq!(std::fs::read(&r.binary_path).unwrap(), b"\x7fELF fake");
+    }
+
+    /// 手写 tar 头, 绕过 `tar::Builder` 对 `..` / 绝对路径的校验。
+    /// 条目: (路径, 类型, 链接目标, 内容)。
+    fn raw_targz(entries: &[(&str, tar::EntryType, &str, &[u8])]) -> tempfile::NamedTempFile {
+        let mut tar = tar::Builder::new(Vec::new());
+        for (path, kind, link, body) in entries {
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.as_old_mut().linkname[..link.len()].copy_from_slice(link.as_bytes());
+            header.set_entry_type(*kind);
+            header.set_size(body.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            tar.append(&header, *body).unwrap();
+        }
+        let raw = tar.into_inner().unwrap();
+        let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut enc, &raw).unwrap();
+        let f = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(f.path(), enc.finish().unwrap()).unwrap();
+        f
+    }
+
+    fn failure_kind(err: DownloaderError) -> FailureKind {
+        match err {
+            DownloaderError::Failed { kind, .. } => kind,
+            other => panic!("expected Failed, got {other:?}"),
+        }
+    }
+
+    const FILE: tar::EntryType = tar::EntryType::Regular;
+    const SYMLINK: tar::EntryType = tar::EntryType::Symlink;
+
+    #[test]
+    fn rejects_parent_and_absolute_entry_paths() {
+        for path in ["../tool", "tool-1.0/../../tool", "/tmp/tool"] {
+            let archive = raw_targz(&[(path, FILE, "", b"x")]);
+            let err = extract(archive.path(), ArchiveFmt::TarGz, &["tool".into()]).unwrap_err();
+            assert_eq!(failure_kind(err), FailureKind::UnsafeArchivePath, "{path}");
+        }
+    }
+
+    #[test]
+    fn rejects_links_pointing_outside() {
+        for (kind, target) in [
+            (SYMLINK, "../../etc/passwd"),
+            (SYMLINK, "/etc/passwd"),
+            (tar::EntryType::Link, "../etc/passwd"),
+        ] {
+            // 哪怕链接本身不是候选, 也整包拒绝
+            let archive = raw_targz(&[
+                ("tool-1.0/tool", FILE, "", b"bin"),
+                ("tool-1.0/evil", kind, target, b""),
+            ]);
+            let err = extract(archive.path(), ArchiveFmt::TarGz, &["tool".into()]).unwrap_err();
+            assert_eq!(failure_kind(err), FailureKind::UnsafeArchiveLink, "{target}");
+        }
+    }
+
+    #[test]
+    fn wanted_symlink_is_copied_as_a_regular_file() {
+        let archive = raw_targz(&[
+            ("tool-1.0/bin/tool", SYMLINK, "../libexec/tool-real", b""),
+            ("tool-1.0/libexec/tool-real", FILE, "", b"real bin"),
+        ]);
+        let r = extract(archive.path(), ArchiveFmt::TarGz, &["tool".into()]).expect("extract ok");
+        assert!(r.binary_path.ends_with("tool-1.0/bin/tool"));
+        let meta = std::fs::symlink_metadata(&r.binary_path).unwrap();
+        assert!(meta.file_type().is_file());
+        assert_eq!(std::fs::read(&r.binary_path).unwrap(), b"real bin");
+    }
+
+    #[test]
+    fn only_candidate_entries_are_written() {
+        let archive = raw_targz(&[
+            ("tool-1.0/README.md", FILE, "", b"docs"),
+            ("tool-1.0/tool", FILE, "", b"bin"),
+        ]);
+        let r = extract(archive.path(), ArchiveFmt::TarGz, &["tool".into()]).expect("extract ok");
+        assert!(r.binary_path.is_file());
+        assert!(!r.temp_dir.path().join("tool-1.0/README.md").exists());
+    }
+
+    #[test]
+    fn size_and_entry_caps_are_enforced() {
+        let archive = raw_targz(&[
+            ("tool-1.0/README.md", FILE, "", &[0u8; 4096]),
+            ("tool-1.0/tool", FILE, "", b"bin"),
+        ]);
+        let run = |limits| {
+            extract_with_bin_paths(archive.path(), ArchiveFmt::TarGz, &["tool".into()], &[], limits)
+        };
+        let tiny = ExtractLimits {
+            max_size: 1024,
+            ..ExtractLimits::default()
+        };
+        assert_eq!(failure_kind(run(tiny).unwrap_err()), FailureKind::ArchiveTooLarge);
+        let few = ExtractLimits {
+            max_entries: 1,
+            ..ExtractLimits::default()
+        };
+        assert_eq!(failure_kind(run(few).unwrap_err()), FailureKind::ArchiveTooManyEntries);
+        assert!(run(ExtractLimits::default()).is_ok());
+
+        let raw = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(raw.path(), [0u8; 2048]).unwrap();
+        let err = extract_with_bin_paths(raw.path(), ArchiveFmt::Bin, &["tool".into()], &[], tiny)
+            .unwrap_err();
+        assert_eq!(failure_kind(err), FailureKind::ArchiveTooLarge);
+    }
+
+    fn zip_with(entries: &[&str]) -> tempfile::NamedTempFile {
+        let f = tempfile::NamedTempFile::new().unwrap();
+        let mut w = zip::ZipWriter::new(std::fs::File::create(f.path()).unwrap());
+        for name in entries {
+            w.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut w, b"bin").unwrap();
+        }
+        w.finish().unwrap();
+        f
+    }
+
+    #[test]
+    fn zip_entries_are_checked_too() {
+        let archive = zip_with(&["tool-1.0/tool", "../evil"]);
+        let err = extract(archive.path(), ArchiveFmt::Zip, &["tool".into()]).unwrap_err();
+        assert_eq!(failure_kind(err), FailureKind::UnsafeArchivePath);
+
+        let archive = zip_with(&["a/tool", "b/readme", "c/license"]);
+        let few = ExtractLimits {
+            max_entries: 2,
+            ..ExtractLimits::default()
+        };
+        let err = extract_with_bin_paths(archive.path(), ArchiveFmt::Zip, &["tool".into()], &[], few)
+            .unwrap_err();
+        assert_eq!(failure_kind(err), FailureKind::ArchiveTooManyEntries);
+        let r = extract(archive.path(), ArchiveFmt::Zip, &["tool".into()]).expect("extract ok");
+        assert!(!r.temp_dir.path().join("b/readme").exists());
+    }
+
     #[test]
     fn temp_dir_cleaned_up_on_drop() {
         let r = extract(&fixture("mdbook-like.tar.gz"), ArchiveFmt::TarGz, &["mdbook".into()])
@@ -0,0 +1,669 @@
+//! 读取 crate 自己声明的 `[package.metadata.binstall]`。
+//!
+//! 很多工具的 release 文件名不符合 `resolve` 里猜的那 10 个模板, 但在
+//! Cargo.toml 里按 cargo-binstall 的约定写明了真实地址:
+//!
+//! ```toml
+//! [package.metadata.binstall]
+//! pkg-url = "{ repo }/releases/download/v{ version }/{ name }-{ target }{ archive-suffix }"
+//! pkg-fmt = "tgz"
+//! bin-dir = "{ name }-{ target }/{ bin }{ binary-ext }"
+//!
+//! [package.metadata.binstall.overrides.x86_64-pc-windows-msvc]
+//! pkg-fmt = "zip"
+//! ```
+//!
+//! sparse index 不带 Cargo.toml, 所以要从 registry 下载目标版本的 `.crate`
+//! (registry `config.json` 的 `dl` 模板), 在内存里解出 `{name}-{version}/Cargo.toml`。
+//! 结果按 (name, version) 进程内缓存——检查阶段的预检与真正安装共用一次下载。
+//! 全新安装 (`cargo fresh install`) 还从同一个 `.crate` 推出 bin target 名。
+//!
+//! 模板语法与 cargo-binstall 一致: `{ var }` (花括号内空白可有可无)。支持的变量:
+//! `name` `version` `repo` `target` `archive-format` / `format` `archive-suffix`
+//! `binary-ext` `target-family` `target-arch` `target-vendor` `target-libc`,
+//! `bin-dir` 里额外有 `bin`。出现未知变量 (或 `repo` 为空时用到 `repo`)
+//! 的模板视为不可用, 调用方照旧走猜测路径。
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures_util::StreamExt;
+use serde::Deserialize;
+
+use crate::downloader::resolve::{ArchiveFmt, CandidateUrl};
+
+/// `.crate` 下载的体积上限。crates.io 默认只收 10MiB 的包, 余量留给私有
+/// registry; 超过的整个放弃, 调用方照旧走猜测路径。
+pub const MAX_CRATE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// 单个 target 的覆盖项。字段与顶层同名, 缺省时沿用顶层。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BinstallOverride {
+    pub pkg_url: Option<String>,
+    pub pkg_fmt: Option<String>,
+    pub bin_dir: Option<String>,
+}
+
+/// `[package.metadata.binstall]` 里 cargo-fresh 用得到的部分。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BinstallMeta {
+    pub pkg_url: Option<String>,
+    pub pkg_fmt: Option<String>,
+    pub bin_dir: Option<String>,
+    /// target triple → 覆盖项。
+    pub overrides: BTreeMap<String, BinstallOverride>,
+}
+
+/// 渲染模板需要的包级上下文。
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateCtx<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    /// crates.io `repository` 字段 (尾随 `/` 和 `.git` 会被去掉)。
+    pub repo: Option<&'a str>,
+}
+
+/// 由声明的 `pkg-url` 渲染出的候选, 记住它对应的 target 以便之后渲染 `bin-dir`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclaredCandidate {
+    pub candidate: CandidateUrl,
+    pub target: String,
+}
+
+impl BinstallMeta {
+    fn pkg_url_for(&self, target: &str) -> Option<&str> {
+        self.overrides
+            .get(target)
+            .and_then(|o| o.pkg_url.as_deref())
+            .or(self.pkg_url.as_deref())
+    }
+
+    fn pkg_fmt_for(&self, target: &str) -> Option<&str> {
+        self.overrides
+            .get(target)
+            .and_then(|o| o.pkg_fmt.as_deref())
+            .or(self.pkg_fmt.as_deref())
+    }
+
+    fn bin_dir_for(&self, target: &str) -> Option<&str> {
+        self.overrides
+            .get(target)
+            .and_then(|o| o.bin_dir.as_deref())
+            .or(self.bin_dir.as_deref())
+    }
+
+    /// 按 target 别名顺序渲染声明的 `pkg-url`。`pkg-fmt` 省略时按 `tgz`;
+    /// cargo-fresh 还解不了的格式 (`txz` / `tzstd` / ...) 跳过该 target。
+    /// 同一格式的每种扩展名 (`tgz` → `.tgz` / `.tar.gz`) 各出一条候选。
+    pub fn candidates(&self, ctx: TemplateCtx<'_>, targets: &[String]) -> Vec<DeclaredCandidate> {
+        let mut out: Vec<DeclaredCandidate> = Vec::new();
+        for target in targets {
+            let Some(tmpl) = self.pkg_url_for(target) else {
+                continue;
+            };
+            let Some((fmt, exts)) = pkg_fmt(self.pkg_fmt_for(target).unwrap_or("tgz")) else {
+                continue;
+            };
+            for ext in exts {
+                let vars = vars(ctx, target, ext, None);
+                let Some(url) = render(tmpl, &vars) else {
+                    continue;
+                };
+                if out.iter().any(|c| c.candidate.url == url) {
+                    continue;
+                }
+                out.push(DeclaredCandidate {
+                    candidate: CandidateUrl {
+                        url,
+                        archive_fmt: fmt,
+                    },
+                    target: target.clone(),
+                });
+            }
+        }
+        out
+    }
+
+    /// 渲染 `bin-dir`: 每个 binary 名 → 它在归档里的相对路径。没声明
+    /// `bin-dir`、渲染失败或路径想跳出解压目录时不给该 binary 提示。
+    pub fn bin_paths(
+        &self,
+        ctx: TemplateCtx<'_>,
+        target: &str,
+        winning_url: &str,
+        bins: &[String],
+    ) -> Vec<(String, PathBuf)> {
+        let Some(tmpl) = self.bin_dir_for(target) else {
+            return Vec::new();
+        };
+        let ext = pkg_fmt(self.pkg_fmt_for(target).unwrap_or("tgz"))
+            .and_then(|(_, exts)| exts.iter().copied().find(|e| winning_url.ends_with(e)))
+            .unwrap_or("");
+        bins.iter()
+            .filter_map(|bin| {
+                let rel = render(tmpl, &vars(ctx, target, ext, Some(bin)))?;
+                let rel = PathBuf::from(rel.trim_start_matches("./"));
+                rel.components()
+                    .all(|c| matches!(c, std::path::Component::Normal(_)))
+                    .then(|| (bin.clone(), rel))
+            })
+            .collect()
+    }
+}
+
+/// binstall `pkg-fmt` → (归档格式, 该格式的扩展名们)。不支持的格式 (未压缩
+/// `tar`、`tzip` 等) 返回 None。
+fn pkg_fmt(fmt: &str) -> Option<(ArchiveFmt, &'static [&'static str])> {
+    match fmt {
+        "tgz" => Some((ArchiveFmt::TarGz, &[".tgz", ".tar.gz"])),
+        "txz" => Some((ArchiveFmt::TarXz, &[".txz", ".tar.xz"])),
+        "tzstd" => Some((ArchiveFmt::TarZst, &[".tzstd", ".tzst", ".tar.zst"])),
+        "tbz2" => Some((ArchiveFmt::TarBz2, &[".tbz2", ".tar.bz2"])),
+        "zip" => Some((ArchiveFmt::Zip, &[".zip"])),
+        "bin" => Some((ArchiveFmt::Bin, &["", ".bin"])),
+        _ => None,
+    }
+}
+
+fn vars<'a>(
+    ctx: TemplateCtx<'a>,
+    target: &'a str,
+    ext: &'a str,
+    bin: Option<&'a str>,
+) -> HashMap<&'static str, String> {
+    let mut v = HashMap::new();
+    v.insert("name", ctx.name.to_string());
+    v.insert("version", ctx.version.to_string());
+    if let Some(repo) = ctx.repo {
+        let repo = repo.trim_end_matches('/');
+        v.insert(
+            "repo",
+            repo.strip_suffix(".git").unwrap_or(repo).to_string(),
+        );
+    }
+    v.insert("target", target.to_string());
+    v.insert("archive-suffix", ext.to_string());
+    v.insert("archive-format", ext.trim_start_matches('.').to_string());
+    v.insert("format", ext.trim_start_matches('.').to_string());
+    let windows = target.contains("windows");
+    v.insert("binary-ext", if windows { ".exe" } else { "" }.to_string());
+    v.insert(
+        "target-family",
+        if windows { "windows" } else { "unix" }.to_string(),
+    );
+    let parts: Vec<&str> = target.split('-').collect();
+    v.insert("target-arch", parts[0].to_string());
+    if parts.len() >= 3 {
+        v.insert("target-vendor", parts[1].to_string());
+    }
+    if parts.len() >= 4 {
+        v.insert("target-libc", parts[3].to_string());
+    }
+    if let Some(bin) = bin {
+        v.insert("bin", bin.to_string());
+    }
+    v
+}
+
+/// 纯函数: 渲染 `{ var }` 模板。任何未定义变量或未闭合的 `{` 返回 None。
+pub fn render(tmpl: &str, vars: &HashMap<&'static str, String>) -> Option<String> {
+    let mut out = String::with_capacity(tmpl.len() + 32);
+    let mut rest = tmpl;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let close = after.find('}')?;
+        out.push_str(vars.get(after[..close].trim())?);
+        rest = &after[close + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// 纯函数: 从 Cargo.toml 正文里取 `[package.metadata.binstall]`。
+/// 没有该表 (绝大多数 crate) 返回 None; 表存在但形状不对也返回 None——
+/// 元数据写错不该让安装失败, 猜测路径照样能用。
+pub fn parse_manifest(body: &str) -> Option<BinstallMeta> {
+    let value: toml::Value = toml::from_str(body).ok()?;
+    let meta = value.get("package")?.get("metadata")?.get("binstall")?;
+    meta.clone().try_into().ok()
+}
+
+/// 从 `.crate` (tar.gz) 字节里取出顶层 `{name}-{version}/Cargo.toml`。
+pub fn cargo_toml_from_crate(bytes: &[u8], name: &str, version: &str) -> Result<String> {
+    let want = PathBuf::from(format!("{name}-{version}")).join("Cargo.toml");
+    let gz = flate2::read::GzDecoder::new(bytes);
+    let mut tar = tar::Archive::new(gz);
+    for entry in tar.entries().context("read .crate")? {
+        let mut entry = entry.context("read .crate entry")?;
+        if entry.path().context("entry path")? != want {
+            continue;
+        }
+        let mut body = String::new();
+        entry.read_to_string(&mut body).context("read Cargo.toml")?;
+        return Ok(body);
+    }
+    bail!("{} not found in .crate", want.display())
+}
+
+/// 从 `.crate` 字节里列出 bin target 名 (见 [`bin_targets`])。
+pub fn bins_from_crate(bytes: &[u8], name: &str, version: &str) -> Result<Vec<String>> {
+    let root = PathBuf::from(format!("{name}-{version}"));
+    let gz = flate2::read::GzDecoder::new(bytes);
+    let mut tar = tar::Archive::new(gz);
+    let mut manifest = None;
+    let mut files = Vec::new();
+    for entry in tar.entries().context("read .crate")? {
+        let mut entry = entry.context("read .crate entry")?;
+        let path = entry.path().context("entry path")?.into_owned();
+        let Ok(rel) = path.strip_prefix(&root) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if rel == "Cargo.toml" {
+            let mut body = String::new();
+            entry.read_to_string(&mut body).context("read Cargo.toml")?;
+            manifest = Some(body);
+        } else {
+            files.push(rel);
+        }
+    }
+    let manifest = manifest.ok_or_else(|| anyhow!("Cargo.toml not found in .crate"))?;
+    Ok(bin_targets(&manifest, &files, name))
+}
+
+/// 纯函数: 按 cargo 的规则推出 crate 的 bin target 名——显式 `[[bin]]`,
+/// 加上 `autobins` 没关时自动发现的 `src/main.rs` (包名)、`src/bin/x.rs` 和
+/// `src/bin/x/main.rs` (x)。`files` 是相对 crate 根目录的路径。
+pub fn bin_targets(manifest: &str, files: &[String], package_name: &str) -> Vec<String> {
+    let value: toml::Value =
+        toml::from_str(manifest).unwrap_or_else(|_| toml::Value::Table(Default::default()));
+    let mut names: Vec<String> = Vec::new();
+    let mut claimed_paths: Vec<String> = Vec::new();
+    for bin in value
+        .get("bin")
+        .and_then(|b| b.as_array())
+        .into_iter()
+        .flatten()
+    {
+        if let Some(name) = bin.get("name").and_then(|n| n.as_str()) {
+            names.push(name.to_string());
+        }
+        if let Some(path) = bin.get("path").and_then(|p| p.as_str()) {
+            claimed_paths.push(path.trim_start_matches("./").to_string());
+        }
+    }
+    let autobins = value
+        .get("package")
+        .and_then(|p| p.get("autobins"))
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(true);
+    if autobins {
+        for file in files {
+            let inferred = if file == "src/main.rs" {
+                Some(package_name)
+            } else if let Some(rest) = file.strip_prefix("src/bin/") {
+                match rest.split_once('/') {
+                    None => rest.strip_suffix(".rs"),
+                    Some((dir, "main.rs")) => Some(dir),
+                    Some(_) => None,
+                }
+            } else {
+                None
+            };
+            let Some(bin) = inferred else {
+                continue;
+            };
+            if !claimed_paths.contains(file) && !names.iter().any(|n| n == bin) {
+                names.push(bin.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// 下载 `.crate` 原始字节。每次 downloader 安装都会走到这里, 所以体积
+/// 有上限 ([`MAX_CRATE_BYTES`]): 先看 `Content-Length`, 再边读边数。
+async fn fetch_crate(
+    client: &reqwest::Client,
+    dl: &str,
+    name: &str,
+    version: &str,
+) -> Result<Vec<u8>> {
+    let url = crate::package::sparse_index::crate_download_url(dl, name, version)
+        .ok_or_else(|| anyhow!("unsupported dl template: {dl}"))?;
+    let resp = client.get(&url).send().await.context("GET .crate")?;
+    if !resp.status().is_success() {
+        bail!("GET {url}: HTTP {}", resp.status());
+    }
+    let too_big = || anyhow!("GET {url}: .crate is larger than {MAX_CRATE_BYTES} bytes");
+    if resp.content_length().is_some_and(|len| len > MAX_CRATE_BYTES) {
+        return Err(too_big());
+    }
+    let mut body = Vec::new();
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("read .crate body")?;
+        if (body.len() + chunk.len()) as u64 > MAX_CRATE_BYTES {
+            return Err(too_big());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// 下载 `.crate` 并解析元数据。`dl` 是 registry `config.json` 的 `dl` 模板。
+/// 网络 / 解包失败返回 Err, crate 没声明元数据返回 Ok(None)。
+pub async fn fetch_meta_from(
+    client: &reqwest::Client,
+    dl: &str,
+    name: &str,
+    version: &str,
+) -> Result<Option<BinstallMeta>> {
+    let bytes = fetch_crate(client, dl, name, version).await?;
+    let body = cargo_toml_from_crate(&bytes, name, version)?;
+    Ok(parse_manifest(&body))
+}
+
+/// 当前 registry (含 source replacement 镜像) 的 `dl` 模板, 进程内只取一次。
+async fn dl_template(client: &reqwest::Client) -> Option<&'static str> {
+    static DL: tokio::sync::OnceCell<Option<String>> = tokio::sync::OnceCell::const_new();
+    DL.get_or_init(|| async {
+        let base = crate::package::registry::sparse_index_base(None);
+        crate::package::sparse_index::fetch_dl_template(client, &base).await
+    })
+    .await
+    .as_deref()
+}
+
+/// 全新安装时还没有 `.crates2.json` 的 bins[] 可查, 从 `.crate` 推出 binary 名。
+/// 任何失败都返回空 Vec (调用方退回包名), 只打 `--debug`。
+pub async fn crate_bins(client: &reqwest::Client, name: &str, version: &str) -> Vec<String> {
+    let Some(dl) = dl_template(client).await else {
+        return Vec::new();
+    };
+    let bins = match fetch_crate(client, dl, name, version).await {
+        Ok(bytes) => bins_from_crate(&bytes, name, version),
+        Err(e) => Err(e),
+    };
+    match bins {
+        Ok(bins) => bins,
+        Err(e) => {
+            crate::display::status_debug("binstall", &format!("{name}: bin targets: {e:#}"));
+            Vec::new()
+        }
+    }
+}
+
+type MetaCache = Mutex<HashMap<(String, String), Option<Arc<BinstallMeta>>>>;
+
+/// 生产路径: 用当前 registry (含 source replacement 镜像) 取元数据, 按
+/// (name, version) 缓存。任何失败都当作"没声明", 只打 `--debug`。
+pub async fn lookup(
+    client: &reqwest::Client,
+    name: &str,
+    version: &str,
+) -> Option<Arc<BinstallMeta>> {
+    static CACHE: OnceLock<MetaCache> = OnceLock::new();
+
+    let cache = CACHE.get_or_init(MetaCache::default);
+    let key = (name.to_string(), version.to_string());
+    if let Some(hit) = cache
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(&key)
+    {
+        return hit.clone();
+    }
+
+    let meta = match dl_template(client).await {
+        Some(dl) => match fetch_meta_from(client, dl, name, version).await {
+            Ok(meta) => meta.map(Arc::new),
+            Err(e) => {
+                crate::display::status_debug("binstall", &format!("{name}: {e:#}"));
+                None
+            }
+        },
+        None => None,
+    };
+    crate::display::status_debug(
+        "binstall",
+        &format!(
+            "{name}@{version}: package.metadata.binstall {}",
+            if meta.is_some() { "found" } else { "absent" }
+        ),
+    );
+    cache
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(key, meta.clone());
+    meta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> TemplateCtx<'static> {
+        TemplateCtx {
+            name: "tool",
+            version: "1.2.3",
+            repo: Some("https://github.com/o/tool.git"),
+        }
+    }
+
+    fn targets(ts: &[&str]) -> Vec<String> {
+        ts.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn render_accepts_spaced_and_tight_braces() {
+        let v = vars(ctx(), "x86_64-unknown-linux-gnu", ".tgz", None);
+        assert_eq!(
+            render(
+                "{ repo }/v{version}/{ name }-{target}{ archive-suffix }",
+                &v
+            )
+            .as_deref(),
+            Some("https://github.com/o/tool/v1.2.3/tool-x86_64-unknown-linux-gnu.tgz")
+        );
+        assert_eq!(
+            render("{target-arch}-{target-vendor}-{target-libc}", &v).as_deref(),
+            Some("x86_64-unknown-gnu")
+        );
+    }
+
+    #[test]
+    fn render_rejects_unknown_or_unclosed() {
+        let v = vars(ctx(), "x86_64-unknown-linux-gnu", ".tgz", None);
+        assert_eq!(render("{nope}", &v), None);
+        assert_eq!(render("{name", &v), None);
+        // bin 只在 bin-dir 里定义
+        assert_eq!(render("{bin}", &v), None);
+    }
+
+    #[test]
+    fn parse_manifest_reads_table_and_overrides() {
+        let meta = parse_manifest(
+            r#"
+[package]
+name = "tool"
+version = "1.2.3"
+
+[package.metadata.binstall]
+pkg-url = "{ repo }/releases/download/v{ version }/tool-{ target }{ archive-suffix }"
+bin-dir = "tool-{ target }/{ bin }{ binary-ext }"
+
+[package.metadata.binstall.overrides.x86_64-pc-windows-msvc]
+pkg-fmt = "zip"
+"#,
+        )
+        .unwrap();
+        assert_eq!(meta.pkg_fmt, None);
+        assert_eq!(
+            meta.overrides["x86_64-pc-windows-msvc"].pkg_fmt.as_deref(),
+            Some("zip")
+        );
+    }
+
+    #[test]
+    fn manifest_without_binstall_is_none() {
+        assert_eq!(parse_manifest("[package]\nname = \"x\"\n"), None);
+        assert_eq!(parse_manifest("not toml ["), None);
+    }
+
+    #[test]
+    fn candidates_use_default_tgz_extensions() {
+        let meta = BinstallMeta {
+            pkg_url: Some(
+                "{ repo }/releases/download/v{ version }/tool-{ target }{ archive-suffix }".into(),
+            ),
+            ..BinstallMeta::default()
+        };
+        let c = meta.candidates(ctx(), &targets(&["x86_64-unknown-linux-gnu"]));
+        let urls: Vec<&str> = c.iter().map(|c| c.candidate.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://github.com/o/tool/releases/download/v1.2.3/tool-x86_64-unknown-linux-gnu.tgz",
+                "https://github.com/o/tool/releases/download/v1.2.3/tool-x86_64-unknown-linux-gnu.tar.gz",
+            ]
+        );
+        assert!(c
+            .iter()
+            .all(|c| c.candidate.archive_fmt == ArchiveFmt::TarGz));
+    }
+
+    #[test]
+    fn overrides_apply_per_target_and_unsupported_fmt_is_skipped() {
+        let mut meta = BinstallMeta {
+            pkg_url: Some("https://dl.example/{ target }{ archive-suffix }".into()),
+            pkg_fmt: Some("tar".into()),
+            ..BinstallMeta::default()
+        };
+        meta.overrides.insert(
+            "aarch64-apple-darwin".into(),
+            BinstallOverride {
+                pkg_fmt: Some("zip".into()),
+                ..BinstallOverride::default()
+            },
+        );
+        let c = meta.candidates(
+            ctx(),
+            &targets(&["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"]),
+        );
+        assert_eq!(c.len(), 1);
+        assert_eq!(
+            c[0].candidate.url,
+            "https://dl.example/aarch64-apple-darwin.zip"
+        );
+        assert_eq!(c[0].target, "aarch64-apple-darwin");
+    }
+
+    #[test]
+    fn repo_template_without_repo_yields_nothing() {
+        let meta = BinstallMeta {
+            pkg_url: Some("{ repo }/x{ archive-suffix }".into()),
+            ..BinstallMeta::default()
+        };
+        let c = meta.candidates(
+            TemplateCtx {
+                repo: None,
+                ..ctx()
+            },
+            &targets(&["x86_64-unknown-linux-gnu"]),
+        );
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn bin_paths_render_and_reject_escapes() {
+        let meta = BinstallMeta {
+            bin_dir: Some("{ name }-{ target }/bin/{ bin }{ binary-ext }".into()),
+            ..BinstallMeta::default()
+        };
+        let paths = meta.bin_paths(
+            ctx(),
+            "x86_64-unknown-linux-gnu",
+            "https://x/tool.tar.gz",
+            &["tool".into()],
+        );
+        assert_eq!(
+            paths,
+            [(
+                "tool".to_string(),
+                PathBuf::from("tool-x86_64-unknown-linux-gnu/bin/tool")
+            )]
+        );
+
+        let evil = BinstallMeta {
+            bin_dir: Some("../{ bin }".into()),
+            ..BinstallMeta::default()
+        };
+        assert!(evil
+            .bin_paths(ctx(), "x86_64-unknown-linux-gnu", "u.tgz", &["tool".into()])
+            .is_empty());
+    }
+
+    #[test]
+    fn cargo_toml_is_read_from_crate_tarball() {
+        let mut builder = tar::Builder::new(Vec::new());
+        let body = b"[package]\nname = \"tool\"\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(body.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "tool-1.2.3/Cargo.toml", &body[..])
+            .unwrap();
+        let tarball = builder.into_inner().unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        std::io::Write::write_all(&mut gz, &tarball).unwrap();
+        let bytes = gz.finish().unwrap();
+
+        let got = cargo_toml_from_crate(&bytes, "tool", "1.2.3").unwrap();
+        assert!(got.contains("name = \"tool\""));
+        assert!(cargo_toml_from_crate(&bytes, "tool", "9.9.9").is_err());
+    }
+
+    #[test]
+    fn bin_targets_follow_cargo_discovery_rules() {
+        let files: Vec<String> = [
+            "src/main.rs",
+            "src/bin/extra.rs",
+            "src/bin/tool/main.rs",
+            "src/lib.rs",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        let manifest = "[package]\nname = \"ripgrep\"\n";
+        assert_eq!(
+            bin_targets(manifest, &files, "ripgrep"),
+            ["extra", "ripgrep", "tool"]
+        );
+
+        // 显式 [[bin]] 改名 src/main.rs, autobins 关掉后 src/bin 不再自动发现
+        let manifest = r#"
+[package]
+name = "ripgrep"
+autobins = false
+
+[[bin]]
+name = "rg"
+path = "src/main.rs"
+"#;
+        assert_eq!(bin_targets(manifest, &files, "ripgrep"), ["rg"]);
+
+        // 纯库 crate
+        let lib_only = bin_targets("[package]\nname = \"lib\"\n", &["src/lib.rs".into()], "lib");
+        assert!(lib_only.is_empty());
+    }
+}
@@ -0,0 +1,498 @@
+//! `cargo fresh bundle` / `--from-bundle` 的执行流程：选中的更新打进离线包，
+//! 或从离线包里装回来，打印状态行并输出 JSON 报告。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::*;
+
+use crate::cli::Cli;
+use crate::config::InstallPolicy;
+use crate::display::report::emit_report;
+use crate::display::{
+    print_results, print_update_selection, print_update_summary, status, status_dim, status_err,
+    status_warn,
+};
+use crate::locale::Language;
+use crate::models::{
+    PackageInfo, PackageSource, SkipReason, UpdateResult, EXIT_ABORTED, EXIT_FAILED, EXIT_OK,
+    EXIT_UPDATES_AVAILABLE,
+};
+use crate::package::{
+    exclude_packages, filter_packages, get_installed_packages, is_stable_version,
+};
+use crate::updater::{note_self_update, record_install, self_replace, unheld};
+
+use super::{bundle_package, is_tarball_path, pack_tarball, BundleWriter, OpenedBundle};
+
+/// `cargo fresh bundle` 的参数, 从子命令里取出来留到检查流程之后用。
+pub struct BundleRequest {
+    pub output: std::path::PathBuf,
+    pub target: Option<String>,
+    pub all: bool,
+}
+
+/// `cargo fresh bundle <OUTPUT>`: 把选中更新的预编译归档收进离线包。
+/// 拿不到预编译产物的包记为跳过——离线机器上没法源码构建, 这里不回退
+/// cargo install; 有跳过时退出码 2, 脚本能发现包不完整。`--target` 时
+/// 按那个 triple 找归档, 本机的 `~/.cargo/bin` 完全不动。
+pub async fn run_bundle(
+    request: &BundleRequest,
+    selected: &[&PackageInfo],
+    cancel: &Arc<AtomicBool>,
+    language: Language,
+) -> Result<i32> {
+    use crate::downloader::events::{DownloaderError, FailureKind};
+    use crate::downloader::resolve;
+
+    let output = request.output.as_path();
+    let targets = match &request.target {
+        Some(triple) => resolve::cross_targets(triple),
+        None => resolve::current_targets(),
+    };
+    let Some(target) = targets.first().cloned() else {
+        anyhow::bail!("no prebuilt target aliases for this platform; pass --target <TRIPLE>");
+    };
+    let tarball = is_tarball_path(output);
+    if tarball && output.exists() {
+        anyhow::bail!("{} already exists", output.display());
+    }
+    // tarball 先在临时目录里攒, 全部完成后一次性打包
+    let staging = if tarball {
+        Some(tempfile::tempdir()?)
+    } else {
+        None
+    };
+    let root = staging
+        .as_ref()
+        .map_or_else(|| output.to_path_buf(), |d| d.path().to_path_buf());
+    let mut writer = BundleWriter::create(&root, targets)?;
+
+    if !crate::display::is_json_mode() {
+        anstream::eprintln!();
+    }
+    let mut skipped: Vec<serde_json::Value> = Vec::new();
+    let mut no_prebuilt: Vec<&str> = Vec::new();
+    let mut aborted = false;
+    for pkg in selected {
+        if cancel.load(Ordering::SeqCst) {
+            aborted = true;
+            break;
+        }
+        // --all 时没有更新的包取 latest (== current), 缺 latest 时退回 current
+        let Some(version) = pkg
+            .latest_version
+            .as_deref()
+            .or(pkg.current_version.as_deref())
+        else {
+            continue;
+        };
+        let downloadable = matches!(pkg.source, PackageSource::Crates)
+            && pkg.install_opts.as_ref().is_none_or(|o| o.is_default());
+        if !downloadable {
+            status_warn(
+                "Skipped",
+                &language.format_text("bundle_needs_downloader", &[("name", &pkg.name)]),
+            );
+            skipped.push(serde_json::json!({
+                "name": pkg.name,
+                "version": version,
+                "reason": "source-build-required",
+            }));
+            continue;
+        }
+        status("Bundling", &format!("{} v{version} ({target})", pkg.name));
+        match bundle_package(&mut writer, &pkg.name, version, cancel).await {
+            Ok(entry) => {
+                status_dim("Archive", &entry.source_url);
+            }
+            Err(DownloaderError::Cancelled) => {
+                aborted = true;
+                break;
+            }
+            Err(e) => {
+                // 不支持 / 所有候选都不中 = 这个 target 没有预编译; 其余是下载或校验出错
+                let reason = match &e {
+                    DownloaderError::Unsupported(_)
+                    | DownloaderError::Failed {
+                        kind: FailureKind::AllUrlsFailed,
+                        ..
+                    } => {
+                        no_prebuilt.push(&pkg.name);
+                        "no-prebuilt"
+                    }
+                    _ => "download-failed",
+                };
+                status_warn("Skipped", &format!("{}: {e}", pkg.name));
+                skipped.push(serde_json::json!({
+                    "name": pkg.name,
+                    "version": version,
+                    "reason": reason,
+                    "detail": e.to_string(),
+                }));
+            }
+        }
+    }
+
+    let manifest = writer.finish()?;
+    if let Some(staging) = &staging {
+        pack_tarball(staging.path(), output)?;
+    }
+
+    let path = output.display().to_string();
+    if crate::display::is_json_mode() {
+        anstream::println!(
+            "{}",
+            serde_json::json!({
+                "schema_version": 2,
+                "bundle": {
+                    "path": path,
+                    "format": if tarball { "tar.gz" } else { "directory" },
+                    "target": target,
+                    "targets": manifest.targets,
+                    "packages": manifest.packages,
+                    "skipped": skipped,
+                    "aborted": aborted,
+                },
+            })
+        );
+    } else {
+        if !no_prebuilt.is_empty() {
+            status_warn(
+                "Note",
+                &language.format_text(
+                    "bundle_no_prebuilt",
+                    &[("target", &target), ("names", &no_prebuilt.join(", "))],
+                ),
+            );
+        }
+        if !skipped.is_empty() {
+            status_warn(
+                "Note",
+                &language.format_text(
+                    "bundle_skipped_summary",
+                    &[("count", &skipped.len().to_string())],
+                ),
+            );
+        }
+        status(
+            "Finished",
+            &language.format_text(
+                "bundle_summary",
+                &[
+                    ("count", &manifest.packages.len().to_string()),
+                    ("path", &path),
+                ],
+            ),
+        );
+    }
+
+    Ok(if aborted {
+        EXIT_ABORTED
+    } else if !skipped.is_empty() {
+        EXIT_FAILED
+    } else {
+        EXIT_OK
+    })
+}
+
+/// `--from-bundle <PATH>`: 离线机器上从离线包安装, 全程不联网。
+///
+/// 只升级本机已安装且版本低于包内版本的 crates.io 包——和普通 downloader
+/// 安装一样只更新既有的 `.crates.toml` / `.crates2.json` 条目。自定义
+/// features、`install = "source-only"` 与配置里 `exclude = true` 的包不装，
+/// 记为跳过（见 [`bundle_skip_reason`]）。选包规则、`--dry-run`、汇总、
+/// JSON 报告与退出码都与普通检查流程一致。
+pub async fn run_from_bundle(
+    cli: &Cli,
+    path: &std::path::Path,
+    cancel: &Arc<AtomicBool>,
+    language: Language,
+) -> Result<i32> {
+    use crate::models::InstallMethod;
+
+    let run_start = std::time::Instant::now();
+    let json_mode = crate::display::is_json_mode();
+    status("Opening", &path.display().to_string());
+    let bundle = OpenedBundle::open(path)?;
+    let local_targets = crate::downloader::resolve::current_targets();
+    if !bundle.matches_targets(&local_targets) {
+        anyhow::bail!(
+            "bundle was built for {}, this machine is {}",
+            bundle
+                .manifest
+                .targets
+                .first()
+                .map_or("<unknown>", String::as_str),
+            local_targets.first().map_or("<unknown>", String::as_str)
+        );
+    }
+    status(
+        "Found",
+        &language.format_text(
+            "from_bundle_opened",
+            &[
+                ("count", &bundle.manifest.packages.len().to_string()),
+                ("by", &bundle.manifest.created_by),
+            ],
+        ),
+    );
+
+    let mut installed = get_installed_packages().await?;
+    let installed_names: std::collections::HashSet<String> = installed
+        .iter()
+        .filter(|p| matches!(p.source, PackageSource::Crates))
+        .map(|p| p.name.clone())
+        .collect();
+    if let Some(filter_pattern) = &cli.filter {
+        filter_packages(&mut installed, filter_pattern)?;
+    }
+    if !cli.exclude.is_empty() {
+        exclude_packages(&mut installed, &cli.exclude)?;
+    }
+
+    // 包内版本当作"最新版本", 之后的 has_update / 分组 / 打印全部沿用
+    let config = crate::config::get();
+    let mut packages: Vec<PackageInfo> = Vec::new();
+    let mut update_results: Vec<UpdateResult> = Vec::new();
+    for entry in &bundle.manifest.packages {
+        let found = installed
+            .iter()
+            .position(|p| p.name == entry.name && matches!(p.source, PackageSource::Crates));
+        let Some(pos) = found else {
+            // 被 --filter / --exclude 排除的包不算"未安装"
+            if !installed_names.contains(&entry.name) {
+                status_warn(
+                    "Skipped",
+                    &language.format_text(
+                        "from_bundle_not_installed",
+                        &[("name", &entry.name), ("version", &entry.version)],
+                    ),
+                );
+            }
+            continue;
+        };
+        let mut pkg = installed.swap_remove(pos);
+        pkg.latest_version = Some(entry.version.clone());
+        // 装不了的包不进候选, 直接记为跳过——不能拿预编译归档静默顶替
+        if let Some(reason) = bundle_skip_reason(cli, config, &pkg) {
+            status_warn(
+                "Skipped",
+                &format!("{} ({})", pkg.name, language.get_text(reason.locale_key())),
+            );
+            update_results.push(UpdateResult::skipped(
+                pkg.name.clone(),
+                pkg.current_version.clone(),
+                reason,
+            ));
+            continue;
+        }
+        packages.push(pkg);
+    }
+    // 配置里排除的包和普通检查流程一样不算"有更新待应用"
+    let gated_pending = update_results
+        .iter()
+        .any(|r| r.skipped != Some(SkipReason::Excluded));
+    crate::state::load()?.apply_holds(&mut packages);
+
+    let stable_updates: Vec<&PackageInfo> = packages
+        .iter()
+        .filter(|p| {
+            p.has_update()
+                && p.latest_version
+                    .as_ref()
+                    .is_some_and(|v| is_stable_version(v))
+        })
+        .collect();
+    let prerelease_updates: Vec<&PackageInfo> = packages
+        .iter()
+        .filter(|p| p.has_update() && p.is_prerelease())
+        .collect();
+    let mut all_updates = stable_updates.clone();
+    all_updates.extend(prerelease_updates.clone());
+
+    if all_updates.is_empty() {
+        status("Finished", language.get_text("from_bundle_nothing"));
+        if json_mode {
+            emit_report(cli, &packages, &[], &update_results, false, run_start, 0);
+        }
+        return Ok(if gated_pending {
+            EXIT_UPDATES_AVAILABLE
+        } else {
+            EXIT_OK
+        });
+    }
+
+    print_results(&packages, cli.updates_only, language);
+
+    let mut selections: Vec<usize> = if cli.batch {
+        unheld(&all_updates)
+    } else if json_mode || cli.no_interactive {
+        Vec::new()
+    } else {
+        print_update_selection(&stable_updates, &prerelease_updates, language)?
+    };
+    // 和普通更新一样, 替换自己放到最后
+    self_replace::schedule_last(&mut selections, |&i| all_updates[i].name.as_str());
+
+    let gated = update_results.len();
+    let mut aborted = false;
+    if selections.is_empty() {
+        status_dim("Note", language.get_text("no_packages_selected"));
+    } else {
+        if !json_mode {
+            anstream::eprintln!();
+            if cli.dry_run {
+                status("Dry run", language.get_text("dry_run_summary"));
+            } else {
+                status("Updating", language.get_text("starting_update"));
+            }
+        }
+        // 离线包里只有本地文件, 安装很快, 顺序执行即可
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        for &index in &selections {
+            if cancel.load(Ordering::SeqCst) {
+                aborted = true;
+                break;
+            }
+            let pkg = all_updates[index];
+            let Some(entry) = bundle.manifest.packages.iter().find(|e| e.name == pkg.name) else {
+                continue;
+            };
+            let old = pkg.current_version.clone();
+            let arrow = format!(
+                "{} {} → {}",
+                pkg.name,
+                old.as_deref().unwrap_or("?"),
+                entry.version
+            );
+            if cli.dry_run {
+                status_dim("Would install", &arrow);
+                update_results.push(
+                    UpdateResult::new(pkg.name.clone(), old, Some(entry.version.clone()), true)
+                        .with_install_method(InstallMethod::Downloader),
+                );
+                continue;
+            }
+            // 更新自己：Windows 上先把运行中的 exe 挪开，安装没写成时 drop 挪回
+            let _moved_self = self_replace::is_self(&pkg.name)
+                .then(self_replace::prepare)
+                .flatten();
+            match bundle.install(entry, &tx, cancel) {
+                Ok(()) => {
+                    status("Installed", &arrow);
+                    let result =
+                        UpdateResult::new(pkg.name.clone(), old, Some(entry.version.clone()), true)
+                            .with_install_method(InstallMethod::Downloader);
+                    record_install(&result);
+                    update_results.push(result);
+                }
+                Err(crate::downloader::events::DownloaderError::Cancelled) => {
+                    aborted = true;
+                    break;
+                }
+                Err(e) => {
+                    status_err(
+                        "Error",
+                        &language.format_text(
+                            "package_error",
+                            &[
+                                ("name", &pkg.name.red().to_string()),
+                                ("error", &e.to_string()),
+                            ],
+                        ),
+                    );
+                    update_results.push(UpdateResult::new(pkg.name.clone(), old, None, false));
+                }
+            }
+        }
+
+        print_update_summary(&update_results, language);
+        if aborted {
+            status_warn(
+                "Aborted",
+                &language.format_text(
+                    "aborted_by_user",
+                    &[
+                        ("done", &(update_results.len() - gated).to_string()),
+                        ("total", &selections.len().to_string()),
+                    ],
+                ),
+            );
+        } else {
+            let succeeded = update_results.iter().filter(|r| r.success).count();
+            let failed = update_results.len() - gated - succeeded;
+            let mut summary = language
+                .get_text("success_count")
+                .replace("{}", &succeeded.to_string());
+            if failed > 0 {
+                summary.push_str(", ");
+                summary.push_str(
+                    &language
+                        .get_text("fail_count")
+                        .replace("{}", &failed.to_string()),
+                );
+                status_err("Finished", &summary);
+            } else {
+                status("Finished", &summary);
+            }
+        }
+        note_self_update(&update_results, cli.dry_run, language);
+    }
+
+    if json_mode {
+        emit_report(
+            cli,
+            &packages,
+            &all_updates,
+            &update_results,
+            aborted,
+            run_start,
+            selections.len(),
+        );
+    }
+
+    // 被 hold 拦下的更新不算"有更新待应用"
+    let updates_available = gated_pending || all_updates.iter().any(|p| !p.held);
+    let applied_any = update_results.iter().any(|r| r.skipped.is_none());
+    Ok(if aborted {
+        EXIT_ABORTED
+    } else if update_results
+        .iter()
+        .any(|r| !r.success && r.skipped.is_none())
+    {
+        EXIT_FAILED
+    } else if updates_available && !applied_any {
+        EXIT_UPDATES_AVAILABLE
+    } else {
+        EXIT_OK
+    })
+}
+
+/// 离线包里的归档不能装给这个包时的跳过原因。只看有更新的包：
+/// 配置排除的、要带自定义 features 源码构建的、策略为 source-only 的。
+fn bundle_skip_reason(
+    cli: &Cli,
+    config: &crate::config::Config,
+    pkg: &PackageInfo,
+) -> Option<SkipReason> {
+    if !pkg.has_update() {
+        return None;
+    }
+    let policy = cli
+        .install_policy_override()
+        .unwrap_or_else(|| config.install_policy(&pkg.name));
+    if config.is_excluded(&pkg.name) {
+        Some(SkipReason::Excluded)
+    } else if !config
+        .install_opts_for(&pkg.name, pkg.install_opts.clone())
+        .is_none_or(|o| o.is_default())
+    {
+        Some(SkipReason::SourceBuildRequired)
+    } else if policy == InstallPolicy::SourceOnly {
+        Some(SkipReason::SourceOnly)
+    } else {
+        None
+    }
+}
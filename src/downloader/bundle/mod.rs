@@ -0,0 +1,476 @@
+//! 离线包 (air-gapped bundle): 联网机器上 `cargo fresh bundle <out>` 把选中
+//! 更新的预编译归档下载下来打成自描述目录或 tarball; 离线机器上
+//! `cargo fresh --from-bundle <path>` 校验后安装。
+//!
+//! 布局:
+//!
+//! ```text
+//! <bundle>/manifest.json                     包列表 + 每个归档的 sha256 / 格式 / binary 位置
+//! <bundle>/SHA256SUMS                        同一份摘要, `sha256sum -c` 可直接校验
+//! <bundle>/archives/<name>-<version>/<file>  原样保存的 release 归档
+//! ```
+//!
+//! 归档走与普通更新完全相同的 resolve / fetch / 签名校验流水线
+//! ([`crate::downloader::acquire`]); 安装走 [`crate::downloader::install_archive`],
+//! 所以 `.crates.toml` / `.crates2.json` 的写法和普通 downloader 安装一致。
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::downloader::events::{DownloaderError, FailureKind, ProgressEvent};
+use crate::downloader::resolve::ArchiveFmt;
+use crate::downloader::{AcquiredArchive, InstallSpec};
+
+pub mod commands;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+const SUMS_FILE: &str = "SHA256SUMS";
+
+/// 当前写出的 manifest 格式版本。读取时拒绝更高的版本。
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub bundle_version: u32,
+    /// 生成这个包的 cargo-fresh, 如 `cargo-fresh 0.12.8`。
+    pub created_by: String,
+    /// Unix 秒。
+    pub created_at: u64,
+    /// 归档对应的 target triple 候选 (本机的 `resolve::current_targets`, 或
+    /// `--target` 的 `resolve::targets_for_triple`), 第一个是请求的 triple。
+    /// 离线安装时据此拒绝跨平台的包。
+    pub targets: Vec<String>,
+    pub packages: Vec<BundleEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub name: String,
+    pub version: String,
+    /// 归档的下载地址 (仅供追溯, 安装时不访问)。
+    pub source_url: String,
+    /// 相对包根目录的归档路径。
+    pub archive: String,
+    pub archive_fmt: ArchiveFmt,
+    pub sha256: String,
+    pub size: u64,
+    /// `.crates2.json` 里的 bins[], 解压时按这些名字找 binary。
+    #[serde(default)]
+    pub bins: Vec<String>,
+    /// crate 声明的 `bin-dir` 推出的 binary 位置 (binary 名 → 归档内相对路径)。
+    #[serde(default)]
+    pub bin_paths: Vec<(String, String)>,
+}
+
+impl BundleEntry {
+    /// 重建安装用的 `InstallSpec`。离线安装用不到 repo URL。
+    pub fn install_spec(&self) -> InstallSpec {
+        InstallSpec {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            repo_url: None,
+            bins: self.bins.clone(),
+            new_record: None,
+        }
+    }
+}
+
+/// 正在写的包: 先在目录里攒归档, 最后 [`BundleWriter::finish`] 写 manifest。
+pub struct BundleWriter {
+    root: PathBuf,
+    manifest: BundleManifest,
+}
+
+impl BundleWriter {
+    /// `root` 必须不存在或是空目录, 避免把别的文件混进包里。
+    pub fn create(root: &Path, targets: Vec<String>) -> Result<Self> {
+        if root.exists() {
+            let mut entries =
+                std::fs::read_dir(root).with_context(|| format!("read {}", root.display()))?;
+            if entries.next().is_some() {
+                bail!("bundle directory {} is not empty", root.display());
+            }
+        }
+        std::fs::create_dir_all(root.join("archives"))
+            .with_context(|| format!("create {}", root.display()))?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest: BundleManifest {
+                bundle_version: BUNDLE_VERSION,
+                created_by: concat!("cargo-fresh ", env!("CARGO_PKG_VERSION")).to_string(),
+                created_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+                targets,
+                packages: Vec::new(),
+            },
+        })
+    }
+
+    /// 把 `acquire` 拿到的归档拷进包里并登记。
+    pub fn add(&mut self, spec: &InstallSpec, acquired: &AcquiredArchive) -> Result<&BundleEntry> {
+        let src = &acquired.fetched.archive_path;
+        let file_name = src
+            .file_name()
+            .and_then(|f| f.to_str())
+            .context("archive has no file name")?;
+        let rel = format!("archives/{}-{}/{file_name}", spec.name, spec.version);
+        let dest = self.root.join(&rel);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create {}", parent.display()))?;
+        }
+        std::fs::copy(src, &dest).with_context(|| format!("copy archive to {}", dest.display()))?;
+        let (sha256, size) = sha256_file(&dest)?;
+        self.manifest.packages.retain(|e| e.name != spec.name);
+        self.manifest.packages.push(BundleEntry {
+            name: spec.name.clone(),
+            version: spec.version.clone(),
+            source_url: acquired.fetched.winning_url.clone(),
+            archive: rel,
+            archive_fmt: acquired.fmt,
+            sha256,
+            size,
+            bins: spec.bins.clone(),
+            bin_paths: acquired
+                .bin_paths
+                .iter()
+                .map(|(name, path)| (name.clone(), path.to_string_lossy().into_owned()))
+                .collect(),
+        });
+        Ok(self.manifest.packages.last().expect("just pushed"))
+    }
+
+    /// 写 `manifest.json` 与 `SHA256SUMS`, 返回 manifest。
+    pub fn finish(self) -> Result<BundleManifest> {
+        let body = serde_json::to_string_pretty(&self.manifest).context("serialize manifest")?;
+        std::fs::write(self.root.join(MANIFEST_FILE), body).context("write manifest.json")?;
+        let sums: String = self
+            .manifest
+            .packages
+            .iter()
+            .map(|e| format!("{}  {}\n", e.sha256, e.archive))
+            .collect();
+        std::fs::write(self.root.join(SUMS_FILE), sums).context("write SHA256SUMS")?;
+        Ok(self.manifest)
+    }
+}
+
+/// 输出路径以 `.tar.gz` / `.tgz` 结尾时打成 tarball, 否则写目录。
+pub fn is_tarball_path(path: &Path) -> bool {
+    let s = path.to_string_lossy();
+    s.ends_with(".tar.gz") || s.ends_with(".tgz")
+}
+
+/// 把目录 `dir` 的内容打成 `out` (tar.gz, 条目相对 `dir`)。
+pub fn pack_tarball(dir: &Path, out: &Path) -> Result<()> {
+    let file = std::fs::File::create(out).with_context(|| format!("create {}", out.display()))?;
+    let gz = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut tar = tar::Builder::new(gz);
+    tar.append_dir_all(".", dir)
+        .context("write bundle tarball")?;
+    tar.into_inner()
+        .context("finish tar")?
+        .finish()
+        .context("finish gzip")?;
+    Ok(())
+}
+
+/// 打开的离线包。tarball 解到临时目录, `_temp` 保活到 drop。
+#[derive(Debug)]
+pub struct OpenedBundle {
+    pub root: PathBuf,
+    pub manifest: BundleManifest,
+    _temp: Option<tempfile::TempDir>,
+}
+
+impl OpenedBundle {
+    /// 打开目录或 tarball 形式的离线包并读 manifest。
+    pub fn open(path: &Path) -> Result<Self> {
+        let (root, temp) = if path.is_dir() {
+            (path.to_path_buf(), None)
+        } else {
+            let temp = tempfile::tempdir().context("mkdir tempdir for bundle")?;
+            let file =
+                std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+            // tar::Archive::unpack 会拒绝 `..` / 绝对路径条目
+            tar::Archive::new(flate2::read::GzDecoder::new(file))
+                .unpack(temp.path())
+                .with_context(|| format!("unpack {}", path.display()))?;
+            (temp.path().to_path_buf(), Some(temp))
+        };
+        let body = std::fs::read_to_string(root.join(MANIFEST_FILE))
+            .with_context(|| format!("{} has no {MANIFEST_FILE}", path.display()))?;
+        let manifest: BundleManifest =
+            serde_json::from_str(&body).context("parse bundle manifest")?;
+        if manifest.bundle_version > BUNDLE_VERSION {
+            bail!(
+                "bundle format {} is newer than this cargo-fresh supports ({BUNDLE_VERSION})",
+                manifest.bundle_version
+            );
+        }
+        Ok(Self {
+            root,
+            manifest,
+            _temp: temp,
+        })
+    }
+
+    /// 包是否给本机平台打的: 生成方的规范 triple 要在本机的候选里。
+    pub fn matches_targets(&self, local: &[String]) -> bool {
+        self.manifest
+            .targets
+            .first()
+            .is_some_and(|t| local.iter().any(|l| l == t))
+    }
+
+    /// 校验某个归档的 sha256, 通过时返回其绝对路径。
+    pub fn verify(&self, entry: &BundleEntry) -> Result<PathBuf, DownloaderError> {
+        let rel = Path::new(&entry.archive);
+        if !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(DownloaderError::Failed {
+                kind: FailureKind::ChecksumMismatch,
+                source: anyhow::anyhow!("archive path escapes the bundle: {}", entry.archive),
+            });
+        }
+        let path = self.root.join(rel);
+        let (actual, _) = sha256_file(&path).map_err(|e| DownloaderError::Failed {
+            kind: FailureKind::ChecksumMismatch,
+            source: e,
+        })?;
+        if actual != entry.sha256 {
+            return Err(DownloaderError::Failed {
+                kind: FailureKind::ChecksumMismatch,
+                source: anyhow::anyhow!(
+                    "{}: expected sha256={}, got sha256={actual}",
+                    entry.archive,
+                    entry.sha256
+                ),
+            });
+        }
+        Ok(path)
+    }
+
+    /// 校验并安装一个条目, 与普通 downloader 安装写同样的元数据。
+    pub fn install(
+        &self,
+        entry: &BundleEntry,
+        events: &UnboundedSender<ProgressEvent>,
+        cancel: &Arc<AtomicBool>,
+    ) -> Result<(), DownloaderError> {
+        let _ = events.send(ProgressEvent::Verifying {
+            name: entry.name.clone(),
+        });
+        let archive = self.verify(entry)?;
+        let bin_paths: Vec<(String, PathBuf)> = entry
+            .bin_paths
+            .iter()
+            .map(|(name, path)| (name.clone(), PathBuf::from(path)))
+            .collect();
+        crate::downloader::install_archive(
+            &entry.install_spec(),
+            &archive,
+            entry.archive_fmt,
+            &bin_paths,
+            events,
+            cancel,
+        )
+    }
+}
+
+/// 为一个待打包的更新拉取预编译归档并收进包里。走与普通更新相同的
+/// [`crate::downloader::acquire`] (缓存 / 自定义来源 / pkg-url / Releases API /
+/// URL 枚举 / 签名校验), 只是最后一步是拷进包里而不是装进 `~/.cargo/bin`。
+/// 按包的 `targets` 找归档, 所以 `--target` 打的包不会碰本机平台。
+pub async fn bundle_package(
+    writer: &mut BundleWriter,
+    name: &str,
+    version: &str,
+    cancel: &Arc<AtomicBool>,
+) -> Result<BundleEntry, DownloaderError> {
+    let client = crate::package::http_client().map_err(|e| DownloaderError::Failed {
+        kind: FailureKind::AllUrlsFailed,
+        source: e,
+    })?;
+    let repo_url = crate::package::crates_api::fetch_repo_url(client, name).await;
+    let bins = crate::package::registry::cargo_home()
+        .map(|home| crate::package::crates2::lookup_bins(&home, name))
+        .unwrap_or_default();
+    let spec = InstallSpec {
+        name: name.to_string(),
+        version: version.to_string(),
+        repo_url,
+        bins,
+        new_record: None,
+    };
+
+    // 打包没有进度行, 只把 downloader 的告警 (如签名 warn 策略) 转成状态行
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<ProgressEvent>();
+    let warnings = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if let ProgressEvent::Warning { name, message } = event {
+                crate::display::status_warn("Warning", &format!("{name}: {message}"));
+            }
+        }
+    });
+    let targets = writer.manifest.targets.clone();
+    let acquired =
+        crate::downloader::acquire_for_targets(client, &spec, &targets, &tx, cancel).await;
+    drop(tx);
+    let _ = warnings.await;
+
+    let acquired = acquired?;
+    writer
+        .add(&spec, &acquired)
+        .cloned()
+        .map_err(|e| DownloaderError::Failed {
+            kind: FailureKind::InstallFailed,
+            source: e,
+        })
+}
+
+fn sha256_file(path: &Path) -> Result<(String, u64)> {
+    use std::io::Read;
+    let mut f = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = f.read(&mut buf).context("read archive")?;
+        if n == 0 {
+            break;
+        }
+        size += n as u64;
+        hasher.update(&buf[..n]);
+    }
+    let hex = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    Ok((hex, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::fetch::FetchedArchive;
+
+    fn acquired(body: &[u8]) -> AcquiredArchive {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive_path = temp_dir.path().join("tool-x86_64-unknown-linux-gnu.tar.gz");
+        std::fs::write(&archive_path, body).unwrap();
+        AcquiredArchive {
+            fetched: FetchedArchive {
+                temp_dir,
+                archive_path,
+                winning_url: "https://example.com/tool-x86_64-unknown-linux-gnu.tar.gz".into(),
+            },
+            fmt: ArchiveFmt::TarGz,
+            bin_paths: vec![("tool".into(), PathBuf::from("tool-1.0.0/tool"))],
+        }
+    }
+
+    fn spec() -> InstallSpec {
+        InstallSpec {
+            name: "tool".into(),
+            version: "1.0.0".into(),
+            repo_url: None,
+            bins: vec!["tool".into()],
+            new_record: None,
+        }
+    }
+
+    fn write_bundle(root: &Path) -> BundleManifest {
+        let mut w = BundleWriter::create(root, vec!["x86_64-unknown-linux-gnu".into()]).unwrap();
+        w.add(&spec(), &acquired(b"archive-bytes")).unwrap();
+        w.finish().unwrap()
+    }
+
+    #[test]
+    fn directory_bundle_round_trips_and_verifies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("bundle");
+        let written = write_bundle(&root);
+        assert_eq!(
+            written.packages[0].archive,
+            "archives/tool-1.0.0/tool-x86_64-unknown-linux-gnu.tar.gz"
+        );
+        let sums = std::fs::read_to_string(root.join(SUMS_FILE)).unwrap();
+        assert!(sums.starts_with(&written.packages[0].sha256));
+
+        let opened = OpenedBundle::open(&root).unwrap();
+        assert_eq!(opened.manifest, written);
+        let entry = &opened.manifest.packages[0];
+        assert_eq!(
+            std::fs::read(opened.verify(entry).unwrap()).unwrap(),
+            b"archive-bytes"
+        );
+        assert!(opened.matches_targets(&["x86_64-unknown-linux-gnu".into()]));
+        assert!(!opened.matches_targets(&["aarch64-apple-darwin".into()]));
+    }
+
+    #[test]
+    fn tarball_bundle_round_trips() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("staging");
+        let written = write_bundle(&root);
+        let out = tmp.path().join("tools.tar.gz");
+        assert!(is_tarball_path(&out));
+        pack_tarball(&root, &out).unwrap();
+
+        let opened = OpenedBundle::open(&out).unwrap();
+        assert_eq!(opened.manifest, written);
+        assert!(opened.verify(&opened.manifest.packages[0]).is_ok());
+    }
+
+    #[test]
+    fn tampered_archive_fails_verification() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("bundle");
+        let written = write_bundle(&root);
+        std::fs::write(root.join(&written.packages[0].archive), b"evil").unwrap();
+
+        let opened = OpenedBundle::open(&root).unwrap();
+        let err = opened.verify(&opened.manifest.packages[0]).unwrap_err();
+        assert!(matches!(
+            err,
+            DownloaderError::Failed {
+                kind: FailureKind::ChecksumMismatch,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn escaping_archive_path_is_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("bundle");
+        write_bundle(&root);
+        let mut opened = OpenedBundle::open(&root).unwrap();
+        opened.manifest.packages[0].archive = "../outside.tar.gz".into();
+        assert!(opened.verify(&opened.manifest.packages[0]).is_err());
+    }
+
+    #[test]
+    fn refuses_non_empty_directory_and_newer_format() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("stray"), b"x").unwrap();
+        assert!(BundleWriter::create(tmp.path(), Vec::new()).is_err());
+
+        let root = tmp.path().join("bundle");
+        let mut manifest = write_bundle(&root);
+        manifest.bundle_version = BUNDLE_VERSION + 1;
+        std::fs::write(
+            root.join(MANIFEST_FILE),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        assert!(OpenedBundle::open(&root).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! `cargo fresh cache` 子命令的执行流程：列出 / 清理归档缓存目录。
+
+use anyhow::Result;
+use indicatif::HumanBytes;
+
+use crate::cli::CacheCommand;
+use crate::config::{self, CacheConfig};
+use crate::display::{format_age, is_json_mode, status, status_dim};
+use crate::locale::Language;
+use crate::models::EXIT_OK;
+
+use super::ArchiveCache;
+
+/// `cargo fresh cache list|prune`。`[cache] enabled` 只管下载时的读写,
+/// 没开启时这里照样能查看 / 清理配置的目录。
+pub fn run_cache_command(action: &CacheCommand, language: Language) -> Result<i32> {
+    let config = config::get();
+    let cache_config = CacheConfig {
+        enabled: true,
+        ..config.cache.clone()
+    };
+    let Some(cache) = ArchiveCache::from_config(&cache_config) else {
+        anyhow::bail!(
+            "cannot locate the cache directory: set [cache] dir, $XDG_CACHE_HOME or $HOME"
+        );
+    };
+    let dir = cache.dir().display().to_string();
+
+    match action {
+        CacheCommand::List => {
+            let entries = cache.entries()?;
+            let total = ArchiveCache::total_bytes(&entries);
+            if is_json_mode() {
+                anstream::println!(
+                    "{}",
+                    serde_json::json!({
+                        "schema_version": 2,
+                        "cache": {
+                            "dir": dir,
+                            "total_bytes": total,
+                            "max_bytes": cache.max_bytes(),
+                            "entries": entries,
+                        },
+                    })
+                );
+                return Ok(EXIT_OK);
+            }
+            if entries.is_empty() {
+                status_dim(
+                    "Cached",
+                    &language.get_text("cache_empty").replace("{}", &dir),
+                );
+                return Ok(EXIT_OK);
+            }
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            for entry in &entries {
+                let size = HumanBytes(entry.size).to_string();
+                let age = format_age(now.saturating_sub(entry.last_used));
+                status_dim(
+                    "Cached",
+                    &language.format_text(
+                        "cache_entry",
+                        &[("size", &size), ("url", &entry.url), ("age", &age)],
+                    ),
+                );
+            }
+            let count = entries.len().to_string();
+            let total = HumanBytes(total).to_string();
+            status(
+                "Finished",
+                &language.format_text(
+                    "cache_summary",
+                    &[("count", &count), ("size", &total), ("dir", &dir)],
+                ),
+            );
+        }
+        CacheCommand::Prune {
+            older_than,
+            max_size,
+        } => {
+            let limit = max_size.or(cache.max_bytes());
+            let report = cache.prune(*older_than, limit)?;
+            if is_json_mode() {
+                anstream::println!(
+                    "{}",
+                    serde_json::json!({
+                        "schema_version": 2,
+                        "cache": {
+                            "dir": dir,
+                            "removed": report.removed,
+                            "freed_bytes": report.freed_bytes,
+                        },
+                    })
+                );
+                return Ok(EXIT_OK);
+            }
+            for entry in &report.removed {
+                status_dim("Removed", &entry.url);
+            }
+            let count = report.removed.len().to_string();
+            let freed = HumanBytes(report.freed_bytes).to_string();
+            status(
+                "Finished",
+                &language.format_text(
+                    "cache_prune_summary",
+                    &[("count", &count), ("size", &freed)],
+                ),
+            );
+        }
+    }
+    Ok(EXIT_OK)
+}
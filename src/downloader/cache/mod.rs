@@ -0,0 +1,610 @@
+//! 预编译归档的持久缓存 (内容寻址)。
+//!
+//! 布局:
+//!
+//! ```text
+//! <dir>/index.json          (URL, 版本) → sha256 / 大小 / 最近使用时间
+//! <dir>/sha256/<hex>        归档本体, 文件名就是内容的 sha256
+//! ```
+//!
+//! - `fetch` 在 HEAD 探测之前按 (候选 URL, 要装的版本) 查索引; 命中且 blob
+//!   重算 sha256 与索引一致才算数, 否则当作未命中并丢掉这条记录。键里带
+//!   版本, 因为 `latest/download/...` 这类不含版本号的 URL 换版本后内容会变。
+//! - 下载 (并通过 `.sha256` 校验) 后写入: blob 先写临时文件再 rename,
+//!   多个 URL 指向同一份内容时只存一份。
+//! - 索引的读改写都在锁里做: 进程内一把 mutex (`--jobs` 的并发任务),
+//!   进程间靠 `index.lock` (多个容器挂同一个卷)。索引同样是临时文件 +
+//!   rename 整体替换, 不会读到半截文件。索引解析失败直接报错, 不当成空
+//!   索引——否则下一次写入会把所有记录连同 blob 一起丢掉。
+//! - 超过 `max-size` 时按最近使用时间从旧到新淘汰, 只删被淘汰记录自己的
+//!   blob。扫描整个目录清孤儿 blob 只在 `cache prune` 里做。
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::CacheConfig;
+
+pub mod commands;
+
+/// 没配 `max-size` 时的默认上限。
+pub const DEFAULT_MAX_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// 等 `index.lock` 的上限; 超时报错, 这次不读写缓存。
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// 比这更旧的 `index.lock` 视为崩溃进程留下的, 直接接管。
+const LOCK_STALE: Duration = Duration::from_secs(60);
+
+/// 进程内串行化索引读改写。
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// 持有期间独占索引; drop 时删掉 `index.lock`。
+struct IndexGuard {
+    lock_path: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Drop for IndexGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.lock_path);
+    }
+}
+
+/// 索引里的一条记录。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    /// 写入时要装的版本。旧索引里没有这个字段, 读出来是空串, 永远不会命中。
+    #[serde(default)]
+    pub version: String,
+    pub sha256: String,
+    pub size: u64,
+    /// 最近一次写入或命中, Unix 秒。
+    pub last_used: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    entries: Vec<CacheEntry>,
+}
+
+/// 一次 `prune` 的结果。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct PruneReport {
+    /// 被移出索引的记录。
+    pub removed: Vec<CacheEntry>,
+    /// 实际删掉的 blob 字节数 (含没有索引引用的孤儿 blob)。
+    pub freed_bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveCache {
+    dir: PathBuf,
+    max_bytes: Option<u64>,
+}
+
+impl ArchiveCache {
+    /// `max_bytes = None` 表示不限大小。
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: Option<u64>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes,
+        }
+    }
+
+    /// 按 `[cache]` 配置打开缓存。`enabled = false` 或推不出目录时返回 None。
+    pub fn from_config(config: &CacheConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let dir = config.dir.clone().or_else(default_dir)?;
+        Some(Self::new(
+            dir,
+            Some(config.max_size.unwrap_or(DEFAULT_MAX_BYTES)),
+        ))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    fn blob_dir(&self) -> PathBuf {
+        self.dir.join("sha256")
+    }
+
+    fn blob_path(&self, sha256: &str) -> PathBuf {
+        self.blob_dir().join(sha256)
+    }
+
+    /// 拿索引锁: 先进程内 mutex, 再用 `create_new` 建 `index.lock`。
+    fn lock_index(&self) -> Result<IndexGuard> {
+        let guard = INDEX_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("create {}", self.dir.display()))?;
+        let lock_path = self.dir.join("index.lock");
+        let deadline = std::time::Instant::now() + LOCK_TIMEOUT;
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_path)
+            {
+                Ok(_) => {
+                    return Ok(IndexGuard {
+                        lock_path,
+                        _guard: guard,
+                    })
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(&lock_path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|t| t.elapsed().ok())
+                        .is_some_and(|age| age > LOCK_STALE);
+                    if stale {
+                        let _ = std::fs::remove_file(&lock_path);
+                        continue;
+                    }
+                    if std::time::Instant::now() >= deadline {
+                        return Err(anyhow!(
+                            "{} is held by another process",
+                            lock_path.display()
+                        ));
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => return Err(anyhow!(e).context(format!("create {}", lock_path.display()))),
+            }
+        }
+    }
+
+    /// 按 (URL, 版本) 查缓存。命中时刷新 `last_used` 并返回 blob 路径与 sha256。
+    /// blob 缺失或内容和索引对不上时清掉该记录, 返回 None。索引读不了
+    /// (锁超时 / 解析失败) 也按未命中处理, 但不改动索引。
+    pub fn lookup(&self, url: &str, version: &str) -> Option<(PathBuf, String)> {
+        let sha256 = {
+            let _lock = self.lock_index().ok()?;
+            let index = self.load_index().ok()?;
+            index
+                .entries
+                .iter()
+                .find(|e| e.url == url && e.version == version)?
+                .sha256
+                .clone()
+        };
+        // 重算摘要不占着锁: 大归档要读好一阵
+        let blob = self.blob_path(&sha256);
+        let intact = sha256_file(&blob).ok().as_deref() == Some(sha256.as_str());
+
+        let _lock = self.lock_index().ok()?;
+        let mut index = self.load_index().ok()?;
+        let pos = index
+            .entries
+            .iter()
+            .position(|e| e.url == url && e.version == version && e.sha256 == sha256)?;
+        if !intact {
+            // 内容对不上的 blob 不会被 store 覆盖 (它只在 blob 不存在时写),
+            // 所以连同引用它的记录一起清掉
+            index.entries.retain(|e| e.sha256 != sha256);
+            let _ = std::fs::remove_file(&blob);
+            let _ = self.save_index(&index);
+            return None;
+        }
+        index.entries[pos].last_used = now_secs();
+        let _ = self.save_index(&index);
+        Some((blob, sha256))
+    }
+
+    /// 把 `path` 存成 (`url`, `version`) 的缓存 (`sha256` 是调用方已经算好的
+    /// 摘要), 之后按大小上限淘汰。
+    pub fn store(&self, url: &str, version: &str, path: &Path, sha256: &str) -> Result<()> {
+        let blob_dir = self.blob_dir();
+        std::fs::create_dir_all(&blob_dir)
+            .with_context(|| format!("create {}", blob_dir.display()))?;
+        let blob = self.blob_path(sha256);
+        if !blob.is_file() {
+            let tmp =
+                tempfile::NamedTempFile::new_in(&blob_dir).context("create cache temp file")?;
+            std::fs::copy(path, tmp.path()).context("copy archive into cache")?;
+            tmp.persist(&blob)
+                .map_err(|e| anyhow!(e.error).context("persist cache blob"))?;
+        }
+        let size = std::fs::metadata(&blob).context("stat cache blob")?.len();
+
+        let _lock = self.lock_index()?;
+        let mut index = self.load_index()?;
+        index.entries.retain(|e| e.url != url || e.version != version);
+        index.entries.push(CacheEntry {
+            url: url.to_string(),
+            version: version.to_string(),
+            sha256: sha256.to_string(),
+            size,
+            last_used: now_secs(),
+        });
+        let evicted = match self.max_bytes {
+            Some(max) => evict_to_fit(&mut index.entries, max),
+            None => Vec::new(),
+        };
+        self.save_index(&index)?;
+        // 只删被淘汰、且没有别的记录再引用的 blob; 目录里其余文件 (别的
+        // 进程刚写进来还没登记的 blob) 不碰
+        for entry in &evicted {
+            if !index.entries.iter().any(|e| e.sha256 == entry.sha256) {
+                let _ = std::fs::remove_file(self.blob_path(&entry.sha256));
+            }
+        }
+        Ok(())
+    }
+
+    /// 按最近使用时间从新到旧列出所有记录。
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let _lock = self.lock_index()?;
+        let mut entries = self.load_index()?.entries;
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+        Ok(entries)
+    }
+
+    /// 去重后的 blob 总大小 (同一份内容被多个 URL 引用只算一次)。
+    pub fn total_bytes(entries: &[CacheEntry]) -> u64 {
+        unique_bytes(entries)
+    }
+
+    /// 删掉 `older_than` 之前最后使用的记录, 再按 `max_bytes` 淘汰最旧的,
+    /// 最后清理没有记录引用的 blob。两个条件都不给时只做孤儿清理。
+    pub fn prune(
+        &self,
+        older_than: Option<Duration>,
+        max_bytes: Option<u64>,
+    ) -> Result<PruneReport> {
+        let _lock = self.lock_index()?;
+        let mut index = self.load_index()?;
+        let mut report = PruneReport::default();
+        if let Some(age) = older_than {
+            let cutoff = now_secs().saturating_sub(age.as_secs());
+            let (stale, kept): (Vec<_>, Vec<_>) = index
+                .entries
+                .into_iter()
+                .partition(|e| e.last_used < cutoff);
+            report.removed.extend(stale);
+            index.entries = kept;
+        }
+        if let Some(max) = max_bytes {
+            report.removed.extend(evict_to_fit(&mut index.entries, max));
+        }
+        self.save_index(&index)?;
+        report.freed_bytes = self.remove_unreferenced(&index);
+        Ok(report)
+    }
+
+    fn load_index(&self) -> Result<Index> {
+        match std::fs::read(self.index_path()) {
+            Ok(bytes) => serde_json::from_slice(&bytes).context("parse cache index"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Index::default()),
+            Err(e) => Err(anyhow!(e).context("read cache index")),
+        }
+    }
+
+    fn save_index(&self, index: &Index) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("create {}", self.dir.display()))?;
+        let mut tmp =
+            tempfile::NamedTempFile::new_in(&self.dir).context("create index temp file")?;
+        serde_json::to_writer(&mut tmp, index).context("serialize cache index")?;
+        tmp.flush().context("flush cache index")?;
+        tmp.persist(self.index_path())
+            .map_err(|e| anyhow!(e.error).context("persist cache index"))?;
+        Ok(())
+    }
+
+    /// 删掉索引不再引用的 blob, 返回释放的字节数。
+    fn remove_unreferenced(&self, index: &Index) -> u64 {
+        let referenced: BTreeSet<&str> = index.entries.iter().map(|e| e.sha256.as_str()).collect();
+        let Ok(dir) = std::fs::read_dir(self.blob_dir()) else {
+            return 0;
+        };
+        let mut freed = 0;
+        for entry in dir.flatten() {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else { continue };
+            // 只动形如 sha256 的文件名; 写到一半的临时文件 (.tmpXXXX) 留给写入方
+            if !is_sha256_hex(name) || referenced.contains(name) {
+                continue;
+            }
+            let size = entry.metadata().map_or(0, |m| m.len());
+            if std::fs::remove_file(entry.path()).is_ok() {
+                freed += size;
+            }
+        }
+        freed
+    }
+}
+
+/// 按最近使用时间从旧到新移除记录, 直到去重后的总大小不超过 `max`。
+/// 返回被移除的记录。
+fn evict_to_fit(entries: &mut Vec<CacheEntry>, max: u64) -> Vec<CacheEntry> {
+    let mut removed = Vec::new();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+    while unique_bytes(entries) > max {
+        match entries.pop() {
+            Some(e) => removed.push(e),
+            None => break,
+        }
+    }
+    removed
+}
+
+fn unique_bytes(entries: &[CacheEntry]) -> u64 {
+    let mut seen: HashMap<&str, u64> = HashMap::new();
+    for e in entries {
+        seen.insert(e.sha256.as_str(), e.size);
+    }
+    seen.values().sum()
+}
+
+fn is_sha256_hex(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    use std::io::Read;
+    let mut f = std::fs::File::open(path).context("open cache blob")?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = f.read(&mut buf).context("read cache blob")?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex_lower(&hasher.finalize()))
+}
+
+fn hex_lower(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 默认目录: `$XDG_CACHE_HOME/cargo-fresh/archives`, 未设时
+/// `$HOME/.cache/cargo-fresh/archives` (与 `config_path` 同一套约定)。
+pub fn default_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .map(|dir| dir.join("cargo-fresh").join("archives"))
+}
+
+/// 解析大小: 纯数字是字节; 后缀 `K`/`M`/`G`/`T` (可带 `B` / `iB`) 一律按
+/// 1024 进位, 大小写不敏感, 如 `500MiB` / `2G` / `1048576`。
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let t = s.trim();
+    let split = t.find(|c: char| !c.is_ascii_digit()).unwrap_or(t.len());
+    let (num, unit) = t.split_at(split);
+    let n: u64 = num
+        .parse()
+        .map_err(|_| format!("invalid size `{s}` (expected e.g. 500MiB, 2G)"))?;
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 10,
+        "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        "t" | "tb" | "tib" => 40,
+        _ => {
+            return Err(format!(
+                "invalid size unit in `{s}` (use B, KiB, MiB, GiB, TiB)"
+            ))
+        }
+    };
+    n.checked_mul(1u64 << shift)
+        .ok_or_else(|| format!("size `{s}` is too large"))
+}
+
+/// 解析时长: 数字 + `s`/`m`/`h`/`d`/`w`, 如 `30d` / `12h` / `2w`。
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let t = s.trim();
+    let split = t.find(|c: char| !c.is_ascii_digit()).unwrap_or(t.len());
+    let (num, unit) = t.split_at(split);
+    let n: u64 = num
+        .parse()
+        .map_err(|_| format!("invalid duration `{s}` (expected e.g. 30d, 12h)"))?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration unit in `{s}` (use s, m, h, d, w)"
+            ))
+        }
+    };
+    n.checked_mul(secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration `{s}` is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_archive(dir: &Path, name: &str, body: &[u8]) -> (PathBuf, String) {
+        let p = dir.join(name);
+        std::fs::write(&p, body).unwrap();
+        let sha = hex_lower(&Sha256::digest(body));
+        (p, sha)
+    }
+
+    #[test]
+    fn store_then_lookup_round_trips() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), None);
+        let (p, sha) = write_archive(tmp.path(), "a.tar.gz", b"archive-a");
+        cache.store("https://x/a.tar.gz", "1.0.0", &p, &sha).unwrap();
+
+        let (blob, got) = cache.lookup("https://x/a.tar.gz", "1.0.0").expect("hit");
+        assert_eq!(got, sha);
+        assert_eq!(std::fs::read(blob).unwrap(), b"archive-a");
+        assert!(cache.lookup("https://x/other.tar.gz", "1.0.0").is_none());
+    }
+
+    #[test]
+    fn same_url_for_another_version_is_a_miss() {
+        // `latest/download/...` 这类 URL 不含版本号: 换版本后不能拿旧归档
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), None);
+        let url = "https://x/latest/download/a.tar.gz";
+        let (p, sha) = write_archive(tmp.path(), "a.tar.gz", b"archive-1.0.0");
+        cache.store(url, "1.0.0", &p, &sha).unwrap();
+
+        assert!(cache.lookup(url, "1.1.0").is_none());
+        assert!(cache.lookup(url, "1.0.0").is_some());
+        let (p, sha) = write_archive(tmp.path(), "a.tar.gz", b"archive-1.1.0");
+        cache.store(url, "1.1.0", &p, &sha).unwrap();
+        let (blob, _) = cache.lookup(url, "1.1.0").expect("hit");
+        assert_eq!(std::fs::read(blob).unwrap(), b"archive-1.1.0");
+        assert_eq!(cache.entries().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn corrupted_blob_is_a_miss_and_dropped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), None);
+        let (p, sha) = write_archive(tmp.path(), "a.tar.gz", b"archive-a");
+        cache.store("https://x/a.tar.gz", "1.0.0", &p, &sha).unwrap();
+        std::fs::write(cache.blob_path(&sha), b"tampered").unwrap();
+
+        assert!(cache.lookup("https://x/a.tar.gz", "1.0.0").is_none());
+        assert!(cache.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn identical_content_under_two_urls_is_stored_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), None);
+        let (p, sha) = write_archive(tmp.path(), "a.tar.gz", b"same");
+        cache.store("https://mirror-a/a.tar.gz", "1.0.0", &p, &sha).unwrap();
+        cache.store("https://mirror-b/a.tar.gz", "1.0.0", &p, &sha).unwrap();
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(ArchiveCache::total_bytes(&entries), 4);
+        assert_eq!(std::fs::read_dir(cache.blob_dir()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn size_limit_evicts_least_recently_used() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), Some(10));
+        let (a, sha_a) = write_archive(tmp.path(), "a", b"aaaaaa");
+        let (b, sha_b) = write_archive(tmp.path(), "b", b"bbbbbb");
+        cache.store("https://x/a", "1.0.0", &a, &sha_a).unwrap();
+        // 让 a 明确比 b 旧
+        let mut index = cache.load_index().unwrap();
+        index.entries[0].last_used -= 100;
+        cache.save_index(&index).unwrap();
+        cache.store("https://x/b", "1.0.0", &b, &sha_b).unwrap();
+
+        let urls: Vec<_> = cache
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.url)
+            .collect();
+        assert_eq!(urls, vec!["https://x/b"]);
+        assert!(!cache.blob_path(&sha_a).exists());
+    }
+
+    #[test]
+    fn prune_older_than_removes_stale_entries_and_blobs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), None);
+        let (a, sha_a) = write_archive(tmp.path(), "a", b"old");
+        let (b, sha_b) = write_archive(tmp.path(), "b", b"new");
+        cache.store("https://x/a", "1.0.0", &a, &sha_a).unwrap();
+        cache.store("https://x/b", "1.0.0", &b, &sha_b).unwrap();
+        let mut index = cache.load_index().unwrap();
+        for e in &mut index.entries {
+            if e.url.ends_with("/a") {
+                e.last_used -= 40 * 24 * 60 * 60;
+            }
+        }
+        cache.save_index(&index).unwrap();
+
+        let report = cache
+            .prune(Some(Duration::from_secs(30 * 24 * 60 * 60)), None)
+            .unwrap();
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.removed[0].url, "https://x/a");
+        assert_eq!(report.freed_bytes, 3);
+        assert!(cache.lookup("https://x/b", "1.0.0").is_some());
+    }
+
+    #[test]
+    fn concurrent_stores_keep_every_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), None);
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let cache = cache.clone();
+                let (p, sha) =
+                    write_archive(tmp.path(), &format!("a{i}"), format!("body-{i}").as_bytes());
+                std::thread::spawn(move || {
+                    cache.store(&format!("https://x/{i}"), "1.0.0", &p, &sha).unwrap()
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(cache.entries().unwrap().len(), 8);
+        assert!(!cache.dir().join("index.lock").exists());
+    }
+
+    #[test]
+    fn corrupt_index_is_an_error_not_an_empty_cache() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(tmp.path().join("c"), None);
+        let (p, sha) = write_archive(tmp.path(), "a", b"archive-a");
+        std::fs::create_dir_all(cache.dir()).unwrap();
+        std::fs::write(cache.index_path(), b"{ not json").unwrap();
+
+        assert!(cache.lookup("https://x/a", "1.0.0").is_none());
+        assert!(cache.store("https://x/a", "1.0.0", &p, &sha).is_err());
+        assert!(cache.entries().is_err());
+        assert_eq!(std::fs::read(cache.index_path()).unwrap(), b"{ not json");
+        assert!(cache.blob_path(&sha).exists());
+    }
+
+    #[test]
+    fn parses_sizes_and_durations() {
+        assert_eq!(parse_size("1048576"), Ok(1 << 20));
+        assert_eq!(parse_size("500MiB"), Ok(500 << 20));
+        assert_eq!(parse_size("2g"), Ok(2 << 30));
+        assert!(parse_size("2 parsecs").is_err());
+        assert_eq!(parse_duration("30d"), Ok(Duration::from_secs(30 * 86_400)));
+        assert_eq!(parse_duration("12h"), Ok(Duration::from_secs(12 * 3_600)));
+        assert!(parse_duration("30").is_err());
+    }
+}
@@ -22,6 +22,8 @@ pub enum ProgressEvent {
     Extracting { name: String },
     /// 解压完成, 正在 atomic rename 到 ~/.cargo/bin。
     Installing { name: String },
+    /// 不致命但值得让用户看到的情况 (如签名策略为 `warn` 时的签名缺失)。
+    Warning { name: String, message: String },
     /// 整条管道完成。
     Done { name: String, version: String },
     /// 这个包失败了——UI 层用来打 Fallback / Skip 提示, 调度器据此推 Phase 2。
@@ -53,6 +55,8 @@ pub enum UnsupportedReason {
     UnsupportedPlatform,
     GitSource,
     PathSource,
+    /// 全新安装的 crate 有多个 binary: 解压只取一个, 整包交给 `cargo install`。
+    MultipleBinaries,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -62,6 +66,18 @@ pub enum FailureKind {
     ChecksumMismatch,
     ExtractFailed,
     InstallFailed,
+    /// 签名校验结局命中了 `source` 策略 (校验失败 / 缺失 / 按配置拒绝)。
+    SignatureRejected,
+    /// 签名校验前读不出已下载的归档 (本地 I/O 错误, 不是签名问题)。
+    ArchiveUnreadable,
+    /// 归档条目是绝对路径或含 `..`, 解压会写到临时目录之外。
+    UnsafeArchivePath,
+    /// 归档里的符号链接 / 硬链接指向临时目录之外。
+    UnsafeArchiveLink,
+    /// 解压总字节数超过 `[extract] max-size`。
+    ArchiveTooLarge,
+    /// 条目数超过 `[extract] max-entries`。
+    ArchiveTooManyEntries,
 }
 
 #[cfg(test)]
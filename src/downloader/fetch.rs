@@ -2,9 +2,12 @@
 //!
 //! - 对一组候选 URL 依次 HEAD, 选第一个 2xx 的胜出。
 //! - 对胜出 URL 流式 GET, 每 chunk 后发 `Downloading` 事件并检查 cancel。
+//! - GET 中途断开按指数退避重试; 服务端支持范围请求时用 `Range` 从断点续传。
 //! - 并发尝试 GET `{url}.sha256` (容忍 404), 校验本地文件 sha256。
 //! - 整个流程的临时文件在返回的 `FetchedArchive` 持有的 TempDir 里——
 //!   caller 用完丢弃即可。
+//! - [`fetch_with_cache`] 额外在 HEAD 之前查持久缓存 (`cache.rs`), 下载
+//!   成功后写回; [`fetch`] 不碰缓存。
 
 use anyhow::{anyhow, Context, Result};
 use futures_util::StreamExt;
@@ -14,6 +17,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
 
+use crate::downloader::cache::ArchiveCache;
 use crate::downloader::events::{DownloaderError, FailureKind, ProgressEvent};
 use crate::downloader::resolve::CandidateUrl;
 
@@ -31,11 +35,34 @@ pub async fn fetch(
     candidates: &[CandidateUrl],
     events: &tokio::sync::mpsc::UnboundedSender<ProgressEvent>,
     cancel: Arc<AtomicBool>,
+) -> Result<FetchedArchive, DownloaderError> {
+    // 不查缓存时版本用不上
+    fetch_with_cache(client, name, "", candidates, events, cancel, None).await
+}
+
+/// 同 [`fetch`], 但先按候选顺序查 `cache`: 任一候选 URL 在 `version` 下命中
+/// 就直接从缓存拷出, 不发 HEAD / GET / `.sha256` 请求。未命中则照常下载,
+/// 成功后写回缓存。缓存读写出错只打 debug 行, 不影响下载本身。
+pub async fn fetch_with_cache(
+    client: &reqwest::Client,
+    name: &str,
+    version: &str,
+    candidates: &[CandidateUrl],
+    events: &tokio::sync::mpsc::UnboundedSender<ProgressEvent>,
+    cancel: Arc<AtomicBool>,
+    cache: Option<&ArchiveCache>,
 ) -> Result<FetchedArchive, DownloaderError> {
     if cancel.load(Ordering::SeqCst) {
         return Err(DownloaderError::Cancelled);
     }
 
+    // 0. 持久缓存
+    if let Some(cache) = cache {
+        if let Some(hit) = fetch_from_cache(cache, name, version, candidates, events).await? {
+            return Ok(hit);
+        }
+    }
+
     // 1. HEAD 探测——并发触发, 第一个 2xx 胜出。
     //    串行版本对 40-160 个候选 × ~150ms 要 6-20s 才能确认"无 prebuilt"
     //    并回退到 cargo install; 并发 + 短超时把这个时间砍到 ~1-2s。
@@ -49,60 +76,19 @@ pub async fn fetch(
         kind: FailureKind::DownloadInterrupted,
         source: anyhow!(e).context("mkdir tempdir for download"),
     })?;
-    let filename = winner
-        .url
-        .rsplit('/')
-        .next()
-        .unwrap_or("download.bin")
-        .to_string();
-    let archive_path = temp_dir.path().join(&filename);
-
-    // 3. GET 流式下载
-    let resp = client.get(&winner.url).send().await.map_err(|e| {
-        DownloaderError::Failed {
-            kind: FailureKind::DownloadInterrupted,
-            source: anyhow!(e).context("GET archive"),
-        }
-    })?;
-    let total = resp.content_length();
-    let mut stream = resp.bytes_stream();
+    let archive_path = temp_dir.path().join(archive_filename(&winner.url));
 
-    let mut file = tokio::fs::File::create(&archive_path)
-        .await
-        .map_err(|e| DownloaderError::Failed {
-            kind: FailureKind::DownloadInterrupted,
-            source: anyhow!(e).context("create archive file"),
-        })?;
-
-    let mut got: u64 = 0;
-    while let Some(chunk) = stream.next().await {
-        if cancel.load(Ordering::SeqCst) {
-            return Err(DownloaderError::Cancelled);
-        }
-        let bytes = chunk.map_err(|e| DownloaderError::Failed {
-            kind: FailureKind::DownloadInterrupted,
-            source: anyhow!(e).context("read chunk"),
-        })?;
-        file.write_all(&bytes)
-            .await
-            .map_err(|e| DownloaderError::Failed {
-                kind: FailureKind::DownloadInterrupted,
-                source: anyhow!(e).context("write chunk"),
-            })?;
-        got += bytes.len() as u64;
-        let _ = events.send(ProgressEvent::Downloading {
-            name: name.to_string(),
-            got,
-            total,
-        });
-    }
-    file.flush()
-        .await
-        .map_err(|e| DownloaderError::Failed {
-            kind: FailureKind::DownloadInterrupted,
-            source: anyhow!(e).context("flush"),
-        })?;
-    drop(file);
+    // 3. GET 流式下载 (断线按指数退避重试, 服务端支持时用 Range 续传)
+    download_resumable(
+        client,
+        &winner.url,
+        &archive_path,
+        name,
+        events,
+        &cancel,
+        RetryPolicy::default(),
+    )
+    .await?;
 
     // 4. sha256 校验 (best-effort)
     let _ = events.send(ProgressEvent::Verifying {
@@ -130,6 +116,26 @@ pub async fn fetch(
         }
     }
 
+    // 5. 写回缓存。`.sha256` 缺失时摘要在这里第一次算出来
+    if let Some(cache) = cache {
+        match compute_sha256(&archive_path).await {
+            Ok(sha256) => {
+                let cache = cache.clone();
+                let url = winner.url.clone();
+                let version = version.to_string();
+                let path = archive_path.clone();
+                let stored = tokio::task::spawn_blocking(move || {
+                    cache.store(&url, &version, &path, &sha256)
+                })
+                .await;
+                if let Ok(Err(e)) = stored {
+                    crate::display::status_debug("cache", &format!("{name}: store failed: {e:#}"));
+                }
+            }
+            Err(e) => crate::display::status_debug("cache", &format!("{name}: {e:#}")),
+        }
+    }
+
     Ok(FetchedArchive {
         temp_dir,
         archive_path,
@@ -137,6 +143,326 @@ pub async fn fetch(
     })
 }
 
+/// 下载重试策略: 最多 `max_attempts` 次 (含首次), 第 n 次重试前等
+/// `base_delay * 2^(n-1)`, 上限 `max_delay`。
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: std::time::Duration,
+    pub max_delay: std::time::Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: std::time::Duration::from_millis(500),
+            max_delay: std::time::Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn delay(&self, retry: u32) -> std::time::Duration {
+        self.base_delay
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(self.max_delay)
+    }
+}
+
+/// 一次 GET 尝试的失败: `retryable` 决定还要不要再试。
+struct AttemptError {
+    retryable: bool,
+    source: anyhow::Error,
+}
+
+impl AttemptError {
+    fn retry(source: anyhow::Error) -> Self {
+        Self {
+            retryable: true,
+            source,
+        }
+    }
+
+    fn fatal(source: anyhow::Error) -> Self {
+        Self {
+            retryable: false,
+            source,
+        }
+    }
+}
+
+/// 跨尝试保留的续传状态。
+#[derive(Debug, Default)]
+struct ResumeState {
+    /// 已落盘的字节数 = 下次续传的起点。
+    got: u64,
+    /// 完整大小 (首个 200 的 Content-Length, 或 206 的 Content-Range 总长)。
+    total: Option<u64>,
+    /// 服务端是否声明支持字节范围请求 (`Accept-Ranges: bytes` 或带 ETag)。
+    ranges: bool,
+    /// `If-Range` 校验值: 强 ETag, 没有时退到 Last-Modified。续传时文件
+    /// 已经变了的话服务端回 200 整个文件, 我们从头写, 不会拼出半新半旧的归档。
+    validator: Option<String>,
+}
+
+/// 把 `url` 下载到 `path`。连接失败 / 5xx / 429 / 读到一半断开都按
+/// `policy` 退避重试; 服务端支持范围请求时从已写入的偏移续传, 否则从头
+/// 重下。`Downloading` 事件的 `got` 从续传偏移继续累加, 进度条不会倒退。
+pub async fn download_resumable(
+    client: &reqwest::Client,
+    url: &str,
+    path: &std::path::Path,
+    name: &str,
+    events: &tokio::sync::mpsc::UnboundedSender<ProgressEvent>,
+    cancel: &Arc<AtomicBool>,
+    policy: RetryPolicy,
+) -> Result<(), DownloaderError> {
+    let mut file = tokio::fs::File::create(path)
+        .await
+        .map_err(|e| DownloaderError::Failed {
+            kind: FailureKind::DownloadInterrupted,
+            source: anyhow!(e).context("create archive file"),
+        })?;
+    let mut state = ResumeState::default();
+    let mut attempt = 1;
+    loop {
+        match download_attempt(client, url, &mut file, &mut state, name, events, cancel).await {
+            Ok(()) => break,
+            Err(None) => return Err(DownloaderError::Cancelled),
+            Err(Some(e)) if e.retryable && attempt < policy.max_attempts => {
+                let delay = policy.delay(attempt);
+                crate::display::status_debug(
+                    "downloader",
+                    &format!(
+                        "{name}: attempt {attempt} failed at byte {}: {:#}; retrying in {}ms{}",
+                        state.got,
+                        e.source,
+                        delay.as_millis(),
+                        if state.ranges { " (resume)" } else { "" },
+                    ),
+                );
+                tokio::time::sleep(delay).await;
+                if cancel.load(Ordering::SeqCst) {
+                    return Err(DownloaderError::Cancelled);
+                }
+                attempt += 1;
+            }
+            Err(Some(e)) => {
+                return Err(DownloaderError::Failed {
+                    kind: FailureKind::DownloadInterrupted,
+                    source: e
+                        .source
+                        .context(format!("GET archive (attempt {attempt}/{})", policy.max_attempts)),
+                });
+            }
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| DownloaderError::Failed {
+            kind: FailureKind::DownloadInterrupted,
+            source: anyhow!(e).context("flush"),
+        })?;
+    Ok(())
+}
+
+/// 单次 GET。`Err(None)` = 用户取消。
+async fn download_attempt(
+    client: &reqwest::Client,
+    url: &str,
+    file: &mut tokio::fs::File,
+    state: &mut ResumeState,
+    name: &str,
+    events: &tokio::sync::mpsc::UnboundedSender<ProgressEvent>,
+    cancel: &Arc<AtomicBool>,
+) -> Result<(), Option<AttemptError>> {
+    use reqwest::header::{
+        ACCEPT_RANGES, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE,
+    };
+    use reqwest::StatusCode;
+
+    let resume = state.got > 0 && state.ranges;
+    // 整个流式下载期间占着该主机的一个并发名额
+    let _permit = crate::downloader::throttle::host_permit(url).await;
+    let mut req = client.get(url);
+    if resume {
+        req = req.header(RANGE, format!("bytes={}-", state.got));
+        if let Some(v) = &state.validator {
+            req = req.header(IF_RANGE, v);
+        }
+    }
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| Some(AttemptError::retry(anyhow!(e))))?;
+    let status = resp.status();
+
+    if resume && status == StatusCode::RANGE_NOT_SATISFIABLE && state.total == Some(state.got) {
+        // 上次其实已经收全了, 只是断在了收尾
+        return Ok(());
+    }
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Some(AttemptError::retry(anyhow!("HTTP {status}"))));
+    }
+    if status == StatusCode::PARTIAL_CONTENT && resume {
+        let range = resp
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_content_range);
+        match range {
+            Some((start, total)) if start == state.got => {
+                if total.is_some() {
+                    state.total = total;
+                }
+            }
+            // 服务端给的区间和我们要的对不上: 不冒险拼接, 下一轮从头来
+            _ => {
+                state.got = 0;
+                state.ranges = false;
+                reset_file(file).await.map_err(|e| Some(AttemptError::fatal(e)))?;
+                return Err(Some(AttemptError::retry(anyhow!(
+                    "unexpected Content-Range in resumed response"
+                ))));
+            }
+        }
+    } else if status.is_success() {
+        // 首次请求, 或者服务端忽略了 Range / If-Range 不匹配 (文件变了): 整个重写
+        if state.got > 0 {
+            reset_file(file).await.map_err(|e| Some(AttemptError::fatal(e)))?;
+            state.got = 0;
+        }
+        let headers = resp.headers();
+        let accepts_bytes = headers
+            .get(ACCEPT_RANGES)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.split(',').any(|u| u.trim().eq_ignore_ascii_case("bytes")));
+        let etag = headers
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        state.ranges = accepts_bytes || etag.is_some();
+        // 弱 ETag 不能用于 If-Range
+        state.validator = etag.filter(|t| !t.starts_with("W/")).or_else(|| {
+            headers
+                .get(LAST_MODIFIED)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        });
+        state.total = resp.content_length();
+    } else {
+        return Err(Some(AttemptError::fatal(anyhow!("HTTP {status}"))));
+    }
+
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(None);
+        }
+        let bytes = chunk.map_err(|e| Some(AttemptError::retry(anyhow!(e).context("read chunk"))))?;
+        file.write_all(&bytes)
+            .await
+            .map_err(|e| Some(AttemptError::fatal(anyhow!(e).context("write chunk"))))?;
+        state.got += bytes.len() as u64;
+        if let Some(limiter) = crate::downloader::throttle::rate_limiter() {
+            limiter.consume(bytes.len() as u64).await;
+        }
+        let _ = events.send(ProgressEvent::Downloading {
+            name: name.to_string(),
+            got: state.got,
+            total: state.total,
+        });
+    }
+    if let Some(total) = state.total {
+        if state.got < total {
+            return Err(Some(AttemptError::retry(anyhow!(
+                "connection closed after {} of {total} bytes",
+                state.got
+            ))));
+        }
+    }
+    Ok(())
+}
+
+async fn reset_file(file: &mut tokio::fs::File) -> Result<()> {
+    use tokio::io::AsyncSeekExt;
+    file.set_len(0).await.context("truncate archive file")?;
+    file.seek(std::io::SeekFrom::Start(0))
+        .await
+        .context("rewind archive file")?;
+    Ok(())
+}
+
+/// `bytes 100-199/1000` → `(100, Some(1000))`; 总长为 `*` 时是 None。
+fn parse_content_range(v: &str) -> Option<(u64, Option<u64>)> {
+    let rest = v.trim().strip_prefix("bytes ")?;
+    let (range, total) = rest.split_once('/')?;
+    let (start, _end) = range.split_once('-')?;
+    let start = start.trim().parse().ok()?;
+    let total = match total.trim() {
+        "*" => None,
+        t => Some(t.parse().ok()?),
+    };
+    Some((start, total))
+}
+
+/// 归档在临时目录里的文件名: 取 URL 最后一段 (解压按后缀判断格式)。
+fn archive_filename(url: &str) -> String {
+    url.rsplit('/').next().unwrap_or("download.bin").to_string()
+}
+
+/// 按候选顺序查缓存, 第一个命中的拷进新临时目录。
+async fn fetch_from_cache(
+    cache: &ArchiveCache,
+    name: &str,
+    version: &str,
+    candidates: &[CandidateUrl],
+    events: &tokio::sync::mpsc::UnboundedSender<ProgressEvent>,
+) -> Result<Option<FetchedArchive>, DownloaderError> {
+    let urls: Vec<String> = candidates.iter().map(|c| c.url.clone()).collect();
+    let lookup_cache = cache.clone();
+    let version = version.to_string();
+    let hit = tokio::task::spawn_blocking(move || {
+        urls.into_iter().find_map(|url| {
+            lookup_cache
+                .lookup(&url, &version)
+                .map(|(blob, _)| (url, blob))
+        })
+    })
+    .await
+    .ok()
+    .flatten();
+    let Some((url, blob)) = hit else {
+        return Ok(None);
+    };
+
+    let temp_dir = tempfile::tempdir().map_err(|e| DownloaderError::Failed {
+        kind: FailureKind::DownloadInterrupted,
+        source: anyhow!(e).context("mkdir tempdir for cached archive"),
+    })?;
+    let archive_path = temp_dir.path().join(archive_filename(&url));
+    // blob 可能刚被别的进程按大小上限淘汰: 拷不出来就当未命中, 照常下载
+    let size = match tokio::fs::copy(&blob, &archive_path).await {
+        Ok(size) => size,
+        Err(e) => {
+            crate::display::status_debug("cache", &format!("{name}: copy out failed: {e}"));
+            return Ok(None);
+        }
+    };
+    crate::display::status_debug("cache", &format!("{name}: hit {url}"));
+    let _ = events.send(ProgressEvent::Downloading {
+        name: name.to_string(),
+        got: size,
+        total: Some(size),
+    });
+    Ok(Some(FetchedArchive {
+        temp_dir,
+        archive_path,
+        winning_url: url,
+    }))
+}
+
 /// 并发 HEAD 探测。最多 16 个同时在飞, 每个 5s 超时。第一个返回 2xx
 /// 的就停, 其余的 task 被 drop 时会 cancel。全部失败 → AllUrlsFailed。
 ///
@@ -170,6 +496,7 @@ async fn head_probe_concurrent<'a>(
         let cancel = cancel.clone();
         tasks.push(async move {
             let _permit = sem.acquire_owned().await.ok()?;
+            let _host_permit = crate::downloader::throttle::host_permit(&url).await;
             if cancel.load(Ordering::SeqCst) {
                 return None;
             }
@@ -249,4 +576,20 @@ mod tests {
     fn parse_sha256_rejects_non_hex() {
         assert_eq!(parse_sha256_hex("z".repeat(64)), None);
     }
+
+    #[test]
+    fn parse_content_range_forms() {
+        assert_eq!(parse_content_range("bytes 100-199/1000"), Some((100, Some(1000))));
+        assert_eq!(parse_content_range("bytes 5-9/*"), Some((5, None)));
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_cap() {
+        let p = RetryPolicy::default();
+        assert_eq!(p.delay(1).as_millis(), 500);
+        assert_eq!(p.delay(2).as_millis(), 1000);
+        assert_eq!(p.delay(3).as_millis(), 2000);
+        assert_eq!(p.delay(10), p.max_delay);
+    }
 }
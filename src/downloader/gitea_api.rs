@@ -0,0 +1,47 @@
+//! Gitea / Forgejo (含 Codeberg) Releases API client。
+//!
+//! Endpoint: `GET {api}/repos/{owner}/{repo}/releases/tags/{tag}`
+//! Auth:    `Authorization: token <token>` (可选)
+//!
+//! 响应形状与 GitHub 一致 (`assets[].name` / `browser_download_url`),
+//! 状态码语义也一致 (见 `release_host::map_status`)。
+
+use serde::Deserialize;
+
+use crate::downloader::release_host::{map_status, ReleaseApiError, ReleaseAsset};
+
+#[derive(Debug, Deserialize)]
+struct ReleaseResponse {
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+pub async fn fetch_release_assets(
+    client: &reqwest::Client,
+    base_url: &str,
+    owner: &str,
+    repo: &str,
+    tag: &str,
+    token: Option<&str>,
+) -> Result<Vec<ReleaseAsset>, ReleaseApiError> {
+    let url = format!("{base_url}/repos/{owner}/{repo}/releases/tags/{tag}");
+    let mut req = client
+        .get(&url)
+        .header("Accept", "application/json")
+        .header(
+            "User-Agent",
+            "cargo-fresh (https://github.com/jenkinpan/cargo-fresh)",
+        );
+    if let Some(t) = token {
+        req = req.header("Authorization", format!("token {t}"));
+    }
+    let resp = req.send().await.map_err(ReleaseApiError::Network)?;
+    if let Some(err) = map_status(resp.status().as_u16()) {
+        return Err(err);
+    }
+    let body: ReleaseResponse = resp
+        .json()
+        .await
+        .map_err(|e| ReleaseApiError::Parse(e.to_string()))?;
+    Ok(body.assets)
+}
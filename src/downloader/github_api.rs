@@ -1,5 +1,5 @@
 //! GitHub Releases API client. 一次 API 拿一个 release 的所有 assets,
-//! 替代盲探 360 个 HEAD 的方案。
+//! 替代盲探 120N 个 HEAD (N = target 别名数) 的方案。
 //!
 //! Endpoint: `GET https://api.github.com/repos/{owner}/{repo}/releases/tags/{tag}`
 //! Auth:    `Authorization: Bearer <token>` (可选;匿名 60/hr,认证 5000/hr)
@@ -12,11 +12,8 @@
 
 use serde::Deserialize;
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct ReleaseAsset {
-    pub name: String,
-    pub browser_download_url: String,
-}
+// 资产与错误类型在各托管平台间共享, 定义在 release_host; 旧名字保留给现有调用方。
+pub use crate::downloader::release_host::{ReleaseApiError as GithubApiError, ReleaseAsset};
 
 #[derive(Debug, Deserialize)]
 struct ReleaseResponse {
@@ -25,26 +22,6 @@ struct ReleaseResponse {
     assets: Vec<ReleaseAsset>,
 }
 
-#[derive(Debug)]
-pub enum GithubApiError {
-    NotFound,
-    RateLimited,
-    Network(reqwest::Error),
-    Parse(String),
-}
-
-impl std::fmt::Display for GithubApiError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            GithubApiError::NotFound => write!(f, "release not found"),
-            GithubApiError::RateLimited => write!(f, "GitHub API rate limited or unauthorized"),
-            GithubApiError::Network(e) => write!(f, "network error: {e}"),
-            GithubApiError::Parse(s) => write!(f, "JSON parse error: {s}"),
-        }
-    }
-}
-impl std::error::Error for GithubApiError {}
-
 pub async fn fetch_release_assets(
     client: &reqwest::Client,
     base_url: &str,
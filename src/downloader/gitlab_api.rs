@@ -0,0 +1,95 @@
+//! GitLab Releases API client (gitlab.com 与自托管实例)。
+//!
+//! Endpoint: `GET {api}/projects/{url-encoded namespace/project}/releases/{url-encoded tag}`
+//! Auth:    `PRIVATE-TOKEN: <token>` (可选; 私有项目必须)
+//!
+//! GitLab 的 release 资产是"链接" (`assets.links[]`), 不是上传的文件本身:
+//! 优先取 `direct_asset_url` (`/-/releases/{tag}/downloads/...` permalink),
+//! 没有时退回 `url`。`assets.sources[]` 是自动生成的源码包, 忽略。
+//! 状态码语义与 GitHub 客户端一致 (见 `release_host::map_status`)。
+
+use serde::Deserialize;
+
+use crate::downloader::release_host::{map_status, ReleaseApiError, ReleaseAsset};
+
+#[derive(Debug, Deserialize)]
+struct ReleaseResponse {
+    assets: Assets,
+}
+
+#[derive(Debug, Deserialize)]
+struct Assets {
+    #[serde(default)]
+    links: Vec<Link>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    name: String,
+    url: String,
+    direct_asset_url: Option<String>,
+}
+
+pub async fn fetch_release_assets(
+    client: &reqwest::Client,
+    base_url: &str,
+    project: &str,
+    tag: &str,
+    token: Option<&str>,
+) -> Result<Vec<ReleaseAsset>, ReleaseApiError> {
+    let url = format!(
+        "{base_url}/projects/{}/releases/{}",
+        encode_path_segment(project),
+        encode_path_segment(tag)
+    );
+    let mut req = client.get(&url).header(
+        "User-Agent",
+        "cargo-fresh (https://github.com/jenkinpan/cargo-fresh)",
+    );
+    if let Some(t) = token {
+        req = req.header("PRIVATE-TOKEN", t);
+    }
+    let resp = req.send().await.map_err(ReleaseApiError::Network)?;
+    if let Some(err) = map_status(resp.status().as_u16()) {
+        return Err(err);
+    }
+    let body: ReleaseResponse = resp
+        .json()
+        .await
+        .map_err(|e| ReleaseApiError::Parse(e.to_string()))?;
+    Ok(body
+        .assets
+        .links
+        .into_iter()
+        .map(|l| ReleaseAsset {
+            name: l.name,
+            browser_download_url: l.direct_asset_url.unwrap_or(l.url),
+        })
+        .collect())
+}
+
+/// GitLab 要求 project 路径与 tag 作为单个路径段出现, `/` 必须编码成 `%2F`。
+/// 只保留 RFC 3986 unreserved 字符, 其余一律百分号编码。
+pub fn encode_path_segment(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_slashes_and_keeps_unreserved() {
+        assert_eq!(encode_path_segment("group/sub/tool"), "group%2Fsub%2Ftool");
+        assert_eq!(encode_path_segment("tool-v1.2.3_rc~1"), "tool-v1.2.3_rc~1");
+        assert_eq!(encode_path_segment("tool/v1+b"), "tool%2Fv1%2Bb");
+    }
+}
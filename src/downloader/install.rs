@@ -9,6 +9,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use crate::downloader::events::{DownloaderError, FailureKind, UnsupportedReason};
+use crate::models::NewInstall;
 
 /// Serialize concurrent writes to `$CARGO_HOME/.crates.toml` and
 /// `$CARGO_HOME/.crates2.json` from this process. Concurrent updates
@@ -19,10 +20,14 @@ use crate::downloader::events::{DownloaderError, FailureKind, UnsupportedReason}
 /// `cargo install`).
 pub(crate) static CRATES_FILES_LOCK: Mutex<()> = Mutex::new(());
 
+/// `new_record` 为 Some 表示全新安装: 按 `package_name` 新建元数据条目,
+/// bins 记成实际装上的 `binary_name`; None 时只改已有条目的版本。
 pub fn install_binary(
     src: &Path,
+    package_name: &str,
     binary_name: &str,
     new_version: &str,
+    new_record: Option<&NewInstall>,
 ) -> Result<PathBuf, DownloaderError> {
     if cfg!(windows) {
         return Err(DownloaderError::Unsupported(
@@ -55,10 +60,34 @@ pub fn install_binary(
         let _guard = CRATES_FILES_LOCK
             .lock()
             .unwrap_or_else(|poisoned| poisoned.into_inner());
-        crate::package::crates2::write_install_record(&cargo_home, binary_name, new_version)
+        if let Some(new) = new_record {
+            let bins = [binary_name.to_string()];
+            crate::package::crates2::insert_install_record(
+                &cargo_home,
+                package_name,
+                new_version,
+                &bins,
+                new,
+            )
             .map_err(|e| failed_install(e.context("update .crates2.json")))?;
-        crate::package::crates_toml::write_install_record(&cargo_home, binary_name, new_version)
+            crate::package::crates_toml::insert_install_record(
+                &cargo_home,
+                package_name,
+                new_version,
+                &new.source_id,
+                &bins,
+            )
             .map_err(|e| failed_install(e.context("update .crates.toml")))?;
+        } else {
+            crate::package::crates2::write_install_record(&cargo_home, binary_name, new_version)
+                .map_err(|e| failed_install(e.context("update .crates2.json")))?;
+            crate::package::crates_toml::write_install_record(
+                &cargo_home,
+                binary_name,
+                new_version,
+            )
+            .map_err(|e| failed_install(e.context("update .crates.toml")))?;
+        }
     }
 
     Ok(dest)
@@ -3,24 +3,37 @@
 //! 单元拆分:
 //! - `events`:  ProgressEvent / DownloaderError 类型 (无逻辑)
 //! - `resolve`: 候选 URL 推导 (纯函数)
+//! - `binstall_meta`: 读 crate 声明的 `[package.metadata.binstall]`, 优先于猜测
+//! - `release_host`: 托管平台抽象 (GitHub / GitLab / Gitea·Forgejo·Codeberg),
+//!   各自的 release API 客户端在 `github_api` / `gitlab_api` / `gitea_api`
 //! - `fetch`:   HTTP 流式下载 + sha256
-//! - `archive`: tar.gz / zip 解压
+//! - `signature`: 按配置钉住的公钥校验 minisign / cosign 分离签名
+//! - `archive`: 按 magic bytes 识别格式, 解 tar.{gz,xz,zst,bz2} / zip / 裸二进制
 //! - `install`: atomic rename + .crates2.json 写
+//! - `bundle`:  离线包的写出 / 校验 / 安装 (`cargo fresh bundle` / `--from-bundle`)
 
 pub mod archive;
+pub mod binstall_meta;
+pub mod bundle;
+pub mod cache;
 pub mod events;
 pub mod fetch;
+pub mod gitea_api;
 pub mod github_api;
+pub mod gitlab_api;
 pub mod install;
 pub mod probe;
+pub mod release_host;
 pub mod resolve;
+pub mod signature;
+pub mod throttle;
 pub mod token;
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc::UnboundedSender;
 
-use crate::downloader::events::{DownloaderError, ProgressEvent, UnsupportedReason};
+use crate::downloader::events::{DownloaderError, FailureKind, ProgressEvent, UnsupportedReason};
 use crate::downloader::resolve::CandidateUrl;
 
 /// 调度器传给 downloader 的输入。
@@ -32,6 +45,8 @@ pub struct InstallSpec {
     /// 包名 != binary 名时 (ripgrep -> rg) 必须填, 否则解压找不到文件。
     /// 空 Vec → fallback 到 `name` 自身。
     pub bins: Vec<String>,
+    /// 全新安装 (`cargo fresh install`) 时新建 cargo 元数据条目用; 升级为 None。
+    pub new_record: Option<crate::models::NewInstall>,
 }
 
 pub struct InstallOutcome {
@@ -40,22 +55,21 @@ pub struct InstallOutcome {
     pub new_version: String,
 }
 
-/// 在 download_and_install 之前先调一次 GitHub Releases API,如果命中
+/// 在 download_and_install 之前先调一次托管平台的 Releases API,如果命中
 /// 直接返回单元素的候选列表;fetch::fetch 拿到只跑 1 个 HEAD 就胜出,
-/// 跳过 360 候选盲探。
+/// 跳过 120N 候选盲探 (N = target 别名数)。
 ///
-/// 返回 None 的两种情况调用方都走 fallback:
-/// - repo_url 不在 github.com 上 (e.g. gitlab.com 自托管)
-/// - API 任一形态失败 (RateLimited / Network / Parse)
+/// 返回 None 时调用方走 URL 枚举 fallback: API 任一形态失败
+/// (RateLimited / Network / Parse), 或所有 tag 都没匹配到 asset。
 async fn try_api_winning_url(
     client: &reqwest::Client,
     spec: &InstallSpec,
-    repo_url: &str,
+    repo: &release_host::ReleaseRepo,
     targets: &[String],
     name_candidates: &[String],
 ) -> Option<CandidateUrl> {
-    let (owner, repo) = github_api::parse_owner_repo(repo_url)?;
-    let token = token::discover_token();
+    let (token, token_source) = repo.token();
+    let token = token.map(Arc::<str>::from);
     let expected = Arc::new(resolve::expected_filenames(
         name_candidates,
         &spec.version,
@@ -73,11 +87,12 @@ async fn try_api_winning_url(
     crate::display::status_debug(
         "downloader",
         &format!(
-            "{}: github={}/{} token={} tags={} expected={}",
+            "{}: {}={}/{} token={} tags={} expected={}",
             spec.name,
-            owner,
-            repo,
-            token::discover_token_source(),
+            repo.kind.as_str(),
+            repo.owner,
+            repo.repo,
+            token_source,
             tag_strings.len(),
             expected.len()
         ),
@@ -93,22 +108,13 @@ async fn try_api_winning_url(
     for tag in tag_strings {
         let sem = sem.clone();
         let client = client.clone();
-        let owner = owner.clone();
         let repo = repo.clone();
+        let token = token.clone();
         let expected = expected.clone();
         let spec_name = spec.name.clone();
         tasks.push(async move {
             let _permit = sem.acquire_owned().await.ok()?;
-            match github_api::fetch_release_assets(
-                &client,
-                "https://api.github.com",
-                &owner,
-                &repo,
-                &tag,
-                token,
-            )
-            .await
-            {
+            match repo.list_assets(&client, &tag, token.as_deref()).await {
                 Ok(assets) => {
                     if let Some(asset) = github_api::match_winning_asset(&assets, &expected) {
                         let asset = asset.clone();
@@ -132,7 +138,7 @@ async fn try_api_winning_url(
                     );
                     None
                 }
-                Err(github_api::GithubApiError::NotFound) => {
+                Err(release_host::ReleaseApiError::NotFound) => {
                     crate::display::status_debug(
                         "downloader",
                         &format!("{spec_name}: API tag={tag} 404"),
@@ -152,7 +158,7 @@ async fn try_api_winning_url(
         });
     }
 
-    let mut result: Option<github_api::ReleaseAsset> = None;
+    let mut result: Option<release_host::ReleaseAsset> = None;
     while let Some(res) = tasks.next().await {
         match res {
             Some(Ok((asset, _tag))) => {
@@ -168,19 +174,10 @@ async fn try_api_winning_url(
     drop(tasks);
 
     match result {
-        Some(asset) => {
-            let archive_fmt = if asset.name.ends_with(".zip") {
-                resolve::ArchiveFmt::Zip
-            } else if asset.name.ends_with(".tar.gz") || asset.name.ends_with(".tgz") {
-                resolve::ArchiveFmt::TarGz
-            } else {
-                resolve::ArchiveFmt::Bin
-            };
-            Some(CandidateUrl {
-                url: asset.browser_download_url.clone(),
-                archive_fmt,
-            })
-        }
+        Some(asset) => Some(CandidateUrl {
+            archive_fmt: release_host::archive_fmt_for(&asset.name),
+            url: asset.browser_download_url,
+        }),
         None => {
             crate::display::status_debug(
                 "downloader",
@@ -194,34 +191,22 @@ async fn try_api_winning_url(
     }
 }
 
-/// 主入口——把 (spec, events_tx, cancel) 串成完整流水线。
-pub async fn download_and_install(
+/// 按托管平台约定猜候选 URL: release API 命中则只有一条, 否则模板全枚举。
+async fn guessed_candidates(
     client: &reqwest::Client,
-    spec: InstallSpec,
-    old_version: Option<String>,
-    events: UnboundedSender<ProgressEvent>,
-    cancel: Arc<AtomicBool>,
-) -> Result<InstallOutcome, DownloaderError> {
-    let _ = events.send(ProgressEvent::Resolving {
-        name: spec.name.clone(),
-    });
-    if cancel.load(Ordering::SeqCst) {
-        return Err(DownloaderError::Cancelled);
-    }
-
-    let targets = resolve::current_targets();
-    if targets.is_empty() {
-        return Err(DownloaderError::Unsupported(
-            UnsupportedReason::UnsupportedPlatform,
-        ));
-    }
-
+    spec: &InstallSpec,
+    targets: &[String],
+) -> Result<Vec<CandidateUrl>, DownloaderError> {
     let repo_url = spec
         .repo_url
         .as_deref()
         .ok_or(DownloaderError::Unsupported(
             UnsupportedReason::NoMetadataAndNoConvention,
         ))?;
+    // 认不出托管平台 (自建 cgit、sourcehut 等) → 没有可用的 release 约定
+    let repo = release_host::ReleaseRepo::parse(repo_url).ok_or(DownloaderError::Unsupported(
+        UnsupportedReason::NoMetadataAndNoConvention,
+    ))?;
 
     // {name} 候选: package 名先 (canonical, 多数包文件名沿用), 再加 binary 名
     // (覆盖 tauri-cli 这种情况: 包名 tauri-cli, 二进制 cargo-tauri,
@@ -232,11 +217,11 @@ pub async fn download_and_install(
             name_candidates.push(b.clone());
         }
     }
-    // API-first: 1 GitHub API request -> single-URL candidate list -> fetch
-    // does 1 HEAD + stream GET. Fallback to full 360-URL candidate enumeration
-    // when API is unreachable / rate-limited / repo isn't on github.com.
+    // API-first: 1 release API request -> single-URL candidate list -> fetch
+    // does 1 HEAD + stream GET. Fallback to the 120-URLs-per-alias candidate enumeration
+    // when the API is unreachable / rate-limited / has no matching asset.
     let candidates =
-        match try_api_winning_url(client, &spec, repo_url, &targets, &name_candidates).await {
+        match try_api_winning_url(client, spec, &repo, targets, &name_candidates).await {
             Some(winner) => {
                 crate::display::status_debug(
                     "downloader",
@@ -245,8 +230,12 @@ pub async fn download_and_install(
                 vec![winner]
             }
             None => {
-                let urls =
-                    resolve::candidate_urls(&name_candidates, &spec.version, repo_url, &targets)?;
+                let urls = resolve::candidate_urls_for_repo(
+                    &name_candidates,
+                    &spec.version,
+                    &repo,
+                    targets,
+                )?;
                 crate::display::status_debug(
                     "downloader",
                     &format!(
@@ -259,30 +248,350 @@ pub async fn download_and_install(
             }
         };
 
-    let fetched = fetch::fetch(client, &spec.name, &candidates, &events, cancel.clone()).await?;
+    Ok(candidates)
+}
 
-    if cancel.load(Ordering::SeqCst) {
-        return Err(DownloaderError::Cancelled);
+/// 同 `fetch::fetch`, 但"所有候选都没 2xx"返回 Ok(None), 让调用方换下一种来源。
+async fn fetch_or_miss(
+    client: &reqwest::Client,
+    name: &str,
+    version: &str,
+    candidates: &[CandidateUrl],
+    events: &UnboundedSender<ProgressEvent>,
+    cancel: &Arc<AtomicBool>,
+    cache: Option<&cache::ArchiveCache>,
+) -> Result<Option<fetch::FetchedArchive>, DownloaderError> {
+    let fetched =
+        fetch::fetch_with_cache(client, name, version, candidates, events, cancel.clone(), cache);
+    match fetched.await {
+        Ok(f) => Ok(Some(f)),
+        Err(DownloaderError::Failed {
+            kind: FailureKind::AllUrlsFailed,
+            ..
+        }) => Ok(None),
+        Err(e) => Err(e),
     }
+}
 
-    let _ = events.send(ProgressEvent::Extracting {
+/// 主入口——把 (spec, events_tx, cancel) 串成完整流水线:
+/// [`acquire`] 拿到校验过的归档, 再 [`install_archive`] 解压安装。
+pub async fn download_and_install(
+    client: &reqwest::Client,
+    spec: InstallSpec,
+    old_version: Option<String>,
+    events: UnboundedSender<ProgressEvent>,
+    cancel: Arc<AtomicBool>,
+) -> Result<InstallOutcome, DownloaderError> {
+    // 先挡掉装不全的包, 免得白下载一次
+    check_new_install_bins(&spec)?;
+    let acquired = acquire(client, &spec, &events, &cancel).await?;
+    install_archive(
+        &spec,
+        &acquired.fetched.archive_path,
+        acquired.fmt,
+        &acquired.bin_paths,
+        &events,
+        &cancel,
+    )?;
+    Ok(InstallOutcome {
+        name: spec.name,
+        old_version,
+        new_version: spec.version,
+    })
+}
+
+/// 下载到本地、通过 sha256 / 签名校验、尚未解压的归档。
+#[derive(Debug)]
+pub struct AcquiredArchive {
+    pub fetched: fetch::FetchedArchive,
+    pub fmt: resolve::ArchiveFmt,
+    /// crate 声明的 `bin-dir` 推出的 binary 在归档内的位置 (可能为空)。
+    pub bin_paths: Vec<(String, std::path::PathBuf)>,
+}
+
+/// 全新安装要写整条 `.crates2.json` 记录, 记录里的每个 binary 都得落盘;
+/// 解压只装一个, 所以多 binary 的全新安装交给 `cargo install`。更新已有记录
+/// 不受影响——那是替换用户已经装着的那个 binary。
+fn check_new_install_bins(spec: &InstallSpec) -> Result<(), DownloaderError> {
+    if spec.new_record.is_some() && spec.bins.len() > 1 {
+        return Err(DownloaderError::Unsupported(
+            UnsupportedReason::MultipleBinaries,
+        ));
+    }
+    Ok(())
+}
+
+/// binary 名候选: `.crates2.json` 的 bins[], 空时退回包名。
+fn bin_candidates(spec: &InstallSpec) -> Vec<String> {
+    if spec.bins.is_empty() {
+        vec![spec.name.clone()]
+    } else {
+        spec.bins.clone()
+    }
+}
+
+/// 流水线前半段: 推导候选 → 下载 → 签名校验。`bundle` 直接拿结果打包,
+/// 不在本机安装。
+pub async fn acquire(
+    client: &reqwest::Client,
+    spec: &InstallSpec,
+    events: &UnboundedSender<ProgressEvent>,
+    cancel: &Arc<AtomicBool>,
+) -> Result<AcquiredArchive, DownloaderError> {
+    acquire_for_targets(client, spec, &resolve::current_targets(), events, cancel).await
+}
+
+/// 同 [`acquire`], 但按给定的 target 别名列表找归档 (`bundle --target`
+/// 给别的机器下载)。`targets` 为空视为平台不支持。
+pub async fn acquire_for_targets(
+    client: &reqwest::Client,
+    spec: &InstallSpec,
+    targets: &[String],
+    events: &UnboundedSender<ProgressEvent>,
+    cancel: &Arc<AtomicBool>,
+) -> Result<AcquiredArchive, DownloaderError> {
+    let _ = events.send(ProgressEvent::Resolving {
         name: spec.name.clone(),
     });
+    if cancel.load(Ordering::SeqCst) {
+        return Err(DownloaderError::Cancelled);
+    }
 
-    // archive_fmt 从 fetched URL 的扩展名复算——更鲁棒
-    let fmt = if fetched.winning_url.ends_with(".zip") {
-        resolve::ArchiveFmt::Zip
+    if targets.is_empty() {
+        return Err(DownloaderError::Unsupported(
+            UnsupportedReason::UnsupportedPlatform,
+        ));
+    }
+
+    let config = crate::config::get();
+    let archive_cache = cache::ArchiveCache::from_config(&config.cache);
+    let archive_cache = archive_cache.as_ref();
+    // [[rewrite]] 作用于每一条候选 (自定义 / 声明 / API / 枚举), 之后的 HEAD、
+    // GET、.sha256 与签名文件都走改写后的地址
+    let rewrite = |cands: Vec<CandidateUrl>| -> Vec<CandidateUrl> {
+        cands
+            .into_iter()
+            .map(|c| CandidateUrl {
+                url: config.rewrite_url(&c.url),
+                ..c
+            })
+            .collect()
+    };
+    // 自定义 / 声明来源给出的格式是确定的 (可能是无扩展名的裸二进制),
+    // 命中时记下来; 猜测路径命中时为 None, 按 URL 后缀复算
+    let mut winner_fmt = None;
+    let mut fetched = None;
+
+    // 1. 配置里的自定义地址 ([packages."<glob>"] prebuilt-urls)
+    if let Some((templates, mode)) = config.prebuilt_sources(&spec.name) {
+        let urls = rewrite(resolve::custom_candidate_urls(
+            templates,
+            &spec.name,
+            &spec.version,
+            targets,
+        ));
+        crate::display::status_debug(
+            "downloader",
+            &format!("{}: {} candidates (configured prebuilt-urls)", spec.name, urls.len()),
+        );
+        fetched = match mode {
+            // instead: 不中就是不中, AllUrlsFailed 直接交给调度器回退源码
+            crate::config::PrebuiltSource::Instead => {
+                Some(
+                    fetch::fetch_with_cache(
+                        client,
+                        &spec.name,
+                        &spec.version,
+                        &urls,
+                        events,
+                        cancel.clone(),
+                        archive_cache,
+                    )
+                    .await?,
+                )
+            }
+            crate::config::PrebuiltSource::Before => {
+                fetch_or_miss(
+                    client,
+                    &spec.name,
+                    &spec.version,
+                    &urls,
+                    events,
+                    cancel,
+                    archive_cache,
+                )
+                .await?
+            }
+        };
+        winner_fmt = fetched
+            .as_ref()
+            .and_then(|f| urls.iter().find(|c| c.url == f.winning_url))
+            .map(|c| c.archive_fmt);
+    }
+
+    // 2. crate 自己声明的 pkg-url (package.metadata.binstall)
+    let meta = if fetched.is_none() {
+        binstall_meta::lookup(client, &spec.name, &spec.version).await
     } else {
-        resolve::ArchiveFmt::TarGz
+        None
+    };
+    let ctx = binstall_meta::TemplateCtx {
+        name: &spec.name,
+        version: &spec.version,
+        repo: spec.repo_url.as_deref(),
+    };
+    let mut declared = meta
+        .as_deref()
+        .map(|m| m.candidates(ctx, targets))
+        .unwrap_or_default();
+    for d in &mut declared {
+        d.candidate.url = config.rewrite_url(&d.candidate.url);
+    }
+    if fetched.is_none() && !declared.is_empty() {
+        let urls: Vec<CandidateUrl> = declared.iter().map(|d| d.candidate.clone()).collect();
+        crate::display::status_debug(
+            "downloader",
+            &format!("{}: {} candidates (declared pkg-url)", spec.name, urls.len()),
+        );
+        fetched = fetch_or_miss(
+            client,
+            &spec.name,
+            &spec.version,
+            &urls,
+            events,
+            cancel,
+            archive_cache,
+        )
+        .await?;
+        if fetched.is_none() {
+            crate::display::status_debug(
+                "downloader",
+                &format!("{}: declared pkg-url missed, trying guessed templates", spec.name),
+            );
+        }
+    }
+    let declared_hit = fetched.as_ref().and_then(|f| {
+        declared
+            .iter()
+            .find(|d| d.candidate.url == f.winning_url)
+    });
+    if let Some(d) = declared_hit {
+        winner_fmt = Some(d.candidate.archive_fmt);
+    }
+
+    // 3. 猜测路径: release API → 模板枚举
+    let fetched = match fetched {
+        Some(f) => f,
+        None => {
+            let candidates = rewrite(guessed_candidates(client, spec, targets).await?);
+            fetch::fetch_with_cache(
+                client,
+                &spec.name,
+                &spec.version,
+                &candidates,
+                events,
+                cancel.clone(),
+                archive_cache,
+            )
+            .await?
+        }
     };
 
+    if cancel.load(Ordering::SeqCst) {
+        return Err(DownloaderError::Cancelled);
+    }
+
+    // 钉了公钥的包: 校验分离签名, 按 [signatures] 策略继续 / 警告 / 回退源码
+    if let Some(keys) = config.pinned_keys(&spec.name) {
+        let outcome = signature::check(client, &fetched.winning_url, &fetched.archive_path, &keys)
+            .await
+            .map_err(|e| DownloaderError::Failed {
+                kind: FailureKind::ArchiveUnreadable,
+                source: e,
+            })?;
+        crate::display::status_debug(
+            "signature",
+            &format!("{}: {outcome:?}", spec.name),
+        );
+        match signature::decide(&outcome, &config.signatures) {
+            signature::Decision::Continue => {}
+            signature::Decision::Warn(message) => {
+                let _ = events.send(ProgressEvent::Warning {
+                    name: spec.name.clone(),
+                    message,
+                });
+            }
+            signature::Decision::Fallback(message) => {
+                return Err(DownloaderError::Failed {
+                    kind: FailureKind::SignatureRejected,
+                    source: anyhow::anyhow!(message),
+                });
+            }
+        }
+    }
+
+    // 格式以文件头的 magic bytes 为准 (扩展名会骗人: 无扩展名的 tar.xz、
+    // 叫 .tar.gz 的裸二进制); 认不出时退回声明的格式, 再退回 URL 扩展名
+    let declared_fmt = winner_fmt
+        .unwrap_or_else(|| release_host::archive_fmt_for(&fetched.winning_url));
+    let fmt = match archive::detect_format(&fetched.archive_path) {
+        Ok(Some(detected)) => {
+            if detected != declared_fmt {
+                crate::display::status_debug(
+                    "downloader",
+                    &format!(
+                        "{}: {} looks like {}, not {}",
+                        spec.name,
+                        fetched.winning_url,
+                        detected.as_str(),
+                        declared_fmt.as_str()
+                    ),
+                );
+            }
+            detected
+        }
+        _ => declared_fmt,
+    };
+    // 声明的 bin-dir 精确指出 binary 在归档里的位置, 找不到再按名字搜索
+    let bin_paths = match (declared_hit, meta.as_deref()) {
+        (Some(d), Some(m)) => {
+            m.bin_paths(ctx, &d.target, &fetched.winning_url, &bin_candidates(spec))
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(AcquiredArchive {
+        fetched,
+        fmt,
+        bin_paths,
+    })
+}
+
+/// 流水线后半段: 解压 `archive_path`, 原子安装到 `~/.cargo/bin` 并更新
+/// `.crates.toml` / `.crates2.json`。`--from-bundle` 从离线包安装也走这里。
+pub fn install_archive(
+    spec: &InstallSpec,
+    archive_path: &std::path::Path,
+    fmt: resolve::ArchiveFmt,
+    bin_paths: &[(String, std::path::PathBuf)],
+    events: &UnboundedSender<ProgressEvent>,
+    cancel: &Arc<AtomicBool>,
+) -> Result<(), DownloaderError> {
+    check_new_install_bins(spec)?;
+    let _ = events.send(ProgressEvent::Extracting {
+        name: spec.name.clone(),
+    });
+
     // 包名 != binary 名时 (ripgrep -> rg), spec.bins 来自 .crates2.json;
     // 空时 fallback 到 spec.name (单 binary 普通包路径)
-    let mut bin_candidates: Vec<String> = spec.bins.clone();
-    if bin_candidates.is_empty() {
-        bin_candidates.push(spec.name.clone());
-    }
-    let extracted = archive::extract(&fetched.archive_path, fmt, &bin_candidates)?;
+    let limits = archive::ExtractLimits::from_config(&crate::config::get().extract);
+    let extracted = archive::extract_with_bin_paths(
+        archive_path,
+        fmt,
+        &bin_candidates(spec),
+        bin_paths,
+        limits,
+    )?;
 
     if cancel.load(Ordering::SeqCst) {
         return Err(DownloaderError::Cancelled);
@@ -296,18 +605,55 @@ pub async fn download_and_install(
     // 当作目标文件名, .crates*.json 写入器会通过 bins[] 找到对应包条目
     let _installed_path = install::install_binary(
         &extracted.binary_path,
+        &spec.name,
         &extracted.binary_name,
         &spec.version,
+        spec.new_record.as_ref(),
     )?;
 
     let _ = events.send(ProgressEvent::Done {
         name: spec.name.clone(),
         version: spec.version.clone(),
     });
+    Ok(())
+}
 
-    Ok(InstallOutcome {
-        name: spec.name,
-        old_version,
-        new_version: spec.version,
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(bins: &[&str], new: bool) -> InstallSpec {
+        InstallSpec {
+            name: "tool".into(),
+            version: "1.0.0".into(),
+            repo_url: None,
+            bins: bins.iter().map(|b| b.to_string()).collect(),
+            new_record: new.then(|| crate::models::NewInstall {
+                source_id: crate::package::registry::CRATES_IO_SOURCE_ID.to_string(),
+                opts: crate::models::InstallOpts::default(),
+            }),
+        }
+    }
+
+    #[test]
+    fn multi_binary_fresh_install_goes_to_cargo_install() {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let err = install_archive(
+            &spec(&["tool", "tool-helper"], true),
+            std::path::Path::new("/nonexistent.tar.gz"),
+            resolve::ArchiveFmt::TarGz,
+            &[],
+            &tx,
+            &cancel,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            DownloaderError::Unsupported(UnsupportedReason::MultipleBinaries)
+        ));
+
+        assert!(check_new_install_bins(&spec(&["tool"], true)).is_ok());
+        assert!(check_new_install_bins(&spec(&["tool", "tool-helper"], false)).is_ok());
+    }
 }
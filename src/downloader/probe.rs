@@ -16,7 +16,8 @@ use std::time::Duration;
 use futures_util::stream::{FuturesUnordered, StreamExt};
 use tokio::sync::Semaphore;
 
-use crate::downloader::resolve::candidate_urls;
+use crate::downloader::release_host::{ReleaseApiError, ReleaseRepo};
+use crate::downloader::resolve::candidate_urls_for_repo;
 use crate::models::{PackageInfo, PackageSource, PrebuiltAvailability};
 
 /// 单包最多同时在飞的 HEAD 请求数。
@@ -45,6 +46,7 @@ pub async fn probe_with_candidates(
             let cancel = cancel.clone();
             async move {
                 let _permit = sem.acquire_owned().await.ok()?;
+                let _host_permit = crate::downloader::throttle::host_permit(&url).await;
                 if cancel.load(Ordering::SeqCst) {
                     return None;
                 }
@@ -107,10 +109,50 @@ pub async fn probe_prebuilt(
     if targets.is_empty() {
         return PrebuiltAvailability::Unknown;
     }
-    let Some(repo) = crate::package::crates_api::fetch_repo_url(client, name).await else {
+    // 配置的自定义地址先探; `instead` 时它就是唯一结论
+    let config = crate::config::get();
+    if let Some((templates, mode)) = config.prebuilt_sources(name) {
+        let urls: Vec<String> =
+            crate::downloader::resolve::custom_candidate_urls(templates, name, version, &targets)
+                .into_iter()
+                .map(|c| config.rewrite_url(&c.url))
+                .collect();
+        let result = probe_with_candidates(client, &urls).await;
+        if mode == crate::config::PrebuiltSource::Instead
+            || result == PrebuiltAvailability::Prebuilt
+        {
+            return result;
+        }
+    }
+
+    let Some(repo_url) = crate::package::crates_api::fetch_repo_url(client, name).await else {
         return PrebuiltAvailability::Unknown;
     };
 
+    // crate 声明了 pkg-url: 先探声明的地址, 与安装路径的顺序一致
+    if let Some(meta) = crate::downloader::binstall_meta::lookup(client, name, version).await {
+        let ctx = crate::downloader::binstall_meta::TemplateCtx {
+            name,
+            version,
+            repo: Some(&repo_url),
+        };
+        let urls: Vec<String> = meta
+            .candidates(ctx, &targets)
+            .into_iter()
+            .map(|d| config.rewrite_url(&d.candidate.url))
+            .collect();
+        if !urls.is_empty()
+            && probe_with_candidates(client, &urls).await == PrebuiltAvailability::Prebuilt
+        {
+            return PrebuiltAvailability::Prebuilt;
+        }
+    }
+
+    // 认不出的托管平台: 既没 API 也没 URL 约定, downloader 一定回退源码
+    let Some(repo) = ReleaseRepo::parse(&repo_url) else {
+        return PrebuiltAvailability::Source;
+    };
+
     // bins 让 monorepo + binary 名 ≠ package 名的情况能命中
     // (tauri-cli 的 `cargo-tauri-aarch64-apple-darwin.zip`)
     let mut name_candidates = vec![name.to_string()];
@@ -122,9 +164,9 @@ pub async fn probe_prebuilt(
         }
     }
 
-    // --- API path (1-6 requests instead of 360 HEADs, now concurrent) -----
-    if let Some((owner, repo_name)) = crate::downloader::github_api::parse_owner_repo(&repo) {
-        let token = crate::downloader::token::discover_token();
+    // --- API path (1-6 requests instead of 120 HEADs per alias, now concurrent) -----
+    {
+        let token: Option<Arc<str>> = repo.token().0.map(Arc::from);
         let expected = Arc::new(crate::downloader::resolve::expected_filenames(
             &name_candidates,
             version,
@@ -145,21 +187,12 @@ pub async fn probe_prebuilt(
         for tag in candidate_tags {
             let sem = sem.clone();
             let client = client.clone();
-            let owner = owner.clone();
-            let repo_name = repo_name.clone();
+            let repo = repo.clone();
+            let token = token.clone();
             let expected = expected.clone();
             tasks.push(async move {
                 let _permit = sem.acquire_owned().await.ok()?;
-                match crate::downloader::github_api::fetch_release_assets(
-                    &client,
-                    "https://api.github.com",
-                    &owner,
-                    &repo_name,
-                    &tag,
-                    token,
-                )
-                .await
-                {
+                match repo.list_assets(&client, &tag, token.as_deref()).await {
                     Ok(assets) => {
                         if crate::downloader::github_api::match_winning_asset(&assets, &expected)
                             .is_some()
@@ -168,9 +201,7 @@ pub async fn probe_prebuilt(
                         }
                         Some(ApiTagResult::NoMatch)
                     }
-                    Err(crate::downloader::github_api::GithubApiError::NotFound) => {
-                        Some(ApiTagResult::NotFound)
-                    }
+                    Err(ReleaseApiError::NotFound) => Some(ApiTagResult::NotFound),
                     Err(_) => None, // RateLimited / Network / Parse — fatal
                 }
             });
@@ -205,9 +236,13 @@ pub async fn probe_prebuilt(
         // API 一次都没成 —— 走 fallback 兜底
     }
 
-    // --- Fallback: 旧的 HEAD 盲探 (API 不可达 / repo 不是 github.com) -------
-    let urls: Vec<String> = match candidate_urls(&name_candidates, version, &repo, &targets) {
-        Ok(cands) => cands.into_iter().map(|c| c.url).collect(),
+    // --- Fallback: 旧的 HEAD 盲探 (API 不可达 / 限流) ----------------------
+    let urls: Vec<String> = match candidate_urls_for_repo(&name_candidates, version, &repo, &targets)
+    {
+        Ok(cands) => cands
+            .into_iter()
+            .map(|c| config.rewrite_url(&c.url))
+            .collect(),
         Err(_) => return PrebuiltAvailability::Source,
     };
     probe_with_candidates(client, &urls).await
@@ -254,6 +289,72 @@ pub async fn annotate_updates(packages: &mut [PackageInfo]) {
     }
 }
 
+/// `--prefer-prebuilt` 最多往回探测几个版本。每个版本都是一轮完整的
+/// `probe_prebuilt`, 不设上限的话落后很多的包会探上一分钟。
+const MAX_OLDER_PROBES: usize = 4;
+
+/// `--prefer-prebuilt` / `[packages."<glob>"] prefer-prebuilt`: 最新版只能
+/// 源码构建时, 从新到旧探测比已安装版本新的旧版本, 把第一个有预编译产物的
+/// 换进 `latest_version`, 跳过的最新版记在 `source_only_latest`。
+///
+/// `all` 为 true 时作用于全部更新候选, 否则只看配置了 `prefer-prebuilt` 的包。
+/// 还没跑过 [`annotate_updates`] 的包先探最新版本身。和 `annotate_updates`
+/// 一样串行, 理由相同。
+pub async fn prefer_prebuilt_versions(
+    packages: &mut [PackageInfo],
+    all: bool,
+    include_prerelease: bool,
+    registry_override: Option<&str>,
+) {
+    let Ok(client) = crate::package::http_client() else {
+        return;
+    };
+    let config = crate::config::get();
+    for pkg in packages.iter_mut() {
+        if !matches!(pkg.source, PackageSource::Crates)
+            || !pkg.has_update()
+            || !(all || config.prefers_prebuilt(&pkg.name))
+        {
+            continue;
+        }
+        let Some(latest) = pkg.latest_version.clone() else {
+            continue;
+        };
+        if pkg.prebuilt.is_none() {
+            pkg.prebuilt = Some(probe_prebuilt(client, &pkg.name, &latest).await);
+        }
+        if pkg.prebuilt != Some(PrebuiltAvailability::Source) {
+            continue;
+        }
+        let base = crate::package::registry::sparse_index_base(
+            config.package_registry(&pkg.name).or(registry_override),
+        );
+        let Ok(index) =
+            crate::package::sparse_index::fetch_latest(client, &base, &pkg.name).await
+        else {
+            continue;
+        };
+        let older = crate::package::older_update_candidates(
+            &index.versions,
+            &latest,
+            pkg.current_version.as_deref(),
+            include_prerelease || config.allows_prerelease(&pkg.name),
+        );
+        for version in older.into_iter().take(MAX_OLDER_PROBES) {
+            let kind = probe_prebuilt(client, &pkg.name, &version).await;
+            crate::display::status_debug(
+                "prebuilt",
+                &format!("{} {version}: {}", pkg.name, kind.kind_str()),
+            );
+            if kind == PrebuiltAvailability::Prebuilt {
+                pkg.source_only_latest = pkg.latest_version.replace(version);
+                pkg.prebuilt = Some(kind);
+                break;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,399 @@
+//! 发布托管平台抽象——把 crates.io `repository` URL 映射到"哪家平台、API 在哪、
+//! 预编译产物的下载 URL 长什么样"。
+//!
+//! 每个平台实现两件事:
+//! - "列出某 tag 的 release assets" (API-first 路径, 见 `github_api` /
+//!   `gitlab_api` / `gitea_api`)
+//! - 盲探用的下载 URL 约定 ([`ReleaseRepo::download_url`])
+//!
+//! | 平台                      | API                                             | 下载 URL                                             |
+//! |---------------------------|-------------------------------------------------|------------------------------------------------------|
+//! | GitHub                    | `{api}/repos/{o}/{r}/releases/tags/{tag}`       | `{web}/{o}/{r}/releases/download/{tag}/{file}`       |
+//! | GitLab                    | `{api}/projects/{o%2Fr}/releases/{tag}`         | `{web}/{o}/{r}/-/releases/{tag}/downloads/{file}`    |
+//! | Gitea / Forgejo / Codeberg| `{api}/repos/{o}/{r}/releases/tags/{tag}`       | `{web}/{o}/{r}/releases/download/{tag}/{file}`       |
+//!
+//! 内置识别 github.com / gitlab.com / codeberg.org; 自托管实例在配置文件
+//! `[hosts."<host>"]` 里声明 (`kind` 省略即 GitHub Enterprise, `api-base`
+//! 覆盖约定的 API 根)。认不出的主机返回 None, 调用方回退 cargo install。
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::config::HostConfig;
+
+/// 托管平台种类。`forgejo` / `codeberg` 是 `gitea` 的别名——三者 release API 同构。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    #[serde(alias = "forgejo", alias = "codeberg")]
+    Gitea,
+}
+
+impl ForgeKind {
+    /// `--debug` 输出用的短名。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "github",
+            ForgeKind::GitLab => "gitlab",
+            ForgeKind::Gitea => "gitea",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// 各平台 release API 的统一错误。
+#[derive(Debug)]
+pub enum ReleaseApiError {
+    NotFound,
+    RateLimited,
+    Network(reqwest::Error),
+    Parse(String),
+}
+
+impl std::fmt::Display for ReleaseApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReleaseApiError::NotFound => write!(f, "release not found"),
+            ReleaseApiError::RateLimited => {
+                write!(f, "release API rate limited or unauthorized")
+            }
+            ReleaseApiError::Network(e) => write!(f, "network error: {e}"),
+            ReleaseApiError::Parse(s) => write!(f, "JSON parse error: {s}"),
+        }
+    }
+}
+impl std::error::Error for ReleaseApiError {}
+
+/// 401/403/429 → RateLimited, 404 → NotFound, 其余非 200 → Parse。
+/// 三个平台共用同一套状态码语义, 调用方据此决定"换下一个 tag"还是"整体回退"。
+pub(crate) fn map_status(status: u16) -> Option<ReleaseApiError> {
+    match status {
+        200 => None,
+        404 => Some(ReleaseApiError::NotFound),
+        401 | 403 | 429 => Some(ReleaseApiError::RateLimited),
+        other => Some(ReleaseApiError::Parse(format!("unexpected status {other}"))),
+    }
+}
+
+/// 一个已识别平台的仓库。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseRepo {
+    pub kind: ForgeKind,
+    /// 小写主机名 (可带端口), 如 `gitlab.com`。token 发现按它区分实例。
+    pub host: String,
+    /// `https://gitlab.com` 形式, 无尾随 `/`。
+    pub web_base: String,
+    /// API 根, 如 `https://api.github.com` / `https://gitlab.com/api/v4`。
+    pub api_base: String,
+    /// GitHub / Gitea 是 owner; GitLab 是完整 namespace (可含子组, `group/sub`)。
+    pub owner: String,
+    pub repo: String,
+    /// 配置里 `token-env` 指定的环境变量名 (per-host token)。
+    pub token_env: Option<String>,
+}
+
+impl ReleaseRepo {
+    /// 用进程级配置里的 `[hosts]` 解析 repo URL。
+    pub fn parse(repo_url: &str) -> Option<Self> {
+        Self::parse_with(repo_url, &crate::config::get().hosts)
+    }
+
+    /// 纯函数版本: 显式传入自托管主机表, 便于测试。
+    pub fn parse_with(repo_url: &str, hosts: &BTreeMap<String, HostConfig>) -> Option<Self> {
+        let trimmed = repo_url.trim().trim_end_matches('/');
+        let (scheme, rest) = trimmed.split_once("://")?;
+        if scheme != "https" && scheme != "http" {
+            return None;
+        }
+        let (host, path) = rest.split_once('/')?;
+        let host = host.to_ascii_lowercase();
+        let web_base = format!("{scheme}://{host}");
+
+        // 配置里的 kind > 内置主机 > 配置过但没写 kind (按 GitHub Enterprise)
+        let cfg = hosts.get(&host);
+        let kind = cfg
+            .and_then(|c| c.kind)
+            .or_else(|| builtin_kind(&host))
+            .or(cfg.map(|_| ForgeKind::GitHub))?;
+        let api_base = cfg.and_then(|c| c.api_base.as_deref()).map_or_else(
+            || default_api_base(kind, &host, &web_base),
+            |b| b.trim_end_matches('/').to_string(),
+        );
+        let token_env = cfg.and_then(|c| c.token_env.clone());
+
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let (owner, repo) = match kind {
+            // GitLab 支持多级子组; `/-/` 之后是 tree/blob 等页面路径
+            ForgeKind::GitLab => {
+                let path = path
+                    .split("/-/")
+                    .next()
+                    .unwrap_or(path)
+                    .trim_end_matches('/');
+                let (ns, project) = path.rsplit_once('/')?;
+                (ns.to_string(), project.to_string())
+            }
+            // GitHub / Gitea 固定 owner/repo 两段, 之后的 /tree/main 之类忽略
+            ForgeKind::GitHub | ForgeKind::Gitea => {
+                let mut parts = path.split('/');
+                (parts.next()?.to_string(), parts.next()?.to_string())
+            }
+        };
+        if owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+        Some(ReleaseRepo {
+            kind,
+            host,
+            web_base,
+            api_base,
+            owner,
+            repo,
+            token_env,
+        })
+    }
+
+    /// 盲探用的产物下载 URL。
+    pub fn download_url(&self, tag: &str, filename: &str) -> String {
+        let Self {
+            web_base,
+            owner,
+            repo,
+            ..
+        } = self;
+        match self.kind {
+            ForgeKind::GitHub | ForgeKind::Gitea => {
+                format!("{web_base}/{owner}/{repo}/releases/download/{tag}/{filename}")
+            }
+            // release 资产链接的 permalink 约定 (link 的 filepath 为 `/{filename}`)
+            ForgeKind::GitLab => {
+                format!("{web_base}/{owner}/{repo}/-/releases/{tag}/downloads/{filename}")
+            }
+        }
+    }
+
+    /// 该平台的 token (及来源, 给 `--debug`)。
+    pub fn token(&self) -> (Option<String>, &'static str) {
+        crate::downloader::token::discover_for(self.kind, &self.host, self.token_env.as_deref())
+    }
+
+    /// "列出某 tag 的 release assets"——按平台分派到对应 API 客户端。
+    pub async fn list_assets(
+        &self,
+        client: &reqwest::Client,
+        tag: &str,
+        token: Option<&str>,
+    ) -> Result<Vec<ReleaseAsset>, ReleaseApiError> {
+        use crate::downloader::{gitea_api, github_api, gitlab_api};
+        match self.kind {
+            ForgeKind::GitHub => {
+                github_api::fetch_release_assets(
+                    client,
+                    &self.api_base,
+                    &self.owner,
+                    &self.repo,
+                    tag,
+                    token,
+                )
+                .await
+            }
+            ForgeKind::GitLab => {
+                let project = format!("{}/{}", self.owner, self.repo);
+                gitlab_api::fetch_release_assets(client, &self.api_base, &project, tag, token).await
+            }
+            ForgeKind::Gitea => {
+                gitea_api::fetch_release_assets(
+                    client,
+                    &self.api_base,
+                    &self.owner,
+                    &self.repo,
+                    tag,
+                    token,
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// 不用配置就认识的公共实例。
+fn builtin_kind(host: &str) -> Option<ForgeKind> {
+    match host {
+        "github.com" => Some(ForgeKind::GitHub),
+        "gitlab.com" => Some(ForgeKind::GitLab),
+        "codeberg.org" => Some(ForgeKind::Gitea),
+        _ => None,
+    }
+}
+
+/// 平台默认的 API 根 (自托管实例按约定推导; GitHub Enterprise 是 `/api/v3`)。
+fn default_api_base(kind: ForgeKind, host: &str, web_base: &str) -> String {
+    match kind {
+        ForgeKind::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+        ForgeKind::GitHub => format!("{web_base}/api/v3"),
+        ForgeKind::GitLab => format!("{web_base}/api/v4"),
+        ForgeKind::Gitea => format!("{web_base}/api/v1"),
+    }
+}
+
+/// 按资产文件名推断归档格式。API 命中的 asset 和最终胜出 URL 都用它。
+pub(crate) fn archive_fmt_for(name: &str) -> crate::downloader::resolve::ArchiveFmt {
+    use crate::downloader::resolve::ArchiveFmt;
+    if name.ends_with(".zip") {
+        ArchiveFmt::Zip
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        ArchiveFmt::TarGz
+    } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+        ArchiveFmt::TarXz
+    } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+        ArchiveFmt::TarZst
+    } else if name.ends_with(".tar.bz2") || name.ends_with(".tbz2") {
+        ArchiveFmt::TarBz2
+    } else {
+        ArchiveFmt::Bin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Option<ReleaseRepo> {
+        ReleaseRepo::parse_with(url, &BTreeMap::new())
+    }
+
+    #[test]
+    fn parses_github() {
+        let r = parse("https://github.com/BurntSushi/ripgrep.git").unwrap();
+        assert_eq!(r.kind, ForgeKind::GitHub);
+        assert_eq!(r.api_base, "https://api.github.com");
+        assert_eq!(
+            (r.owner.as_str(), r.repo.as_str()),
+            ("BurntSushi", "ripgrep")
+        );
+    }
+
+    #[test]
+    fn github_ignores_tree_suffix() {
+        let r = parse("https://github.com/tauri-apps/tauri/tree/dev/crates/tauri-cli").unwrap();
+        assert_eq!((r.owner.as_str(), r.repo.as_str()), ("tauri-apps", "tauri"));
+    }
+
+    #[test]
+    fn parses_gitlab_nested_groups() {
+        let r = parse("https://gitlab.com/group/sub/tool/-/tree/main").unwrap();
+        assert_eq!(r.kind, ForgeKind::GitLab);
+        assert_eq!(r.api_base, "https://gitlab.com/api/v4");
+        assert_eq!((r.owner.as_str(), r.repo.as_str()), ("group/sub", "tool"));
+    }
+
+    #[test]
+    fn parses_codeberg_as_gitea() {
+        let r = parse("https://codeberg.org/owner/tool/").unwrap();
+        assert_eq!(r.kind, ForgeKind::Gitea);
+        assert_eq!(r.api_base, "https://codeberg.org/api/v1");
+    }
+
+    #[test]
+    fn unknown_host_is_none() {
+        assert!(parse("https://example.com/x/y").is_none());
+        assert!(parse("https://github.com/only-owner").is_none());
+        assert!(parse("git@github.com:x/y.git").is_none());
+    }
+
+    #[test]
+    fn configured_host_uses_conventional_api_base() {
+        let mut hosts = BTreeMap::new();
+        hosts.insert(
+            "git.corp.example".to_string(),
+            HostConfig {
+                kind: Some(ForgeKind::Gitea),
+                ..HostConfig::default()
+            },
+        );
+        let r = ReleaseRepo::parse_with("https://Git.Corp.Example/team/tool", &hosts).unwrap();
+        assert_eq!(r.kind, ForgeKind::Gitea);
+        assert_eq!(r.host, "git.corp.example");
+        assert_eq!(r.api_base, "https://git.corp.example/api/v1");
+    }
+
+    #[test]
+    fn configured_host_without_kind_is_github_enterprise() {
+        let mut hosts = BTreeMap::new();
+        hosts.insert(
+            "ghe.corp.example".to_string(),
+            HostConfig {
+                api_base: Some("https://ghe-api.corp.example/v3/".to_string()),
+                token_env: Some("GHE_TOKEN".to_string()),
+                ..HostConfig::default()
+            },
+        );
+        let r = ReleaseRepo::parse_with("https://ghe.corp.example/team/tool", &hosts).unwrap();
+        assert_eq!(r.kind, ForgeKind::GitHub);
+        assert_eq!(r.api_base, "https://ghe-api.corp.example/v3");
+        assert_eq!(r.token_env.as_deref(), Some("GHE_TOKEN"));
+        assert_eq!(
+            r.download_url("v1.0.0", "tool.tar.gz"),
+            "https://ghe.corp.example/team/tool/releases/download/v1.0.0/tool.tar.gz"
+        );
+
+        hosts.get_mut("ghe.corp.example").unwrap().api_base = None;
+        let r = ReleaseRepo::parse_with("https://ghe.corp.example/team/tool", &hosts).unwrap();
+        assert_eq!(r.api_base, "https://ghe.corp.example/api/v3");
+    }
+
+    #[test]
+    fn builtin_host_entry_keeps_builtin_kind() {
+        // 只为改 token 来源写了 `[hosts."github.com"]`, 不应把 API 根改成 /api/v3
+        let mut hosts = BTreeMap::new();
+        hosts.insert(
+            "github.com".to_string(),
+            HostConfig {
+                token_env: Some("MY_GH".to_string()),
+                ..HostConfig::default()
+            },
+        );
+        let r = ReleaseRepo::parse_with("https://github.com/o/r", &hosts).unwrap();
+        assert_eq!(r.kind, ForgeKind::GitHub);
+        assert_eq!(r.api_base, "https://api.github.com");
+    }
+
+    #[test]
+    fn download_url_conventions() {
+        let gh = parse("https://github.com/o/r").unwrap();
+        assert_eq!(
+            gh.download_url("v1.0.0", "r.tar.gz"),
+            "https://github.com/o/r/releases/download/v1.0.0/r.tar.gz"
+        );
+        let gl = parse("https://gitlab.com/g/r").unwrap();
+        assert_eq!(
+            gl.download_url("v1.0.0", "r.tar.gz"),
+            "https://gitlab.com/g/r/-/releases/v1.0.0/downloads/r.tar.gz"
+        );
+        let cb = parse("https://codeberg.org/o/r").unwrap();
+        assert_eq!(
+            cb.download_url("v1.0.0", "r.tar.gz"),
+            "https://codeberg.org/o/r/releases/download/v1.0.0/r.tar.gz"
+        );
+    }
+
+    #[test]
+    fn archive_fmt_from_asset_name() {
+        use crate::downloader::resolve::ArchiveFmt;
+        assert_eq!(archive_fmt_for("x.zip"), ArchiveFmt::Zip);
+        assert_eq!(archive_fmt_for("x.tgz"), ArchiveFmt::TarGz);
+        assert_eq!(archive_fmt_for("x.tar.gz"), ArchiveFmt::TarGz);
+        assert_eq!(archive_fmt_for("x"), ArchiveFmt::Bin);
+        assert_eq!(archive_fmt_for("x.tar.xz"), ArchiveFmt::TarXz);
+        assert_eq!(archive_fmt_for("x.tar.zst"), ArchiveFmt::TarZst);
+        assert_eq!(archive_fmt_for("x.tbz2"), ArchiveFmt::TarBz2);
+    }
+}
@@ -1,30 +1,59 @@
-//! 把 (包名, 版本, 仓库 URL, target triple) 推导成一组候选 GitHub Release URL。
+//! 把 (包名, 版本, 仓库 URL, target triple) 推导成一组候选 release 下载 URL。
 //!
-//! 纯函数, 不做 HTTP——HEAD 探测在 fetch.rs。
+//! 纯函数, 不做 HTTP——HEAD 探测在 fetch.rs。下载 URL 的拼法按托管平台
+//! 分派 (`release_host::ReleaseRepo::download_url`), 文件名模板各平台共用。
 //!
 //! 文件名/路径模板列表借鉴自 cargo-binstall 的
 //! `crates/binstalk-fetchers/src/gh_crate_meta/hosting.rs`
 //! (Apache-2.0 OR MIT, https://github.com/cargo-bins/cargo-binstall)。
-//! 这里只保留 GitHub Release 路径, 不解析 `package.metadata.binstall` 自定义模板。
-//! 10 个文件名模板 × 2 个 tag 前缀 (v{version} / {version}) × 2 个归档格式
-//! (.tar.gz / .zip) × N 个 target 别名 = 40N 个候选 URL。
+//! 这里只保留 release 下载路径; crate 自己声明的 `package.metadata.binstall`
+//! 模板在 `binstall_meta` 里渲染, 优先于这里的猜测。
+//! 认得的文件名: 10 个文件名模板 × 6 种扩展名 (.tar.gz / .zip / .tar.xz /
+//! .tar.zst / .tgz / .tar.bz2) 再加 2 个裸二进制模板, 用于和 release API 列出的
+//! asset 做匹配。API 不可用时的 HEAD 盲探只试 .tar.gz / .zip 两种:
+//! 10 × 2 × 6 种 tag 路径 × N 个 target 别名 = 120N, 不把限流时的请求量翻三倍。
 
 use crate::downloader::events::{DownloaderError, UnsupportedReason};
+use crate::downloader::release_host::ReleaseRepo;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CandidateUrl {
     pub url: String,
-    /// 归档扩展名: "tar.gz" / "zip" / "bin"——给 archive.rs 分派用。
+    /// 按 URL / 声明推断的格式。下载后 `archive::detect_format` 会按文件头
+    /// 的 magic bytes 复核, 两者不一致时以文件内容为准。
     pub archive_fmt: ArchiveFmt,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum ArchiveFmt {
+    #[serde(rename = "tar.gz")]
     TarGz,
+    #[serde(rename = "tar.xz")]
+    TarXz,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+    #[serde(rename = "tar.bz2")]
+    TarBz2,
+    #[serde(rename = "zip")]
     Zip,
+    #[serde(rename = "bin")]
     Bin,
 }
 
+impl ArchiveFmt {
+    /// manifest / 调试输出里用的名字, 与 serde 的写法一致。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ArchiveFmt::TarGz => "tar.gz",
+            ArchiveFmt::TarXz => "tar.xz",
+            ArchiveFmt::TarZst => "tar.zst",
+            ArchiveFmt::TarBz2 => "tar.bz2",
+            ArchiveFmt::Zip => "zip",
+            ArchiveFmt::Bin => "bin",
+        }
+    }
+}
+
 /// 文件名模板 (借鉴自 cargo-binstall FULL_FILENAMES + NOVERSION_FILENAMES)。
 /// 占位符: {name} {version} {target} {ext}
 const FILENAME_TEMPLATES: &[&str] = &[
@@ -42,16 +71,60 @@ const FILENAME_TEMPLATES: &[&str] = &[
     "{name}_{target}.{ext}",
 ];
 
-const ARCHIVE_EXTS: &[(ArchiveFmt, &str)] = &[(ArchiveFmt::TarGz, "tar.gz"), (ArchiveFmt::Zip, "zip")];
+/// 归档扩展名, 按常见程度排序。
+const ARCHIVE_EXTS: &[(ArchiveFmt, &str)] = &[
+    (ArchiveFmt::TarGz, "tar.gz"),
+    (ArchiveFmt::Zip, "zip"),
+    (ArchiveFmt::TarXz, "tar.xz"),
+    (ArchiveFmt::TarZst, "tar.zst"),
+    (ArchiveFmt::TarGz, "tgz"),
+    (ArchiveFmt::TarBz2, "tar.bz2"),
+];
+
+/// HEAD 盲探只试的扩展名 (`ARCHIVE_EXTS` 的前两项, 覆盖绝大多数发布)。
+const PROBE_EXTS: &[(ArchiveFmt, &str)] = &[(ArchiveFmt::TarGz, "tar.gz"), (ArchiveFmt::Zip, "zip")];
+
+/// 裸二进制的文件名模板 (没有扩展名, 直接 chmod +x 安装)。
+const BIN_TEMPLATES: &[&str] = &["{name}-{target}", "{name}-{version}-{target}"];
+
+/// 一个 (name, version, target) 组合下的文件名。`all_formats` 为 true 时是
+/// 归档模板 × 全部扩展名再加裸二进制模板 (`expected_filenames` 匹配 asset 用,
+/// 不发请求); 为 false 时只有 .tar.gz / .zip (`candidate_urls` 盲探用)。
+fn filenames_for(
+    name: &str,
+    version: &str,
+    target: &str,
+    all_formats: bool,
+) -> Vec<(ArchiveFmt, String)> {
+    let render = |tmpl: &str| {
+        tmpl.replace("{name}", name)
+            .replace("{version}", version)
+            .replace("{target}", target)
+    };
+    let exts = if all_formats { ARCHIVE_EXTS } else { PROBE_EXTS };
+    let mut out: Vec<(ArchiveFmt, String)> = FILENAME_TEMPLATES
+        .iter()
+        .flat_map(|tmpl| {
+            exts.iter()
+                .map(move |(fmt, ext)| (*fmt, render(tmpl).replace("{ext}", ext)))
+        })
+        .collect();
+    if all_formats {
+        out.extend(BIN_TEMPLATES.iter().map(|tmpl| (ArchiveFmt::Bin, render(tmpl))));
+    }
+    out
+}
 
 /// 推导候选 URL 列表。第一个返回 2xx 的胜出。
 ///
 /// `repo_url` 形如 "https://github.com/owner/repo" (尾随 / 容忍)。
-/// 非 github.com 域返回 `Unsupported(NoMetadataAndNoConvention)`。
+/// 认不出托管平台的 URL (见 `release_host`) 返回 `Unsupported(NoMetadataAndNoConvention)`。
 ///
 /// `targets` 是一组等价的 target 别名 (例如 macOS aarch64 通常发布为
 /// `aarch64-apple-darwin` 也可能是 `arm64-apple-darwin` 或 `darwin-arm64`)。
-/// 输出长度 = 10 文件名 × 2 tag 前缀 × 2 归档 × N 别名 = 40N。
+/// 只试 .tar.gz / .zip: 输出长度 = 10 文件名 × 2 扩展名 × 6 tag 路径 × N 别名
+/// = 120N。其它格式和裸二进制只在 release API 的 asset 匹配里认
+/// (见 [`expected_filenames`])。
 /// `name_candidates` 是一组要试的 `{name}` 替换值: 通常包含 package 名 +
 /// binary 名 (例如 tauri-cli 包的 binary 是 cargo-tauri, 而文件名形如
 /// `cargo-tauri-aarch64-apple-darwin.zip` ——必须用 binary 名作 {name}
@@ -62,12 +135,19 @@ pub fn candidate_urls(
     repo_url: &str,
     targets: &[String],
 ) -> Result<Vec<CandidateUrl>, DownloaderError> {
-    let repo = repo_url.trim_end_matches('/');
-    if !repo.starts_with("https://github.com/") && !repo.starts_with("http://github.com/") {
-        return Err(DownloaderError::Unsupported(
-            UnsupportedReason::NoMetadataAndNoConvention,
-        ));
-    }
+    let repo = ReleaseRepo::parse(repo_url).ok_or(DownloaderError::Unsupported(
+        UnsupportedReason::NoMetadataAndNoConvention,
+    ))?;
+    candidate_urls_for_repo(name_candidates, version, &repo, targets)
+}
+
+/// 同 [`candidate_urls`], 但接收已解析好的 [`ReleaseRepo`]。
+pub fn candidate_urls_for_repo(
+    name_candidates: &[String],
+    version: &str,
+    repo: &ReleaseRepo,
+    targets: &[String],
+) -> Result<Vec<CandidateUrl>, DownloaderError> {
     if targets.is_empty() || name_candidates.is_empty() {
         return Err(DownloaderError::Unsupported(
             UnsupportedReason::NoMetadataAndNoConvention,
@@ -89,25 +169,21 @@ pub fn candidate_urls(
         format!("{pkg}/v{version}"),
         format!("{pkg}/{version}"),
     ];
-    let mut out = Vec::with_capacity(40 * targets.len() * name_candidates.len() * tag_paths.len() / 2);
+    let mut out = Vec::new();
     let mut seen = std::collections::HashSet::new();
     for name in name_candidates {
         for target in targets {
+            let filenames = filenames_for(name, version, target, false);
             for tag_path in &tag_paths {
-                let base = format!("{repo}/releases/download/{tag_path}");
-                for tmpl in FILENAME_TEMPLATES {
-                    for (fmt, ext) in ARCHIVE_EXTS {
-                        let filename = tmpl
-                            .replace("{name}", name)
-                            .replace("{version}", version)
-                            .replace("{target}", target)
-                            .replace("{ext}", ext);
-                        let url = format!("{base}/{filename}");
-                        // 同一 name (如 package == binary 时) 会产生重复
-                        // 候选, 这里去重避免做无效 HEAD
-                        if seen.insert(url.clone()) {
-                            out.push(CandidateUrl { url, archive_fmt: *fmt });
-                        }
+                for (fmt, filename) in &filenames {
+                    let url = repo.download_url(tag_path, filename);
+                    // 同一 name (如 package == binary 时) 会产生重复
+                    // 候选, 这里去重避免做无效 HEAD
+                    if seen.insert(url.clone()) {
+                        out.push(CandidateUrl {
+                            url,
+                            archive_fmt: *fmt,
+                        });
                     }
                 }
             }
@@ -116,39 +192,315 @@ pub fn candidate_urls(
     Ok(out)
 }
 
-/// 当前进程的 target triple 别名列表 (canonical 在最前)。
+/// 展开配置里的自定义 URL 模板 (`[packages."<glob>"] prebuilt-urls`)。
 ///
-/// 不同发布者命名约定不一 (Rust triple vs Go/npm 风格 vs Apple 简写),
+/// 占位符与 `FILENAME_TEMPLATES` 相同。带 `{ext}` 的模板对每种归档格式各出
+/// 一条; 不带的按 URL 后缀推断格式 (`.zip` / `.tar.gz` / 其余视为裸二进制)。
+/// 顺序: 模板 → target 别名 → 归档格式, 与配置的书写顺序一致。
+pub fn custom_candidate_urls(
+    templates: &[String],
+    name: &str,
+    version: &str,
+    targets: &[String],
+) -> Vec<CandidateUrl> {
+    let mut out = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for tmpl in templates {
+        for target in targets {
+            let base = tmpl
+                .replace("{name}", name)
+                .replace("{version}", version)
+                .replace("{target}", target);
+            let expanded: Vec<CandidateUrl> = if base.contains("{ext}") {
+                ARCHIVE_EXTS
+                    .iter()
+                    .map(|(fmt, ext)| CandidateUrl {
+                        url: base.replace("{ext}", ext),
+                        archive_fmt: *fmt,
+                    })
+                    .collect()
+            } else {
+                vec![CandidateUrl {
+                    archive_fmt: crate::downloader::release_host::archive_fmt_for(&base),
+                    url: base,
+                }]
+            };
+            for c in expanded {
+                if seen.insert(c.url.clone()) {
+                    out.push(c);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// 内置 target 表: (arch, os, 别名列表)。arch / os 取 `std::env::consts` 的写法
+/// (小端 powerpc64 记作 `powerpc64le`、ARMv7 记作 `armv7`, 见 [`host_arch`]),
+/// 别名列表的第一项是规范 Rust triple, gnu 在 musl 之前。一行里 glibc / musl /
+/// 通用名混排, 取用时按 libc 过滤 (见 [`libc_compatible`])。
+///
+/// 不同发布者命名约定不一 (Rust triple vs Go/npm/Debian 风格 vs Apple 简写),
 /// 所以同一 (arch, os) 给出多个等价候选, 由 fetch 阶段 HEAD 探测决定哪个真实存在。
-pub fn current_targets() -> Vec<String> {
-    let arch = std::env::consts::ARCH;
-    let os = std::env::consts::OS;
-    match (arch, os) {
-        ("aarch64", "macos") => vec![
-            "aarch64-apple-darwin".into(),
-            "arm64-apple-darwin".into(),
-            "darwin-arm64".into(),
+/// 可以用配置 `[targets."<triple>"]` 追加别名或新增行 (见 [`target_table`])。
+const TARGET_ALIASES: &[(&str, &str, &[&str])] = &[
+    (
+        "aarch64",
+        "macos",
+        &["aarch64-apple-darwin", "arm64-apple-darwin", "darwin-arm64"],
+    ),
+    (
+        "x86_64",
+        "macos",
+        &[
+            "x86_64-apple-darwin",
+            "x64-apple-darwin",
+            "darwin-amd64",
+            "darwin-x64",
+        ],
+    ),
+    (
+        "aarch64",
+        "linux",
+        &[
+            "aarch64-unknown-linux-gnu",
+            "aarch64-unknown-linux-musl",
+            "arm64-unknown-linux-gnu",
+            "linux-arm64",
+        ],
+    ),
+    (
+        "x86_64",
+        "linux",
+        &[
+            "x86_64-unknown-linux-gnu",
+            "x86_64-unknown-linux-musl",
+            "linux-amd64",
+            "linux-x64",
+        ],
+    ),
+    // ARMv7 也能跑 ARMv6 的产物, 排在 v7 的名字之后兜底; 反过来不行,
+    // 所以 ARMv6 (树莓派 Zero / 1) 单独一行
+    (
+        "armv7",
+        "linux",
+        &[
+            "armv7-unknown-linux-gnueabihf",
+            "armv7-unknown-linux-musleabihf",
+            "armhf",
+            "linux-armv7",
+            "arm-unknown-linux-gnueabihf",
+            "arm-unknown-linux-musleabihf",
         ],
-        ("x86_64", "macos") => vec![
-            "x86_64-apple-darwin".into(),
-            "x64-apple-darwin".into(),
-            "darwin-amd64".into(),
-            "darwin-x64".into(),
+    ),
+    (
+        "arm",
+        "linux",
+        &[
+            "arm-unknown-linux-gnueabihf",
+            "arm-unknown-linux-musleabihf",
+            "linux-armv6",
+            "linux-arm",
         ],
-        ("aarch64", "linux") => vec![
-            "aarch64-unknown-linux-gnu".into(),
-            "aarch64-unknown-linux-musl".into(),
-            "arm64-unknown-linux-gnu".into(),
-            "linux-arm64".into(),
+    ),
+    (
+        "riscv64",
+        "linux",
+        &[
+            "riscv64gc-unknown-linux-gnu",
+            "riscv64gc-unknown-linux-musl",
+            "riscv64-unknown-linux-gnu",
+            "riscv64",
+            "linux-riscv64",
         ],
-        ("x86_64", "linux") => vec![
-            "x86_64-unknown-linux-gnu".into(),
-            "x86_64-unknown-linux-musl".into(),
-            "linux-amd64".into(),
-            "linux-x64".into(),
+    ),
+    (
+        "x86",
+        "linux",
+        &[
+            "i686-unknown-linux-gnu",
+            "i686-unknown-linux-musl",
+            "i386",
+            "linux-386",
+            "linux-i686",
+            "linux-x86",
         ],
-        _ => Vec::new(),
+    ),
+    (
+        "powerpc64le",
+        "linux",
+        &[
+            "powerpc64le-unknown-linux-gnu",
+            "powerpc64le-unknown-linux-musl",
+            "ppc64le",
+            "linux-ppc64le",
+        ],
+    ),
+    (
+        "s390x",
+        "linux",
+        &["s390x-unknown-linux-gnu", "s390x", "linux-s390x"],
+    ),
+];
+
+/// target 表的一行。`arch` / `os` 为 None 的行 (配置里只给了 triple) 只在
+/// `--target` 查找时用到, 不参与本机匹配。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetRow {
+    pub arch: Option<String>,
+    pub os: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+/// 内置表 + 配置 `[targets."<triple>"]` 合成的完整 target 表。纯函数。
+///
+/// triple 已在某行里时把配置的别名去重追加到那一行; 否则新增一行
+/// (`[triple, aliases...]`), 新增行排在内置行之前, 本机匹配时优先。
+pub fn target_table(
+    extra: &std::collections::BTreeMap<String, crate::config::TargetConfig>,
+) -> Vec<TargetRow> {
+    let mut builtin: Vec<TargetRow> = TARGET_ALIASES
+        .iter()
+        .map(|(arch, os, aliases)| TargetRow {
+            arch: Some((*arch).to_string()),
+            os: Some((*os).to_string()),
+            aliases: aliases.iter().map(|t| (*t).to_string()).collect(),
+        })
+        .collect();
+    let mut custom: Vec<TargetRow> = Vec::new();
+    for (triple, cfg) in extra {
+        let row = match builtin
+            .iter_mut()
+            .chain(custom.iter_mut())
+            .find(|r| r.aliases.contains(triple))
+        {
+            Some(row) => row,
+            None => {
+                custom.push(TargetRow {
+                    arch: cfg.arch.clone(),
+                    os: cfg.os.clone(),
+                    aliases: vec![triple.clone()],
+                });
+                custom.last_mut().expect("just pushed")
+            }
+        };
+        for alias in &cfg.aliases {
+            if !row.aliases.contains(alias) {
+                row.aliases.push(alias.clone());
+            }
+        }
+    }
+    custom.extend(builtin);
+    custom
+}
+
+/// 本机的 arch 名: `std::env::consts::ARCH`, 小端 powerpc64 记作 `powerpc64le`
+/// (ARCH 不区分字节序, 而大小端的预编译产物不通用), 带 v7 特性的 arm 记作
+/// `armv7` (ARCH 对 v6 / v7 都是 `arm`)。
+pub fn host_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "powerpc64" if cfg!(target_endian = "little") => "powerpc64le",
+        "arm" if cfg!(target_feature = "v7") => "armv7",
+        arch => arch,
+    }
+}
+
+/// 本机的 libc: 取 cargo-fresh 自己编译时的 `target_env`。musl 构建的
+/// cargo-fresh 基本就是跑在 Alpine 这类 musl 系统上。
+pub fn host_libc() -> Libc {
+    if cfg!(target_env = "musl") {
+        Libc::Musl
+    } else if cfg!(target_env = "gnu") {
+        Libc::Gnu
+    } else {
+        Libc::Unspecified
+    }
+}
+
+/// 当前进程的 target triple 别名列表 (canonical 在最前), 含配置扩展。
+pub fn current_targets() -> Vec<String> {
+    targets_for_host(
+        &target_table(&crate::config::get().targets),
+        host_arch(),
+        std::env::consts::OS,
+        host_libc(),
+    )
+}
+
+/// `--target <triple>` 的别名列表, 含配置扩展。
+pub fn cross_targets(triple: &str) -> Vec<String> {
+    targets_for_triple(&target_table(&crate::config::get().targets), triple)
+}
+
+/// 给定 (arch, os) 的别名列表, 只留和 `libc` 兼容的 (见 [`libc_compatible`]);
+/// 表里没有时为空 (downloader 视为不支持)。纯函数。
+pub fn targets_for_host(table: &[TargetRow], arch: &str, os: &str, libc: Libc) -> Vec<String> {
+    table
+        .iter()
+        .find(|r| r.arch.as_deref() == Some(arch) && r.os.as_deref() == Some(os))
+        .map(|r| {
+            r.aliases
+                .iter()
+                .filter(|t| libc_compatible(libc, t))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 别名里看得出的 libc。`linux-amd64` 这类通用名和非 Linux 的别名都是
+/// `Unspecified`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Libc {
+    Gnu,
+    Musl,
+    Unspecified,
+}
+
+/// 按名字判断别名的 libc: 含 `musl` 的是 musl (含 `musleabihf`), 含 `-gnu`
+/// 的是 glibc (含 `gnueabihf`)。
+pub fn libc_of(alias: &str) -> Libc {
+    if alias.contains("musl") {
+        Libc::Musl
+    } else if alias.contains("-gnu") {
+        Libc::Gnu
+    } else {
+        Libc::Unspecified
+    }
+}
+
+/// 请求 `wanted` 时能不能拿 `alias` 的产物顶上: musl 只认 musl (glibc 的
+/// binary 在 Alpine 上跑不起来, 通用名多半也是 glibc 构建); glibc 还认通用名
+/// 和 musl (静态链接, glibc 上照样能跑); 没标 libc 的请求什么都认。
+pub fn libc_compatible(wanted: Libc, alias: &str) -> bool {
+    match wanted {
+        Libc::Musl => libc_of(alias) == Libc::Musl,
+        Libc::Gnu | Libc::Unspecified => true,
+    }
+}
+
+/// 某个 triple 的别名列表: 请求的 triple 放最前, 其后是同一行里 libc
+/// 兼容的别名 (见 [`libc_compatible`]: `x86_64-unknown-linux-gnu` 也会试
+/// `linux-amd64`, `x86_64-unknown-linux-musl` 只试 musl 的名字)。
+/// 表里没有的 triple 原样作为唯一候选——发布者用的就是这个名字时照样能命中。
+pub fn targets_for_triple(table: &[TargetRow], triple: &str) -> Vec<String> {
+    let wanted = libc_of(triple);
+    let mut out = vec![triple.to_string()];
+    // 优先以它为规范名的那一行: `arm-unknown-linux-gnueabihf` 也出现在
+    // armv7 行里兜底, 但请求 ARMv6 时不能拿 v7 的产物
+    let row = table
+        .iter()
+        .find(|r| r.aliases.first().is_some_and(|t| t == triple))
+        .or_else(|| table.iter().find(|r| r.aliases.iter().any(|t| t == triple)));
+    if let Some(row) = row {
+        out.extend(
+            row.aliases
+                .iter()
+                .filter(|t| *t != triple && libc_compatible(wanted, t))
+                .cloned(),
+        );
     }
+    out
 }
 
 /// 纯函数:本地枚举一个包在给定版本下"如果有预编译,文件名最可能长什么样"。
@@ -161,22 +513,13 @@ pub(crate) fn expected_filenames(
     version: &str,
     targets: &[String],
 ) -> Vec<String> {
-    let mut out = Vec::with_capacity(
-        FILENAME_TEMPLATES.len() * ARCHIVE_EXTS.len() * name_candidates.len() * targets.len(),
-    );
+    let mut out = Vec::new();
     let mut seen = std::collections::HashSet::new();
     for name in name_candidates {
         for target in targets {
-            for tmpl in FILENAME_TEMPLATES {
-                for (_fmt, ext) in ARCHIVE_EXTS {
-                    let filename = tmpl
-                        .replace("{name}", name)
-                        .replace("{version}", version)
-                        .replace("{target}", target)
-                        .replace("{ext}", ext);
-                    if seen.insert(filename.clone()) {
-                        out.push(filename);
-                    }
+            for (_fmt, filename) in filenames_for(name, version, target, true) {
+                if seen.insert(filename.clone()) {
+                    out.push(filename);
                 }
             }
         }
@@ -193,11 +536,11 @@ mod tests {
     }
 
     #[test]
-    fn non_github_repo_is_unsupported() {
+    fn unknown_host_is_unsupported() {
         let err = candidate_urls(
             &["ripgrep".into()],
             "14.1.2",
-            "https://gitlab.com/x/y",
+            "https://git.example.com/x/y",
             &one("x86_64-apple-darwin"),
         )
         .unwrap_err();
@@ -231,8 +574,11 @@ mod tests {
             &one("x86_64-apple-darwin"),
         )
         .unwrap();
-        // 10 filenames × 6 tag paths × 2 archives = 120
+        // 10 filenames × 2 exts × 6 tag paths = 120
         assert_eq!(cands.len(), 120);
+        assert!(cands
+            .iter()
+            .all(|c| matches!(c.archive_fmt, ArchiveFmt::TarGz | ArchiveFmt::Zip)));
     }
 
     #[test]
@@ -339,6 +685,78 @@ mod tests {
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn custom_templates_expand_ext_and_keep_order() {
+        let cands = custom_candidate_urls(
+            &[
+                "https://a.example/{name}/{version}/{name}-{target}.{ext}".into(),
+                "https://b.example/{name}-{target}".into(),
+            ],
+            "tool",
+            "1.0.0",
+            &one("x86_64-unknown-linux-gnu"),
+        );
+        let urls: Vec<(&str, ArchiveFmt)> =
+            cands.iter().map(|c| (c.url.as_str(), c.archive_fmt)).collect();
+        assert_eq!(
+            urls,
+            [
+                (
+                    "https://a.example/tool/1.0.0/tool-x86_64-unknown-linux-gnu.tar.gz",
+                    ArchiveFmt::TarGz
+                ),
+                (
+                    "https://a.example/tool/1.0.0/tool-x86_64-unknown-linux-gnu.zip",
+                    ArchiveFmt::Zip
+                ),
+                (
+                    "https://a.example/tool/1.0.0/tool-x86_64-unknown-linux-gnu.tar.xz",
+                    ArchiveFmt::TarXz
+                ),
+                (
+                    "https://a.example/tool/1.0.0/tool-x86_64-unknown-linux-gnu.tar.zst",
+                    ArchiveFmt::TarZst
+                ),
+                (
+                    "https://a.example/tool/1.0.0/tool-x86_64-unknown-linux-gnu.tgz",
+                    ArchiveFmt::TarGz
+                ),
+                (
+                    "https://a.example/tool/1.0.0/tool-x86_64-unknown-linux-gnu.tar.bz2",
+                    ArchiveFmt::TarBz2
+                ),
+                ("https://b.example/tool-x86_64-unknown-linux-gnu", ArchiveFmt::Bin),
+            ]
+        );
+    }
+
+    #[test]
+    fn gitlab_repo_uses_release_permalinks() {
+        let cands = candidate_urls(
+            &["tool".into()],
+            "1.0.0",
+            "https://gitlab.com/group/tool",
+            &one("x86_64-unknown-linux-gnu"),
+        )
+        .unwrap();
+        assert_eq!(cands.len(), 120);
+        assert!(cands.iter().any(|c| c.url
+            == "https://gitlab.com/group/tool/-/releases/v1.0.0/downloads/tool-1.0.0-x86_64-unknown-linux-gnu.tar.gz"));
+    }
+
+    #[test]
+    fn codeberg_repo_uses_github_style_paths() {
+        let cands = candidate_urls(
+            &["tool".into()],
+            "1.0.0",
+            "https://codeberg.org/owner/tool",
+            &one("x86_64-unknown-linux-gnu"),
+        )
+        .unwrap();
+        assert!(cands.iter().any(|c| c.url
+            == "https://codeberg.org/owner/tool/releases/download/v1.0.0/tool-v1.0.0-x86_64-unknown-linux-gnu.zip"));
+    }
+
     #[test]
     fn current_targets_returns_nonempty_on_supported_unix() {
         if std::env::consts::OS == "macos" || std::env::consts::OS == "linux" {
@@ -348,6 +766,122 @@ mod tests {
         }
     }
 
+    fn builtin() -> Vec<TargetRow> {
+        target_table(&Default::default())
+    }
+
+    #[test]
+    fn targets_for_host_is_pure_table_lookup() {
+        let table = builtin();
+        for (arch, canonical) in [
+            ("aarch64", "aarch64-unknown-linux-gnu"),
+            ("armv7", "armv7-unknown-linux-gnueabihf"),
+            ("arm", "arm-unknown-linux-gnueabihf"),
+            ("riscv64", "riscv64gc-unknown-linux-gnu"),
+            ("x86", "i686-unknown-linux-gnu"),
+            ("powerpc64le", "powerpc64le-unknown-linux-gnu"),
+            ("s390x", "s390x-unknown-linux-gnu"),
+        ] {
+            let ts = targets_for_host(&table, arch, "linux", Libc::Gnu);
+            assert_eq!(ts.first().map(String::as_str), Some(canonical), "{arch}");
+        }
+        let armv7 = targets_for_host(&table, "armv7", "linux", Libc::Gnu);
+        assert!(armv7.contains(&"armhf".to_string()));
+        assert!(armv7.contains(&"arm-unknown-linux-gnueabihf".to_string()));
+        // ARMv6 不能拿 v7 的产物
+        let armv6 = targets_for_host(&table, "arm", "linux", Libc::Gnu);
+        assert!(armv6.iter().all(|t| !t.contains("v7") && t != "armhf"));
+        assert!(targets_for_host(&table, "riscv64", "linux", Libc::Gnu)
+            .contains(&"riscv64".to_string()));
+        assert!(targets_for_host(&table, "mips", "linux", Libc::Gnu).is_empty());
+        assert!(targets_for_host(&table, "powerpc64", "linux", Libc::Gnu).is_empty());
+    }
+
+    #[test]
+    fn musl_hosts_only_get_musl_aliases() {
+        let table = builtin();
+        for arch in ["x86_64", "aarch64", "armv7", "arm", "riscv64", "x86", "powerpc64le"] {
+            let ts = targets_for_host(&table, arch, "linux", Libc::Musl);
+            assert!(!ts.is_empty(), "{arch}");
+            assert!(ts.iter().all(|t| libc_of(t) == Libc::Musl), "{arch}: {ts:?}");
+        }
+        // s390x 没有 musl 产物: 宁可回退源码构建
+        assert!(targets_for_host(&table, "s390x", "linux", Libc::Musl).is_empty());
+        assert_eq!(
+            targets_for_host(&table, "aarch64", "macos", Libc::Unspecified),
+            vec!["aarch64-apple-darwin", "arm64-apple-darwin", "darwin-arm64"]
+        );
+    }
+
+    #[test]
+    fn targets_for_triple_puts_requested_alias_first() {
+        let table = builtin();
+        let ts = targets_for_triple(&table, "x86_64-unknown-linux-gnu");
+        assert_eq!(ts[0], "x86_64-unknown-linux-gnu");
+        assert!(ts.contains(&"x86_64-unknown-linux-musl".to_string()));
+        assert!(ts.contains(&"linux-amd64".to_string()));
+        assert_eq!(ts.iter().filter(|t| *t == "x86_64-unknown-linux-gnu").count(), 1);
+
+        // musl 请求不能拿 glibc 或通用名的产物顶替
+        assert_eq!(
+            targets_for_triple(&table, "x86_64-unknown-linux-musl"),
+            vec!["x86_64-unknown-linux-musl"]
+        );
+        assert_eq!(
+            targets_for_triple(&table, "armv7-unknown-linux-musleabihf"),
+            vec![
+                "armv7-unknown-linux-musleabihf",
+                "arm-unknown-linux-musleabihf"
+            ]
+        );
+        assert_eq!(
+            targets_for_triple(&table, "arm-unknown-linux-gnueabihf"),
+            vec![
+                "arm-unknown-linux-gnueabihf",
+                "arm-unknown-linux-musleabihf",
+                "linux-armv6",
+                "linux-arm"
+            ]
+        );
+
+        assert_eq!(
+            targets_for_triple(&table, "wasm32-wasip1"),
+            vec!["wasm32-wasip1".to_string()]
+        );
+    }
+
+    #[test]
+    fn config_extends_existing_rows_and_adds_new_ones() {
+        let config = crate::config::parse_config(
+            "[targets.\"armhf\"]\naliases = [\"armv7l-linux\", \"linux-armv7\"]\n\n\
+             [targets.\"loongarch64-unknown-linux-gnu\"]\narch = \"loongarch64\"\nos = \"linux\"\n\
+             aliases = [\"linux-loong64\"]\n\n\
+             [targets.\"x86_64-linux-custom\"]\narch = \"x86_64\"\nos = \"linux\"\n",
+        )
+        .unwrap();
+        let table = target_table(&config.targets);
+
+        // 追加到 armv7 那一行, 已有的别名不重复
+        let arm = targets_for_host(&table, "armv7", "linux", Libc::Gnu);
+        assert_eq!(arm[0], "armv7-unknown-linux-gnueabihf");
+        assert_eq!(arm.last().map(String::as_str), Some("armv7l-linux"));
+        assert_eq!(arm.iter().filter(|t| *t == "linux-armv7").count(), 1);
+
+        assert_eq!(
+            targets_for_host(&table, "loongarch64", "linux", Libc::Gnu),
+            vec!["loongarch64-unknown-linux-gnu", "linux-loong64"]
+        );
+        // 配置新增的行优先于内置行
+        assert_eq!(
+            targets_for_host(&table, "x86_64", "linux", Libc::Gnu),
+            vec!["x86_64-linux-custom"]
+        );
+        assert_eq!(
+            targets_for_triple(&table, "linux-loong64"),
+            vec!["linux-loong64", "loongarch64-unknown-linux-gnu"]
+        );
+    }
+
     #[test]
     fn expected_filenames_includes_canonical_ripgrep() {
         let names = vec!["ripgrep".to_string()];
@@ -355,4 +889,16 @@ mod tests {
         let filenames = expected_filenames(&names, "15.1.0", &targets);
         assert!(filenames.iter().any(|f| f == "ripgrep-15.1.0-aarch64-apple-darwin.tar.gz"));
     }
+
+    #[test]
+    fn filenames_cover_new_compressions_and_bare_binaries() {
+        let files = filenames_for("typos", "1.0.0", "x86_64-unknown-linux-musl", true);
+        let has = |fmt: ArchiveFmt, name: &str| files.iter().any(|(f, n)| *f == fmt && n == name);
+        assert!(has(ArchiveFmt::TarXz, "typos-v1.0.0-x86_64-unknown-linux-musl.tar.xz"));
+        assert!(has(ArchiveFmt::TarZst, "typos-1.0.0-x86_64-unknown-linux-musl.tar.zst"));
+        assert!(has(ArchiveFmt::TarBz2, "typos-x86_64-unknown-linux-musl.tar.bz2"));
+        assert!(has(ArchiveFmt::TarGz, "typos-x86_64-unknown-linux-musl.tgz"));
+        assert!(has(ArchiveFmt::Bin, "typos-x86_64-unknown-linux-musl"));
+        assert!(has(ArchiveFmt::Bin, "typos-1.0.0-x86_64-unknown-linux-musl"));
+    }
 }
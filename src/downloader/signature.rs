@@ -0,0 +1,294 @@
+//! 预编译产物的分离签名校验。
+//!
+//! 支持三种随 release 发布的签名文件，按顺序探测 `{archive_url}{suffix}`：
+//! - `.minisig`       —— minisign（Ed25519），用包钉住的 `minisign-key` 校验
+//! - `.sig`           —— `cosign sign-blob --key` 产出的 base64 DER ECDSA P-256
+//! - `.sigstore.json` —— sigstore bundle 里的 `messageSignature`，同样用 `cosign-key`
+//!
+//! 只认"钉在配置里的公钥"：不做 Fulcio 证书链 / Rekor 透明日志校验。只带
+//! 证书（keyless）的 bundle 没法用钉住的公钥验证，按"签名缺失"处理。
+//!
+//! 模块分两层：[`check`] 负责下载签名文件并得出三态结局；[`decide`] 是纯函数，
+//! 把结局按 `[signatures]` 策略映射成"继续 / 警告 / 回退源码"。
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::config::{PinnedKeys, SignatureConfig, SignaturePolicy};
+
+/// 签名文件后缀，按探测顺序。
+pub const SIGNATURE_SUFFIXES: &[&str] = &[".minisig", ".sig", ".sigstore.json"];
+
+/// 单个签名文件的体积上限。`.minisig` / `.sig` 不到 1KiB，带证书和透明日志
+/// 条目的 sigstore bundle 也只有几十 KiB；超过的按签名无效处理。
+pub const MAX_SIGNATURE_BYTES: usize = 256 * 1024;
+
+/// 一次签名校验的结局。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureOutcome {
+    /// 找到签名且用钉住的公钥验证通过。`suffix` 是命中的签名文件后缀。
+    Verified { suffix: &'static str },
+    /// 找到签名但校验失败（公钥不匹配、内容被改、格式损坏）。
+    Invalid {
+        suffix: &'static str,
+        detail: String,
+    },
+    /// 没有可用钉住公钥验证的签名文件。
+    Missing,
+}
+
+/// [`decide`] 的结果：调用方据此继续、警告或放弃预编译产物。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Continue,
+    Warn(String),
+    Fallback(String),
+}
+
+/// 纯函数：结局 × 策略 → 决定。`Warn` / `Fallback` 附带一句给用户看的说明。
+pub fn decide(outcome: &SignatureOutcome, policies: &SignatureConfig) -> Decision {
+    let (policy, detail) = match outcome {
+        SignatureOutcome::Verified { suffix } => {
+            (policies.verified, format!("signature verified ({suffix})"))
+        }
+        SignatureOutcome::Invalid { suffix, detail } => (
+            policies.invalid,
+            format!("signature check failed ({suffix}): {detail}"),
+        ),
+        SignatureOutcome::Missing => (
+            policies.missing,
+            "no signature matching the pinned key was published".to_string(),
+        ),
+    };
+    match policy {
+        SignaturePolicy::Allow => Decision::Continue,
+        SignaturePolicy::Warn => Decision::Warn(detail),
+        SignaturePolicy::Source => Decision::Fallback(detail),
+    }
+}
+
+/// 为已下载的产物探测签名文件并校验。
+///
+/// 只探测有对应公钥的后缀（没钉 cosign 公钥就不请求 `.sig`）。第一个存在的
+/// 签名文件决定结局；网络错误 / 非 2xx 视为该后缀不存在，超过
+/// [`MAX_SIGNATURE_BYTES`] 的视为无效。读不了本地归档是 I/O 错误而不是
+/// 签名问题，返回 Err，不伪装成 `Invalid`。
+pub async fn check(
+    client: &reqwest::Client,
+    archive_url: &str,
+    archive_path: &Path,
+    keys: &PinnedKeys,
+) -> Result<SignatureOutcome> {
+    let data = tokio::fs::read(archive_path)
+        .await
+        .with_context(|| format!("read {}", archive_path.display()))?;
+
+    for &suffix in SIGNATURE_SUFFIXES {
+        let key = match suffix {
+            ".minisig" => keys.minisign.as_deref(),
+            _ => keys.cosign.as_deref(),
+        };
+        let Some(key) = key else {
+            continue;
+        };
+        let Some(body) = fetch_signature(client, &format!("{archive_url}{suffix}")).await else {
+            continue;
+        };
+        let verdict = body.and_then(|body| match suffix {
+            ".minisig" => verify_minisign(key, &data, &body).map(|()| true),
+            ".sig" => verify_cosign_blob(key, &data, &body).map(|()| true),
+            _ => verify_sigstore_bundle(key, &data, &body),
+        });
+        match verdict {
+            Ok(true) => return Ok(SignatureOutcome::Verified { suffix }),
+            // keyless bundle：没法用钉住的公钥验证，继续看下一个后缀
+            Ok(false) => {
+                crate::display::status_debug(
+                    "signature",
+                    &format!(
+                        "{archive_url}{suffix}: keyless bundle, not verifiable with a pinned key"
+                    ),
+                );
+            }
+            Err(e) => {
+                return Ok(SignatureOutcome::Invalid {
+                    suffix,
+                    detail: format!("{e:#}"),
+                })
+            }
+        }
+    }
+    Ok(SignatureOutcome::Missing)
+}
+
+/// 下载签名文件。不存在（网络错误 / 非 2xx）返回 None；超过
+/// [`MAX_SIGNATURE_BYTES`] 或不是 UTF-8 时返回 `Some(Err)`。
+async fn fetch_signature(client: &reqwest::Client, url: &str) -> Option<Result<String>> {
+    let resp = client.get(url).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let too_big = || anyhow!("signature file is larger than {MAX_SIGNATURE_BYTES} bytes");
+    if resp
+        .content_length()
+        .is_some_and(|len| len > MAX_SIGNATURE_BYTES as u64)
+    {
+        return Some(Err(too_big()));
+    }
+    let mut body = Vec::new();
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.ok()?;
+        if body.len() + chunk.len() > MAX_SIGNATURE_BYTES {
+            return Some(Err(too_big()));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Some(String::from_utf8(body).context("signature file is not UTF-8"))
+}
+
+/// minisign：`pubkey` 是 `minisign.pub` 第二行的 base64，`sig` 是 `.minisig` 全文。
+pub fn verify_minisign(pubkey: &str, data: &[u8], sig: &str) -> Result<()> {
+    let pk = minisign_verify::PublicKey::from_base64(pubkey.trim())
+        .map_err(|e| anyhow!("invalid minisign public key: {e}"))?;
+    let signature =
+        minisign_verify::Signature::decode(sig).map_err(|e| anyhow!("malformed .minisig: {e}"))?;
+    pk.verify(data, &signature, false)
+        .map_err(|e| anyhow!("minisign: {e}"))
+}
+
+/// cosign `sign-blob --key` 的 `.sig`：base64 编码的 DER ECDSA P-256 签名
+/// （对原文的 SHA-256）。`pem` 是 `cosign.pub` 全文。
+pub fn verify_cosign_blob(pem: &str, data: &[u8], sig_b64: &str) -> Result<()> {
+    let der = decode_b64(sig_b64.trim()).context("malformed .sig")?;
+    verify_p256(pem, data, &der)
+}
+
+/// sigstore bundle（`.sigstore.json`）。返回：
+/// - `Ok(true)`  —— `messageSignature` 用钉住的公钥验证通过
+/// - `Ok(false)` —— 只能靠证书链验证的 bundle（keyless / DSSE），这里不处理
+/// - `Err(_)`    —— bundle 损坏、摘要不符或签名无效
+pub fn verify_sigstore_bundle(pem: &str, data: &[u8], bundle: &str) -> Result<bool> {
+    let v: serde_json::Value = serde_json::from_str(bundle).context("malformed sigstore bundle")?;
+    let Some(msg) = v.get("messageSignature") else {
+        return Ok(false);
+    };
+    let material = v.get("verificationMaterial");
+    let keyed = material.is_some_and(|m| m.get("publicKey").is_some());
+    if !keyed {
+        return Ok(false);
+    }
+    if let Some(digest) = msg
+        .get("messageDigest")
+        .and_then(|d| d.get("digest"))
+        .and_then(|d| d.as_str())
+    {
+        let expected = decode_b64(digest).context("malformed messageDigest")?;
+        let actual = Sha256::digest(data);
+        if expected.as_slice() != actual.as_slice() {
+            bail!("bundle messageDigest does not match the archive");
+        }
+    }
+    let sig = msg
+        .get("signature")
+        .and_then(|s| s.as_str())
+        .ok_or_else(|| anyhow!("bundle messageSignature has no signature"))?;
+    let der = decode_b64(sig).context("malformed bundle signature")?;
+    verify_p256(pem, data, &der)?;
+    Ok(true)
+}
+
+fn verify_p256(pem: &str, data: &[u8], der: &[u8]) -> Result<()> {
+    use p256::ecdsa::signature::Verifier;
+    use p256::pkcs8::DecodePublicKey;
+    let key = p256::ecdsa::VerifyingKey::from_public_key_pem(pem.trim())
+        .map_err(|e| anyhow!("invalid cosign public key: {e}"))?;
+    let sig =
+        p256::ecdsa::Signature::from_der(der).map_err(|e| anyhow!("malformed signature: {e}"))?;
+    key.verify(data, &sig)
+        .map_err(|_| anyhow!("ECDSA signature does not match the pinned key"))
+}
+
+fn decode_b64(s: &str) -> Result<Vec<u8>> {
+    Ok(base64::engine::general_purpose::STANDARD.decode(s.trim())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policies(
+        verified: SignaturePolicy,
+        invalid: SignaturePolicy,
+        missing: SignaturePolicy,
+    ) -> SignatureConfig {
+        SignatureConfig {
+            verified,
+            invalid,
+            missing,
+        }
+    }
+
+    #[test]
+    fn decide_maps_each_outcome_to_its_policy() {
+        use SignaturePolicy::*;
+        let p = policies(Allow, Source, Warn);
+        assert_eq!(
+            decide(&SignatureOutcome::Verified { suffix: ".minisig" }, &p),
+            Decision::Continue
+        );
+        assert!(matches!(
+            decide(
+                &SignatureOutcome::Invalid {
+                    suffix: ".sig",
+                    detail: "bad".into()
+                },
+                &p
+            ),
+            Decision::Fallback(d) if d.contains(".sig") && d.contains("bad")
+        ));
+        assert!(matches!(
+            decide(&SignatureOutcome::Missing, &p),
+            Decision::Warn(_)
+        ));
+    }
+
+    #[test]
+    fn decide_verified_can_warn() {
+        use SignaturePolicy::*;
+        let p = policies(Warn, Allow, Allow);
+        assert!(matches!(
+            decide(&SignatureOutcome::Verified { suffix: ".sig" }, &p),
+            Decision::Warn(d) if d.contains("verified")
+        ));
+        assert_eq!(decide(&SignatureOutcome::Missing, &p), Decision::Continue);
+    }
+
+    #[test]
+    fn minisign_rejects_garbage_key() {
+        assert!(verify_minisign("not-a-key", b"x", "").is_err());
+    }
+
+    #[test]
+    fn cosign_rejects_garbage_pem() {
+        assert!(verify_cosign_blob("nope", b"x", "AAAA").is_err());
+    }
+
+    #[test]
+    fn keyless_bundle_is_not_verifiable() {
+        let bundle = r#"{"verificationMaterial":{"certificate":{"rawBytes":"AA=="}},"messageSignature":{"signature":"AA=="}}"#;
+        assert!(!verify_sigstore_bundle("unused", b"x", bundle).unwrap());
+        let dsse = r#"{"verificationMaterial":{"publicKey":{}},"dsseEnvelope":{}}"#;
+        assert!(!verify_sigstore_bundle("unused", b"x", dsse).unwrap());
+    }
+
+    #[test]
+    fn bundle_digest_mismatch_is_invalid() {
+        let bundle = r#"{"verificationMaterial":{"publicKey":{"hint":"k"}},"messageSignature":{"messageDigest":{"algorithm":"SHA2_256","digest":"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="},"signature":"AA=="}}"#;
+        let err = verify_sigstore_bundle("unused", b"x", bundle).unwrap_err();
+        assert!(err.to_string().contains("messageDigest"), "got: {err}");
+    }
+}
@@ -0,0 +1,214 @@
+//! 进程级网络节流: 全局带宽令牌桶 + 按主机的并发连接上限。
+//!
+//! - `--limit-rate` (或配置 `[network] limit-rate`) 设一个所有下载流共享的
+//!   令牌桶: 每收到一个 chunk 就扣掉对应字节数, 不够时睡到补足为止。
+//!   `--jobs 8` 时 8 条流加起来也不会超过这个速率。
+//! - 每个主机同时在飞的 HEAD / GET 不超过 `[network] per-host-connections`
+//!   (默认 16, 即单个包 HEAD 探测本来的并发度)。多个 job 同时探测 GitHub
+//!   时排队共享这 16 个名额, 而不是 jobs × 16 条连接一起打到同一个 CDN。
+//!   按请求 URL 的主机计数——重定向后的下载主机 (如 GitHub 的对象存储)
+//!   记在发起请求的主机名下。
+//!
+//! 与 `config` 相同的 OnceLock 模式: `main` 启动期调一次 [`init`], 下载路径
+//! 通过 [`rate_limiter`] / [`host_permit`] 取用; 没 init 过 (测试、库调用方)
+//! 时不限速, 主机上限取默认值。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 没配置时每个主机的并发上限。
+pub const DEFAULT_PER_HOST_CONNECTIONS: usize = 16;
+
+/// 令牌桶。容量等于一秒的配额, 允许短暂突发; 超额的 chunk 记成欠账,
+/// 调用方睡到欠账还清——单个 chunk 比容量大也不会卡死。
+#[derive(Debug)]
+pub struct RateLimiter {
+    bytes_per_sec: u64,
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// 可用字节数, 负数表示欠账。
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    /// `bytes_per_sec` 为 0 时按 1 处理 (不存在"零带宽")。
+    pub fn new(bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+        Self {
+            bytes_per_sec,
+            state: Mutex::new(Bucket {
+                tokens: bytes_per_sec as f64,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    /// 扣掉 `n` 字节, 返回需要等待的时长 (不睡)。纯逻辑, 便于测试。
+    fn reserve(&self, n: u64) -> Duration {
+        let rate = self.bytes_per_sec as f64;
+        let mut b = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let now = Instant::now();
+        let elapsed = now.duration_since(b.last).as_secs_f64();
+        b.last = now;
+        b.tokens = (b.tokens + elapsed * rate).min(rate) - n as f64;
+        if b.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-b.tokens / rate)
+        }
+    }
+
+    /// 记下刚收到的 `n` 字节; 超出配额时睡到补足。
+    pub async fn consume(&self, n: u64) {
+        let wait = self.reserve(n);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// 主机名 → 信号量。信号量按需创建, 进程内永不回收 (主机数量很小)。
+#[derive(Debug)]
+pub struct HostLimiter {
+    per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    /// `per_host` 为 0 时按 1 处理。
+    pub fn new(per_host: usize) -> Self {
+        Self {
+            per_host: per_host.max(1),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 等到 `url` 所在主机有空位。URL 解析不出主机时不限制 (返回 None)。
+    pub async fn acquire(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let host = host_of(url)?;
+        let sem = {
+            let mut hosts = self
+                .hosts
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            hosts
+                .entry(host)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.per_host)))
+                .clone()
+        };
+        sem.acquire_owned().await.ok()
+    }
+}
+
+/// `https://User@Host:8443/path` → `host:8443` (小写)。
+fn host_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    (!authority.is_empty()).then(|| authority.to_ascii_lowercase())
+}
+
+static RATE: OnceLock<Option<RateLimiter>> = OnceLock::new();
+static HOSTS: OnceLock<HostLimiter> = OnceLock::new();
+
+/// 启动期调用一次。重复调用时以第一次为准。
+pub fn init(limit_rate: Option<u64>, per_host_connections: Option<usize>) {
+    let _ = RATE.set(limit_rate.map(RateLimiter::new));
+    let _ = HOSTS.set(HostLimiter::new(
+        per_host_connections.unwrap_or(DEFAULT_PER_HOST_CONNECTIONS),
+    ));
+}
+
+/// 全局令牌桶; 未限速时 None。
+pub fn rate_limiter() -> Option<&'static RateLimiter> {
+    RATE.get().and_then(Option::as_ref)
+}
+
+/// 取 `url` 所在主机的一个并发名额, 持有期间计入上限。
+pub async fn host_permit(url: &str) -> Option<OwnedSemaphorePermit> {
+    HOSTS
+        .get_or_init(|| HostLimiter::new(DEFAULT_PER_HOST_CONNECTIONS))
+        .acquire(url)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn host_of_strips_scheme_userinfo_and_path() {
+        assert_eq!(
+            host_of("https://GitHub.com/o/r/releases/download/v1/x.tar.gz").as_deref(),
+            Some("github.com")
+        );
+        assert_eq!(
+            host_of("http://user:pw@127.0.0.1:8080/a?b").as_deref(),
+            Some("127.0.0.1:8080")
+        );
+        assert_eq!(host_of("not a url"), None);
+    }
+
+    #[test]
+    fn bucket_allows_one_second_burst_then_charges_debt() {
+        let limiter = RateLimiter::new(1000);
+        assert_eq!(limiter.reserve(600), Duration::ZERO);
+        assert_eq!(limiter.reserve(400), Duration::ZERO);
+        // 桶空了: 再要 500 字节得等约 0.5s
+        let wait = limiter.reserve(500);
+        assert!(
+            wait > Duration::from_millis(450) && wait <= Duration::from_millis(500),
+            "wait = {wait:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn consume_throttles_to_rate() {
+        let limiter = RateLimiter::new(10_000);
+        let start = Instant::now();
+        // 首秒突发 10k 免费, 之后 2k 需要 ~0.2s
+        limiter.consume(10_000).await;
+        limiter.consume(2_000).await;
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn host_limiter_caps_concurrency_per_host() {
+        let limiter = Arc::new(HostLimiter::new(2));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut tasks = Vec::new();
+        for i in 0..6 {
+            let (limiter, in_flight, peak) = (limiter.clone(), in_flight.clone(), peak.clone());
+            tasks.push(tokio::spawn(async move {
+                let _permit = limiter.acquire(&format!("https://cdn.example/{i}")).await;
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+            }));
+        }
+        // 另一个主机不受 cdn.example 名额影响
+        let other = limiter.acquire("https://api.example/x").await;
+        assert!(other.is_some());
+        for t in tasks {
+            t.await.unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+}
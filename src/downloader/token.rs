@@ -1,6 +1,13 @@
-//! GitHub token discovery for API authentication.
+//! Release API token discovery, 按托管平台分开。
 //!
-//! 优先级: `$GITHUB_TOKEN` > `$GH_TOKEN` > `gh auth token` 子进程
+//! GitHub (github.com) 优先级: `$GITHUB_TOKEN` > `$GH_TOKEN` > `gh auth token` 子进程
+//! GitHub Enterprise: `$GH_ENTERPRISE_TOKEN` > `$GITHUB_ENTERPRISE_TOKEN` >
+//!                    `gh auth token --hostname <host>` (与 `gh` CLI 同一套约定)
+//! GitLab:  `$GITLAB_TOKEN` > `$GL_TOKEN` (与 `glab` CLI 同名)
+//! Gitea / Forgejo: (仅 codeberg.org) `$CODEBERG_TOKEN` > `$FORGEJO_TOKEN` > `$GITEA_TOKEN`
+//!
+//! 配置文件 `[hosts."<host>"]` 里的 `token-env` 指定的变量排在所有平台默认之前。
+//! github.com 的 token 只发给 github.com——自托管 GitHub 实例不复用它。
 //!
 //! - 匿名调 GitHub API 是 60/hr,带 token 是 5000/hr。CI / 频繁手动跑都
 //!   只能靠认证。
@@ -11,7 +18,10 @@
 //! Test seam: `discover_token_uncached` 是裸函数(不走 OnceLock),
 //! 测试可以每次拿到新鲜决策;生产路径走 `discover_token` (OnceLock)。
 
-use std::sync::OnceLock;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::downloader::release_host::ForgeKind;
 
 static TOKEN_CACHE: OnceLock<(Option<String>, &'static str)> = OnceLock::new();
 
@@ -41,25 +51,101 @@ fn discover_with_source() -> (Option<String>, &'static str) {
             return (Some(t), "env:GH_TOKEN");
         }
     }
-    // `gh auth token` 失败/不存在静默兜底为 None —— 用户没装 gh CLI 是常态
-    let Some(out) = std::process::Command::new("gh")
-        .args(["auth", "token"])
-        .output()
-        .ok()
-    else {
-        return (None, "none");
-    };
+    match gh_auth_token(None) {
+        Some(t) => (Some(t), "gh"),
+        None => (None, "none"),
+    }
+}
+
+/// `gh auth token [--hostname <host>]`。失败/不存在静默兜底为 None ——
+/// 用户没装 gh CLI 是常态。
+fn gh_auth_token(hostname: Option<&str>) -> Option<String> {
+    let mut cmd = std::process::Command::new("gh");
+    cmd.args(["auth", "token"]);
+    if let Some(h) = hostname {
+        cmd.args(["--hostname", h]);
+    }
+    let out = cmd.output().ok()?;
     if !out.status.success() {
-        return (None, "none");
+        return None;
+    }
+    let s = String::from_utf8(out.stdout).ok()?.trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+/// GitHub Enterprise 主机 → `gh auth token --hostname` 结果。按主机缓存,
+/// 一次运行里每个实例至多 spawn 一次 `gh`。
+static ENTERPRISE_GH_CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+fn enterprise_token(host: &str) -> (Option<String>, &'static str) {
+    let from_env = first_env(&[
+        ("GH_ENTERPRISE_TOKEN", "env:GH_ENTERPRISE_TOKEN"),
+        ("GITHUB_ENTERPRISE_TOKEN", "env:GITHUB_ENTERPRISE_TOKEN"),
+    ]);
+    if from_env.0.is_some() {
+        return from_env;
     }
-    let Some(s) = String::from_utf8(out.stdout).ok().map(|s| s.trim().to_string()) else {
-        return (None, "none");
-    };
-    if s.is_empty() {
-        (None, "none")
-    } else {
-        (Some(s), "gh")
+    let cache = ENTERPRISE_GH_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut cache = cache.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    let token = cache
+        .entry(host.to_string())
+        .or_insert_with(|| gh_auth_token(Some(host)))
+        .clone();
+    match token {
+        Some(t) => (Some(t), "gh"),
+        None => (None, "none"),
+    }
+}
+
+/// 按平台 + 主机发现 token, 返回 `(token, 来源)`。来源串给 `--debug` 用,
+/// 形如 `env:GITLAB_TOKEN` / `gh` / `none`。
+///
+/// `token_env` 是配置里该主机的 `token-env`, 设了且非空时最优先。
+/// GitHub 走上面的 OnceLock 缓存 (可能 spawn `gh`); 其余平台只读 env, 不缓存。
+/// `GITLAB_TOKEN` / `FORGEJO_TOKEN` 这类通用变量只发给公共实例 (gitlab.com /
+/// codeberg.org)——自托管实例必须在 `[hosts]` 里配 `token-env`, 否则公共
+/// 实例的 token 会泄露给任意公司内部或第三方主机。
+pub fn discover_for(
+    kind: ForgeKind,
+    host: &str,
+    token_env: Option<&str>,
+) -> (Option<String>, &'static str) {
+    if let Some(var) = token_env {
+        if let Ok(t) = std::env::var(var) {
+            if !t.is_empty() {
+                return (Some(t), "config:token-env");
+            }
+        }
+    }
+    match kind {
+        ForgeKind::GitHub if host == "github.com" => {
+            let (token, source) = TOKEN_CACHE.get_or_init(discover_with_source);
+            (token.clone(), source)
+        }
+        ForgeKind::GitHub => enterprise_token(host),
+        ForgeKind::GitLab if host == "gitlab.com" => first_env(&[
+            ("GITLAB_TOKEN", "env:GITLAB_TOKEN"),
+            ("GL_TOKEN", "env:GL_TOKEN"),
+        ]),
+        ForgeKind::Gitea if host == "codeberg.org" => first_env(&[
+            ("CODEBERG_TOKEN", "env:CODEBERG_TOKEN"),
+            ("FORGEJO_TOKEN", "env:FORGEJO_TOKEN"),
+            ("GITEA_TOKEN", "env:GITEA_TOKEN"),
+        ]),
+        ForgeKind::GitLab | ForgeKind::Gitea => (None, "none"),
+    }
+}
+
+/// 按顺序取第一个非空的环境变量。元组第二项是 `--debug` 用的来源串。
+fn first_env(vars: &[(&str, &'static str)]) -> (Option<String>, &'static str) {
+    for &(var, source) in vars {
+        if let Ok(t) = std::env::var(var) {
+            if !t.is_empty() {
+                return (Some(t), source);
+            }
+        }
     }
+    (None, "none")
 }
 
 /// 测试/重新查的路径:每次都走全套流程,不命中 OnceLock。
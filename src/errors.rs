@@ -39,6 +39,21 @@ pub enum Hint {
     NetworkConnectTimeout,
     /// `--filter` / `--exclude` 传了非法 glob。
     InvalidGlob,
+    // 以下由 `cargo fresh doctor` 在对应检查没通过时给出。
+    /// cargo / rustc 不可用：用 rustup 安装工具链。
+    InstallToolchain,
+    /// `$CARGO_HOME/bin` 不在 PATH 里。
+    CargoBinNotInPath,
+    /// 没有 GitHub token，或 API 配额快用完了。
+    GithubToken,
+    /// HTTP 客户端 / TLS 初始化失败。
+    TlsInit,
+    /// `.crates.toml` 与 `.crates2.json` 对不上。
+    RepairMetadata,
+    /// 本机平台不在 target 表里，拿不到预编译产物。
+    UnknownTarget,
+    /// `$CARGO_HOME/bin` 里有孤儿文件或残留的临时文件。
+    PruneOrphans,
 }
 
 impl Hint {
@@ -48,6 +63,13 @@ impl Hint {
             Hint::CargoListFailed => "hint_cargo_list_failed",
             Hint::NetworkConnectTimeout => "hint_network_connect_timeout",
             Hint::InvalidGlob => "hint_invalid_glob",
+            Hint::InstallToolchain => "hint_install_toolchain",
+            Hint::CargoBinNotInPath => "hint_cargo_bin_not_in_path",
+            Hint::GithubToken => "hint_github_token",
+            Hint::TlsInit => "hint_tls_init",
+            Hint::RepairMetadata => "hint_repair_metadata",
+            Hint::UnknownTarget => "hint_unknown_target",
+            Hint::PruneOrphans => "hint_prune_orphans",
         }
     }
 }
@@ -130,6 +152,13 @@ mod tests {
             Hint::CargoListFailed,
             Hint::NetworkConnectTimeout,
             Hint::InvalidGlob,
+            Hint::InstallToolchain,
+            Hint::CargoBinNotInPath,
+            Hint::GithubToken,
+            Hint::TlsInit,
+            Hint::RepairMetadata,
+            Hint::UnknownTarget,
+            Hint::PruneOrphans,
         ] {
             let key = hint.locale_key();
             assert!(!get_english_text(key).is_empty(), "missing EN for {key}");
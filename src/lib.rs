@@ -5,10 +5,14 @@
 //! 启动子进程的方式去触达。bin 与 lib 共用同一份模块树。
 
 pub mod cli;
+pub mod config;
 pub mod display;
+pub mod doctor;
 pub mod downloader;
 pub mod errors;
 pub mod locale;
+pub mod manifest;
 pub mod models;
 pub mod package;
+pub mod state;
 pub mod updater;
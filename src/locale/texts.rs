@@ -60,6 +60,30 @@ pub fn get_english_text(key: &str) -> &'static str {
             "Invalid glob in `--filter` / `--exclude`. Patterns use glob syntax \
              (`*`, `?`, `[abc]`); quote the pattern in your shell and close any `[` bracket."
         }
+        "hint_install_toolchain" => {
+            "Install the Rust toolchain with rustup (https://rustup.rs), then open a new shell."
+        }
+        "hint_cargo_bin_not_in_path" => {
+            "Add $CARGO_HOME/bin (usually ~/.cargo/bin) to PATH, \
+             otherwise installed binaries cannot be run by name."
+        }
+        "hint_github_token" => {
+            "Set GITHUB_TOKEN (or run `gh auth login`) to raise the GitHub API limit \
+             from 60 to 5000 requests per hour."
+        }
+        "hint_tls_init" => {
+            "TLS setup failed. Check the system CA certificates and the SSL_CERT_FILE / \
+             HTTPS_PROXY environment variables."
+        }
+        "hint_repair_metadata" => {
+            "Reinstall the listed packages with `cargo install --force <name>` \
+             so cargo rewrites both .crates.toml and .crates2.json."
+        }
+        "hint_unknown_target" => {
+            "No prebuilt target for this platform; add one under [targets] in the config \
+             file, or updates will always build from source."
+        }
+        "hint_prune_orphans" => "Run `cargo fresh prune --orphans` to review and remove them.",
 
         // Version labels
         "version_unchanged" => "version unchanged",
@@ -127,6 +151,79 @@ pub fn get_english_text(key: &str) -> &'static str {
         "completion_install_summary" => "{written} written, {skipped} skipped",
         "completion_no_targets" => "no completion targets selected",
 
+        // Download cache
+        "cache_empty" => "no archives cached in {}",
+        "cache_entry" => "{size}  {url}  (used {age} ago)",
+        "cache_summary" => "{count} archive(s), {size} in {dir}",
+        "cache_prune_summary" => "removed {count} archive(s), freed {size}",
+
+        "bundle_summary" => "bundled {count} package(s) into {path}",
+        "bundle_skipped_summary" => "{count} package(s) left out of the bundle",
+        "bundle_no_prebuilt" => "no prebuilt for {target}: {names}",
+        "bundle_needs_downloader" => "{name}: needs a source build (git/path source or custom features), not bundled",
+        "from_bundle_opened" => "{count} package(s) in bundle from {by}",
+        "from_bundle_not_installed" => "{name} {version}: not installed here, bundles only upgrade installed packages",
+        "from_bundle_nothing" => "nothing in the bundle is newer than what is installed",
+        "source_only_newer" => "({version} source-only)",
+        "skipped_prebuilt_only" => "no prebuilt binary, prebuilt-only",
+        "skipped_source_build" => "custom features need a source build",
+        "skipped_source_only" => "source-only, not installing the prebuilt archive",
+        "skipped_excluded" => "excluded by config",
+        "held_at" => "held at {at}, {latest} available",
+        "hold_set" => "{name} held at {at}",
+        "hold_removed" => "{name} is no longer held",
+        "hold_not_found" => "{name} is not held",
+        "already_installed" => "{name} {version} is already installed, pass --force to reinstall",
+        "export_written" => "{count} packages written to {path}",
+        "sync_plan" => "{install} to install, {update} to update, {downgrade} to downgrade, {reinstall} to reinstall, {uninstall} to uninstall",
+        "sync_up_to_date" => "installed packages already match {path}",
+        "sync_extra" => "{name} {version} is not in the manifest (pass --uninstall-extras to remove it)",
+        "sync_reason_source" => "source changed",
+        "sync_reason_features" => "features changed",
+        "use_already" => "{name} is already at {version}",
+        "uninstall_missing" => "already gone from bin/: {bins}",
+        "prune_candidate" => "{name} (last used {age} ago)",
+        "prune_candidate_unknown" => "{name} (last use unknown)",
+        "prune_none" => "no installed packages match",
+        "prune_select" => "Select packages to uninstall (space to toggle, enter to confirm)",
+        "list_col_package" => "Package",
+        "list_col_version" => "Version",
+        "list_col_method" => "Method",
+        "list_col_size" => "Size",
+        "list_col_modified" => "Modified",
+        "list_col_bins" => "Binaries",
+        "list_col_features" => "Features",
+        "list_summary" => "{count} packages, {size} on disk",
+        "doctor_cargo_home_unknown" => "cannot determine CARGO_HOME (no home directory)",
+        "doctor_path_missing" => "{path} does not exist",
+        "doctor_bin_not_in_path" => "{path} is not in PATH",
+        "doctor_cargo_home_ok" => "{path} ({count} entries in bin)",
+        "doctor_metadata_ok" => "{count} packages recorded, .crates.toml and .crates2.json agree",
+        "doctor_only_in_toml" => "only in .crates.toml: {names}",
+        "doctor_only_in_json" => "only in .crates2.json: {names}",
+        "doctor_records_differ" => "version or source differs: {names}",
+        "doctor_bins_missing" => "recorded binaries missing from bin: {names}",
+        "doctor_http_ok" => "HTTP client ready",
+        "doctor_registry_ok" => "{url} reachable",
+        "doctor_token_none" => "no GitHub token found, unauthenticated limit applies",
+        "doctor_token_ok" => "token from {source}",
+        "doctor_rate_limit" => "{remaining}/{limit} requests left, resets in {minutes} min",
+        "doctor_rate_unreadable" => "could not read the GitHub rate limit",
+        "doctor_targets_none" => "no prebuilt target known for {host}",
+        "doctor_summary" => "{pass} passed, {warn} warnings, {fail} failed",
+        "doctor_orphans_none" => "no orphaned files in bin",
+        "doctor_orphans" => "unowned: {unowned}, stale temp files: {temp} ({names})",
+        "shadowed_binary" => {
+            "{bin}: {path} comes first in PATH, so the updated binary will not be the one that runs"
+        }
+        "orphan_unowned" => "{name} ({size}, not owned by any installed package)",
+        "orphan_stale_temp" => "{name} ({size}, left over by an interrupted install)",
+        "orphans_none" => "no orphaned files in $CARGO_HOME/bin",
+        "self_updated" => {
+            "cargo-fresh was updated to {version}; this run is still the old binary, re-run cargo fresh to use it"
+        }
+        "self_up_to_date" => "cargo-fresh {version} is up to date",
+
         _ => "",
     }
 }
@@ -185,6 +282,21 @@ pub fn get_chinese_text(key: &str) -> &'static str {
             "`--filter` / `--exclude` 的 glob 非法。模式使用 glob 语法\
              （`*`、`?`、`[abc]`）；请在 shell 中引用模式并闭合 `[`。"
         }
+        "hint_install_toolchain" => "用 rustup（https://rustup.rs）安装 Rust 工具链，然后重新打开 shell。",
+        "hint_cargo_bin_not_in_path" => {
+            "把 $CARGO_HOME/bin（通常是 ~/.cargo/bin）加入 PATH，否则无法直接按名字运行已安装的程序。"
+        }
+        "hint_github_token" => {
+            "设置 GITHUB_TOKEN（或运行 `gh auth login`），GitHub API 配额会从每小时 60 次提升到 5000 次。"
+        }
+        "hint_tls_init" => "TLS 初始化失败。检查系统 CA 证书以及 SSL_CERT_FILE / HTTPS_PROXY 环境变量。",
+        "hint_repair_metadata" => {
+            "用 `cargo install --force <name>` 重装列出的包，让 cargo 重写 .crates.toml 和 .crates2.json。"
+        }
+        "hint_unknown_target" => {
+            "本平台没有预编译 target；在配置文件的 [targets] 里添加，否则更新总是从源码构建。"
+        }
+        "hint_prune_orphans" => "运行 `cargo fresh prune --orphans` 查看并清理。",
 
         // 版本标签
         "version_unchanged" => "版本未变",
@@ -252,6 +364,75 @@ pub fn get_chinese_text(key: &str) -> &'static str {
         "completion_install_summary" => "写入 {written}，跳过 {skipped}",
         "completion_no_targets" => "未选择任何补全目标",
 
+        // 下载缓存
+        "cache_empty" => "{} 中没有缓存的归档",
+        "cache_entry" => "{size}  {url}（{age}前使用）",
+        "cache_summary" => "共 {count} 个归档，{size}，位于 {dir}",
+        "cache_prune_summary" => "移除 {count} 个归档，释放 {size}",
+
+        "bundle_summary" => "已将 {count} 个包打入 {path}",
+        "bundle_skipped_summary" => "{count} 个包未打入离线包",
+        "bundle_no_prebuilt" => "{target} 没有预编译产物：{names}",
+        "bundle_needs_downloader" => "{name}：需要源码构建（git/path 来源或自定义 features），未打包",
+        "from_bundle_opened" => "离线包含 {count} 个包，由 {by} 生成",
+        "from_bundle_not_installed" => "{name} {version}：本机未安装，离线包只能升级已安装的包",
+        "from_bundle_nothing" => "离线包中没有比已安装版本更新的包",
+        "source_only_newer" => "（{version} 仅源码）",
+        "skipped_prebuilt_only" => "无预编译产物，仅限预编译",
+        "skipped_source_build" => "自定义 features 需要源码构建",
+        "skipped_source_only" => "仅限源码构建，不装预编译归档",
+        "skipped_excluded" => "已在配置中排除",
+        "held_at" => "锁定在 {at}，{latest} 可用",
+        "hold_set" => "{name} 已锁定在 {at}",
+        "hold_removed" => "{name} 已解除锁定",
+        "hold_not_found" => "{name} 没有被锁定",
+        "already_installed" => "{name} {version} 已安装，加 --force 重新安装",
+        "export_written" => "已写入 {count} 个包到 {path}",
+        "sync_plan" => "安装 {install} 个，升级 {update} 个，降级 {downgrade} 个，重装 {reinstall} 个，卸载 {uninstall} 个",
+        "sync_up_to_date" => "已安装的包和 {path} 一致",
+        "sync_extra" => "{name} {version} 不在清单里（加 --uninstall-extras 卸载）",
+        "sync_reason_source" => "来源变了",
+        "sync_reason_features" => "features 变了",
+        "use_already" => "{name} 已经是 {version}",
+        "uninstall_missing" => "bin/ 下已经没有：{bins}",
+        "prune_candidate" => "{name}（{age}前使用过）",
+        "prune_candidate_unknown" => "{name}（不知道上次使用时间）",
+        "prune_none" => "没有符合条件的已安装包",
+        "prune_select" => "选择要卸载的包（空格切换，回车确认）",
+        "list_col_package" => "包",
+        "list_col_version" => "版本",
+        "list_col_method" => "安装方式",
+        "list_col_size" => "大小",
+        "list_col_modified" => "修改时间",
+        "list_col_bins" => "可执行文件",
+        "list_col_features" => "Features",
+        "list_summary" => "共 {count} 个包，占用 {size}",
+        "doctor_cargo_home_unknown" => "无法确定 CARGO_HOME（找不到 home 目录）",
+        "doctor_path_missing" => "{path} 不存在",
+        "doctor_bin_not_in_path" => "{path} 不在 PATH 中",
+        "doctor_cargo_home_ok" => "{path}（bin 下 {count} 项）",
+        "doctor_metadata_ok" => "记录了 {count} 个包，.crates.toml 与 .crates2.json 一致",
+        "doctor_only_in_toml" => "仅在 .crates.toml 中：{names}",
+        "doctor_only_in_json" => "仅在 .crates2.json 中：{names}",
+        "doctor_records_differ" => "版本或来源不一致：{names}",
+        "doctor_bins_missing" => "记录的可执行文件不在 bin 下：{names}",
+        "doctor_http_ok" => "HTTP 客户端就绪",
+        "doctor_registry_ok" => "{url} 可访问",
+        "doctor_token_none" => "未找到 GitHub token，使用未认证配额",
+        "doctor_token_ok" => "token 来自 {source}",
+        "doctor_rate_limit" => "剩余 {remaining}/{limit} 次请求，{minutes} 分钟后重置",
+        "doctor_rate_unreadable" => "无法读取 GitHub 配额",
+        "doctor_targets_none" => "{host} 没有已知的预编译 target",
+        "doctor_summary" => "{pass} 项通过，{warn} 项警告，{fail} 项失败",
+        "doctor_orphans_none" => "bin 下没有孤儿文件",
+        "doctor_orphans" => "无主文件 {unowned} 个，残留临时文件 {temp} 个（{names}）",
+        "shadowed_binary" => "{bin}：PATH 中 {path} 在前，更新后运行的仍不是新版本",
+        "orphan_unowned" => "{name}（{size}，不属于任何已安装的包）",
+        "orphan_stale_temp" => "{name}（{size}，中断的安装留下的临时文件）",
+        "orphans_none" => "$CARGO_HOME/bin 下没有孤儿文件",
+        "self_updated" => "cargo-fresh 已更新到 {version}；本次运行的仍是旧版本，重新运行 cargo fresh 即可使用新版",
+        "self_up_to_date" => "cargo-fresh {version} 已是最新版本",
+
         _ => "",
     }
 }
@@ -311,6 +492,13 @@ mod tests {
             "hint_cargo_list_failed",
             "hint_network_connect_timeout",
             "hint_invalid_glob",
+            "hint_install_toolchain",
+            "hint_cargo_bin_not_in_path",
+            "hint_github_token",
+            "hint_tls_init",
+            "hint_repair_metadata",
+            "hint_unknown_target",
+            "hint_prune_orphans",
             "version_unchanged",
             "unknown_version",
             "version_info_unknown",
@@ -354,6 +542,72 @@ mod tests {
             "completion_target_cargo",
             "completion_install_summary",
             "completion_no_targets",
+            "cache_empty",
+            "cache_entry",
+            "cache_summary",
+            "cache_prune_summary",
+            "bundle_summary",
+            "bundle_skipped_summary",
+            "bundle_no_prebuilt",
+            "bundle_needs_downloader",
+            "from_bundle_opened",
+            "from_bundle_not_installed",
+            "from_bundle_nothing",
+            "source_only_newer",
+            "skipped_prebuilt_only",
+            "skipped_source_build",
+            "skipped_source_only",
+            "skipped_excluded",
+            "held_at",
+            "hold_set",
+            "hold_removed",
+            "hold_not_found",
+            "already_installed",
+            "export_written",
+            "sync_plan",
+            "sync_up_to_date",
+            "sync_extra",
+            "sync_reason_source",
+            "sync_reason_features",
+            "use_already",
+            "uninstall_missing",
+            "prune_candidate",
+            "prune_candidate_unknown",
+            "prune_none",
+            "prune_select",
+            "list_col_package",
+            "list_col_version",
+            "list_col_method",
+            "list_col_size",
+            "list_col_modified",
+            "list_col_bins",
+            "list_col_features",
+            "list_summary",
+            "doctor_cargo_home_unknown",
+            "doctor_path_missing",
+            "doctor_bin_not_in_path",
+            "doctor_cargo_home_ok",
+            "doctor_metadata_ok",
+            "doctor_only_in_toml",
+            "doctor_only_in_json",
+            "doctor_records_differ",
+            "doctor_bins_missing",
+            "doctor_http_ok",
+            "doctor_registry_ok",
+            "doctor_token_none",
+            "doctor_token_ok",
+            "doctor_rate_limit",
+            "doctor_rate_unreadable",
+            "doctor_targets_none",
+            "doctor_summary",
+            "doctor_orphans_none",
+            "doctor_orphans",
+            "shadowed_binary",
+            "orphan_unowned",
+            "orphan_stale_temp",
+            "orphans_none",
+            "self_updated",
+            "self_up_to_date",
         ];
 
         for key in &english_keys {
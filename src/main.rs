@@ -2,105 +2,35 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::Result;
-use clap::Parser;
 use colored::*;
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
 use cargo_fresh::cli::{Cli, Commands, OutputFormat};
+use cargo_fresh::config::commands::run_config_command;
+use cargo_fresh::display::report::emit_report;
 use cargo_fresh::display::{
     print_results, print_update_selection, print_update_summary, set_json_mode, status, status_dim,
     status_err, status_warn,
 };
+use cargo_fresh::doctor::commands::run_doctor;
+use cargo_fresh::downloader::bundle::commands::{run_bundle, run_from_bundle, BundleRequest};
+use cargo_fresh::downloader::cache::commands::run_cache_command;
 use cargo_fresh::locale::detect_language;
+use cargo_fresh::manifest::commands::{run_export, run_sync};
 use cargo_fresh::models::{
-    JsonCheckError, JsonReport, JsonResult, JsonSkipped, JsonSummary, JsonUpdateCandidate,
-    PackageInfo, PackageSource, UpdateResult,
+    PackageInfo, PackageSource, UpdateResult, EXIT_ABORTED, EXIT_FAILED, EXIT_OK,
+    EXIT_UPDATES_AVAILABLE,
 };
+use cargo_fresh::package::inventory::commands::run_list;
+use cargo_fresh::package::uninstall::commands::{run_prune, run_prune_orphans, run_uninstall};
 use cargo_fresh::package::{
     check_package_updates, exclude_packages, filter_packages, get_installed_packages,
     is_stable_version,
 };
-use cargo_fresh::updater::update_package;
-
-/// Outcome of a single package update, ready for the orchestrator to fold
-/// into `success_count` / `fail_count` / `aborted_at` / `update_results`.
-enum SlotOutcome {
-    Success(UpdateResult),
-    Failed(UpdateResult),
-    Aborted,
-    Error(String, anyhow::Error),
-}
-
-#[allow(clippy::too_many_arguments)]
-async fn run_one_update(
-    package_name: String,
-    target_version: Option<String>,
-    source: PackageSource,
-    install_opts: Option<cargo_fresh::models::InstallOpts>,
-    dry_run: bool,
-    verbose: bool,
-    cancel: Arc<AtomicBool>,
-    row: Option<(indicatif::ProgressBar, usize)>,
-) -> SlotOutcome {
-    let row_for_finalize = row.clone();
-    let target = target_version.as_deref();
-    let opts_ref = install_opts.as_ref();
-
-    match update_package(
-        &package_name,
-        target,
-        &source,
-        opts_ref,
-        dry_run,
-        verbose,
-        cancel,
-        row,
-    )
-    .await
-    {
-        Ok(Some(result)) => {
-            if let Some((pb, w)) = &row_for_finalize {
-                if result.success {
-                    cargo_fresh::updater::finalize_installed(pb, *w);
-                } else {
-                    cargo_fresh::updater::finalize_failed(pb, *w, "");
-                }
-            }
-            if result.success {
-                SlotOutcome::Success(result)
-            } else {
-                SlotOutcome::Failed(result)
-            }
-        }
-        Ok(None) => {
-            if let Some((pb, w)) = &row_for_finalize {
-                cargo_fresh::updater::finalize_aborted(pb, *w);
-            }
-            SlotOutcome::Aborted
-        }
-        Err(e) => {
-            if let Some((pb, w)) = &row_for_finalize {
-                cargo_fresh::updater::finalize_failed(pb, *w, &e.to_string());
-            }
-            SlotOutcome::Error(package_name, e)
-        }
-    }
-}
-
-/// 退出码契约（在 README 同步文档化）：
-///
-/// | 码  | 含义                                            |
-/// |-----|-------------------------------------------------|
-/// | 0   | 无更新候选；或所有选中包更新成功                |
-/// | 1   | 有更新候选但本次未应用（JSON 模式无 --batch；或 --no-interactive 没选中包） |
-/// | 2   | 至少一个包更新失败                              |
-/// | 130 | 用户按 Ctrl-C 中断                              |
-/// | 其他| clap 用法错误等由 clap / anyhow 直接返回的标准码 |
-const EXIT_OK: i32 = 0;
-const EXIT_UPDATES_AVAILABLE: i32 = 1;
-const EXIT_FAILED: i32 = 2;
-const EXIT_ABORTED: i32 = 130;
+use cargo_fresh::state::commands::{run_hold, run_unhold};
+use cargo_fresh::updater::install::{run_install, run_self_update, run_use, InstallRequest};
+use cargo_fresh::updater::{note_self_update, run_one_update, self_replace, unheld, SlotOutcome};
 
 #[tokio::main]
 async fn main() {
@@ -128,10 +58,10 @@ async fn main() {
 
 async fn run() -> Result<i32> {
     let args: Vec<String> = std::env::args().collect();
-    let cli = if args.get(1) == Some(&"fresh".to_string()) {
-        Cli::parse_from(args.into_iter().skip(1))
+    let (mut cli, matches) = if args.get(1) == Some(&"fresh".to_string()) {
+        Cli::parse_with_matches(args.into_iter().skip(1))
     } else {
-        Cli::parse()
+        Cli::parse_with_matches(args)
     };
 
     // 颜色决策权交给 anstream：它读取 NO_COLOR / CLICOLOR[_FORCE] / TERM / TTY
@@ -154,6 +84,22 @@ async fn run() -> Result<i32> {
 
     let language = detect_language();
 
+    // 配置文件解析失败直接报错退出: 签名策略这类设置静默回退默认值更危险。
+    // completion / man 用不到配置, 配置写坏了也要能生成补全和手册
+    let needs_config = !matches!(
+        cli.command,
+        Some(Commands::Completion { .. } | Commands::Man)
+    );
+    if needs_config {
+        cargo_fresh::config::init()?;
+        cli.apply_defaults(&cargo_fresh::config::get().defaults, &matches);
+        let network = &cargo_fresh::config::get().network;
+        cargo_fresh::downloader::throttle::init(
+            cli.limit_rate.or(network.limit_rate),
+            network.per_host_connections,
+        );
+    }
+
     let cancel = Arc::new(AtomicBool::new(false));
     {
         let cancel = cancel.clone();
@@ -177,7 +123,9 @@ async fn run() -> Result<i32> {
         });
     }
 
-    if let Some(command) = cli.command {
+    // `bundle` 复用下面的检查 / 选包流程, 只有最后一步不同
+    let mut bundle_request = None;
+    if let Some(command) = cli.command.take() {
         match command {
             Commands::Completion {
                 shell,
@@ -245,9 +193,91 @@ async fn run() -> Result<i32> {
                 Cli::generate_man()?;
                 return Ok(EXIT_OK);
             }
+            Commands::Cache { action } => {
+                return run_cache_command(&action, language);
+            }
+            Commands::Config { action } => {
+                return run_config_command(&action, json_mode);
+            }
+            Commands::List { sort, reverse, csv } => {
+                return run_list(&cli, sort, reverse, csv, json_mode, language).await;
+            }
+            Commands::Install {
+                crate_spec,
+                force,
+                features,
+                all_features,
+                no_default_features,
+            } => {
+                let request = InstallRequest {
+                    crate_spec,
+                    force,
+                    opts: cargo_fresh::models::InstallOpts {
+                        no_default_features,
+                        all_features,
+                        features,
+                    },
+                };
+                return run_install(&cli, request, &cancel, language).await;
+            }
+            Commands::Use { spec, no_hold } => {
+                return run_use(&cli, &spec, !no_hold, &cancel, language).await;
+            }
+            Commands::Uninstall { packages } => {
+                return run_uninstall(&cli, &packages, json_mode, language).await;
+            }
+            Commands::Prune { orphans: true, .. } => {
+                return run_prune_orphans(&cli, json_mode, language);
+            }
+            Commands::Prune {
+                unused_for,
+                patterns,
+                orphans: false,
+            } => {
+                return run_prune(&cli, unused_for, &patterns, json_mode, language).await;
+            }
+            Commands::Export { output } => {
+                return run_export(output.as_deref(), json_mode, language).await;
+            }
+            Commands::Sync {
+                manifest,
+                uninstall_extras,
+            } => {
+                return run_sync(&cli, &manifest, uninstall_extras, &cancel, language).await;
+            }
+            Commands::Hold { package, at } => {
+                return run_hold(&package, at.as_deref(), language).await;
+            }
+            Commands::Unhold { package } => {
+                return run_unhold(&package, language);
+            }
+            Commands::Doctor => {
+                return run_doctor(&cli, json_mode, language).await;
+            }
+            Commands::SelfUpdate => {
+                return run_self_update(&cli, &cancel, language).await;
+            }
+            Commands::Bundle {
+                output,
+                target,
+                all,
+            } => {
+                if cli.from_bundle.is_some() {
+                    anyhow::bail!("`bundle` cannot be combined with --from-bundle");
+                }
+                bundle_request = Some(BundleRequest {
+                    output,
+                    target,
+                    all,
+                });
+            }
         }
     }
 
+    if let Some(path) = cli.from_bundle.clone() {
+        return run_from_bundle(&cli, &path, &cancel, language).await;
+    }
+
     let run_start = std::time::Instant::now();
     status("Checking", language.get_text("checking_packages"));
 
@@ -267,7 +297,10 @@ async fn run() -> Result<i32> {
     if !cli.exclude.is_empty() {
         exclude_packages(&mut packages, &cli.exclude)?;
     }
-    if (cli.filter.is_some() || !cli.exclude.is_empty()) && packages.is_empty() {
+    let config = cargo_fresh::config::get();
+    let config_excluded = packages.iter().any(|p| config.is_excluded(&p.name));
+    packages.retain(|p| !config.is_excluded(&p.name));
+    if (cli.filter.is_some() || !cli.exclude.is_empty() || config_excluded) && packages.is_empty() {
         status_warn("Note", language.get_text("no_packages_found"));
         if json_mode {
             emit_report(&cli, &[], &[], &[], false, run_start, 0);
@@ -299,6 +332,20 @@ async fn run() -> Result<i32> {
     if cli.check_prebuilt {
         cargo_fresh::downloader::probe::annotate_updates(&mut packages).await;
     }
+    // --prefer-prebuilt (或按包配置): 最新版只能源码构建时退到最新的有预编译产物的版本
+    cargo_fresh::downloader::probe::prefer_prebuilt_versions(
+        &mut packages,
+        cli.prefer_prebuilt,
+        cli.include_prerelease,
+        cli.registry_url.as_deref(),
+    )
+    .await;
+    cargo_fresh::state::load()?.apply_holds(&mut packages);
+    if bundle_request.is_none() {
+        if let Some(home) = cargo_fresh::package::registry::cargo_home() {
+            cargo_fresh::package::bins::annotate_shadowed(&mut packages, &home);
+        }
+    }
 
     let stable_updates: Vec<&PackageInfo> = packages
         .iter()
@@ -318,6 +365,35 @@ async fn run() -> Result<i32> {
     let mut all_updates = stable_updates.clone();
     all_updates.extend(prerelease_updates.clone());
 
+    // bundle: 没有"只检查"的意义, 非交互 (--batch / JSON / --no-interactive)
+    // 时打包全部候选; `--all` 把没有更新的包也按当前最新版本打进去
+    if let Some(request) = bundle_request {
+        if request.all {
+            let candidates: Vec<&PackageInfo> = packages
+                .iter()
+                .filter(|p| p.check_error.is_none() && matches!(p.source, PackageSource::Crates))
+                .collect();
+            print_results(&packages, cli.updates_only, language);
+            return run_bundle(&request, &candidates, &cancel, language).await;
+        }
+        if all_updates.is_empty() {
+            status("Finished", language.get_text("all_up_to_date"));
+            return Ok(EXIT_OK);
+        }
+        print_results(&packages, cli.updates_only, language);
+        let selections: Vec<usize> = if cli.batch || json_mode || cli.no_interactive {
+            unheld(&all_updates)
+        } else {
+            print_update_selection(&stable_updates, &prerelease_updates, language)?
+        };
+        if selections.is_empty() {
+            status_dim("Note", language.get_text("no_packages_selected"));
+            return Ok(EXIT_UPDATES_AVAILABLE);
+        }
+        let selected: Vec<&PackageInfo> = selections.iter().map(|&i| all_updates[i]).collect();
+        return run_bundle(&request, &selected, &cancel, language).await;
+    }
+
     if all_updates.is_empty() {
         status("Finished", language.get_text("all_up_to_date"));
         if json_mode {
@@ -334,13 +410,15 @@ async fn run() -> Result<i32> {
     // - human --batch：选所有
     // - human 交互：dialoguer 多选
     // - human --no-interactive：不选
-    let selections: Vec<usize> = if cli.batch {
-        (0..all_updates.len()).collect()
+    let mut selections: Vec<usize> = if cli.batch {
+        unheld(&all_updates)
     } else if json_mode || cli.no_interactive {
         Vec::new()
     } else {
         print_update_selection(&stable_updates, &prerelease_updates, language)?
     };
+    // cargo-fresh 自己排最后：换掉运行中的 binary 之后这次运行不再装别的
+    self_replace::schedule_last(&mut selections, |&i| all_updates[i].name.as_str());
 
     let mut update_results: Vec<UpdateResult> = Vec::new();
     let mut aborted = false;
@@ -393,12 +471,22 @@ async fn run() -> Result<i32> {
             let package_name = pkg.name.clone();
             let target_version = pkg.latest_version.clone();
             let source = pkg.source.clone();
-            let install_opts = pkg.install_opts.clone();
+            let install_opts = cargo_fresh::config::get()
+                .install_opts_for(&package_name, pkg.install_opts.clone());
+            let policy = cli
+                .install_policy_override()
+                .unwrap_or_else(|| cargo_fresh::config::get().install_policy(&package_name));
 
             let row = plan_arc.as_ref().map(|p| (p.row(i), p.name_width()));
 
             // acquire_owned BEFORE spawn — this is what bounds concurrency.
-            let Ok(permit) = sem.clone().acquire_owned().await else {
+            // cargo-fresh 自己要拿满所有 permit，即等其余包全部装完再开始。
+            let permits = if self_replace::is_self(&package_name) {
+                cap as u32
+            } else {
+                1
+            };
+            let Ok(permit) = sem.clone().acquire_many_owned(permits).await else {
                 break;
             };
             let cancel_task = cancel.clone();
@@ -416,6 +504,7 @@ async fn run() -> Result<i32> {
                     verbose,
                     cancel_task,
                     row,
+                    policy,
                 )
                 .await;
                 (i, outcome)
@@ -446,6 +535,9 @@ async fn run() -> Result<i32> {
                     fail_count += 1;
                     update_results.push(result);
                 }
+                SlotOutcome::Skipped(result) => {
+                    update_results.push(result);
+                }
                 SlotOutcome::Aborted => {
                     aborted_at.get_or_insert(i);
                 }
@@ -510,6 +602,7 @@ async fn run() -> Result<i32> {
                 status("Finished", &summary);
             }
         }
+        note_self_update(&update_results, cli.dry_run, language);
     } else {
         status_dim("Note", language.get_text("no_packages_selected"));
     }
@@ -527,9 +620,11 @@ async fn run() -> Result<i32> {
     }
 
     // 计算退出码
-    let any_failed = update_results.iter().any(|r| !r.success);
-    let updates_available = !all_updates.is_empty();
-    let applied_any = !update_results.is_empty();
+    // prebuilt-only 跳过的包不算失败, 也不算"已应用"
+    let any_failed = update_results.iter().any(|r| !r.success && r.skipped.is_none());
+    // 被 hold 拦下的更新不算"有更新待应用"
+    let updates_available = all_updates.iter().any(|p| !p.held);
+    let applied_any = update_results.iter().any(|r| r.skipped.is_none());
 
     let code = if aborted {
         EXIT_ABORTED
@@ -544,285 +639,3 @@ async fn run() -> Result<i32> {
     Ok(code)
 }
 
-/// 纯函数：把整次运行的快照组装成 JSON 报告结构。
-/// 不做 I/O，便于单元测试；`emit_report` 负责真正写 stdout。
-fn build_report<'a>(
-    cli: &'a Cli,
-    packages: &'a [PackageInfo],
-    all_updates: &[&'a PackageInfo],
-    update_results: &'a [UpdateResult],
-    aborted: bool,
-    start: std::time::Instant,
-    selected: usize,
-) -> JsonReport<'a> {
-    let updates_available: Vec<JsonUpdateCandidate> = all_updates
-        .iter()
-        .filter_map(|p| {
-            p.latest_version
-                .as_deref()
-                .map(|latest| JsonUpdateCandidate {
-                    name: p.name.as_str(),
-                    current: p.current_version.as_deref(),
-                    latest,
-                    source: p.source.kind_str(),
-                    prerelease: p.is_prerelease(),
-                    prebuilt: p.prebuilt.map(|k| k.kind_str()),
-                })
-        })
-        .collect();
-
-    let fresh: Vec<&str> = packages
-        .iter()
-        .filter(|p| !p.has_update() && p.source.is_crates() && p.check_error.is_none())
-        .map(|p| p.name.as_str())
-        .collect();
-
-    let skipped: Vec<JsonSkipped> = packages
-        .iter()
-        .filter(|p| !p.source.is_crates())
-        .map(|p| JsonSkipped {
-            name: p.name.as_str(),
-            source: p.source.kind_str(),
-            reason_code: p.source.skip_reason_code(),
-            reason: "non-crates source: version check skipped",
-        })
-        .collect();
-
-    let version_check_errors: Vec<JsonCheckError> = packages
-        .iter()
-        .filter_map(|p| {
-            p.check_error.as_ref().map(|e| JsonCheckError {
-                name: p.name.as_str(),
-                kind: e.kind.kind_str(),
-                error: e.message.as_str(),
-            })
-        })
-        .collect();
-
-    let results: Vec<JsonResult> = update_results
-        .iter()
-        .map(|r| JsonResult {
-            name: r.package_name.as_str(),
-            old_version: r.old_version.as_deref(),
-            new_version: r.new_version.as_deref(),
-            success: r.success,
-            install_method: r.install_method.json_str(),
-        })
-        .collect();
-
-    let succeeded = results.iter().filter(|r| r.success).count();
-    let failed = results.iter().filter(|r| !r.success).count();
-
-    let summary = JsonSummary {
-        checked: packages.len(),
-        available: updates_available.len(),
-        selected,
-        attempted: results.len(),
-        succeeded,
-        failed,
-        skipped: skipped.len(),
-        check_errors: version_check_errors.len(),
-        duration_ms: start.elapsed().as_millis(),
-    };
-
-    JsonReport {
-        schema_version: 2,
-        format: "cargo-fresh-v1",
-        version: env!("CARGO_PKG_VERSION"),
-        include_prerelease: cli.include_prerelease,
-        dry_run: cli.dry_run,
-        registry_url: cli.registry_url.as_deref(),
-        updates_available,
-        fresh,
-        skipped,
-        version_check_errors,
-        results,
-        summary,
-        aborted,
-    }
-}
-
-fn emit_report(
-    cli: &Cli,
-    packages: &[PackageInfo],
-    all_updates: &[&PackageInfo],
-    update_results: &[UpdateResult],
-    aborted: bool,
-    start: std::time::Instant,
-    selected: usize,
-) {
-    print_json(&build_report(
-        cli,
-        packages,
-        all_updates,
-        update_results,
-        aborted,
-        start,
-        selected,
-    ));
-}
-
-fn print_json(report: &JsonReport) {
-    // JSON 报告永远不需要颜色，但仍走 anstream::println! 以保持 stdout 通道一致
-    match serde_json::to_string(report) {
-        Ok(s) => anstream::println!("{}", s),
-        Err(e) => {
-            anstream::eprintln!(
-                "{{\"schema_version\":2,\"error\":\"failed to serialize report: {}\"}}",
-                e
-            );
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cargo_fresh::models::{PackageInfo, PackageSource};
-    use clap::Parser;
-
-    fn empty_cli() -> Cli {
-        Cli::parse_from(["cargo-fresh"])
-    }
-
-    #[test]
-    fn build_report_counts_packages_and_sets_format() {
-        let cli = empty_cli();
-        let packages = vec![PackageInfo::with_source(
-            "ripgrep".into(),
-            Some("14.1.1".into()),
-            PackageSource::Crates,
-        )];
-        let report = build_report(
-            &cli,
-            &packages,
-            &[],
-            &[],
-            false,
-            std::time::Instant::now(),
-            0,
-        );
-        assert_eq!(report.schema_version, 2);
-        assert_eq!(report.format, "cargo-fresh-v1");
-        assert_eq!(report.summary.checked, 1);
-        assert_eq!(report.fresh, vec!["ripgrep"]);
-    }
-
-    #[test]
-    fn build_report_sets_skip_reason_code() {
-        let cli = empty_cli();
-        let packages = vec![PackageInfo::with_source(
-            "my-tool".into(),
-            Some("0.1.0".into()),
-            PackageSource::Git {
-                url: "u".into(),
-                rev: None,
-            },
-        )];
-        let report = build_report(
-            &cli,
-            &packages,
-            &[],
-            &[],
-            false,
-            std::time::Instant::now(),
-            0,
-        );
-        assert_eq!(report.skipped.len(), 1);
-        assert_eq!(report.skipped[0].reason_code, "git_source");
-    }
-
-    #[test]
-    fn build_report_excludes_check_error_packages_from_fresh() {
-        use cargo_fresh::models::{CheckError, CheckErrorKind};
-
-        let cli = empty_cli();
-        let mut errored =
-            PackageInfo::with_source("bat".into(), Some("0.24.0".into()), PackageSource::Crates);
-        errored.check_error = Some(CheckError {
-            kind: CheckErrorKind::Unavailable,
-            message: "sparse index HTTP 503".into(),
-        });
-        let fresh_pkg = PackageInfo::with_source(
-            "ripgrep".into(),
-            Some("14.1.1".into()),
-            PackageSource::Crates,
-        );
-        let packages = vec![errored, fresh_pkg];
-
-        let report = build_report(
-            &cli,
-            &packages,
-            &[],
-            &[],
-            false,
-            std::time::Instant::now(),
-            0,
-        );
-
-        assert_eq!(report.fresh, vec!["ripgrep"]);
-        assert_eq!(report.version_check_errors.len(), 1);
-        assert_eq!(report.version_check_errors[0].name, "bat");
-        assert_eq!(report.version_check_errors[0].kind, "unavailable");
-        assert_eq!(report.summary.check_errors, 1);
-    }
-
-    #[test]
-    fn build_report_maps_prebuilt_to_json() {
-        // --check-prebuilt 探测出的 PrebuiltAvailability 必须落到 updates_available[].prebuilt
-        use cargo_fresh::models::PrebuiltAvailability;
-        let cli = empty_cli();
-        let mut pkg = PackageInfo::with_source(
-            "cargo-deny".into(),
-            Some("0.19.6".into()),
-            PackageSource::Crates,
-        );
-        pkg.latest_version = Some("0.19.7".into());
-        pkg.prebuilt = Some(PrebuiltAvailability::Source);
-        let packages = vec![pkg];
-        let all_updates: Vec<&PackageInfo> = packages.iter().collect();
-        let report = build_report(
-            &cli,
-            &packages,
-            &all_updates,
-            &[],
-            false,
-            std::time::Instant::now(),
-            0,
-        );
-        assert_eq!(report.updates_available.len(), 1);
-        assert_eq!(report.updates_available[0].prebuilt, Some("source"));
-    }
-
-    #[test]
-    fn build_report_prebuilt_is_null_when_not_probed() {
-        // 没跑 --check-prebuilt 时 pkg.prebuilt 为 None,JSON 里应是 null
-        let cli = empty_cli();
-        let mut pkg = PackageInfo::with_source(
-            "ripgrep".into(),
-            Some("14.1.0".into()),
-            PackageSource::Crates,
-        );
-        pkg.latest_version = Some("14.1.1".into());
-        let packages = vec![pkg];
-        let all_updates: Vec<&PackageInfo> = packages.iter().collect();
-        let report = build_report(
-            &cli,
-            &packages,
-            &all_updates,
-            &[],
-            false,
-            std::time::Instant::now(),
-            0,
-        );
-        assert_eq!(report.updates_available[0].prebuilt, None);
-    }
-
-    #[test]
-    fn build_report_summary_has_selection_counts() {
-        let cli = empty_cli();
-        let report = build_report(&cli, &[], &[], &[], false, std::time::Instant::now(), 3);
-        assert_eq!(report.summary.selected, 3);
-        assert_eq!(report.summary.attempted, 0);
-    }
-}
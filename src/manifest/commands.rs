@@ -0,0 +1,355 @@
+//! `cargo fresh export` / `sync` 的执行流程：读写清单文件、打印计划、逐个
+//! 安装，输出 JSON 报告。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::*;
+
+use crate::cli::Cli;
+use crate::display::{status, status_dim, status_err, status_warn};
+use crate::locale::Language;
+use crate::models::{PackageSource, EXIT_ABORTED, EXIT_FAILED, EXIT_OK, EXIT_UPDATES_AVAILABLE};
+use crate::package::get_installed_packages;
+use crate::package::registry::require_cargo_home;
+use crate::updater::install::resolve_install_version;
+use crate::updater::{run_one_update, SlotOutcome};
+
+use super::{
+    from_installed, hold_changes, load, plan, render, HoldChanges, ReinstallReason, SyncAction,
+    SyncStep,
+};
+
+/// `cargo fresh export [FILE]`：已安装的包写成工具清单（不给 FILE 时写 stdout）。
+/// JSON 模式下 stdout 输出清单的 JSON 形式。
+pub async fn run_export(
+    output: Option<&std::path::Path>,
+    json_mode: bool,
+    language: Language,
+) -> Result<i32> {
+    use anyhow::Context;
+
+    let packages = get_installed_packages().await?;
+    let state = crate::state::load()?;
+    let manifest = from_installed(&packages, &state);
+    let body = render(&manifest);
+    match output {
+        Some(path) => {
+            std::fs::write(path, &body)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            let count = manifest.packages.len().to_string();
+            let path = path.display().to_string();
+            status(
+                "Exported",
+                &language.format_text("export_written", &[("count", &count), ("path", &path)]),
+            );
+        }
+        None if !json_mode => anstream::print!("{body}"),
+        None => {}
+    }
+    if json_mode {
+        anstream::println!(
+            "{}",
+            serde_json::json!({ "schema_version": 2, "manifest": manifest })
+        );
+    }
+    Ok(EXIT_OK)
+}
+
+/// `cargo fresh sync <manifest>`：把已安装的包对齐到清单。先查齐所有要装的
+/// 版本再动手，查不到任何一个都直接报错、什么也不改。`--dry-run` 只打印计划。
+pub async fn run_sync(
+    cli: &Cli,
+    manifest_path: &std::path::Path,
+    uninstall_extras: bool,
+    cancel: &Arc<AtomicBool>,
+    language: Language,
+) -> Result<i32> {
+    let config = crate::config::get();
+    let manifest = load(manifest_path)?;
+    let manifest_shown = manifest_path.display().to_string();
+    status("Checking", &manifest_shown);
+    let installed = get_installed_packages().await?;
+    let mut actions = plan(&manifest, &installed, uninstall_extras);
+
+    // 精确版本也查一次 index，确认它确实发布过
+    let lookups = actions
+        .iter()
+        .enumerate()
+        .filter(|(_, action)| action.needs_version())
+        .map(|(i, action)| {
+            let req = manifest.packages[&action.name].requirement();
+            async move {
+                let version = match req {
+                    Ok(req) => resolve_install_version(cli, &action.name, req.as_ref()).await,
+                    Err(e) => Err(e),
+                };
+                (i, version)
+            }
+        });
+    for (i, version) in futures_util::future::join_all(lookups).await {
+        actions[i].set_target(version?);
+    }
+
+    let state = crate::state::load()?;
+    let holds = hold_changes(&manifest, &state, &actions);
+
+    let count = |step: SyncStep| {
+        actions
+            .iter()
+            .filter(|a| a.step == step)
+            .count()
+            .to_string()
+    };
+    let changed = actions.iter().filter(|a| a.step.changes()).count();
+    for action in &actions {
+        let name = action.name.cyan().to_string();
+        let from = action.from.as_deref().unwrap_or("?");
+        let to = sync_target_label(action);
+        match action.step {
+            SyncStep::Install => status("Install", &format!("{name} {to}")),
+            SyncStep::Update => status("Update", &format!("{name} {from} -> {to}")),
+            SyncStep::Downgrade => status_warn("Downgrade", &format!("{name} {from} -> {to}")),
+            SyncStep::Reinstall => {
+                let reason = match action.reason {
+                    Some(ReinstallReason::Source) => language.get_text("sync_reason_source"),
+                    _ => language.get_text("sync_reason_features"),
+                };
+                status("Reinstall", &format!("{name} {to} ({reason})"));
+            }
+            SyncStep::Uninstall => status_warn("Uninstall", &format!("{name} {from}")),
+            SyncStep::Extra => status_dim(
+                "Extra",
+                &language.format_text("sync_extra", &[("name", &name), ("version", from)]),
+            ),
+            SyncStep::Keep if cli.verbose => status_dim("Keep", &format!("{name} {from}")),
+            SyncStep::Keep => {}
+        }
+    }
+    for (name, at) in &holds.set {
+        let shown = crate::state::Hold { at: at.clone() }.display().to_string();
+        status(
+            "Hold",
+            &language.format_text(
+                "hold_set",
+                &[("name", &name.cyan().to_string()), ("at", &shown)],
+            ),
+        );
+    }
+    for name in &holds.removed {
+        status(
+            "Unhold",
+            &language.format_text("hold_removed", &[("name", &name.cyan().to_string())]),
+        );
+    }
+
+    let mut outcomes: Vec<Option<(bool, Option<&'static str>)>> = vec![None; actions.len()];
+    let emit = |outcomes: &[Option<(bool, Option<&'static str>)>], aborted: bool| {
+        if crate::display::is_json_mode() {
+            anstream::println!(
+                "{}",
+                sync_json(
+                    &manifest_shown,
+                    cli.dry_run,
+                    &actions,
+                    outcomes,
+                    &holds,
+                    aborted
+                )
+            );
+        }
+    };
+    if changed == 0 && holds.is_empty() {
+        status(
+            "Finished",
+            &language.format_text("sync_up_to_date", &[("path", &manifest_shown)]),
+        );
+        emit(&outcomes, false);
+        return Ok(EXIT_OK);
+    }
+    let (install, update, downgrade) = (
+        count(SyncStep::Install),
+        count(SyncStep::Update),
+        count(SyncStep::Downgrade),
+    );
+    let (reinstall, uninstall) = (count(SyncStep::Reinstall), count(SyncStep::Uninstall));
+    status(
+        "Syncing",
+        &language.format_text(
+            "sync_plan",
+            &[
+                ("install", &install),
+                ("update", &update),
+                ("downgrade", &downgrade),
+                ("reinstall", &reinstall),
+                ("uninstall", &uninstall),
+            ],
+        ),
+    );
+    if cli.dry_run {
+        status("Dry run", language.get_text("dry_run_summary"));
+        emit(&outcomes, false);
+        return Ok(EXIT_UPDATES_AVAILABLE);
+    }
+
+    let mut aborted = false;
+    for (i, action) in actions.iter().enumerate() {
+        if !action.step.changes() {
+            continue;
+        }
+        if cancel.load(Ordering::SeqCst) {
+            aborted = true;
+            break;
+        }
+        let name = action.name.cyan().to_string();
+        if action.step == SyncStep::Uninstall {
+            let removed = require_cargo_home()
+                .and_then(|home| crate::package::uninstall::uninstall(&home, &action.name));
+            match removed {
+                Ok(_) => {
+                    status("Uninstalled", &name);
+                    outcomes[i] = Some((true, None));
+                }
+                Err(e) => {
+                    status_err("Failed", &format!("{name}: {e}"));
+                    outcomes[i] = Some((false, None));
+                }
+            }
+            continue;
+        }
+        let policy = cli
+            .install_policy_override()
+            .unwrap_or_else(|| config.install_policy(&action.name));
+        let opts = (!action.opts.is_default()).then(|| action.opts.clone());
+        let outcome = run_one_update(
+            action.name.clone(),
+            action.to.clone(),
+            action.source.clone(),
+            opts,
+            false,
+            cli.verbose,
+            cancel.clone(),
+            None,
+            policy,
+        )
+        .await;
+        outcomes[i] = match outcome {
+            SlotOutcome::Success(result) => {
+                let verb = match action.step {
+                    SyncStep::Install => "Installed",
+                    SyncStep::Downgrade => "Downgraded",
+                    SyncStep::Reinstall => "Reinstalled",
+                    _ => "Updated",
+                };
+                let shown = result.new_version.as_deref().or(action.to.as_deref());
+                status(verb, &format!("{name} {}", shown.unwrap_or_default()));
+                Some((true, result.install_method.json_str()))
+            }
+            SlotOutcome::Skipped(_) => {
+                status_warn(
+                    "Skipped",
+                    &format!("{name} ({})", language.get_text("skipped_prebuilt_only")),
+                );
+                None
+            }
+            SlotOutcome::Failed(result) => {
+                status_err(
+                    "Failed",
+                    &format!(
+                        "{} ({})",
+                        action.name.red(),
+                        language.get_text("update_failed")
+                    ),
+                );
+                Some((false, result.install_method.json_str()))
+            }
+            SlotOutcome::Error(_, e) => {
+                status_err("Failed", &format!("{}: {e}", action.name.red()));
+                Some((false, None))
+            }
+            SlotOutcome::Aborted => {
+                aborted = true;
+                break;
+            }
+        };
+    }
+
+    if !aborted && !holds.is_empty() {
+        // 重新读一遍：执行期间安装账本已经写过状态文件
+        crate::state::update(|state| holds.apply(state))?;
+    }
+    emit(&outcomes, aborted);
+    let pending = |o: &Option<(bool, Option<&str>)>| o.is_none();
+    let code = if aborted {
+        EXIT_ABORTED
+    } else if outcomes.iter().flatten().any(|(success, _)| !success) {
+        EXIT_FAILED
+    } else if actions
+        .iter()
+        .zip(&outcomes)
+        .any(|(action, outcome)| action.step.changes() && pending(outcome))
+    {
+        // prebuilt-only 跳过的包：没装上但不算失败
+        EXIT_UPDATES_AVAILABLE
+    } else {
+        EXIT_OK
+    };
+    Ok(code)
+}
+
+/// sync 计划里显示的目标：crates.io 包是版本，git 包是 `url#rev`，path 包是目录。
+fn sync_target_label(action: &SyncAction) -> String {
+    match &action.source {
+        PackageSource::Git {
+            url,
+            rev: Some(rev),
+        } => format!("{url}#{rev}"),
+        PackageSource::Git { url, rev: None } => url.clone(),
+        PackageSource::Path { dir } => dir.clone(),
+        _ => action.to.clone().unwrap_or_default(),
+    }
+}
+
+/// `sync` 的 JSON 输出。`success` / `install_method` 在没执行（dry-run、
+/// keep、extra、被跳过）时为 null。
+fn sync_json(
+    manifest: &str,
+    dry_run: bool,
+    actions: &[SyncAction],
+    outcomes: &[Option<(bool, Option<&'static str>)>],
+    holds: &HoldChanges,
+    aborted: bool,
+) -> serde_json::Value {
+    let actions: Vec<serde_json::Value> = actions
+        .iter()
+        .zip(outcomes)
+        .map(|(action, outcome)| {
+            let source = match &action.source {
+                PackageSource::Crates => "crates",
+                PackageSource::Git { .. } => "git",
+                PackageSource::Path { .. } => "path",
+                PackageSource::Unknown(_) => "unknown",
+            };
+            serde_json::json!({
+                "name": action.name,
+                "action": action.step.as_str(),
+                "from": action.from,
+                "to": action.to,
+                "source": source,
+                "success": outcome.map(|(success, _)| success),
+                "install_method": outcome.and_then(|(_, method)| method),
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "schema_version": 2,
+        "sync": {
+            "manifest": manifest,
+            "dry_run": dry_run,
+            "aborted": aborted,
+            "actions": actions,
+            "holds": { "set": holds.set, "removed": holds.removed },
+        },
+    })
+}
@@ -0,0 +1,633 @@
+//! `cargo fresh export` / `sync` 的工具清单：一份 TOML，列出机器上应有的
+//! cargo 安装包，团队共用一份，`sync` 把本机对齐到它。
+//!
+//! ```toml
+//! [packages.ripgrep]
+//! version = "14.1.1"
+//! features = ["pcre2"]
+//!
+//! [packages.bevy_cli]
+//! version = "0.21"
+//! pinned = true
+//!
+//! [packages.mytool]
+//! git = "https://github.com/me/mytool"
+//! rev = "3f2c1a9"
+//!
+//! [packages.local-tool]
+//! path = "/home/me/src/local-tool"
+//! ```
+//!
+//! `version` 是 semver 要求，和 `cargo fresh install name@spec` 一样裸版本号
+//! 表示精确版本；省略时装最新版。`pinned = true` 的 crates.io 包 sync 后 hold
+//! 在这个要求上（见 `state`）。toml crate 没开序列化，导出时手写 TOML。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::layers::{key_segment, quote};
+use crate::models::{InstallOpts, PackageInfo, PackageSource};
+use crate::state::State;
+
+pub mod commands;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// 包名 → 条目。
+    pub packages: BTreeMap<String, ManifestEntry>,
+}
+
+/// 清单里的一个包。`git` 和 `path` 都没有时是 crates.io 包。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ManifestEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub all_features: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub no_default_features: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+impl ManifestEntry {
+    pub fn source(&self) -> PackageSource {
+        match (&self.git, &self.path) {
+            (Some(url), _) => PackageSource::Git {
+                url: url.clone(),
+                rev: self.rev.clone(),
+            },
+            (None, Some(dir)) => PackageSource::Path { dir: dir.clone() },
+            (None, None) => PackageSource::Crates,
+        }
+    }
+
+    pub fn opts(&self) -> InstallOpts {
+        InstallOpts {
+            no_default_features: self.no_default_features,
+            all_features: self.all_features,
+            features: self.features.clone(),
+        }
+    }
+
+    /// `version` 解析成的要求；没写时为 None（装最新版）。
+    pub fn requirement(&self) -> Result<Option<semver::VersionReq>> {
+        self.version
+            .as_deref()
+            .map(crate::package::parse_install_requirement)
+            .transpose()
+    }
+}
+
+/// 解析清单，并拒绝 sync 无法执行的组合。
+pub fn parse(body: &str) -> Result<Manifest> {
+    let manifest: Manifest = toml::from_str(body)?;
+    for (name, entry) in &manifest.packages {
+        if entry.git.is_some() && entry.path.is_some() {
+            anyhow::bail!("`{name}`: `git` and `path` cannot both be set");
+        }
+        if entry.rev.is_some() && entry.git.is_none() {
+            anyhow::bail!("`{name}`: `rev` needs `git`");
+        }
+        if entry.version.is_some() && entry.git.is_none() && entry.path.is_none() {
+            entry
+                .requirement()
+                .with_context(|| format!("`{name}`: invalid `version`"))?;
+        } else if entry.version.is_some() {
+            anyhow::bail!("`{name}`: `version` only applies to crates.io packages");
+        }
+    }
+    Ok(manifest)
+}
+
+pub fn load(path: &Path) -> Result<Manifest> {
+    let body = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    parse(&body).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// 从已安装的包生成清单。held 的 crates.io 包写 hold 的要求并标 `pinned`；
+/// 来源无法识别的包跳过（sync 也装不回来）。
+pub fn from_installed(packages: &[PackageInfo], state: &State) -> Manifest {
+    let mut manifest = Manifest::default();
+    for pkg in packages {
+        let opts = pkg.install_opts.clone().unwrap_or_default();
+        let mut entry = ManifestEntry {
+            features: opts.features,
+            all_features: opts.all_features,
+            no_default_features: opts.no_default_features,
+            ..ManifestEntry::default()
+        };
+        match &pkg.source {
+            PackageSource::Crates => match state.holds.get(&pkg.name) {
+                Some(hold) => {
+                    entry.version = Some(hold.display().to_string());
+                    entry.pinned = true;
+                }
+                None => entry.version = pkg.current_version.clone(),
+            },
+            PackageSource::Git { url, rev } => {
+                entry.git = Some(url.clone());
+                entry.rev = rev.clone();
+            }
+            PackageSource::Path { dir } => entry.path = Some(dir.clone()),
+            PackageSource::Unknown(_) => continue,
+        }
+        manifest.packages.insert(pkg.name.clone(), entry);
+    }
+    manifest
+}
+
+/// 渲染成 TOML，字段顺序同 [`ManifestEntry`]。
+pub fn render(manifest: &Manifest) -> String {
+    let mut out = String::from(
+        "# cargo-fresh tool manifest: `cargo fresh sync <file>` installs these packages.\n",
+    );
+    for (name, entry) in &manifest.packages {
+        out.push_str(&format!("\n[packages.{}]\n", key_segment(name)));
+        for (key, value) in [
+            ("version", &entry.version),
+            ("git", &entry.git),
+            ("rev", &entry.rev),
+            ("path", &entry.path),
+        ] {
+            if let Some(value) = value {
+                out.push_str(&format!("{key} = {}\n", quote(value)));
+            }
+        }
+        if !entry.features.is_empty() {
+            let features: Vec<String> = entry.features.iter().map(|f| quote(f)).collect();
+            out.push_str(&format!("features = [{}]\n", features.join(", ")));
+        }
+        for (key, value) in [
+            ("all-features", entry.all_features),
+            ("no-default-features", entry.no_default_features),
+            ("pinned", entry.pinned),
+        ] {
+            if value {
+                out.push_str(&format!("{key} = true\n"));
+            }
+        }
+    }
+    out
+}
+
+/// sync 对一个包要做的事。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStep {
+    Install,
+    Update,
+    Downgrade,
+    /// 版本不用变，但来源或 features 和清单不一致
+    Reinstall,
+    /// 不在清单里，且给了 `--uninstall-extras`
+    Uninstall,
+    /// 不在清单里，保留不动
+    Extra,
+    Keep,
+}
+
+impl SyncStep {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncStep::Install => "install",
+            SyncStep::Update => "update",
+            SyncStep::Downgrade => "downgrade",
+            SyncStep::Reinstall => "reinstall",
+            SyncStep::Uninstall => "uninstall",
+            SyncStep::Extra => "extra",
+            SyncStep::Keep => "keep",
+        }
+    }
+
+    /// 是否会改动本机。
+    pub fn changes(self) -> bool {
+        !matches!(self, SyncStep::Extra | SyncStep::Keep)
+    }
+}
+
+/// 为什么要重装：locale key 后缀，`sync_reason_<reason>`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReinstallReason {
+    Source,
+    Features,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncAction {
+    pub name: String,
+    pub step: SyncStep,
+    /// 已安装的版本
+    pub from: Option<String>,
+    /// 要装的版本。crates.io 包由调用方查 index 后用 [`SyncAction::set_target`]
+    /// 填入；git / path 包始终为 None
+    pub to: Option<String>,
+    pub source: PackageSource,
+    pub opts: InstallOpts,
+    pub reason: Option<ReinstallReason>,
+}
+
+impl SyncAction {
+    /// 是否还要查 index 定下要装的 crates.io 版本。
+    pub fn needs_version(&self) -> bool {
+        self.source.is_crates()
+            && self.to.is_none()
+            && matches!(
+                self.step,
+                SyncStep::Install | SyncStep::Update | SyncStep::Reinstall
+            )
+    }
+
+    /// 填入要装的版本；比已安装的旧时 `Update` 改为 `Downgrade`。
+    pub fn set_target(&mut self, version: String) {
+        if self.step == SyncStep::Update {
+            let older = match (self.from.as_deref(), semver::Version::parse(&version)) {
+                (Some(from), Ok(to)) => semver::Version::parse(from).is_ok_and(|from| to < from),
+                _ => false,
+            };
+            if older {
+                self.step = SyncStep::Downgrade;
+            }
+        }
+        self.to = Some(version);
+    }
+}
+
+/// 比较清单和已安装的包，得出每个包要做的事：先是清单里的包（按名字），
+/// 再是清单之外的已安装包。要求写成范围时，已装版本满足要求就不动。
+///
+/// cargo-fresh 自己即使不在清单里也不会被卸载。
+pub fn plan(
+    manifest: &Manifest,
+    installed: &[PackageInfo],
+    uninstall_extras: bool,
+) -> Vec<SyncAction> {
+    let mut actions = Vec::new();
+    for (name, entry) in &manifest.packages {
+        let source = entry.source();
+        let opts = entry.opts();
+        let pkg = installed.iter().find(|p| &p.name == name);
+        let (step, reason) = match pkg {
+            None => (SyncStep::Install, None),
+            Some(pkg) if !source_matches(&source, &pkg.source) => {
+                (SyncStep::Reinstall, Some(ReinstallReason::Source))
+            }
+            Some(pkg) if !same_features(&opts, &pkg.install_opts.clone().unwrap_or_default()) => {
+                (SyncStep::Reinstall, Some(ReinstallReason::Features))
+            }
+            Some(pkg) => {
+                let satisfied = match entry.requirement().ok().flatten() {
+                    Some(req) => pkg
+                        .current_version
+                        .as_deref()
+                        .and_then(|v| semver::Version::parse(v).ok())
+                        .is_some_and(|v| req.matches(&v)),
+                    None => true,
+                };
+                if satisfied {
+                    (SyncStep::Keep, None)
+                } else {
+                    (SyncStep::Update, None)
+                }
+            }
+        };
+        let from = pkg.and_then(|p| p.current_version.clone());
+        actions.push(SyncAction {
+            name: name.clone(),
+            step,
+            to: (step == SyncStep::Keep).then(|| from.clone()).flatten(),
+            from,
+            source,
+            opts,
+            reason,
+        });
+    }
+
+    let mut extras: Vec<&PackageInfo> = installed
+        .iter()
+        .filter(|p| !manifest.packages.contains_key(&p.name))
+        .collect();
+    extras.sort_by(|a, b| a.name.cmp(&b.name));
+    for pkg in extras {
+        let step = if uninstall_extras && pkg.name != env!("CARGO_PKG_NAME") {
+            SyncStep::Uninstall
+        } else {
+            SyncStep::Extra
+        };
+        actions.push(SyncAction {
+            name: pkg.name.clone(),
+            step,
+            from: pkg.current_version.clone(),
+            to: None,
+            source: pkg.source.clone(),
+            opts: pkg.install_opts.clone().unwrap_or_default(),
+            reason: None,
+        });
+    }
+    actions
+}
+
+/// sync 后 hold 的变化。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HoldChanges {
+    /// 包名 → 新的 hold 要求
+    pub set: BTreeMap<String, String>,
+    pub removed: Vec<String>,
+}
+
+impl HoldChanges {
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn apply(&self, state: &mut State) {
+        for (name, at) in &self.set {
+            state
+                .holds
+                .insert(name.clone(), crate::state::Hold { at: at.clone() });
+        }
+        for name in &self.removed {
+            state.holds.remove(name);
+        }
+    }
+}
+
+/// 清单里的 crates.io 包：`pinned` 的 hold 在 `version`（没写时是要装的版本），
+/// 没标 `pinned` 的去掉已有 hold。git / path 包和清单之外的包不动。
+pub fn hold_changes(manifest: &Manifest, state: &State, actions: &[SyncAction]) -> HoldChanges {
+    let mut changes = HoldChanges::default();
+    for (name, entry) in &manifest.packages {
+        if !entry.source().is_crates() {
+            continue;
+        }
+        let current = state.holds.get(name).map(|h| h.at.as_str());
+        if !entry.pinned {
+            if current.is_some() {
+                changes.removed.push(name.clone());
+            }
+            continue;
+        }
+        let at = match entry.version.as_deref() {
+            Some(version) => hold_requirement(version),
+            None => {
+                let Some(version) = actions
+                    .iter()
+                    .find(|a| &a.name == name)
+                    .and_then(|a| a.to.as_ref().or(a.from.as_ref()))
+                else {
+                    continue;
+                };
+                format!("={version}")
+            }
+        };
+        if current != Some(at.as_str()) {
+            changes.set.insert(name.clone(), at);
+        }
+    }
+    changes
+}
+
+/// 清单 `version` 对应的 hold 要求：裸版本号是精确版本，其余原样。
+fn hold_requirement(version: &str) -> String {
+    let version = version.trim();
+    if semver::Version::parse(version).is_ok() {
+        format!("={version}")
+    } else {
+        version.to_string()
+    }
+}
+
+fn source_matches(wanted: &PackageSource, installed: &PackageSource) -> bool {
+    match (wanted, installed) {
+        (PackageSource::Crates, PackageSource::Crates) => true,
+        (
+            PackageSource::Git { url, rev },
+            PackageSource::Git {
+                url: have_url,
+                rev: have_rev,
+            },
+        ) => {
+            // 清单里常写短 commit，`cargo install --list` 给的是完整的，按前缀比较
+            let rev_matches = match (rev, have_rev) {
+                (None, _) => true,
+                (Some(want), Some(have)) => {
+                    have.starts_with(want.as_str()) || want.starts_with(have.as_str())
+                }
+                (Some(_), None) => false,
+            };
+            normalize_git_url(url) == normalize_git_url(have_url) && rev_matches
+        }
+        (PackageSource::Path { dir }, PackageSource::Path { dir: have }) => {
+            dir.trim_end_matches('/') == have.trim_end_matches('/')
+        }
+        _ => false,
+    }
+}
+
+fn normalize_git_url(url: &str) -> &str {
+    let url = url.trim_end_matches('/');
+    url.strip_suffix(".git").unwrap_or(url)
+}
+
+fn same_features(a: &InstallOpts, b: &InstallOpts) -> bool {
+    let sorted = |opts: &InstallOpts| {
+        let mut features = opts.features.clone();
+        features.sort();
+        features.dedup();
+        features
+    };
+    a.all_features == b.all_features
+        && a.no_default_features == b.no_default_features
+        && sorted(a) == sorted(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Hold;
+
+    fn crate_pkg(name: &str, version: &str) -> PackageInfo {
+        PackageInfo::with_source(name.into(), Some(version.into()), PackageSource::Crates)
+    }
+
+    fn step_of(actions: &[SyncAction], name: &str) -> SyncStep {
+        actions.iter().find(|a| a.name == name).expect(name).step
+    }
+
+    #[test]
+    fn export_round_trips_through_render_and_parse() {
+        let mut rg = crate_pkg("ripgrep", "14.1.1");
+        rg.install_opts = Some(InstallOpts {
+            features: vec!["pcre2".into()],
+            ..InstallOpts::default()
+        });
+        let git = PackageInfo::with_source(
+            "mytool".into(),
+            Some("0.3.0".into()),
+            PackageSource::Git {
+                url: "https://github.com/me/mytool".into(),
+                rev: Some("3f2c1a9".into()),
+            },
+        );
+        let unknown = PackageInfo::with_source(
+            "odd".into(),
+            Some("1.0.0".into()),
+            PackageSource::Unknown("sparse+https://example".into()),
+        );
+        let mut state = State::default();
+        state
+            .holds
+            .insert("bevy_cli".into(), Hold { at: "0.21".into() });
+        let packages = [rg, git, unknown, crate_pkg("bevy_cli", "0.21.3")];
+
+        let manifest = from_installed(&packages, &state);
+        assert!(!manifest.packages.contains_key("odd"));
+        assert_eq!(
+            manifest.packages["bevy_cli"].version.as_deref(),
+            Some("0.21")
+        );
+        assert!(manifest.packages["bevy_cli"].pinned);
+        let body = render(&manifest);
+        assert!(body.contains("[packages.ripgrep]\nversion = \"14.1.1\"\nfeatures = [\"pcre2\"]\n"));
+        assert_eq!(parse(&body).expect("parse"), manifest);
+    }
+
+    #[test]
+    fn parse_rejects_conflicting_fields() {
+        assert!(parse("[packages.a]\ngit = \"x\"\npath = \"y\"\n").is_err());
+        assert!(parse("[packages.a]\nrev = \"abc\"\n").is_err());
+        assert!(parse("[packages.a]\nversion = \"not a version\"\n").is_err());
+        assert!(parse("[packages.a]\npath = \"/src/a\"\nversion = \"1\"\n").is_err());
+        assert!(parse("[packages.a]\nversoin = \"1\"\n").is_err());
+        assert!(parse("").expect("empty").packages.is_empty());
+    }
+
+    #[test]
+    fn plan_compares_versions_sources_and_features() {
+        let manifest = parse(
+            r#"
+[packages.ripgrep]
+version = "14.1.1"
+
+[packages.bevy_cli]
+version = "0.21"
+
+[packages.fd-find]
+version = "9.0.0"
+
+[packages.just]
+
+[packages.mytool]
+git = "https://github.com/me/mytool"
+rev = "3f2c1a9"
+
+[packages.tokei]
+features = ["all"]
+"#,
+        )
+        .expect("parse");
+        let mut git = PackageInfo::with_source(
+            "mytool".into(),
+            Some("0.3.0".into()),
+            PackageSource::Git {
+                url: "https://github.com/me/mytool.git".into(),
+                rev: Some("3f2c1a9d0e".into()),
+            },
+        );
+        git.install_opts = Some(InstallOpts::default());
+        let installed = [
+            crate_pkg("ripgrep", "14.0.0"),
+            crate_pkg("bevy_cli", "0.21.3"),
+            crate_pkg("fd-find", "10.0.0"),
+            git,
+            crate_pkg("tokei", "12.1.2"),
+            crate_pkg("cargo-fresh", "0.12.8"),
+            crate_pkg("bat", "0.24.0"),
+        ];
+
+        let mut actions = plan(&manifest, &installed, true);
+        assert_eq!(step_of(&actions, "just"), SyncStep::Install);
+        assert_eq!(step_of(&actions, "ripgrep"), SyncStep::Update);
+        assert_eq!(step_of(&actions, "bevy_cli"), SyncStep::Keep);
+        assert_eq!(step_of(&actions, "mytool"), SyncStep::Keep);
+        assert_eq!(step_of(&actions, "tokei"), SyncStep::Reinstall);
+        assert_eq!(step_of(&actions, "bat"), SyncStep::Uninstall);
+        assert_eq!(step_of(&actions, "cargo-fresh"), SyncStep::Extra);
+
+        let fd = actions.iter_mut().find(|a| a.name == "fd-find").unwrap();
+        assert!(fd.needs_version());
+        fd.set_target("9.0.0".into());
+        assert_eq!(fd.step, SyncStep::Downgrade);
+        assert!(!fd.needs_version());
+
+        let kept = plan(&manifest, &installed, false);
+        assert_eq!(step_of(&kept, "bat"), SyncStep::Extra);
+    }
+
+    #[test]
+    fn hold_changes_follow_pinned_entries() {
+        let manifest = parse(
+            r#"
+[packages.bevy_cli]
+version = "0.21"
+pinned = true
+
+[packages.ripgrep]
+version = "14.1.1"
+pinned = true
+
+[packages.just]
+pinned = true
+
+[packages.fd-find]
+"#,
+        )
+        .expect("parse");
+        let mut state = State::default();
+        state
+            .holds
+            .insert("bevy_cli".into(), Hold { at: "0.21".into() });
+        state.holds.insert(
+            "fd-find".into(),
+            Hold {
+                at: "=9.0.0".into(),
+            },
+        );
+        let mut just = SyncAction {
+            name: "just".into(),
+            step: SyncStep::Install,
+            from: None,
+            to: None,
+            source: PackageSource::Crates,
+            opts: InstallOpts::default(),
+            reason: None,
+        };
+        just.set_target("1.36.0".into());
+
+        let changes = hold_changes(&manifest, &state, &[just]);
+        assert_eq!(
+            changes.set,
+            BTreeMap::from([
+                ("just".to_string(), "=1.36.0".to_string()),
+                ("ripgrep".to_string(), "=14.1.1".to_string()),
+            ])
+        );
+        assert_eq!(changes.removed, ["fd-find"]);
+        changes.apply(&mut state);
+        assert!(!state.holds.contains_key("fd-find"));
+        assert_eq!(state.holds["ripgrep"].at, "=14.1.1");
+    }
+}
@@ -7,6 +7,20 @@ pub const RETRY_DELAY_MS: u64 = 2000;
 pub const VERSION_UPDATE_DELAY_MS: u64 = 1000;
 pub const PROGRESS_TICK_MS: u64 = 100;
 
+/// 退出码契约（在 README 同步文档化）：
+///
+/// | 码  | 含义                                            |
+/// |-----|-------------------------------------------------|
+/// | 0   | 无更新候选；或所有选中包更新成功                |
+/// | 1   | 有更新候选但本次未应用（JSON 模式无 --batch；或 --no-interactive 没选中包） |
+/// | 2   | 至少一个包更新失败                              |
+/// | 130 | 用户按 Ctrl-C 中断                              |
+/// | 其他| clap 用法错误等由 clap / anyhow 直接返回的标准码 |
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_UPDATES_AVAILABLE: i32 = 1;
+pub const EXIT_FAILED: i32 = 2;
+pub const EXIT_ABORTED: i32 = 130;
+
 /// 包的安装来源。
 ///
 /// `cargo install --list` 输出会在 `name vVERSION` 之后附带括号标识来源，
@@ -80,6 +94,15 @@ impl InstallOpts {
     }
 }
 
+/// 全新安装 (`cargo fresh install`) 时要新建的 `.crates.toml` / `.crates2.json`
+/// 条目。版本和 bins 以实际装上的为准，这里只带 cargo PackageId 的 source 段
+/// 和安装时的 features。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewInstall {
+    pub source_id: String,
+    pub opts: InstallOpts,
+}
+
 /// 版本检查失败的可判别分类。决定 JSON `version_check_errors[].kind`。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CheckErrorKind {
@@ -150,6 +173,25 @@ pub struct PackageInfo {
     /// Downloader probe result, populated when `--check-prebuilt` runs.
     /// `None` = not probed (flag absent, or package isn't a crates.io update candidate).
     pub prebuilt: Option<PrebuiltAvailability>,
+    /// `--prefer-prebuilt` 跳过的最新版本 (只能源码构建); 此时 `latest_version`
+    /// 是退回去的、有预编译产物的版本。
+    pub source_only_latest: Option<String>,
+    /// `cargo fresh hold` 设下的版本要求（显示形式），没有 hold 时为 None。
+    pub hold: Option<String>,
+    /// 有更新但超出 `hold` 的要求：照常检查和展示，但 `--batch` 不选、
+    /// 交互多选里不预选。
+    pub held: bool,
+    /// 这个包在 `$CARGO_HOME/bin` 下的 binary 被 PATH 里更靠前的同名文件
+    /// 遮住的情况；只对更新候选检查（`package::bins::annotate_shadowed`）。
+    pub shadowed: Vec<ShadowedBin>,
+}
+
+/// `$CARGO_HOME/bin/<bin>` 被 PATH 里更靠前的 `path` 遮住——更新之后
+/// 直接敲 `<bin>` 跑的仍是 `path`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShadowedBin {
+    pub bin: String,
+    pub path: std::path::PathBuf,
 }
 
 /// 这次更新走了哪条安装路径——给汇总分组用 (rustup 风格:
@@ -187,6 +229,59 @@ pub struct UpdateResult {
     pub new_version: Option<String>,
     pub success: bool,
     pub install_method: InstallMethod,
+    /// 刻意没装的包（如 `prebuilt-only` 策略下没有预编译产物）。既不算成功
+    /// 也不算失败：JSON 里进 `skipped[]`（`reason_code` 见 [`SkipReason`]）而不是
+    /// `results[]`。
+    pub skipped: Option<SkipReason>,
+}
+
+/// 更新被刻意跳过的原因。决定 JSON `skipped[].reason_code`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// `prebuilt-only` 策略下没有可用的预编译产物。
+    PrebuiltUnavailable,
+    /// 要源码构建（自定义 features），离线包里只有预编译归档。
+    SourceBuildRequired,
+    /// 按包配置 `install = "source-only"`，不装离线包里的预编译归档。
+    SourceOnly,
+    /// 按包配置 `exclude = true`。
+    Excluded,
+}
+
+impl SkipReason {
+    /// JSON `skipped[].reason_code`，稳定枚举。
+    pub fn code(self) -> &'static str {
+        match self {
+            SkipReason::PrebuiltUnavailable => "prebuilt_unavailable",
+            SkipReason::SourceBuildRequired => "source_build_required",
+            SkipReason::SourceOnly => "source_only",
+            SkipReason::Excluded => "excluded",
+        }
+    }
+
+    /// JSON `skipped[].reason`，给人看的说明，不保证稳定。
+    pub fn reason(self) -> &'static str {
+        match self {
+            SkipReason::PrebuiltUnavailable => {
+                "prebuilt-only policy: no prebuilt binary, not compiling from source"
+            }
+            SkipReason::SourceBuildRequired => {
+                "custom features need a source build; the bundle only has prebuilt archives"
+            }
+            SkipReason::SourceOnly => "source-only policy: not installing a prebuilt archive",
+            SkipReason::Excluded => "excluded by the config file",
+        }
+    }
+
+    /// 汇总里 `Skipped` 行括号内的文案。
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            SkipReason::PrebuiltUnavailable => "skipped_prebuilt_only",
+            SkipReason::SourceBuildRequired => "skipped_source_build",
+            SkipReason::SourceOnly => "skipped_source_only",
+            SkipReason::Excluded => "skipped_excluded",
+        }
+    }
 }
 
 impl PackageInfo {
@@ -209,6 +304,10 @@ impl PackageInfo {
             install_opts: None,
             check_error: None,
             prebuilt: None,
+            source_only_latest: None,
+            hold: None,
+            held: false,
+            shadowed: Vec::new(),
         }
     }
 
@@ -265,6 +364,8 @@ pub struct JsonReport<'a> {
     pub fresh: Vec<&'a str>,
     pub skipped: Vec<JsonSkipped<'a>>,
     pub version_check_errors: Vec<JsonCheckError<'a>>,
+    /// `cargo fresh hold` 的包；和 `skipped` 分开——它们照常检查过版本。
+    pub holds: Vec<JsonHold<'a>>,
     pub results: Vec<JsonResult<'a>>,
     pub summary: JsonSummary,
     pub aborted: bool,
@@ -280,6 +381,21 @@ pub struct JsonUpdateCandidate<'a> {
     /// Downloader 预编译可用性:`"prebuilt"` / `"source"` / `"unknown"`,
     /// 未跑 `--check-prebuilt` 时为 `null`。0.12 起取代旧字段 `binstall`。
     pub prebuilt: Option<&'static str>,
+    /// `--prefer-prebuilt` 跳过的、只能源码构建的最新版本; 没退版本时为 `null`。
+    pub source_only_latest: Option<&'a str>,
+    /// 被 PATH 里更靠前的同名文件遮住的 binary; 没有时为空数组。
+    pub shadowed: &'a [ShadowedBin],
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonHold<'a> {
+    pub name: &'a str,
+    /// hold 的版本要求（`=14.1.0` 显示为 `14.1.0`）。
+    pub at: &'a str,
+    pub current: Option<&'a str>,
+    pub latest: Option<&'a str>,
+    /// 最新版本超出要求、这次被拦下时为 true。
+    pub held: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -347,6 +463,15 @@ impl UpdateResult {
             new_version,
             success,
             install_method: InstallMethod::Unknown,
+            skipped: None,
+        }
+    }
+
+    /// 刻意跳过的包：版本不变，不计入失败。
+    pub fn skipped(package_name: String, old_version: Option<String>, reason: SkipReason) -> Self {
+        Self {
+            skipped: Some(reason),
+            ..Self::new(package_name, old_version.clone(), old_version, false)
         }
     }
 
@@ -0,0 +1,251 @@
+//! `$CARGO_HOME/bin` 里的两类问题：
+//!
+//! - 遮挡：PATH 里 `$CARGO_HOME/bin` 之前有同名文件（`/usr/bin/rg`、Homebrew
+//!   装的副本），更新之后敲 `rg` 跑的还是旧的。check 阶段对更新候选提示。
+//! - 孤儿：没有任何 `.crates.toml` / `.crates2.json` 条目认领的文件，以及
+//!   `install_binary` 中途崩溃留下的 `.cargo-fresh-*.tmp`。`doctor` 报告，
+//!   `prune --orphans` 清理。rustup 的代理（`cargo`、`rustc` 等）不算孤儿。
+
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use crate::models::{PackageInfo, ShadowedBin};
+use crate::package::{crates2, crates_toml};
+
+/// 比这更新的 `.cargo-fresh-*.tmp` 可能属于正在进行的安装，不算残留。
+pub const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// rustup 在 `$CARGO_HOME/bin` 放的代理（都是指向 rustup 的硬链接），
+/// 不在任何安装记录里。
+const RUSTUP_PROXIES: &[&str] = &[
+    "rustup",
+    "cargo",
+    "cargo-clippy",
+    "cargo-fmt",
+    "cargo-miri",
+    "clippy-driver",
+    "rls",
+    "rust-analyzer",
+    "rust-gdb",
+    "rust-gdbgui",
+    "rust-lldb",
+    "rustc",
+    "rustdoc",
+    "rustfmt",
+];
+
+/// 两个目录是否相同：都能 canonicalize 时比真实路径，否则按字面比。
+pub fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b
+        || match (a.canonicalize(), b.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+}
+
+/// `bin_dir/<bin>` 被 `path`（PATH 格式）里更靠前的同名文件遮住时，返回
+/// 那个文件。`bin_dir` 里没有这个 binary，或 `bin_dir` 根本不在 PATH 里
+/// （`doctor` 另有检查）时返回 None。
+pub fn find_shadowing(bin_dir: &Path, bin: &str, path: &OsStr) -> Option<PathBuf> {
+    if !bin_dir.join(bin).is_file() {
+        return None;
+    }
+    let mut first = None;
+    for dir in std::env::split_paths(path) {
+        if same_dir(&dir, bin_dir) {
+            return first;
+        }
+        if first.is_none() {
+            let candidate = dir.join(bin);
+            first = candidate.is_file().then_some(candidate);
+        }
+    }
+    None
+}
+
+/// 给更新候选填上 `shadowed`：逐个检查记录里的 binary 在当前 PATH 下
+/// 会不会被遮住。
+pub fn annotate_shadowed(packages: &mut [PackageInfo], cargo_home: &Path) {
+    let Some(path) = std::env::var_os("PATH") else {
+        return;
+    };
+    let bin_dir = cargo_home.join("bin");
+    for pkg in packages.iter_mut().filter(|p| p.has_update()) {
+        let bins = crate::package::uninstall::recorded_bins(cargo_home, &pkg.name)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        pkg.shadowed = bins
+            .into_iter()
+            .filter(|bin| !bin.contains(['/', '\\']))
+            .filter_map(|bin| {
+                let path = find_shadowing(&bin_dir, &bin, &path)?;
+                Some(ShadowedBin { bin, path })
+            })
+            .collect();
+    }
+}
+
+/// 孤儿文件的种类。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrphanKind {
+    /// 没有任何安装记录认领
+    Unowned,
+    /// 中断的安装留下的 `.cargo-fresh-*.tmp`
+    StaleTemp,
+}
+
+impl OrphanKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrphanKind::Unowned => "unowned",
+            OrphanKind::StaleTemp => "stale_temp",
+        }
+    }
+}
+
+/// `$CARGO_HOME/bin` 下的一个孤儿文件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Orphan {
+    pub path: PathBuf,
+    pub kind: OrphanKind,
+    pub size: u64,
+}
+
+impl Orphan {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// 纯函数：按文件名（和 `.tmp` 的年龄）判断是不是孤儿。`owned` 是两个
+/// 元数据文件里所有条目的 bins。
+pub fn classify(name: &str, owned: &HashSet<String>, age: Option<Duration>) -> Option<OrphanKind> {
+    if name.starts_with(".cargo-fresh-") && name.ends_with(".tmp") {
+        // 读不到修改时间的也当残留：正在写的文件总能读到
+        return age
+            .is_none_or(|age| age >= STALE_TEMP_AGE)
+            .then_some(OrphanKind::StaleTemp);
+    }
+    let stem = name.strip_suffix(".exe").unwrap_or(name);
+    if owned.contains(name) || RUSTUP_PROXIES.contains(&stem) {
+        return None;
+    }
+    Some(OrphanKind::Unowned)
+}
+
+/// 列出 `$CARGO_HOME/bin` 下的孤儿文件，按文件名排序。元数据文件解析
+/// 失败时报错——不能因为读不懂记录就把所有 binary 都当成孤儿。
+pub fn find_orphans(cargo_home: &Path, now: SystemTime) -> Result<Vec<Orphan>> {
+    let read = |name: &str| match std::fs::read_to_string(cargo_home.join(name)) {
+        Ok(body) => Ok(body),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(anyhow::Error::new(e).context(format!("failed to read {name}"))),
+    };
+    let mut owned: HashSet<String> = crates_toml::entries(&read(".crates.toml")?)
+        .into_iter()
+        .flat_map(|(_, bins)| bins)
+        .collect();
+    owned.extend(
+        crates2::entries(&read(".crates2.json")?)?
+            .into_iter()
+            .flat_map(|(_, bins)| bins),
+    );
+
+    let bin_dir = cargo_home.join("bin");
+    let read_dir = match std::fs::read_dir(&bin_dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(
+                anyhow::Error::new(e).context(format!("failed to read {}", bin_dir.display()))
+            )
+        }
+    };
+    let mut orphans = Vec::new();
+    for entry in read_dir {
+        let entry = entry.with_context(|| format!("failed to read {}", bin_dir.display()))?;
+        let Ok(meta) = entry.path().symlink_metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let age = meta
+            .modified()
+            .ok()
+            .map(|t| now.duration_since(t).unwrap_or_default());
+        if let Some(kind) = classify(&name, &owned, age) {
+            orphans.push(Orphan {
+                path: entry.path(),
+                kind,
+                size: meta.len(),
+            });
+        }
+    }
+    orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(orphans)
+}
+
+/// 删掉一个孤儿文件。持有 `CRATES_FILES_LOCK`，不和本进程的安装交错。
+pub fn remove_orphan(orphan: &Orphan) -> Result<()> {
+    let _guard = crate::downloader::install::CRATES_FILES_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    std::fs::remove_file(&orphan.path)
+        .with_context(|| format!("failed to remove {}", orphan.path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_skips_owned_bins_rustup_proxies_and_fresh_temp_files() {
+        let owned: HashSet<String> = ["rg".to_string()].into();
+        let hour = Some(STALE_TEMP_AGE);
+        assert_eq!(classify("rg", &owned, hour), None);
+        assert_eq!(classify("cargo", &owned, hour), None);
+        assert_eq!(classify("rustfmt.exe", &owned, hour), None);
+        assert_eq!(classify("fd", &owned, hour), Some(OrphanKind::Unowned));
+        let tmp = ".cargo-fresh-rg-1a2b.tmp";
+        assert_eq!(classify(tmp, &owned, hour), Some(OrphanKind::StaleTemp));
+        assert_eq!(classify(tmp, &owned, None), Some(OrphanKind::StaleTemp));
+        assert_eq!(classify(tmp, &owned, Some(Duration::from_secs(30))), None);
+    }
+
+    #[test]
+    fn shadowing_is_the_first_match_before_cargo_bin() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_bin = dir.path().join("cargo-bin");
+        let system = dir.path().join("usr-bin");
+        let empty = dir.path().join("empty");
+        for d in [&cargo_bin, &system, &empty] {
+            std::fs::create_dir_all(d).unwrap();
+        }
+        std::fs::write(cargo_bin.join("rg"), "new").unwrap();
+        std::fs::write(system.join("rg"), "old").unwrap();
+
+        let path = std::env::join_paths([&empty, &system, &cargo_bin]).unwrap();
+        assert_eq!(
+            find_shadowing(&cargo_bin, "rg", &path),
+            Some(system.join("rg"))
+        );
+        let path = std::env::join_paths([&empty, &cargo_bin, &system]).unwrap();
+        assert_eq!(find_shadowing(&cargo_bin, "rg", &path), None);
+        // cargo bin 不在 PATH 里：不算遮挡
+        let path = std::env::join_paths([&system]).unwrap();
+        assert_eq!(find_shadowing(&cargo_bin, "rg", &path), None);
+        // 没装这个 binary：不算遮挡
+        let path = std::env::join_paths([&system, &cargo_bin]).unwrap();
+        assert_eq!(find_shadowing(&cargo_bin, "fd", &path), None);
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 
-use crate::models::{InstallOpts, PackageSource};
+use crate::models::{InstallOpts, NewInstall, PackageSource};
 
 /// 解析 `.crates2.json` 文本，返回 `key -> InstallOpts`。
 ///
@@ -101,6 +101,20 @@ pub fn match_install_opts(
     Some(candidates[0].1.clone())
 }
 
+/// `name` 是否已有默认 features 的注册表 (`registry+`) 条目。
+///
+/// downloader 升级只改这种条目 key 里的版本段; 没有时 (全新安装、从 git /
+/// path 换回 crates.io、换掉自定义 features) 要整条重建, 否则旧来源和旧
+/// features 会留在元数据里。
+pub fn has_default_registry_record(map: &HashMap<String, InstallOpts>, name: &str) -> bool {
+    map.iter().any(|(k, opts)| {
+        k.split(' ').next() == Some(name)
+            && k.split_once('(')
+                .is_some_and(|(_, s)| s.starts_with("registry+"))
+            && opts.is_default()
+    })
+}
+
 /// 查 `.crates2.json` 拿到 `package_name` 对应的 bins 列表。
 ///
 /// 包名 != binary 名时 (ripgrep -> rg, tauri-cli -> cargo-tauri),
@@ -129,8 +143,8 @@ pub fn lookup_bins(cargo_home: &std::path::Path, package_name: &str) -> Vec<Stri
         .unwrap_or_default()
 }
 
-/// 把一次成功的 binary 安装写回 `.crates2.json`——更新该包的
-/// `version_req` 字段。如果包不在文件里就新增条目。
+/// 把一次成功的 binary 安装写回 `.crates2.json`——把该包条目 key 里的
+/// 版本段换成 `new_version`。包不在文件里时不改动。
 ///
 /// 文件不存在 / 解析失败的边角:返回 Err, caller (install.rs) 据此决定
 /// 是否要把 InstallFailed 上报到 UI——这次是真失败 (cargo install --list
@@ -180,13 +194,151 @@ pub fn write_install_record(
         );
         installs.insert(new_key, entry);
     }
-    // 新条目情况: MVP 不处理 (binstall 路径理论上不会安装全新包,
-    // cargo-fresh 只处理已安装的升级)
+    // 找不到条目时什么都不加: 升级路径不知道 source / features,
+    // 全新安装由调用方改走 [`insert_install_record`]
     let new_body = serde_json::to_string_pretty(&json).context("serialize .crates2.json")?;
     std::fs::write(&path, new_body).context("write .crates2.json")?;
     Ok(())
 }
 
+/// 全新安装 (`cargo fresh install`): 按本次安装新建 `.crates2.json` 条目,
+/// bins / features / source 都照实写, 之后 `cargo install --list`、
+/// `cargo uninstall` 和 cargo-fresh 自己的升级都能认出它。
+///
+/// 同名包已有的条目先删掉; 文件不存在就新建。文件存在但解析失败返回 Err
+/// ——不能为了写一条记录把别的包的元数据冲掉。
+pub fn insert_install_record(
+    cargo_home: &std::path::Path,
+    package_name: &str,
+    version: &str,
+    bins: &[String],
+    new: &NewInstall,
+) -> anyhow::Result<()> {
+    use anyhow::Context;
+    let path = cargo_home.join(".crates2.json");
+    let body = match std::fs::read_to_string(&path) {
+        Ok(body) => body,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(anyhow::Error::new(e).context("read .crates2.json")),
+    };
+    let new_body = insert_record(&body, package_name, version, bins, new)?;
+    std::fs::write(&path, new_body).context("write .crates2.json")?;
+    Ok(())
+}
+
+/// 纯函数: [`insert_install_record`] 的文本变换。`body` 为空视为新文件。
+pub fn insert_record(
+    body: &str,
+    package_name: &str,
+    version: &str,
+    bins: &[String],
+    new: &NewInstall,
+) -> anyhow::Result<String> {
+    use anyhow::Context;
+    let mut json: serde_json::Value = if body.trim().is_empty() {
+        serde_json::json!({ "installs": {} })
+    } else {
+        serde_json::from_str(body).context("parse .crates2.json")?
+    };
+    let installs = json
+        .as_object_mut()
+        .context(".crates2.json is not an object")?
+        .entry("installs")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .context(".crates2.json 'installs' is not an object")?;
+
+    installs.retain(|k, _| k.split(' ').next() != Some(package_name));
+    let mut features = new.opts.features.clone();
+    features.sort();
+    features.dedup();
+    let mut bins = bins.to_vec();
+    bins.sort();
+    bins.dedup();
+    installs.insert(
+        format!("{package_name} {version} ({})", new.source_id),
+        serde_json::json!({
+            "version_req": null,
+            "bins": bins,
+            "features": features,
+            "all_features": new.opts.all_features,
+            "no_default_features": new.opts.no_default_features,
+            "profile": "release",
+            "target": null,
+            "rustc": null,
+        }),
+    );
+    serde_json::to_string_pretty(&json).context("serialize .crates2.json")
+}
+
+/// 纯函数 (卸载): 去掉 `package_name` 的条目, 同时返回它们的 bins。
+/// 没有该包 (含空文件) 时返回 None; 解析失败返回 Err。
+pub fn remove_record(
+    body: &str,
+    package_name: &str,
+) -> anyhow::Result<Option<(String, Vec<String>)>> {
+    use anyhow::Context;
+    if body.trim().is_empty() {
+        return Ok(None);
+    }
+    let mut json: serde_json::Value = serde_json::from_str(body).context("parse .crates2.json")?;
+    let Some(installs) = json.get_mut("installs").and_then(|v| v.as_object_mut()) else {
+        return Ok(None);
+    };
+    let keys: Vec<String> = installs
+        .keys()
+        .filter(|k| k.split(' ').next() == Some(package_name))
+        .cloned()
+        .collect();
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let mut bins = Vec::new();
+    for key in keys {
+        if let Some(entry) = installs.remove(&key) {
+            let listed = entry.get("bins").and_then(|b| b.as_array()).cloned();
+            bins.extend(
+                listed
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|b| b.as_str().map(str::to_string)),
+            );
+        }
+    }
+    let body = serde_json::to_string_pretty(&json).context("serialize .crates2.json")?;
+    Ok(Some((body, bins)))
+}
+
+/// 纯函数 (`doctor`): 所有条目的 PackageId 原文和 bins, 按 key 排序。
+/// 空文件或没有 `installs` 时为空 (cargo 也这样对待空文件); 解析失败返回 Err。
+pub fn entries(body: &str) -> anyhow::Result<Vec<(String, Vec<String>)>> {
+    use anyhow::Context;
+    if body.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let json: serde_json::Value = serde_json::from_str(body).context("parse .crates2.json")?;
+    let Some(installs) = json.get("installs").and_then(|v| v.as_object()) else {
+        return Ok(Vec::new());
+    };
+    let mut out: Vec<(String, Vec<String>)> = installs
+        .iter()
+        .map(|(key, entry)| {
+            let bins = entry
+                .get("bins")
+                .and_then(|b| b.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|b| b.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            (key.clone(), bins)
+        })
+        .collect();
+    out.sort();
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,6 +410,23 @@ mod tests {
         assert!(parse_crates2(r#"{"v1":{}}"#).is_empty());
     }
 
+    #[test]
+    fn entries_list_keys_and_bins_and_accept_empty_file() {
+        let out = entries(
+            r#"{"installs":{"y 2.0.0 (registry+x)":{"bins":["y"]},"x 1.0.0 (registry+x)":{}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            [
+                ("x 1.0.0 (registry+x)".to_string(), vec![]),
+                ("y 2.0.0 (registry+x)".to_string(), vec!["y".to_string()]),
+            ]
+        );
+        assert!(entries("").unwrap().is_empty());
+        assert!(entries("{not valid json").is_err());
+    }
+
     #[test]
     fn missing_fields_default_to_false_empty() {
         let m = parse_crates2(
@@ -280,6 +449,31 @@ mod tests {
         assert!(match_install_opts(&m, "does-not-exist", &PackageSource::Crates).is_none());
     }
 
+    #[test]
+    fn remove_record_drops_entry_and_returns_bins() {
+        let (body, bins) = remove_record(SAMPLE, "ripgrep").unwrap().expect("removed");
+        assert_eq!(bins, ["rg"]);
+        let m = parse_crates2(&body);
+        assert!(match_install_opts(&m, "ripgrep", &PackageSource::Crates).is_none());
+        assert!(match_install_opts(&m, "cargo-binstall", &PackageSource::Crates).is_some());
+        assert!(remove_record(SAMPLE, "missing").unwrap().is_none());
+        assert!(remove_record("{not json", "ripgrep").is_err());
+    }
+
+    #[test]
+    fn default_registry_record_needs_registry_source_and_default_features() {
+        let mut m = parse_crates2(SAMPLE);
+        assert!(has_default_registry_record(&m, "cargo-binstall"));
+        assert!(!has_default_registry_record(&m, "ripgrep"));
+        assert!(!has_default_registry_record(&m, "fat"));
+        assert!(!has_default_registry_record(&m, "missing"));
+        m.insert(
+            "tool 0.1.0 (git+https://github.com/me/tool#3f2c1a9)".into(),
+            InstallOpts::default(),
+        );
+        assert!(!has_default_registry_record(&m, "tool"));
+    }
+
     #[test]
     fn match_prefers_source_consistent_entry() {
         let json = r#"{"installs":{
@@ -302,4 +496,33 @@ mod tests {
             vec!["git".to_string()]
         );
     }
+
+    #[test]
+    fn insert_record_writes_new_entry_that_parses_back() {
+        let new = NewInstall {
+            source_id: "registry+https://github.com/rust-lang/crates.io-index".into(),
+            opts: InstallOpts {
+                features: vec!["pcre2".into()],
+                ..Default::default()
+            },
+        };
+        let out = insert_record(SAMPLE, "ripgrep", "15.0.0", &["rg".to_string()], &new).unwrap();
+        let m = parse_crates2(&out);
+        // 旧版本条目被替换, 其它包原样保留
+        assert!(!m.keys().any(|k| k.starts_with("ripgrep 14.1.1")));
+        assert!(m.contains_key(
+            "cargo-binstall 1.19.1 (registry+https://github.com/rust-lang/crates.io-index)"
+        ));
+        let key = "ripgrep 15.0.0 (registry+https://github.com/rust-lang/crates.io-index)";
+        assert_eq!(m[key].features, vec!["pcre2".to_string()]);
+
+        let fresh = insert_record("", "tokei", "12.1.2", &["tokei".to_string()], &new).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fresh).unwrap();
+        let entry = &json["installs"]
+            ["tokei 12.1.2 (registry+https://github.com/rust-lang/crates.io-index)"];
+        assert_eq!(entry["bins"], serde_json::json!(["tokei"]));
+        assert_eq!(entry["profile"], "release");
+
+        assert!(insert_record("{not json", "x", "1.0.0", &[], &new).is_err());
+    }
 }
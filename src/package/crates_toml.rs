@@ -22,7 +22,7 @@ use anyhow::Context;
 /// 2. 找不到再扫 bins 列表 (支持 `package_name != binary_name`, 如 ripgrep -> rg)。
 ///
 /// 文件缺失 / 解析失败 → 返回 Err, caller 据此把 InstallFailed 上报。
-/// 找不到匹配条目 → 返回 Ok(()), 静默跳过 (新装的包应走 `cargo install` 路径)。
+/// 找不到匹配条目 → 返回 Ok(()), 静默跳过 (全新安装走 [`insert_install_record`])。
 pub fn write_install_record(
     cargo_home: &std::path::Path,
     binary_name: &str,
@@ -36,6 +36,151 @@ pub fn write_install_record(
     Ok(())
 }
 
+/// 全新安装: 写入 `"<package_name> <version> (<source_id>)" = [bins]` 条目。
+///
+/// 同名包已有的条目先删掉 (`install --force` 重装 / 换来源), 文件不存在就新建。
+pub fn insert_install_record(
+    cargo_home: &std::path::Path,
+    package_name: &str,
+    version: &str,
+    source_id: &str,
+    bins: &[String],
+) -> anyhow::Result<()> {
+    let path = cargo_home.join(".crates.toml");
+    let body = match std::fs::read_to_string(&path) {
+        Ok(body) => body,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(anyhow::Error::new(e).context("read .crates.toml")),
+    };
+    let new_body = insert_record(&body, package_name, version, source_id, bins);
+    std::fs::write(&path, new_body).context("write .crates.toml")?;
+    Ok(())
+}
+
+/// 纯函数: 去掉 `package_name` 的旧条目, 把新条目追加到 `[v1]` 段末尾
+/// (没有 `[v1]` 段就补一个)。
+pub fn insert_record(
+    body: &str,
+    package_name: &str,
+    version: &str,
+    source_id: &str,
+    bins: &[String],
+) -> String {
+    let quoted: Vec<String> = bins.iter().map(|b| format!("\"{b}\"")).collect();
+    let entry = format!(
+        "\"{package_name} {version} ({source_id})\" = [{}]",
+        quoted.join(", ")
+    );
+
+    let mut out: Vec<String> = Vec::new();
+    let mut in_v1 = false;
+    let mut inserted = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if in_v1 && !inserted {
+                push_after_last_entry(&mut out, &entry);
+                inserted = true;
+            }
+            in_v1 = trimmed == "[v1]";
+        } else if in_v1 && key_package_name(trimmed) == Some(package_name) {
+            continue;
+        }
+        out.push(line.to_string());
+    }
+    if !inserted {
+        if !in_v1 {
+            if out.iter().any(|l| !l.trim().is_empty()) {
+                out.push(String::new());
+            }
+            out.push("[v1]".to_string());
+        }
+        push_after_last_entry(&mut out, &entry);
+    }
+
+    let mut joined = out.join("\n");
+    joined.push('\n');
+    joined
+}
+
+/// 纯函数 (卸载): 去掉 `[v1]` 段里 `package_name` 的条目, 同时返回它们的 bins。
+/// 没有匹配条目时返回 None。
+pub fn remove_record(body: &str, package_name: &str) -> Option<(String, Vec<String>)> {
+    let mut out: Vec<&str> = Vec::new();
+    let mut bins = Vec::new();
+    let mut in_v1 = false;
+    let mut removed = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_v1 = trimmed == "[v1]";
+        } else if in_v1 && key_package_name(trimmed) == Some(package_name) {
+            bins.extend(entry_bins(trimmed));
+            removed = true;
+            continue;
+        }
+        out.push(line);
+    }
+    if !removed {
+        return None;
+    }
+    let mut joined = out.join("\n");
+    joined.push('\n');
+    Some((joined, bins))
+}
+
+/// 纯函数 (`doctor`): `[v1]` 段里每个条目的 PackageId 原文和 bins, 按 key 排序。
+pub fn entries(body: &str) -> Vec<(String, Vec<String>)> {
+    let mut out = Vec::new();
+    let mut in_v1 = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_v1 = trimmed == "[v1]";
+        } else if in_v1 {
+            if let Some(key) = trimmed
+                .strip_prefix('"')
+                .and_then(|rest| rest.find('"').map(|end| &rest[..end]))
+            {
+                out.push((key.to_string(), entry_bins(trimmed)));
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+/// `"..." = ["rg", "rg-extra"]` 行里的 bins 列表。
+fn entry_bins(trimmed: &str) -> Vec<String> {
+    let Some((_, list)) = trimmed.rsplit_once('=') else {
+        return Vec::new();
+    };
+    list.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|b| b.trim().trim_matches('"'))
+        .filter(|b| !b.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 插到最后一个非空行之后——段落间的空行留在新条目下面。
+fn push_after_last_entry(out: &mut Vec<String>, entry: &str) {
+    let at = out
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .map_or(0, |i| i + 1);
+    out.insert(at, entry.to_string());
+}
+
+/// `"<pkg_name> <version> (<source>)" = [...]` 行里的包名。
+fn key_package_name(trimmed: &str) -> Option<&str> {
+    let key = trimmed.strip_prefix('"')?;
+    let key = &key[..key.find('"')?];
+    key.split(' ').next()
+}
+
 /// 纯函数: 把 `.crates.toml` 文本里 `binary_name` 对应的版本替换成 `new_version`。
 ///
 /// 找不到匹配条目时原样返回 (不报错), caller 不应假设新条目场景。
@@ -133,6 +278,50 @@ mod tests {
         assert!(!out.contains("\"ripgrep 14.1.1"));
     }
 
+    #[test]
+    fn insert_adds_new_entry_and_replaces_same_package() {
+        let bins = vec!["tokei".to_string()];
+        let out = insert_record(SAMPLE, "tokei", "12.1.2", "registry+https://x/index", &bins);
+        assert!(out.contains("\"tokei 12.1.2 (registry+https://x/index)\" = [\"tokei\"]\n"));
+        assert!(out.contains("\"mdbook 0.4.40"));
+
+        // 重装同名包: 旧条目被替换而不是并存
+        let bins = vec!["rg".to_string()];
+        let out = insert_record(
+            SAMPLE,
+            "ripgrep",
+            "15.0.0",
+            "registry+https://x/index",
+            &bins,
+        );
+        assert!(out.contains("\"ripgrep 15.0.0 (registry+https://x/index)\" = [\"rg\"]"));
+        assert!(!out.contains("ripgrep 14.1.1"));
+        assert_eq!(out.lines().count(), SAMPLE.lines().count());
+    }
+
+    #[test]
+    fn insert_into_empty_file_creates_v1_section() {
+        let bins = vec!["a".to_string(), "b".to_string()];
+        let out = insert_record("", "ab", "1.0.0", "registry+https://x/index", &bins);
+        assert_eq!(
+            out,
+            "[v1]\n\"ab 1.0.0 (registry+https://x/index)\" = [\"a\", \"b\"]\n"
+        );
+    }
+
+    #[test]
+    fn remove_drops_entry_and_returns_its_bins() {
+        let body = r#"[v1]
+"multi 1.0.0 (registry+x)" = ["bin-a", "bin-b"]
+"foo 1.0.0 (registry+x)" = ["foo"]
+"#;
+        let (out, bins) = remove_record(body, "multi").expect("removed");
+        assert_eq!(bins, ["bin-a", "bin-b"]);
+        assert_eq!(out, "[v1]\n\"foo 1.0.0 (registry+x)\" = [\"foo\"]\n");
+        // 只按包名匹配, 不按 binary 名
+        assert!(remove_record(SAMPLE, "rg").is_none());
+    }
+
     #[test]
     fn unmatched_binary_leaves_body_unchanged() {
         let out = update_record(SAMPLE, "nonexistent", "1.0.0").unwrap();
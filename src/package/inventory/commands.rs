@@ -0,0 +1,75 @@
+//! `cargo fresh list` 的执行流程：盘点已安装的包，按表格 / CSV / JSON 输出。
+
+use anyhow::Result;
+
+use crate::cli::Cli;
+use crate::display::{print_inventory, status_dim, status_warn};
+use crate::locale::Language;
+use crate::models::EXIT_OK;
+use crate::package::registry::require_cargo_home;
+use crate::package::{exclude_packages, filter_packages, get_installed_packages};
+
+use super::{collect, to_csv, unix_secs, SortKey};
+
+/// `cargo fresh list`：不联网，只读 `cargo install --list`、两个元数据文件和
+/// 安装账本。表格 / CSV 写 stdout；JSON 模式输出 `{"list": {"packages": [...]}}`。
+pub async fn run_list(
+    cli: &Cli,
+    sort: SortKey,
+    reverse: bool,
+    csv: bool,
+    json_mode: bool,
+    language: Language,
+) -> Result<i32> {
+    if csv && json_mode {
+        anyhow::bail!("--csv cannot be combined with --format json");
+    }
+    let home = require_cargo_home()?;
+    let mut packages = get_installed_packages().await?;
+    if let Some(filter_pattern) = &cli.filter {
+        filter_packages(&mut packages, filter_pattern)?;
+    }
+    if !cli.exclude.is_empty() {
+        exclude_packages(&mut packages, &cli.exclude)?;
+    }
+    let state = crate::state::load()?;
+    let mut entries = collect(&home, packages, &state);
+    super::sort(&mut entries, sort, reverse);
+
+    if json_mode {
+        let packages: Vec<serde_json::Value> = entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "name": entry.name,
+                    "version": entry.version,
+                    "source": entry.source.kind_str(),
+                    "bins": entry.bins,
+                    "features": entry.opts.features,
+                    "all_features": entry.opts.all_features,
+                    "no_default_features": entry.opts.no_default_features,
+                    "install_method": entry.method.map(|m| m.as_str()),
+                    "size": entry.size,
+                    "modified": entry.modified.map(unix_secs),
+                })
+            })
+            .collect();
+        anstream::println!(
+            "{}",
+            serde_json::json!({ "schema_version": 2, "list": { "packages": packages } })
+        );
+    } else if csv {
+        anstream::print!("{}", to_csv(&entries));
+    } else if entries.is_empty() {
+        status_warn("Note", language.get_text("no_packages_found"));
+    } else {
+        print_inventory(&entries, language);
+        let count = entries.len().to_string();
+        let size = indicatif::HumanBytes(entries.iter().map(|e| e.size).sum()).to_string();
+        status_dim(
+            "Total",
+            &language.format_text("list_summary", &[("count", &count), ("size", &size)]),
+        );
+    }
+    Ok(EXIT_OK)
+}
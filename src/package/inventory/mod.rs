@@ -0,0 +1,235 @@
+//! `cargo fresh list`：不查版本，只盘点已安装的包——版本、来源、binary、
+//! 安装时的 features、安装方式（安装账本里有记录时）、binary 的磁盘占用和
+//! 修改时间。
+//!
+//! binary 列表取自 `.crates.toml` / `.crates2.json`，和卸载用的是同一份记录；
+//! 记录里有、磁盘上没有的 binary 照样列出，只是不计入大小。
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::{InstallOpts, PackageInfo, PackageSource};
+use crate::state::{InstalledVia, State};
+
+pub mod commands;
+
+/// `list` 的一行。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub source: PackageSource,
+    pub bins: Vec<String>,
+    pub opts: InstallOpts,
+    /// 安装账本里这个版本的安装方式；没记过（或之后手动重装过）为 None
+    pub method: Option<InstalledVia>,
+    /// 磁盘上 binary 的总字节数
+    pub size: u64,
+    /// binary 里最新的修改时间；一个都不在时为 None
+    pub modified: Option<SystemTime>,
+}
+
+impl InventoryEntry {
+    /// features 的简写：默认 features 为空串，否则形如 `no-default,pcre2`、`all`。
+    pub fn features_label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.opts.no_default_features {
+            parts.push("no-default".to_string());
+        }
+        if self.opts.all_features {
+            parts.push("all".to_string());
+        }
+        parts.extend(self.opts.features.iter().cloned());
+        parts.join(",")
+    }
+}
+
+/// 排序键。键相同的项按名字排，保证输出稳定。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Package name (default)
+    #[default]
+    Name,
+    /// Total binary size on disk
+    Size,
+    /// Last modification of the binaries
+    Modified,
+}
+
+/// 给已安装的包补上 binary、大小、修改时间和账本里的安装方式。
+pub fn collect(
+    cargo_home: &Path,
+    packages: Vec<PackageInfo>,
+    state: &State,
+) -> Vec<InventoryEntry> {
+    let bin_dir = cargo_home.join("bin");
+    packages
+        .into_iter()
+        .map(|pkg| {
+            let bins = crate::package::uninstall::recorded_bins(cargo_home, &pkg.name)
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            let metas: Vec<std::fs::Metadata> = bins
+                .iter()
+                .filter(|bin| !bin.contains(['/', '\\']) && *bin != "..")
+                .filter_map(|bin| std::fs::metadata(bin_dir.join(bin)).ok())
+                .collect();
+            let method = pkg
+                .current_version
+                .as_deref()
+                .and_then(|version| state.installed_via(&pkg.name, version));
+            InventoryEntry {
+                method,
+                size: metas.iter().map(std::fs::Metadata::len).sum(),
+                modified: metas.iter().filter_map(|m| m.modified().ok()).max(),
+                opts: pkg.install_opts.unwrap_or_default(),
+                bins,
+                name: pkg.name,
+                version: pkg.current_version,
+                source: pkg.source,
+            }
+        })
+        .collect()
+}
+
+pub fn sort(entries: &mut [InventoryEntry], key: SortKey, reverse: bool) {
+    entries.sort_by(|a, b| {
+        let order = match key {
+            SortKey::Name => std::cmp::Ordering::Equal,
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Modified => a.modified.cmp(&b.modified),
+        };
+        order.then_with(|| a.name.cmp(&b.name))
+    });
+    if reverse {
+        entries.reverse();
+    }
+}
+
+/// Unix 秒。
+pub fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// `YYYY-MM-DD`（UTC）。没有引入日期库，按 proleptic Gregorian 历法换算。
+pub fn format_date(time: SystemTime) -> String {
+    let days = (unix_secs(time) / 86_400) as i64;
+    // Howard Hinnant 的 civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// CSV（RFC 4180）：首行表头，含逗号 / 引号 / 换行的字段加引号。binary 和
+/// features 各占一列，内部用空格分隔。
+pub fn to_csv(entries: &[InventoryEntry]) -> String {
+    let mut out = String::from("name,version,source,bins,features,method,size_bytes,modified\n");
+    for entry in entries {
+        let fields = [
+            entry.name.clone(),
+            entry.version.clone().unwrap_or_default(),
+            entry.source.kind_str().to_string(),
+            entry.bins.join(" "),
+            entry.features_label().replace(',', " "),
+            entry
+                .method
+                .map(InstalledVia::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            entry.size.to_string(),
+            entry.modified.map(format_date).unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn entry(name: &str, size: u64, modified_days: Option<u64>) -> InventoryEntry {
+        InventoryEntry {
+            name: name.into(),
+            version: Some("1.0.0".into()),
+            source: PackageSource::Crates,
+            bins: vec![name.into()],
+            opts: InstallOpts::default(),
+            method: None,
+            size,
+            modified: modified_days.map(|d| UNIX_EPOCH + Duration::from_secs(d * 86_400)),
+        }
+    }
+
+    #[test]
+    fn sort_by_size_and_modified_breaks_ties_by_name() {
+        let mut entries = vec![
+            entry("tokei", 10, Some(3)),
+            entry("just", 30, None),
+            entry("bat", 10, Some(5)),
+        ];
+        sort(&mut entries, SortKey::Size, true);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["just", "tokei", "bat"]);
+        sort(&mut entries, SortKey::Modified, false);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["just", "tokei", "bat"]);
+        sort(&mut entries, SortKey::Name, false);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["bat", "just", "tokei"]);
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_formats_dates() {
+        let mut ripgrep = entry("ripgrep", 5_000_000, Some(20_000));
+        ripgrep.bins = vec!["rg".into()];
+        ripgrep.opts.no_default_features = true;
+        ripgrep.opts.features = vec!["pcre2".into()];
+        ripgrep.method = Some(InstalledVia::Prebuilt);
+        let mut odd = entry("odd", 0, None);
+        odd.source = PackageSource::Path {
+            dir: "/tmp/a,b".into(),
+        };
+        odd.version = Some("0.1.0+\"x\"".into());
+
+        let csv = to_csv(&[ripgrep, odd]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "name,version,source,bins,features,method,size_bytes,modified"
+        );
+        assert_eq!(
+            lines[1],
+            "ripgrep,1.0.0,crates,rg,no-default pcre2,prebuilt,5000000,2024-10-04"
+        );
+        assert_eq!(lines[2], r#"odd,"0.1.0+""x""",path,odd,,,0,"#);
+    }
+
+    #[test]
+    fn dates_convert_across_leap_years() {
+        assert_eq!(format_date(UNIX_EPOCH), "1970-01-01");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(11_016 * 86_400);
+        assert_eq!(format_date(leap_day), "2000-02-29");
+        let later = UNIX_EPOCH + Duration::from_secs(1_767_225_600);
+        assert_eq!(format_date(later), "2026-01-01");
+    }
+}
@@ -14,11 +14,14 @@ use crate::errors::CargoFreshError;
 use crate::locale::detection::detect_language;
 use crate::models::{PackageInfo, PackageSource};
 
+pub mod bins;
 pub mod crates2;
 pub mod crates_api;
 pub mod crates_toml;
+pub mod inventory;
 pub mod registry;
 pub mod sparse_index;
+pub mod uninstall;
 
 /// 单进程共享的 HTTP 客户端，启用 connection pool。
 ///
@@ -51,7 +54,7 @@ const MAX_CONCURRENT_INDEX_REQUESTS: usize = 16;
 /// 单条模式被规范化：不含 `*`/`?`/`[` 等 glob 通配符时，自动包裹为 `*pattern*`
 /// 做子串匹配——保留旧版"模糊匹配"的友好行为，同时让真正的 glob 模式
 /// （如 `cargo-*`、`*update`）按预期工作。
-fn build_globset(patterns: &[&str]) -> Result<GlobSet> {
+pub(crate) fn build_globset(patterns: &[&str]) -> Result<GlobSet> {
     let mut builder = GlobSetBuilder::new();
     for raw in patterns {
         let p = raw.trim();
@@ -420,12 +423,12 @@ pub async fn fetch_latest_versions(
             let versions = if is_stable_version(&v) {
                 sparse_index::LatestVersions {
                     stable: Some(v),
-                    prerelease: None,
+                    ..Default::default()
                 }
             } else {
                 sparse_index::LatestVersions {
-                    stable: None,
                     prerelease: Some(v),
+                    ..Default::default()
                 }
             };
             VersionLookup {
@@ -488,6 +491,72 @@ pub fn choose_latest(
     stable.map(|s| s.to_string())
 }
 
+/// `latest` 只能源码构建时可以退而求其次的版本, 新到旧: 比 `current` 新、
+/// 比 `latest` 旧; 预发布只在 `include_prerelease` 时算。`versions` 是 sparse
+/// index 的全部未 yank 版本, 解析不了的版本号直接忽略。
+pub fn older_update_candidates(
+    versions: &[String],
+    latest: &str,
+    current: Option<&str>,
+    include_prerelease: bool,
+) -> Vec<String> {
+    let Ok(latest) = Version::parse(latest) else {
+        return Vec::new();
+    };
+    let current = current.and_then(|c| Version::parse(c).ok());
+    let mut picked: Vec<Version> = versions
+        .iter()
+        .filter_map(|v| Version::parse(v).ok())
+        .filter(|v| *v < latest && current.as_ref().is_none_or(|c| v > c))
+        .filter(|v| include_prerelease || v.pre.is_empty())
+        .collect();
+    picked.sort_by(|a, b| b.cmp(a));
+    picked.iter().map(ToString::to_string).collect()
+}
+
+/// `cargo fresh install name@spec` 的版本要求。和 `cargo install` 一致:
+/// 裸版本号 (`14.1.1`) 是精确版本 `=14.1.1`, 其余按 semver 要求解析 (`0.21`、`~1.4`)。
+pub fn parse_install_requirement(spec: &str) -> Result<semver::VersionReq> {
+    use anyhow::Context;
+    let spec = spec.trim();
+    let req = if Version::parse(spec).is_ok() {
+        format!("={spec}")
+    } else {
+        spec.to_string()
+    };
+    semver::VersionReq::parse(&req).with_context(|| format!("invalid version `{spec}`"))
+}
+
+/// 从 sparse index 结果里挑 `install` 要装的版本。没有要求时同
+/// [`choose_latest`] (预发布只在 `include_prerelease` 时考虑); 有要求时取满足
+/// 它的最新版本——按 semver 规则, 只有要求本身写了预发布才会匹配预发布。
+pub fn pick_install_version(
+    latest: &sparse_index::LatestVersions,
+    req: Option<&semver::VersionReq>,
+    include_prerelease: bool,
+) -> Option<String> {
+    let Some(req) = req else {
+        return choose_latest(
+            latest.stable.as_deref(),
+            latest.prerelease.as_deref(),
+            None,
+            include_prerelease,
+        );
+    };
+    // cargo search 兜底只带回 stable / prerelease 两个版本
+    let known: Vec<&String> = if latest.versions.is_empty() {
+        latest.stable.iter().chain(&latest.prerelease).collect()
+    } else {
+        latest.versions.iter().collect()
+    };
+    known
+        .into_iter()
+        .filter_map(|v| Version::parse(v).ok())
+        .filter(|v| req.matches(v))
+        .max()
+        .map(|v| v.to_string())
+}
+
 /// 并发查询所有 crates.io 源包的最新版本（稳定 + 预发布一次拿齐）。
 ///
 /// 行为：
@@ -503,9 +572,9 @@ pub async fn check_package_updates(
     no_fallback: bool,
 ) -> Result<()> {
     let language = detect_language();
+    let config = crate::config::get();
     let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_INDEX_REQUESTS));
     let mut handles = Vec::new();
-    let registry = Arc::new(registry_override);
 
     for (index, package) in packages.iter().enumerate() {
         if !package.source.is_crates() {
@@ -513,7 +582,11 @@ pub async fn check_package_updates(
         }
         let package_name = package.name.clone();
         let sem = semaphore.clone();
-        let registry = registry.clone();
+        // `[packages."<glob>"] registry` 优先于全局 --registry-url
+        let registry = config
+            .package_registry(&package_name)
+            .map(str::to_string)
+            .or_else(|| registry_override.clone());
         let handle = tokio::spawn(async move {
             // 持有 permit 直到任务结束，自动释放
             let _permit = sem.acquire_owned().await.ok();
@@ -553,7 +626,7 @@ pub async fn check_package_updates(
             lookup.versions.stable.as_deref(),
             lookup.versions.prerelease.as_deref(),
             current.as_deref(),
-            include_prerelease,
+            include_prerelease || config.allows_prerelease(&package_name),
         );
 
         if verbose {
@@ -642,6 +715,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn older_update_candidates_sit_between_current_and_latest() {
+        let versions: Vec<String> = ["14.1.0", "15.0.0", "15.1.0", "15.2.0-rc.1", "15.2.0"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            older_update_candidates(&versions, "15.2.0", Some("14.1.0"), false),
+            vec!["15.1.0", "15.0.0"]
+        );
+        assert_eq!(
+            older_update_candidates(&versions, "15.2.0", Some("15.0.0"), true),
+            vec!["15.2.0-rc.1", "15.1.0"]
+        );
+        assert!(older_update_candidates(&versions, "15.0.0", Some("14.1.0"), false).is_empty());
+        assert!(older_update_candidates(&versions, "not-semver", None, false).is_empty());
+    }
+
+    #[test]
+    fn install_version_honours_requirement_and_prerelease_policy() {
+        let latest = sparse_index::LatestVersions {
+            stable: Some("0.22.1".into()),
+            prerelease: Some("0.23.0-rc.1".into()),
+            versions: ["0.21.0", "0.21.3", "0.22.0", "0.22.1", "0.23.0-rc.1"]
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+        };
+        let pick = |req: Option<&str>, include_prerelease: bool| {
+            let req = req.map(|r| parse_install_requirement(r).unwrap());
+            pick_install_version(&latest, req.as_ref(), include_prerelease)
+        };
+        assert_eq!(pick(None, false).as_deref(), Some("0.22.1"));
+        assert_eq!(pick(Some("0.21"), true).as_deref(), Some("0.21.3"));
+        // 裸版本号是精确版本, 不是 ^0.21.0
+        assert_eq!(pick(Some("0.21.0"), false).as_deref(), Some("0.21.0"));
+        assert_eq!(
+            pick(Some("=0.23.0-rc.1"), false).as_deref(),
+            Some("0.23.0-rc.1")
+        );
+        assert_eq!(pick(Some("2"), false), None);
+        assert!(parse_install_requirement("latest").is_err());
+    }
+
     #[test]
     fn choose_latest_empty_returns_none() {
         assert_eq!(choose_latest(None, None, Some("1.0.0"), true), None);
@@ -900,6 +1017,10 @@ mod tests {
             install_opts: None,
             check_error: None,
             prebuilt: None,
+            source_only_latest: None,
+            hold: None,
+            held: false,
+            shadowed: Vec::new(),
         }
     }
 
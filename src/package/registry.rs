@@ -26,6 +26,10 @@ use std::sync::OnceLock;
 
 pub const DEFAULT_SPARSE_INDEX: &str = "https://index.crates.io";
 
+/// crates.io 在 cargo PackageId 里的 source 段。即使配了 sparse 镜像 (source
+/// replacement), cargo 记到 `.crates.toml` / `.crates2.json` 的仍是这个原始 id。
+pub const CRATES_IO_SOURCE_ID: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
 /// 取 sparse index base URL。命中顺序：
 /// 1. CLI 显式 `--registry-url`
 /// 2. `$CARGO_HOME/config.toml` 的 source replacement
@@ -46,7 +50,7 @@ fn normalize(url: &str) -> String {
     url.trim().trim_end_matches('/').to_string()
 }
 
-pub(crate) fn cargo_home() -> Option<PathBuf> {
+pub fn cargo_home() -> Option<PathBuf> {
     if let Ok(s) = env::var("CARGO_HOME") {
         if !s.is_empty() {
             return Some(PathBuf::from(s));
@@ -55,6 +59,13 @@ pub(crate) fn cargo_home() -> Option<PathBuf> {
     env::var("HOME").ok().map(|h| PathBuf::from(h).join(".cargo"))
 }
 
+/// 同 [`cargo_home`]，定位不到时报错（要改 `$CARGO_HOME` 的子命令用）。
+pub fn require_cargo_home() -> anyhow::Result<PathBuf> {
+    cargo_home().ok_or_else(|| {
+        anyhow::anyhow!("cannot locate CARGO_HOME (neither CARGO_HOME nor HOME is set)")
+    })
+}
+
 fn resolve_from_config() -> Option<String> {
     let path = cargo_home()?.join("config.toml");
     let body = std::fs::read_to_string(&path).ok()?;
@@ -27,6 +27,8 @@ struct IndexEntry {
 pub struct LatestVersions {
     pub stable: Option<String>,
     pub prerelease: Option<String>,
+    /// 全部未 yank 的版本, semver 升序 (`--prefer-prebuilt` 往回找时用)。
+    pub versions: Vec<String>,
 }
 
 /// `fetch_latest` 的失败分类——决定 JSON `version_check_errors[].kind`。
@@ -66,6 +68,7 @@ pub fn index_path(name: &str) -> String {
 pub fn parse_index_body(body: &str) -> LatestVersions {
     let mut max_stable: Option<Version> = None;
     let mut max_prerelease: Option<Version> = None;
+    let mut all: Vec<Version> = Vec::new();
 
     for line in body.lines() {
         let line = line.trim();
@@ -81,6 +84,7 @@ pub fn parse_index_body(body: &str) -> LatestVersions {
         let Ok(version) = Version::parse(&entry.vers) else {
             continue;
         };
+        all.push(version.clone());
         if version.pre.is_empty() {
             if max_stable.as_ref().is_none_or(|cur| version > *cur) {
                 max_stable = Some(version);
@@ -90,9 +94,12 @@ pub fn parse_index_body(body: &str) -> LatestVersions {
         }
     }
 
+    all.sort();
+    all.dedup();
     LatestVersions {
         stable: max_stable.map(|v| v.to_string()),
         prerelease: max_prerelease.map(|v| v.to_string()),
+        versions: all.iter().map(ToString::to_string).collect(),
     }
 }
 
@@ -149,10 +156,92 @@ pub async fn fetch_latest(
     })))
 }
 
+/// registry `config.json` 的 `dl` 字段——`.crate` 文件的下载地址模板。
+#[derive(Debug, Deserialize)]
+struct RegistryConfig {
+    dl: String,
+}
+
+/// 拉取 `{base_url}/config.json` 取 `dl` 模板。失败返回 None——只有需要
+/// 下载 `.crate` 的功能 (读 `package.metadata.binstall`) 依赖它, 取不到就跳过。
+pub async fn fetch_dl_template(client: &reqwest::Client, base_url: &str) -> Option<String> {
+    let url = format!("{}/config.json", base_url.trim_end_matches('/'));
+    let resp = client.get(&url).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let cfg: RegistryConfig = resp.json().await.ok()?;
+    Some(cfg.dl)
+}
+
+/// 按 registry 协议展开 `dl` 模板。
+///
+/// 模板里没有任何标记时按约定追加 `/{crate}/{version}/download`;
+/// 支持 `{crate}` `{version}` `{prefix}` `{lowerprefix}`。
+/// `{sha256-checksum}` 需要索引里的校验和, 这里拿不到, 返回 None。
+pub fn crate_download_url(dl: &str, name: &str, version: &str) -> Option<String> {
+    const MARKERS: &[&str] = &[
+        "{crate}",
+        "{version}",
+        "{prefix}",
+        "{lowerprefix}",
+        "{sha256-checksum}",
+    ];
+    let dl = dl.trim_end_matches('/');
+    if !MARKERS.iter().any(|m| dl.contains(m)) {
+        return Some(format!("{dl}/{name}/{version}/download"));
+    }
+    if dl.contains("{sha256-checksum}") {
+        return None;
+    }
+    // prefix 与索引路径的目录部分相同, 但保留原始大小写
+    let prefix = match name.chars().count() {
+        1 => "1".to_string(),
+        2 => "2".to_string(),
+        3 => format!("3/{}", &name[..1]),
+        _ => format!("{}/{}", &name[..2], &name[2..4]),
+    };
+    Some(
+        dl.replace("{crate}", name)
+            .replace("{version}", version)
+            .replace("{lowerprefix}", &prefix.to_lowercase())
+            .replace("{prefix}", &prefix),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn crate_download_url_appends_default_suffix() {
+        assert_eq!(
+            crate_download_url("https://static.crates.io/crates/", "ripgrep", "14.1.1").as_deref(),
+            Some("https://static.crates.io/crates/ripgrep/14.1.1/download")
+        );
+    }
+
+    #[test]
+    fn crate_download_url_expands_markers() {
+        assert_eq!(
+            crate_download_url(
+                "https://mirror.example/{lowerprefix}/{crate}/{crate}-{version}.crate",
+                "Serde",
+                "1.0.0"
+            )
+            .as_deref(),
+            Some("https://mirror.example/se/rd/Serde/Serde-1.0.0.crate")
+        );
+        assert_eq!(
+            crate_download_url("https://m.example/{prefix}/{crate}", "abc", "1.0.0").as_deref(),
+            Some("https://m.example/3/a/abc")
+        );
+        assert_eq!(
+            crate_download_url("https://m.example/{sha256-checksum}", "abc", "1.0.0"),
+            None
+        );
+    }
+
     #[test]
     fn index_path_one_char() {
         assert_eq!(index_path("a"), "1/a");
@@ -204,6 +293,7 @@ mod tests {
         assert_eq!(v.stable.as_deref(), Some("1.0.0"));
         // semver 排序：rc.1 > beta.5
         assert_eq!(v.prerelease.as_deref(), Some("2.0.0-rc.1"));
+        assert_eq!(v.versions, vec!["1.0.0", "2.0.0-beta.5", "2.0.0-rc.1"]);
     }
 
     #[test]
@@ -215,6 +305,7 @@ mod tests {
         let v = parse_index_body(body);
         // 2.0.0 被 yank，不应该被选中
         assert_eq!(v.stable.as_deref(), Some("1.0.0"));
+        assert_eq!(v.versions, vec!["1.0.0"]);
     }
 
     #[test]
@@ -0,0 +1,327 @@
+//! `uninstall` / `prune` 子命令的执行流程：选包、逐个卸载并打印状态行，
+//! 输出 JSON 报告。
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use colored::*;
+
+use crate::cli::Cli;
+use crate::display::{format_age, status, status_dim, status_err, status_warn};
+use crate::locale::Language;
+use crate::models::{EXIT_FAILED, EXIT_OK, EXIT_UPDATES_AVAILABLE};
+use crate::package::get_installed_packages;
+use crate::package::registry::require_cargo_home;
+
+use super::{last_used, preview, prune_candidates, recorded_bins, uninstall, PruneCandidate};
+
+/// 依次卸载 `names`（`dry_run` 时只预览），逐包打印状态行。一个包失败不影响
+/// 其余的包。返回每个包的 JSON 记录，以及是否有包失败。
+fn uninstall_packages(
+    names: &[String],
+    versions: &HashMap<&str, &str>,
+    dry_run: bool,
+    language: Language,
+) -> Result<(Vec<serde_json::Value>, bool)> {
+    let home = require_cargo_home()?;
+    let mut reports = Vec::new();
+    let mut failed = false;
+    for name in names {
+        let version = versions.get(name.as_str()).copied();
+        let shown = format!("{} {}", name.cyan(), version.unwrap_or_default())
+            .trim_end()
+            .to_string();
+        let result = if dry_run {
+            preview(&home, name)
+        } else {
+            uninstall(&home, name)
+        };
+        let report = match result {
+            Ok(done) => {
+                let removed: Vec<String> = done
+                    .removed
+                    .iter()
+                    .filter_map(|p| p.file_name())
+                    .map(|f| f.to_string_lossy().into_owned())
+                    .collect();
+                if dry_run {
+                    status("Would remove", &format!("{shown} ({})", removed.join(", ")));
+                } else {
+                    status("Uninstalled", &shown);
+                    if let Err(e) = crate::state::forget_install(name) {
+                        crate::display::status_debug(
+                            "state",
+                            &format!("failed to update the install ledger: {e:#}"),
+                        );
+                    }
+                }
+                if !done.missing.is_empty() {
+                    status_dim(
+                        "Note",
+                        &language.format_text(
+                            "uninstall_missing",
+                            &[("bins", &done.missing.join(", "))],
+                        ),
+                    );
+                }
+                serde_json::json!({
+                    "name": name,
+                    "version": version,
+                    "removed": removed,
+                    "missing": done.missing,
+                    "success": true,
+                })
+            }
+            Err(e) => {
+                failed = true;
+                status_err("Failed", &format!("{}: {e}", name.red()));
+                serde_json::json!({
+                    "name": name,
+                    "version": version,
+                    "success": false,
+                    "error": e.to_string(),
+                })
+            }
+        };
+        reports.push(report);
+    }
+    Ok((reports, failed))
+}
+
+/// `cargo fresh uninstall <pkg...>`：直接删 binary 和安装记录，不调用
+/// `cargo uninstall`。有包失败时退出码 2。
+pub async fn run_uninstall(
+    cli: &Cli,
+    packages: &[String],
+    json_mode: bool,
+    language: Language,
+) -> Result<i32> {
+    let installed = get_installed_packages().await?;
+    let versions = installed
+        .iter()
+        .filter_map(|p| Some((p.name.as_str(), p.current_version.as_deref()?)))
+        .collect();
+    let (reports, failed) = uninstall_packages(packages, &versions, cli.dry_run, language)?;
+    if cli.dry_run {
+        status("Dry run", language.get_text("dry_run_summary"));
+    }
+    if json_mode {
+        anstream::println!(
+            "{}",
+            serde_json::json!({
+                "schema_version": 2,
+                "uninstall": { "dry_run": cli.dry_run, "packages": reports },
+            })
+        );
+    }
+    Ok(if failed { EXIT_FAILED } else { EXIT_OK })
+}
+
+/// `cargo fresh prune`：按 binary 的 atime 和 / 或名字 glob 找出候选，选中的
+/// 卸载掉。交互时默认一个不选；`--batch` 全删；`--dry-run`、`--no-interactive`
+/// 和不带 `--batch` 的 JSON 模式只列出候选（有候选时退出码 1，同检查更新）。
+pub async fn run_prune(
+    cli: &Cli,
+    unused_for: Option<std::time::Duration>,
+    patterns: &[String],
+    json_mode: bool,
+    language: Language,
+) -> Result<i32> {
+    let home = require_cargo_home()?;
+    let installed = get_installed_packages().await?;
+    let now = std::time::SystemTime::now();
+    let packages = installed
+        .iter()
+        .map(|p| {
+            let bins = recorded_bins(&home, &p.name)
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            PruneCandidate {
+                name: p.name.clone(),
+                version: p.current_version.clone(),
+                last_used: last_used(&home, &bins),
+            }
+        })
+        .collect();
+    let candidates = prune_candidates(packages, now, unused_for, patterns)?;
+
+    let labels: Vec<String> = candidates
+        .iter()
+        .map(|c| {
+            let name = format!(
+                "{} {}",
+                c.name.cyan(),
+                c.version.as_deref().unwrap_or_default()
+            );
+            match c.last_used {
+                Some(t) => {
+                    let age = format_age(now.duration_since(t).unwrap_or_default().as_secs());
+                    language.format_text(
+                        "prune_candidate",
+                        &[("name", name.trim_end()), ("age", &age)],
+                    )
+                }
+                None => {
+                    language.format_text("prune_candidate_unknown", &[("name", name.trim_end())])
+                }
+            }
+        })
+        .collect();
+    if candidates.is_empty() {
+        status("Finished", language.get_text("prune_none"));
+    }
+    let selections: Vec<usize> = if candidates.is_empty() {
+        Vec::new()
+    } else if cli.dry_run {
+        for label in &labels {
+            status("Would prune", label);
+        }
+        status("Dry run", language.get_text("dry_run_summary"));
+        Vec::new()
+    } else if cli.batch {
+        (0..candidates.len()).collect()
+    } else if json_mode || cli.no_interactive {
+        for label in &labels {
+            status("Unused", label);
+        }
+        Vec::new()
+    } else {
+        crate::display::print_prune_selection(&labels, language)?
+    };
+
+    let names: Vec<String> = selections
+        .iter()
+        .map(|&i| candidates[i].name.clone())
+        .collect();
+    let versions = candidates
+        .iter()
+        .filter_map(|c| Some((c.name.as_str(), c.version.as_deref()?)))
+        .collect();
+    let (reports, failed) = uninstall_packages(&names, &versions, false, language)?;
+
+    if json_mode {
+        let listed: Vec<serde_json::Value> = candidates
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "name": c.name,
+                    "version": c.version,
+                    "last_used": c.last_used.map(|t| {
+                        t.duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs())
+                    }),
+                    "idle_days": c.idle_days(now),
+                })
+            })
+            .collect();
+        anstream::println!(
+            "{}",
+            serde_json::json!({
+                "schema_version": 2,
+                "prune": {
+                    "dry_run": cli.dry_run,
+                    "candidates": listed,
+                    "uninstalled": reports,
+                },
+            })
+        );
+    }
+    let code = if failed {
+        EXIT_FAILED
+    } else if !candidates.is_empty() && names.is_empty() {
+        EXIT_UPDATES_AVAILABLE
+    } else {
+        EXIT_OK
+    };
+    Ok(code)
+}
+
+/// `cargo fresh prune --orphans`：清理 `$CARGO_HOME/bin` 里没有安装记录认领的
+/// 文件和中断安装留下的临时文件，选择流程同 `prune`。
+pub fn run_prune_orphans(cli: &Cli, json_mode: bool, language: Language) -> Result<i32> {
+    use crate::package::bins::{self, OrphanKind};
+
+    let home = require_cargo_home()?;
+    let orphans = bins::find_orphans(&home, std::time::SystemTime::now())?;
+    let labels: Vec<String> = orphans
+        .iter()
+        .map(|orphan| {
+            let key = match orphan.kind {
+                OrphanKind::Unowned => "orphan_unowned",
+                OrphanKind::StaleTemp => "orphan_stale_temp",
+            };
+            let name = orphan.file_name().cyan().to_string();
+            let size = indicatif::HumanBytes(orphan.size).to_string();
+            language.format_text(key, &[("name", &name), ("size", &size)])
+        })
+        .collect();
+    if orphans.is_empty() {
+        status("Finished", language.get_text("orphans_none"));
+    }
+    let selections: Vec<usize> = if orphans.is_empty() {
+        Vec::new()
+    } else if cli.dry_run {
+        for label in &labels {
+            status("Would prune", label);
+        }
+        status("Dry run", language.get_text("dry_run_summary"));
+        Vec::new()
+    } else if cli.batch {
+        (0..orphans.len()).collect()
+    } else if json_mode || cli.no_interactive {
+        for label in &labels {
+            status_warn("Orphaned", label);
+        }
+        Vec::new()
+    } else {
+        crate::display::print_prune_selection(&labels, language)?
+    };
+
+    let mut removed = Vec::new();
+    let mut failed = false;
+    for &i in &selections {
+        let path = orphans[i].path.display().to_string();
+        match bins::remove_orphan(&orphans[i]) {
+            Ok(()) => {
+                status("Removed", &path);
+                removed.push(path);
+            }
+            Err(e) => {
+                status_err("Failed", &format!("{e:#}"));
+                failed = true;
+            }
+        }
+    }
+
+    if json_mode {
+        let listed: Vec<serde_json::Value> = orphans
+            .iter()
+            .map(|orphan| {
+                serde_json::json!({
+                    "path": orphan.path.display().to_string(),
+                    "kind": orphan.kind.as_str(),
+                    "size": orphan.size,
+                })
+            })
+            .collect();
+        anstream::println!(
+            "{}",
+            serde_json::json!({
+                "schema_version": 2,
+                "prune": {
+                    "dry_run": cli.dry_run,
+                    "orphans": listed,
+                    "removed": removed,
+                },
+            })
+        );
+    }
+    let code = if failed {
+        EXIT_FAILED
+    } else if !orphans.is_empty() && selections.is_empty() {
+        EXIT_UPDATES_AVAILABLE
+    } else {
+        EXIT_OK
+    };
+    Ok(code)
+}
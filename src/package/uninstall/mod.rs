@@ -0,0 +1,230 @@
+//! `cargo fresh uninstall` / `prune`：不经 `cargo uninstall`，直接删掉
+//! `$CARGO_HOME/bin` 下记录的 binary，再去掉 `.crates.toml` / `.crates2.json`
+//! 里的条目。
+//!
+//! 读改写两个元数据文件时持有 `CRATES_FILES_LOCK`，和 downloader 的
+//! `install_binary` 同一把锁——`sync` 里安装和卸载交错也不会互相覆盖。
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use crate::downloader::install::CRATES_FILES_LOCK;
+use crate::package::{crates2, crates_toml};
+
+pub mod commands;
+
+/// 一次卸载的结果。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Uninstalled {
+    /// 删掉的 binary
+    pub removed: Vec<PathBuf>,
+    /// 元数据里记着、磁盘上已经没有的 binary
+    pub missing: Vec<String>,
+}
+
+/// 两个元数据文件里 `package_name` 的条目去掉后的新内容（None = 文件里没有
+/// 这个包），以及条目里记录的 bins。
+struct Records {
+    crates_toml: Option<String>,
+    crates2: Option<String>,
+    bins: Vec<String>,
+}
+
+fn read_records(cargo_home: &Path, package_name: &str) -> Result<Records> {
+    let toml = read_if_exists(&cargo_home.join(".crates.toml"))?
+        .and_then(|body| crates_toml::remove_record(&body, package_name));
+    let json = match read_if_exists(&cargo_home.join(".crates2.json"))? {
+        Some(body) => crates2::remove_record(&body, package_name)?,
+        None => None,
+    };
+    let mut bins: Vec<String> = toml
+        .iter()
+        .chain(&json)
+        .flat_map(|(_, bins)| bins.iter().cloned())
+        .collect();
+    bins.sort();
+    bins.dedup();
+    Ok(Records {
+        crates_toml: toml.map(|(body, _)| body),
+        crates2: json.map(|(body, _)| body),
+        bins,
+    })
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(body) => Ok(Some(body)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow::Error::new(e).context(format!("failed to read {}", path.display()))),
+    }
+}
+
+/// `package_name` 安装的 binary 名；两个文件里都没有这个包时返回 None。
+pub fn recorded_bins(cargo_home: &Path, package_name: &str) -> Result<Option<Vec<String>>> {
+    let records = read_records(cargo_home, package_name)?;
+    if records.crates_toml.is_none() && records.crates2.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(records.bins))
+}
+
+/// 卸载一个包：先删 binary，再写回两个元数据文件（和 cargo 的顺序一致——
+/// 中途失败时元数据里多一条指向不存在文件的记录，下次卸载照样能清掉）。
+///
+/// 只删 `bin/` 下的普通文件名，记录里带路径分隔符或 `..` 的 bin 一律跳过。
+pub fn uninstall(cargo_home: &Path, package_name: &str) -> Result<Uninstalled> {
+    let _guard = CRATES_FILES_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let records = read_records(cargo_home, package_name)?;
+    if records.crates_toml.is_none() && records.crates2.is_none() {
+        anyhow::bail!("{package_name} is not installed");
+    }
+
+    let bin_dir = cargo_home.join("bin");
+    let mut out = Uninstalled::default();
+    for bin in &records.bins {
+        if !is_plain_file_name(bin) {
+            out.missing.push(bin.clone());
+            continue;
+        }
+        let path = bin_dir.join(bin);
+        match std::fs::remove_file(&path) {
+            Ok(()) => out.removed.push(path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => out.missing.push(bin.clone()),
+            Err(e) => {
+                return Err(
+                    anyhow::Error::new(e).context(format!("failed to remove {}", path.display()))
+                )
+            }
+        }
+    }
+
+    if let Some(body) = records.crates2 {
+        std::fs::write(cargo_home.join(".crates2.json"), body).context("write .crates2.json")?;
+    }
+    if let Some(body) = records.crates_toml {
+        std::fs::write(cargo_home.join(".crates.toml"), body).context("write .crates.toml")?;
+    }
+    crate::package::invalidate_installed_version(package_name);
+    Ok(out)
+}
+
+/// `uninstall --dry-run`：不改任何文件，按现状分出会删掉和已经不在的 binary。
+pub fn preview(cargo_home: &Path, package_name: &str) -> Result<Uninstalled> {
+    let Some(bins) = recorded_bins(cargo_home, package_name)? else {
+        anyhow::bail!("{package_name} is not installed");
+    };
+    let bin_dir = cargo_home.join("bin");
+    let mut out = Uninstalled::default();
+    for bin in bins {
+        let path = bin_dir.join(&bin);
+        if is_plain_file_name(&bin) && path.symlink_metadata().is_ok() {
+            out.removed.push(path);
+        } else {
+            out.missing.push(bin);
+        }
+    }
+    Ok(out)
+}
+
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && name != ".." && name != "." && !name.contains(['/', '\\'])
+}
+
+/// 包的 binary 最近一次被访问的时间（多个 binary 取最新的）。一个都读不到时
+/// 返回 None。依赖文件系统的 atime：`relatime` 下一天内最多更新一次，
+/// `noatime` 挂载时它只是安装时间。
+pub fn last_used(cargo_home: &Path, bins: &[String]) -> Option<SystemTime> {
+    let bin_dir = cargo_home.join("bin");
+    bins.iter()
+        .filter(|bin| is_plain_file_name(bin))
+        .filter_map(|bin| std::fs::metadata(bin_dir.join(bin)).ok())
+        .filter_map(|meta| meta.accessed().ok())
+        .max()
+}
+
+/// `prune` 的候选包。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruneCandidate {
+    pub name: String,
+    pub version: Option<String>,
+    pub last_used: Option<SystemTime>,
+}
+
+impl PruneCandidate {
+    /// 距上次使用的整天数；不知道时为 None。
+    pub fn idle_days(&self, now: SystemTime) -> Option<u64> {
+        let idle = now.duration_since(self.last_used?).unwrap_or_default();
+        Some(idle.as_secs() / 86_400)
+    }
+}
+
+/// 按条件筛出 `prune` 的候选：给了 `unused_for` 时要求至少这么久没用过
+/// （读不到 atime 的包不算），给了 `patterns` 时名字要匹配其中之一（规则同
+/// `--exclude`）；两者都给时同时满足。cargo-fresh 自己永远不在候选里。
+pub fn prune_candidates(
+    packages: Vec<PruneCandidate>,
+    now: SystemTime,
+    unused_for: Option<Duration>,
+    patterns: &[String],
+) -> Result<Vec<PruneCandidate>> {
+    let globs = if patterns.is_empty() {
+        None
+    } else {
+        let refs: Vec<&str> = patterns.iter().map(String::as_str).collect();
+        Some(crate::package::build_globset(&refs)?)
+    };
+    Ok(packages
+        .into_iter()
+        .filter(|c| c.name != env!("CARGO_PKG_NAME"))
+        .filter(|c| globs.as_ref().is_none_or(|set| set.is_match(&c.name)))
+        .filter(|c| {
+            unused_for.is_none_or(|age| {
+                c.last_used
+                    .is_some_and(|t| now.duration_since(t).unwrap_or_default() >= age)
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, idle_days: Option<u64>, now: SystemTime) -> PruneCandidate {
+        PruneCandidate {
+            name: name.into(),
+            version: Some("1.0.0".into()),
+            last_used: idle_days.map(|d| now - Duration::from_secs(d * 86_400)),
+        }
+    }
+
+    #[test]
+    fn prune_candidates_combine_age_and_globs() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 * 86_400);
+        let packages = vec![
+            candidate("cargo-expand", Some(200), now),
+            candidate("cargo-edit", Some(3), now),
+            candidate("ripgrep", Some(120), now),
+            candidate("tokei", None, now),
+            candidate("cargo-fresh", Some(500), now),
+        ];
+        let names = |found: Vec<PruneCandidate>| -> Vec<String> {
+            found.into_iter().map(|c| c.name).collect()
+        };
+        let ninety = Some(Duration::from_secs(90 * 86_400));
+
+        let found = prune_candidates(packages.clone(), now, ninety, &[]).unwrap();
+        assert_eq!(names(found), ["cargo-expand", "ripgrep"]);
+        let globs = ["cargo-*".to_string()];
+        let found = prune_candidates(packages.clone(), now, None, &globs).unwrap();
+        assert_eq!(names(found), ["cargo-expand", "cargo-edit"]);
+        let found = prune_candidates(packages.clone(), now, ninety, &globs).unwrap();
+        assert_eq!(names(found), ["cargo-expand"]);
+        assert_eq!(packages[0].idle_days(now), Some(200));
+        assert_eq!(packages[3].idle_days(now), None);
+    }
+}
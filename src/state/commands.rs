@@ -0,0 +1,61 @@
+//! `hold` / `unhold` 子命令的执行流程：改写状态文件里的 hold 记录。
+
+use anyhow::Result;
+use colored::*;
+
+use crate::display::{status, status_warn};
+use crate::locale::Language;
+use crate::models::EXIT_OK;
+use crate::package::get_installed_packages;
+
+use super::{load, parse_requirement, save, Hold};
+
+/// `cargo fresh hold <pkg> [--at <req>]`：不带 `--at` 时锁在当前安装的版本。
+pub async fn run_hold(package: &str, at: Option<&str>, language: Language) -> Result<i32> {
+    let at = match at {
+        Some(req) => parse_requirement(req)?,
+        None => {
+            let installed = get_installed_packages().await?;
+            let Some(version) = installed
+                .iter()
+                .find(|p| p.name == package)
+                .and_then(|p| p.current_version.clone())
+            else {
+                anyhow::bail!("{package} is not installed; use --at <REQ> to hold it anyway");
+            };
+            format!("={version}")
+        }
+    };
+    let mut state = load()?;
+    let hold = Hold { at };
+    let shown = hold.display().to_string();
+    state.holds.insert(package.to_string(), hold);
+    save(&state)?;
+    status(
+        "Held",
+        &language.format_text(
+            "hold_set",
+            &[("name", &package.cyan().to_string()), ("at", &shown)],
+        ),
+    );
+    Ok(EXIT_OK)
+}
+
+/// `cargo fresh unhold <pkg>`。本来就没有 hold 只提示，不算错误。
+pub fn run_unhold(package: &str, language: Language) -> Result<i32> {
+    let mut state = load()?;
+    let name = package.cyan().to_string();
+    if state.holds.remove(package).is_none() {
+        status_warn(
+            "Note",
+            &language.format_text("hold_not_found", &[("name", &name)]),
+        );
+        return Ok(EXIT_OK);
+    }
+    save(&state)?;
+    status(
+        "Unheld",
+        &language.format_text("hold_removed", &[("name", &name)]),
+    );
+    Ok(EXIT_OK)
+}
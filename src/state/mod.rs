@@ -0,0 +1,307 @@
+//! cargo-fresh 自己的持久状态：`$XDG_STATE_HOME/cargo-fresh/state.json`
+//! （未设 XDG_STATE_HOME 时回退 `$HOME/.local/state/cargo-fresh/state.json`）。
+//!
+//! 和配置文件不同，这里的内容由子命令写入（`cargo fresh hold` / `unhold`，
+//! 以及每次成功安装后记一笔的安装账本），不需要手工编辑，所以和
+//! `.crates2.json` 一样用 JSON。
+//!
+//! ```json
+//! {
+//!   "holds": { "ripgrep": { "at": "=14.1.0" }, "bevy_cli": { "at": "0.21" } },
+//!   "installs": { "ripgrep": { "version": "14.1.1", "method": "prebuilt", "at": 1760000000 } }
+//! }
+//! ```
+//!
+//! 文件不存在 = 空状态；解析失败直接报错，避免一次写入把已有 hold 全部冲掉。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{InstallMethod, PackageInfo};
+
+pub mod commands;
+
+/// 一个包的 hold：`at` 是 semver 版本要求，原样保存用户写的内容
+/// （不带 `--at` 时为 `=<当时安装的版本>`）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hold {
+    pub at: String,
+}
+
+impl Hold {
+    /// 给人看的形式：`=14.1.0` 显示成 `14.1.0`，其余原样。
+    pub fn display(&self) -> &str {
+        self.at.strip_prefix('=').unwrap_or(&self.at).trim()
+    }
+
+    /// `version` 是否落在 hold 的要求内。要求或版本解析失败时按"不允许"处理：
+    /// hold 的本意是拦住升级，拿不准就拦。
+    pub fn allows(&self, version: &str) -> bool {
+        let (Ok(req), Ok(version)) = (
+            semver::VersionReq::parse(&self.at),
+            semver::Version::parse(version),
+        ) else {
+            return false;
+        };
+        req.matches(&version)
+    }
+}
+
+/// 安装账本里的安装方式，取值和 JSON `results[].install_method` 相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstalledVia {
+    Prebuilt,
+    Source,
+}
+
+impl InstalledVia {
+    /// `Unknown`（没走到安装）不进账本。
+    pub fn from_method(method: InstallMethod) -> Option<Self> {
+        match method {
+            InstallMethod::Downloader => Some(InstalledVia::Prebuilt),
+            InstallMethod::CargoInstall => Some(InstalledVia::Source),
+            InstallMethod::Unknown => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InstalledVia::Prebuilt => "prebuilt",
+            InstalledVia::Source => "source",
+        }
+    }
+}
+
+/// 安装账本的一条：cargo-fresh 最近一次装这个包时的版本、方式和时间（Unix 秒）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Installed {
+    pub version: String,
+    pub method: InstalledVia,
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// 包名 → hold。
+    pub holds: BTreeMap<String, Hold>,
+    /// 包名 → 安装账本。
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub installs: BTreeMap<String, Installed>,
+}
+
+impl State {
+    /// 把 hold 标到检查结果上：记下 `hold`，最新版本超出要求时置 `held`。
+    pub fn apply_holds(&self, packages: &mut [PackageInfo]) {
+        for pkg in packages.iter_mut() {
+            let Some(hold) = self.holds.get(&pkg.name) else {
+                continue;
+            };
+            pkg.hold = Some(hold.display().to_string());
+            pkg.held = pkg.has_update()
+                && pkg
+                    .latest_version
+                    .as_deref()
+                    .is_some_and(|latest| !hold.allows(latest));
+        }
+    }
+
+    /// `name` 当前安装的 `version` 是怎么装的。账本里的版本对不上（之后又用
+    /// `cargo install` 装过别的版本）时不算数，返回 None。
+    pub fn installed_via(&self, name: &str, version: &str) -> Option<InstalledVia> {
+        self.installs
+            .get(name)
+            .filter(|record| record.version == version)
+            .map(|record| record.method)
+    }
+}
+
+/// 校验 `--at` 给出的版本要求。
+pub fn parse_requirement(req: &str) -> Result<String> {
+    let req = req.trim();
+    semver::VersionReq::parse(req)
+        .with_context(|| format!("invalid version requirement `{req}`"))?;
+    Ok(req.to_string())
+}
+
+/// 状态文件路径。`$HOME` 和 `$XDG_STATE_HOME` 都没有时返回 None。
+pub fn state_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("state"))
+        })
+        .map(|dir| dir.join("cargo-fresh").join("state.json"))
+}
+
+/// 读取状态；文件不存在返回空状态。
+pub fn load() -> Result<State> {
+    match state_path() {
+        Some(path) => load_from(&path),
+        None => Ok(State::default()),
+    }
+}
+
+pub fn load_from(path: &Path) -> Result<State> {
+    if !path.is_file() {
+        return Ok(State::default());
+    }
+    let body = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&body).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// 写回状态：先写同目录临时文件再 rename，中途被杀也不会留下半个文件。
+pub fn save(state: &State) -> Result<()> {
+    let path = state_path().context("cannot locate the state file: set $XDG_STATE_HOME or $HOME")?;
+    save_to(state, &path)
+}
+
+/// 读-改-写状态文件。并发的更新任务各自记账时由进程内的锁串行化，
+/// 不会互相覆盖。
+pub fn update(change: impl FnOnce(&mut State)) -> Result<()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    let _guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut state = load()?;
+    let before = state.clone();
+    change(&mut state);
+    if state != before {
+        save(&state)?;
+    }
+    Ok(())
+}
+
+/// 安装成功后记账。`method` 为 `Unknown` 时什么也不做。
+pub fn record_install(name: &str, version: &str, method: InstallMethod) -> Result<()> {
+    let Some(method) = InstalledVia::from_method(method) else {
+        return Ok(());
+    };
+    let at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    update(|state| {
+        state.installs.insert(
+            name.to_string(),
+            Installed {
+                version: version.to_string(),
+                method,
+                at,
+            },
+        );
+    })
+}
+
+/// 卸载后把账本里的记录去掉。
+pub fn forget_install(name: &str) -> Result<()> {
+    update(|state| {
+        state.installs.remove(name);
+    })
+}
+
+pub fn save_to(state: &State, path: &Path) -> Result<()> {
+    let dir = path.parent().context("state path has no parent directory")?;
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("failed to create a temp file in {}", dir.display()))?;
+    serde_json::to_writer_pretty(&mut tmp, state)?;
+    tmp.persist(path)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PackageSource;
+
+    fn pkg(name: &str, current: &str, latest: &str) -> PackageInfo {
+        let mut p =
+            PackageInfo::with_source(name.into(), Some(current.into()), PackageSource::Crates);
+        p.latest_version = Some(latest.into());
+        p
+    }
+
+    #[test]
+    fn hold_blocks_only_versions_outside_the_requirement() {
+        let mut state = State::default();
+        state
+            .holds
+            .insert("bevy_cli".into(), Hold { at: "0.21".into() });
+        state
+            .holds
+            .insert("ripgrep".into(), Hold { at: "=14.1.0".into() });
+        let mut packages = vec![
+            pkg("bevy_cli", "0.21.0", "0.22.1"),
+            pkg("ripgrep", "14.1.0", "14.1.0"),
+            pkg("fd-find", "9.0.0", "10.0.0"),
+        ];
+        state.apply_holds(&mut packages);
+
+        assert!(packages[0].held);
+        assert_eq!(packages[0].hold.as_deref(), Some("0.21"));
+        // 没有更新就谈不上被拦
+        assert!(!packages[1].held);
+        assert_eq!(packages[1].hold.as_deref(), Some("14.1.0"));
+        assert!(!packages[2].held);
+        assert_eq!(packages[2].hold, None);
+
+        // 要求内的更新照常放行
+        let mut packages = vec![pkg("bevy_cli", "0.21.0", "0.21.3")];
+        state.apply_holds(&mut packages);
+        assert!(!packages[0].held);
+    }
+
+    #[test]
+    fn state_round_trips_and_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/state.json");
+        assert_eq!(load_from(&path).unwrap(), State::default());
+
+        let mut state = State::default();
+        state
+            .holds
+            .insert("ripgrep".into(), Hold { at: "=14.1.0".into() });
+        save_to(&state, &path).unwrap();
+        assert_eq!(load_from(&path).unwrap(), state);
+
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(load_from(&path).is_err());
+    }
+
+    #[test]
+    fn ledger_only_counts_for_the_recorded_version() {
+        let mut state = State::default();
+        state.installs.insert(
+            "ripgrep".into(),
+            Installed {
+                version: "14.1.1".into(),
+                method: InstalledVia::Prebuilt,
+                at: 0,
+            },
+        );
+        assert_eq!(
+            state.installed_via("ripgrep", "14.1.1"),
+            Some(InstalledVia::Prebuilt)
+        );
+        assert_eq!(state.installed_via("ripgrep", "14.1.0"), None);
+        assert_eq!(state.installed_via("tokei", "12.1.2"), None);
+
+        let body = serde_json::to_string(&state).unwrap();
+        assert!(body.contains(r#""method":"prebuilt""#));
+        // 没有账本的旧状态文件照常读
+        let old: State = serde_json::from_str(r#"{"holds":{}}"#).unwrap();
+        assert!(old.installs.is_empty());
+    }
+
+    #[test]
+    fn requirement_is_validated() {
+        assert_eq!(parse_requirement(" ~0.21 ").unwrap(), "~0.21");
+        assert!(parse_requirement("latest").is_err());
+    }
+}
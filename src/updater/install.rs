@@ -0,0 +1,397 @@
+//! `cargo fresh install` / `use` / `self-update`：装单个包，走和批量更新相同的流水线
+//! （[`run_one_update`]），版本从 sparse index 解析。
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::*;
+
+use crate::cli::Cli;
+use crate::display::report::emit_report;
+use crate::display::{status, status_dim, status_err, status_warn};
+use crate::locale::Language;
+use crate::models::{
+    PackageInfo, PackageSource, UpdateResult, EXIT_ABORTED, EXIT_FAILED, EXIT_OK,
+    EXIT_UPDATES_AVAILABLE,
+};
+use crate::package::get_installed_packages;
+
+use super::{note_self_update, run_one_update, SlotOutcome};
+
+/// `cargo fresh install` 的参数。
+pub struct InstallRequest {
+    pub crate_spec: String,
+    pub force: bool,
+    pub opts: crate::models::InstallOpts,
+}
+
+/// `cargo fresh install <crate>[@<version>]`：从 sparse index 解析版本，然后和
+/// 更新走同一条流水线（downloader 优先、`cargo install` 兜底、`--prebuilt-only`
+/// / `--source-only` / `--dry-run` 照常生效）。已安装时不动，除非 `--force`。
+pub async fn run_install(
+    cli: &Cli,
+    request: InstallRequest,
+    cancel: &Arc<AtomicBool>,
+    language: Language,
+) -> Result<i32> {
+    let run_start = std::time::Instant::now();
+    let config = crate::config::get();
+    let (name, req) = match request.crate_spec.split_once('@') {
+        Some((name, spec)) => (
+            name.trim().to_string(),
+            Some(crate::package::parse_install_requirement(spec)?),
+        ),
+        None => (request.crate_spec.trim().to_string(), None),
+    };
+    if name.is_empty() {
+        anyhow::bail!("missing crate name in `{}`", request.crate_spec);
+    }
+
+    let installed = get_installed_packages()
+        .await?
+        .into_iter()
+        .find(|p| p.name == name);
+    let current = installed.as_ref().and_then(|p| p.current_version.clone());
+    if let (Some(pkg), false) = (&installed, request.force) {
+        status_warn(
+            "Note",
+            &language.format_text(
+                "already_installed",
+                &[
+                    ("name", &name.cyan().to_string()),
+                    ("version", current.as_deref().unwrap_or("?")),
+                ],
+            ),
+        );
+        if crate::display::is_json_mode() {
+            emit_report(
+                cli,
+                std::slice::from_ref(pkg),
+                &[],
+                &[],
+                false,
+                run_start,
+                0,
+            );
+        }
+        return Ok(EXIT_OK);
+    }
+
+    status("Resolving", &name.cyan().to_string());
+    let version = resolve_install_version(cli, &name, req.as_ref()).await?;
+
+    let recorded = (!request.opts.is_default()).then_some(request.opts);
+    let install_opts = config.install_opts_for(&name, recorded);
+    let policy = cli
+        .install_policy_override()
+        .unwrap_or_else(|| config.install_policy(&name));
+
+    let mut pkg = installed
+        .unwrap_or_else(|| PackageInfo::with_source(name.clone(), None, PackageSource::Crates));
+    pkg.latest_version = Some(version.clone());
+    status("Installing", &format!("{} {version}", name.cyan()));
+
+    let outcome = run_one_update(
+        name.clone(),
+        Some(version.clone()),
+        PackageSource::Crates,
+        install_opts,
+        cli.dry_run,
+        cli.verbose,
+        cancel.clone(),
+        None,
+        policy,
+    )
+    .await;
+    let (result, code) = finish_single_install(&name, &version, outcome, cli.dry_run, language)?;
+    note_self_update(result.as_slice(), cli.dry_run, language);
+
+    if crate::display::is_json_mode() {
+        let results: Vec<UpdateResult> = result.into_iter().collect();
+        emit_report(
+            cli,
+            std::slice::from_ref(&pkg),
+            &[&pkg],
+            &results,
+            code == EXIT_ABORTED,
+            run_start,
+            1,
+        );
+    }
+    Ok(code)
+}
+
+/// `cargo fresh use <pkg>@<version>`：把已安装的 crates.io 包换到指定版本（可以比
+/// 当前旧），features 沿用安装记录。`hold` 时随后锁定在这个版本，下次检查不会
+/// 马上提示升级回去。
+pub async fn run_use(
+    cli: &Cli,
+    spec: &str,
+    hold: bool,
+    cancel: &Arc<AtomicBool>,
+    language: Language,
+) -> Result<i32> {
+    let run_start = std::time::Instant::now();
+    let config = crate::config::get();
+    let Some((name, version_spec)) = spec.split_once('@') else {
+        anyhow::bail!("expected PACKAGE@VERSION, got `{spec}`");
+    };
+    let name = name.trim();
+    let req = crate::package::parse_install_requirement(version_spec)?;
+
+    let installed = get_installed_packages().await?;
+    let Some(mut pkg) = installed.into_iter().find(|p| p.name == name) else {
+        anyhow::bail!("{name} is not installed; use `cargo fresh install {spec}`");
+    };
+    if !pkg.source.is_crates() {
+        anyhow::bail!("{name} is not installed from a registry, so it has no versions to pick");
+    }
+
+    status(
+        "Resolving",
+        &format!("{}@{}", name.cyan(), version_spec.trim()),
+    );
+    let version = resolve_install_version(cli, name, Some(&req)).await?;
+    let current = pkg.current_version.clone().unwrap_or_default();
+    let (result, code) = if current == version {
+        status_dim(
+            "Note",
+            &language.format_text(
+                "use_already",
+                &[("name", &name.cyan().to_string()), ("version", &version)],
+            ),
+        );
+        (None, EXIT_OK)
+    } else {
+        let install_opts = config.install_opts_for(name, pkg.install_opts.clone());
+        let policy = cli
+            .install_policy_override()
+            .unwrap_or_else(|| config.install_policy(name));
+        status(
+            "Switching",
+            &format!("{} {current} -> {version}", name.cyan()),
+        );
+        let outcome = run_one_update(
+            name.to_string(),
+            Some(version.clone()),
+            PackageSource::Crates,
+            install_opts,
+            cli.dry_run,
+            cli.verbose,
+            cancel.clone(),
+            None,
+            policy,
+        )
+        .await;
+        finish_single_install(name, &version, outcome, cli.dry_run, language)?
+    };
+    note_self_update(result.as_slice(), cli.dry_run, language);
+
+    pkg.latest_version = Some(version.clone());
+    if hold && code == EXIT_OK && !cli.dry_run {
+        let mut state = crate::state::load()?;
+        let at = format!("={version}");
+        if state.holds.get(name).map(|h| h.at.as_str()) != Some(at.as_str()) {
+            state
+                .holds
+                .insert(name.to_string(), crate::state::Hold { at });
+            crate::state::save(&state)?;
+            status(
+                "Held",
+                &language.format_text(
+                    "hold_set",
+                    &[("name", &name.cyan().to_string()), ("at", &version)],
+                ),
+            );
+        }
+        pkg.hold = Some(version.clone());
+    }
+
+    if crate::display::is_json_mode() {
+        let results: Vec<UpdateResult> = result.into_iter().collect();
+        emit_report(
+            cli,
+            std::slice::from_ref(&pkg),
+            &[&pkg],
+            &results,
+            code == EXIT_ABORTED,
+            run_start,
+            results.len(),
+        );
+    }
+    Ok(code)
+}
+
+/// `install` / `use` 装完一个包：打印结果状态行，换算成结果和退出码
+/// （跳过、失败、中止的语义同批量更新）。
+pub fn finish_single_install(
+    name: &str,
+    version: &str,
+    outcome: SlotOutcome,
+    dry_run: bool,
+    language: Language,
+) -> Result<(Option<UpdateResult>, i32)> {
+    use crate::models::InstallMethod;
+
+    let finished = match outcome {
+        SlotOutcome::Success(result) => {
+            if !dry_run {
+                let shown = result.new_version.as_deref().unwrap_or(version);
+                status("Installed", &format!("{} {shown}", name.cyan()));
+                let method = match result.install_method {
+                    InstallMethod::Downloader => Some("summary_prebuilt"),
+                    InstallMethod::CargoInstall => Some("summary_compiled"),
+                    _ => None,
+                };
+                if let Some(key) = method {
+                    status_dim(language.get_text(key), name);
+                }
+            }
+            (Some(result), EXIT_OK)
+        }
+        SlotOutcome::Skipped(result) => {
+            status_warn(
+                "Skipped",
+                &format!(
+                    "{} ({})",
+                    name.cyan(),
+                    language.get_text("skipped_prebuilt_only")
+                ),
+            );
+            // 和更新一样: 没装上但不算失败
+            (Some(result), EXIT_UPDATES_AVAILABLE)
+        }
+        SlotOutcome::Failed(result) => {
+            status_err(
+                "Failed",
+                &format!("{} ({})", name.red(), language.get_text("update_failed")),
+            );
+            (Some(result), EXIT_FAILED)
+        }
+        SlotOutcome::Aborted => (None, EXIT_ABORTED),
+        SlotOutcome::Error(_, e) => return Err(e),
+    };
+    Ok(finished)
+}
+
+/// 查 index 定下要装的版本：`req` 为 None 时取最新版（预发布看
+/// `--include-prerelease` 和配置）。`install` 和 `sync` 共用。
+pub async fn resolve_install_version(
+    cli: &Cli,
+    name: &str,
+    req: Option<&semver::VersionReq>,
+) -> Result<String> {
+    use crate::models::CheckErrorKind;
+
+    let config = crate::config::get();
+    let include_prerelease = cli.include_prerelease || config.allows_prerelease(name);
+    let registry = config
+        .package_registry(name)
+        .or(cli.registry_url.as_deref());
+    let lookup = crate::package::fetch_latest_versions(
+        name,
+        include_prerelease,
+        registry,
+        crate::package::cargo_search_fallback_disabled(cli.no_cargo_search_fallback),
+        cli.verbose,
+    )
+    .await;
+    if let Some(err) = lookup.error {
+        match err.kind {
+            CheckErrorKind::NotFound => {
+                anyhow::bail!("crate `{name}` was not found in the registry index")
+            }
+            CheckErrorKind::Unavailable => {
+                anyhow::bail!("failed to look up `{name}`: {}", err.message)
+            }
+        }
+    }
+    let Some(version) =
+        crate::package::pick_install_version(&lookup.versions, req, include_prerelease)
+    else {
+        match req {
+            Some(req) => anyhow::bail!("no published version of `{name}` matches `{req}`"),
+            None => anyhow::bail!(
+                "`{name}` has no stable release; pass --include-prerelease or name a version"
+            ),
+        }
+    };
+    Ok(version)
+}
+
+/// `cargo fresh self-update`：只更新 cargo-fresh 自己，和批量更新走同一条流水线
+/// （downloader 优先、`cargo install` 兜底）。只处理 `cargo install` 装进
+/// `$CARGO_HOME` 的副本；别的方式装的交给原来的包管理器。
+pub async fn run_self_update(
+    cli: &Cli,
+    cancel: &Arc<AtomicBool>,
+    language: Language,
+) -> Result<i32> {
+    let run_start = std::time::Instant::now();
+    let config = crate::config::get();
+    let name = super::self_replace::SELF_NAME;
+
+    let installed = get_installed_packages().await?;
+    let Some(mut pkg) = installed.into_iter().find(|p| p.name == name) else {
+        anyhow::bail!(
+            "{name} was not installed with `cargo install`; update it the way it was installed"
+        );
+    };
+    if !pkg.source.is_crates() {
+        anyhow::bail!(
+            "{name} is installed from {}, not a registry; reinstall it from there",
+            pkg.source.kind_str()
+        );
+    }
+
+    status("Resolving", &name.cyan().to_string());
+    let version = resolve_install_version(cli, name, None).await?;
+    pkg.latest_version = Some(version.clone());
+    let current = pkg.current_version.clone().unwrap_or_default();
+    let (result, code) = if !pkg.has_update() {
+        status(
+            "Finished",
+            &language.format_text("self_up_to_date", &[("version", &current)]),
+        );
+        (None, EXIT_OK)
+    } else {
+        let install_opts = config.install_opts_for(name, pkg.install_opts.clone());
+        let policy = cli
+            .install_policy_override()
+            .unwrap_or_else(|| config.install_policy(name));
+        status(
+            "Updating",
+            &format!("{} {current} -> {version}", name.cyan()),
+        );
+        let outcome = run_one_update(
+            name.to_string(),
+            Some(version.clone()),
+            PackageSource::Crates,
+            install_opts,
+            cli.dry_run,
+            cli.verbose,
+            cancel.clone(),
+            None,
+            policy,
+        )
+        .await;
+        finish_single_install(name, &version, outcome, cli.dry_run, language)?
+    };
+    let results: Vec<UpdateResult> = result.into_iter().collect();
+    note_self_update(&results, cli.dry_run, language);
+
+    if crate::display::is_json_mode() {
+        let updates: Vec<&PackageInfo> = pkg.has_update().then_some(&pkg).into_iter().collect();
+        emit_report(
+            cli,
+            std::slice::from_ref(&pkg),
+            &updates,
+            &results,
+            code == EXIT_ABORTED,
+            run_start,
+            results.len(),
+        );
+    }
+    Ok(code)
+}
@@ -7,7 +7,10 @@ use std::sync::{Arc, OnceLock};
 use tokio::process::Command;
 use tokio::sync::mpsc;
 
-use crate::display::{pb_status_dim, pb_status_err, pb_status_warn, status, status_dim};
+use crate::config::InstallPolicy;
+use crate::display::{
+    pb_status_dim, pb_status_err, pb_status_warn, status, status_dim, status_warn,
+};
 use crate::downloader::{
     self,
     events::{DownloaderError, ProgressEvent},
@@ -15,11 +18,14 @@ use crate::downloader::{
 };
 use crate::locale::detection::detect_language;
 use crate::models::{
-    InstallMethod, InstallOpts, PackageSource, UpdateResult, MAX_RETRY_ATTEMPTS, PROGRESS_TICK_MS,
-    RETRY_DELAY_MS, VERSION_UPDATE_DELAY_MS,
+    InstallMethod, InstallOpts, NewInstall, PackageInfo, PackageSource, SkipReason, UpdateResult,
+    MAX_RETRY_ATTEMPTS, PROGRESS_TICK_MS, RETRY_DELAY_MS, VERSION_UPDATE_DELAY_MS,
 };
 use crate::package::{get_installed_version, invalidate_installed_version};
 
+pub mod install;
+pub mod self_replace;
+
 /// 全局共享的 `MultiProgress` —— 0.11.0 串行只挂一条 bar, 0.12.0 并发调度器
 /// 复用同一个实例同时挂 N 条。`pb.println`/`mp.println` 会在所有 bar 上方
 /// 滚屏, 这样状态行 ("Updating ripgrep ..." 等) 不会被 bar 覆盖。
@@ -167,6 +173,14 @@ pub fn finalize_failed(pb: &ProgressBar, name_width: usize, detail: &str) {
     pb.finish();
 }
 
+/// 把行定格成 skipped (黄)——`prebuilt-only` 下没有预编译产物的包。
+pub fn finalize_skipped(pb: &ProgressBar, name_width: usize) {
+    pb.disable_steady_tick();
+    pb.set_style(static_style(name_width));
+    pb.set_prefix("skipped".yellow().bold().to_string());
+    pb.finish();
+}
+
 /// 把行定格成 aborted (黄).
 pub fn finalize_aborted(pb: &ProgressBar, name_width: usize) {
     pb.disable_steady_tick();
@@ -387,6 +401,10 @@ fn report_command_failure(pb: &ProgressBar, package_name: &str, output: &Output)
 /// - `Ok(true)`  — 安装成功，调用方直接走 verify_and_report_update。
 /// - `Ok(false)` — 不支持或失败，调用方应回退到 cargo install。
 /// - `Err(_)`    — Cancelled（Ctrl-C），调用方应立即返回 Ok(None)。
+///
+/// `then` 是 `Ok(false)` 之后调用方要做的事，只用于提示文案
+/// ("falling back to cargo install" / "skipping (prebuilt-only)")。
+#[allow(clippy::too_many_arguments)]
 async fn try_downloader_install(
     pb: &ProgressBar,
     name_width: usize,
@@ -395,8 +413,9 @@ async fn try_downloader_install(
     old_version: &Option<String>,
     cancel_arc: Arc<AtomicBool>,
     verbose: bool,
+    then: &str,
 ) -> Result<bool, DownloaderError> {
-    // 先从 crates.io API 拿 repo_url；拿不到直接走 cargo install。
+    // 先从 crates.io API 拿 repo_url；拿不到 (且没配置自定义地址) 直接走 cargo install。
     // HTTP 客户端建不起来 → downloader 没法工作，当作"不支持"回退 cargo install。
     let Ok(client) = crate::package::http_client() else {
         return Ok(false);
@@ -409,20 +428,44 @@ async fn try_downloader_install(
             repo_url.as_deref().unwrap_or("<none>")
         ),
     );
-    if repo_url.is_none() {
+    // 配置了自定义预编译地址的包不依赖 repo URL
+    let has_custom = crate::config::get()
+        .prebuilt_sources(package_name)
+        .is_some();
+    if repo_url.is_none() && !has_custom {
         pb_status_dim(
             pb,
             "Downloader",
-            &format!("{package_name}: no repo URL, falling back to cargo install"),
+            &format!("{package_name}: no repo URL, {then}"),
         );
         return Ok(false);
     }
 
     // 从 .crates2.json 查 bins[] —— ripgrep 包名 vs "rg" binary 名要靠这个区分
-    let bins = crate::package::registry::cargo_home()
+    let mut bins = crate::package::registry::cargo_home()
         .map(|home| crate::package::crates2::lookup_bins(&home, package_name))
         .unwrap_or_default();
-    crate::display::status_debug("downloader", &format!("{package_name}: bins={bins:?}"));
+    // 没有默认 features 的 crates.io 条目: 全新安装 (`cargo fresh install`), 或从
+    // git / path / 自定义 features 换过来 (`cargo fresh sync`)。装完整条重建元数据;
+    // downloader 只处理默认 features, 照此记录。bins 以 .crate 推出的为准。
+    let has_record = crate::package::crates2::has_default_registry_record(
+        &crate::package::crates2::load_install_opts(),
+        package_name,
+    );
+    let new_record = (!has_record).then(|| NewInstall {
+        source_id: crate::package::registry::CRATES_IO_SOURCE_ID.to_string(),
+        opts: InstallOpts::default(),
+    });
+    if new_record.is_some() {
+        let from_crate = downloader::binstall_meta::crate_bins(client, package_name, version).await;
+        if !from_crate.is_empty() {
+            bins = from_crate;
+        }
+    }
+    crate::display::status_debug(
+        "downloader",
+        &format!("{package_name}: bins={bins:?} new={}", new_record.is_some()),
+    );
 
     let (tx, mut rx) = mpsc::unbounded_channel::<ProgressEvent>();
     let spec = InstallSpec {
@@ -430,6 +473,7 @@ async fn try_downloader_install(
         version: version.to_string(),
         repo_url,
         bins,
+        new_record,
     };
 
     // 消费进度事件 —— 行已经在 main.rs 预注册, 这里只切样式 + 更新 prefix
@@ -486,6 +530,9 @@ async fn try_downloader_install(
                 ProgressEvent::Installing { .. } => {
                     pb_clone.set_prefix("installing".green().bold().to_string());
                 }
+                ProgressEvent::Warning { name, message } => {
+                    pb_status_warn(&pb_clone, "Warning", &format!("{name}: {message}"));
+                }
                 ProgressEvent::Done { .. } | ProgressEvent::Failed { .. } => {
                     // handled by caller
                 }
@@ -506,7 +553,7 @@ async fn try_downloader_install(
             pb_status_dim(
                 pb,
                 "Downloader",
-                &format!("{package_name}: unsupported ({reason:?}), falling back to cargo install"),
+                &format!("{package_name}: unsupported ({reason:?}), {then}"),
             );
             Ok(false)
         }
@@ -514,9 +561,7 @@ async fn try_downloader_install(
             pb_status_dim(
                 pb,
                 "Fallback",
-                &format!(
-                    "{package_name}: downloader failed ({kind:?}: {source}), falling back to cargo install"
-                ),
+                &format!("{package_name}: downloader failed ({kind:?}: {source}), {then}"),
             );
             Ok(false)
         }
@@ -528,7 +573,9 @@ async fn try_downloader_install(
 /// 返回 `Ok(None)` 表示**用户按 Ctrl-C 中途取消了这个包**——它既不是成功
 /// 也不是失败,调用方应据此停止后续包并标记中止,不要把它计入失败数。
 /// `cancel` 是 `main` 持有的 `Arc<AtomicBool>`，Ctrl-C 信号处理任务置位后
-/// 下载器和 cargo 子进程循环都能实时感知。
+/// 下载器和 cargo 子进程循环都能实时感知。`policy` 决定能走哪条安装路径：
+/// `PrebuiltOnly` 拿不到预编译产物时返回 [`UpdateResult::skipped`]，
+/// `SourceOnly` 不碰 downloader。
 // 参数已到 8 个(本就贴着 clippy 阈值)。这些是"每包参数 + 全程运行上下文"
 // 的混合，真要收拢应抽 UpdateContext 结构体——留作独立重构。
 #[allow(clippy::too_many_arguments)]
@@ -541,6 +588,7 @@ pub async fn update_package(
     verbose: bool,
     cancel: Arc<AtomicBool>,
     row: Option<(ProgressBar, usize)>,
+    policy: InstallPolicy,
 ) -> Result<Option<UpdateResult>> {
     // 在做任何事(连 cargo install --list 都还没查)之前先看取消标志。
     if cancel.load(Ordering::SeqCst) {
@@ -554,18 +602,24 @@ pub async fn update_package(
     //   Unsupported/Failed 时回退 cargo install。cargo binstall subprocess 不再调用。
     // - Crates 源 + 自定义 features：直接走 cargo install（downloader 不支持任意 features）。
     // - Git / Path 源：cargo install（downloader 仅支持 crates.io 包）。
+    // - `SourceOnly` 一律 cargo install；`PrebuiltOnly` 不回退 cargo install。
     let opts_allow_downloader = install_opts.is_none_or(|o| o.is_default());
+    let use_downloader = matches!(source, PackageSource::Crates)
+        && opts_allow_downloader
+        && policy != InstallPolicy::SourceOnly;
+    let allow_cargo_install = policy != InstallPolicy::PrebuiltOnly;
 
     crate::display::status_debug(
         "updater",
         &format!(
-            "{package_name}: source={} default_features={} → {}",
+            "{package_name}: source={} default_features={} policy={policy:?} → {}",
             source.kind_str(),
             opts_allow_downloader,
-            if matches!(source, PackageSource::Crates) && opts_allow_downloader {
-                "downloader (fallback: cargo install)"
-            } else {
-                "cargo install"
+            match (use_downloader, allow_cargo_install) {
+                (true, true) => "downloader (fallback: cargo install)",
+                (true, false) => "downloader (no fallback)",
+                (false, true) => "cargo install",
+                (false, false) => "skip",
             }
         ),
     );
@@ -597,19 +651,33 @@ pub async fn update_package(
             format!("{} {}", package_name.cyan().bold(), marker.dimmed())
         };
         // For Crates + default features, show the downloader as primary path.
-        if matches!(source, PackageSource::Crates) && opts_allow_downloader {
-            status(
+        match (use_downloader, allow_cargo_install) {
+            (true, true) => status(
                 "Would run",
                 &format!(
                     "{header}: self-hosted downloader → cargo {}",
                     cargo_install_args.join(" ")
                 ),
-            );
-        } else {
-            status(
+            ),
+            (true, false) => status(
+                "Would run",
+                &format!("{header}: self-hosted downloader (prebuilt-only, no cargo install)"),
+            ),
+            (false, true) => status(
                 "Would run",
                 &format!("{}: cargo {}", header, cargo_install_args.join(" ")),
-            );
+            ),
+            (false, false) => {
+                status_warn(
+                    "Would skip",
+                    &format!("{header}: prebuilt-only, but this package needs a source build"),
+                );
+                return Ok(Some(UpdateResult::skipped(
+                    package_name.to_string(),
+                    old_version,
+                    SkipReason::PrebuiltUnavailable,
+                )));
+            }
         }
         return Ok(Some(UpdateResult::new(
             package_name.to_string(),
@@ -633,6 +701,10 @@ pub async fn update_package(
         }
     };
     let _pb_guard = PbGuard(&pb);
+    // 更新自己：Windows 上先把运行中的 exe 挪开，安装没写成时 drop 挪回
+    let _moved_self = self_replace::is_self(package_name)
+        .then(self_replace::prepare)
+        .flatten();
     let slow_handle = spawn_slow_warning(pb.clone(), package_name.to_string());
     let _slow_guard = SlowGuard(slow_handle);
 
@@ -659,8 +731,13 @@ pub async fn update_package(
 
     // 自托管 downloader 路径：Crates 源 + 默认 features。
     // 无论系统是否安装了 cargo-binstall，都走这条路。
-    // Unsupported/Failed → 回退 cargo install。Cancelled → 中止。
-    if matches!(source, PackageSource::Crates) && opts_allow_downloader {
+    // Unsupported/Failed → 回退 cargo install (`PrebuiltOnly` 时跳过)。Cancelled → 中止。
+    let then = if allow_cargo_install {
+        "falling back to cargo install"
+    } else {
+        "skipping (prebuilt-only)"
+    };
+    if use_downloader {
         if let Some(v) = target_version {
             match try_downloader_install(
                 &pb,
@@ -670,6 +747,7 @@ pub async fn update_package(
                 &old_version,
                 cancel.clone(),
                 verbose,
+                then,
             )
             .await
             {
@@ -692,6 +770,19 @@ pub async fn update_package(
         }
     }
 
+    if !allow_cargo_install {
+        pb_status_warn(
+            &pb,
+            "Skip",
+            &format!("{package_name}: no prebuilt binary, not compiling (prebuilt-only)"),
+        );
+        return Ok(Some(UpdateResult::skipped(
+            package_name.to_string(),
+            old_version,
+            SkipReason::PrebuiltUnavailable,
+        )));
+    }
+
     // cargo install subprocess — fallback for Crates (after downloader fail/unsupported),
     // or primary for Git/Path/custom-features. 这条路径肯定是源码编译, 让行
     // 上的 phase 文案直接说 "compiling from source"——用户看到 ripgrep 这种
@@ -768,10 +859,131 @@ pub async fn update_package(
     )))
 }
 
+/// Outcome of a single package update, ready for the orchestrator to fold
+/// into `success_count` / `fail_count` / `aborted_at` / `update_results`.
+pub enum SlotOutcome {
+    Success(UpdateResult),
+    Failed(UpdateResult),
+    /// `prebuilt-only` 下拿不到预编译产物，既不算成功也不算失败。
+    Skipped(UpdateResult),
+    Aborted,
+    Error(String, anyhow::Error),
+}
+
+/// [`update_package`] 加上收尾：结束进度行、成功时记安装账本，结果归成
+/// [`SlotOutcome`]。批量更新、`install` / `use` 与 `sync` 共用。
+#[allow(clippy::too_many_arguments)]
+pub async fn run_one_update(
+    package_name: String,
+    target_version: Option<String>,
+    source: PackageSource,
+    install_opts: Option<InstallOpts>,
+    dry_run: bool,
+    verbose: bool,
+    cancel: Arc<AtomicBool>,
+    row: Option<(ProgressBar, usize)>,
+    policy: InstallPolicy,
+) -> SlotOutcome {
+    let row_for_finalize = row.clone();
+    let target = target_version.as_deref();
+    let opts_ref = install_opts.as_ref();
+
+    match update_package(
+        &package_name,
+        target,
+        &source,
+        opts_ref,
+        dry_run,
+        verbose,
+        cancel,
+        row,
+        policy,
+    )
+    .await
+    {
+        Ok(Some(result)) if result.skipped.is_some() => {
+            if let Some((pb, w)) = &row_for_finalize {
+                finalize_skipped(pb, *w);
+            }
+            SlotOutcome::Skipped(result)
+        }
+        Ok(Some(result)) => {
+            if let Some((pb, w)) = &row_for_finalize {
+                if result.success {
+                    finalize_installed(pb, *w);
+                } else {
+                    finalize_failed(pb, *w, "");
+                }
+            }
+            if result.success {
+                if !dry_run {
+                    record_install(&result);
+                }
+                SlotOutcome::Success(result)
+            } else {
+                SlotOutcome::Failed(result)
+            }
+        }
+        Ok(None) => {
+            if let Some((pb, w)) = &row_for_finalize {
+                finalize_aborted(pb, *w);
+            }
+            SlotOutcome::Aborted
+        }
+        Err(e) => {
+            if let Some((pb, w)) = &row_for_finalize {
+                finalize_failed(pb, *w, &e.to_string());
+            }
+            SlotOutcome::Error(package_name, e)
+        }
+    }
+}
+
+/// 这次运行把 cargo-fresh 自己换成了新版本时，提示重新运行：进程里跑的
+/// 仍是旧 binary。
+pub fn note_self_update(
+    results: &[UpdateResult],
+    dry_run: bool,
+    language: crate::locale::Language,
+) {
+    if dry_run {
+        return;
+    }
+    let updated = results
+        .iter()
+        .find(|r| self_replace::is_self(&r.package_name) && r.success && r.skipped.is_none());
+    if let Some(version) = updated.and_then(|r| r.new_version.as_deref()) {
+        status_warn(
+            "Restart",
+            &language.format_text("self_updated", &[("version", version)]),
+        );
+    }
+}
+
+/// 成功装上的包记进安装账本（`list` 据此显示安装方式）。账本只是辅助信息，
+/// 写不进去不影响这次安装的结果。
+pub fn record_install(result: &UpdateResult) {
+    let Some(version) = result.new_version.as_deref() else {
+        return;
+    };
+    if let Err(e) =
+        crate::state::record_install(&result.package_name, version, result.install_method)
+    {
+        crate::display::status_debug("state", &format!("failed to record install: {e:#}"));
+    }
+}
+
+/// `--batch` 等非交互选包：全部更新候选里去掉被 hold 拦下的。
+pub fn unheld(all_updates: &[&PackageInfo]) -> Vec<usize> {
+    (0..all_updates.len())
+        .filter(|&i| !all_updates[i].held)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::build_args;
-    use crate::models::{InstallOpts, PackageSource};
+    use crate::models::{InstallOpts, PackageSource, SkipReason};
 
     fn s(v: &[&str]) -> Vec<String> {
         v.iter().map(|x| x.to_string()).collect()
@@ -792,6 +1004,7 @@ mod tests {
             false, // verbose
             cancel,
             None, // row
+            crate::config::InstallPolicy::Auto,
         )
         .await
         .expect("update_package 不应返回 Err");
@@ -801,6 +1014,32 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn prebuilt_only_skips_packages_that_need_a_source_build() {
+        // git 源只能 cargo install; prebuilt-only 下应直接跳过, 不算失败
+        use std::sync::{atomic::AtomicBool, Arc};
+        let source = PackageSource::Git {
+            url: "https://github.com/example/tool".into(),
+            rev: None,
+        };
+        let result = super::update_package(
+            "tool",
+            None,
+            &source,
+            None,
+            true,  // dry_run
+            false, // verbose
+            Arc::new(AtomicBool::new(false)),
+            None, // row
+            crate::config::InstallPolicy::PrebuiltOnly,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(result.skipped, Some(SkipReason::PrebuiltUnavailable));
+        assert!(!result.success);
+    }
+
     #[test]
     fn crates_default_opts_no_extra_flags() {
         let got = build_args("ripgrep", Some("14.1.1"), &PackageSource::Crates, None);
@@ -0,0 +1,129 @@
+//! cargo-fresh 更新自己。
+//!
+//! 一批更新里有 cargo-fresh 时，main 把它排到最后、等其余包都装完再开始，
+//! 装完提示用户重新运行——这次运行剩下的部分仍是旧 binary。
+//!
+//! 替换正在运行的可执行文件：Unix 上 downloader 的 tmp + rename 和
+//! `cargo install` 都是换目录项，运行中的进程继续用旧 inode，不需要额外处理。
+//! Windows 不允许覆盖或删除运行中的 exe，但允许改名：先把它挪到同目录的
+//! `.cargo-fresh-self-<pid>.tmp`，让安装写入新文件；安装没写成时挪回去。
+//! 留下的 `.tmp` 之后由 `prune --orphans` 清理。
+
+use std::path::{Path, PathBuf};
+
+/// cargo-fresh 自己的包名。
+pub const SELF_NAME: &str = env!("CARGO_PKG_NAME");
+
+pub fn is_self(package_name: &str) -> bool {
+    package_name == SELF_NAME
+}
+
+/// 当前运行的可执行文件是否就是 `$CARGO_HOME/bin` 下那份（更新会替换它）。
+pub fn running_from(cargo_home: &Path) -> bool {
+    let installed = cargo_home
+        .join("bin")
+        .join(format!("{SELF_NAME}{}", std::env::consts::EXE_SUFFIX));
+    match (
+        std::env::current_exe().and_then(|p| p.canonicalize()),
+        installed.canonicalize(),
+    ) {
+        (Ok(running), Ok(installed)) => running == installed,
+        _ => false,
+    }
+}
+
+/// 被挪开的运行中 binary。drop 时如果原位置还是空的（安装没写成），挪回去。
+#[derive(Debug)]
+pub struct MovedAside {
+    original: PathBuf,
+    aside: PathBuf,
+}
+
+impl MovedAside {
+    pub fn aside(&self) -> &Path {
+        &self.aside
+    }
+}
+
+impl Drop for MovedAside {
+    fn drop(&mut self) {
+        if self.original.symlink_metadata().is_err() {
+            let _ = std::fs::rename(&self.aside, &self.original);
+        }
+    }
+}
+
+/// 把 `exe` 改名为同目录的 `.cargo-fresh-self-<pid>.tmp`。
+pub fn move_aside(exe: &Path) -> std::io::Result<MovedAside> {
+    let aside = exe.with_file_name(format!(".{SELF_NAME}-self-{}.tmp", std::process::id()));
+    std::fs::rename(exe, &aside)?;
+    Ok(MovedAside {
+        original: exe.to_path_buf(),
+        aside,
+    })
+}
+
+/// 更新 cargo-fresh 之前调用：需要时（Windows，且运行的就是要被替换的那份）
+/// 把运行中的 binary 挪开。挪不动只记 debug，交给安装本身报错。
+pub fn prepare() -> Option<MovedAside> {
+    if !cfg!(windows) {
+        return None;
+    }
+    let cargo_home = crate::package::registry::cargo_home()?;
+    if !running_from(&cargo_home) {
+        return None;
+    }
+    let exe = std::env::current_exe().ok()?;
+    match move_aside(&exe) {
+        Ok(moved) => {
+            crate::display::status_debug(
+                "self-update",
+                &format!("moved running binary to {}", moved.aside().display()),
+            );
+            Some(moved)
+        }
+        Err(e) => {
+            crate::display::status_debug(
+                "self-update",
+                &format!("could not move {} aside: {e}", exe.display()),
+            );
+            None
+        }
+    }
+}
+
+/// 把 cargo-fresh 挪到更新顺序的最后（稳定排序，其余包的相对顺序不变）。
+pub fn schedule_last<'a, T>(items: &mut [T], name_of: impl Fn(&T) -> &'a str) {
+    items.sort_by_key(|item| is_self(name_of(item)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_is_scheduled_last_without_reordering_the_rest() {
+        let mut names = vec!["bat", SELF_NAME, "ripgrep", "just"];
+        schedule_last(&mut names, |n| *n);
+        assert_eq!(names, ["bat", "ripgrep", "just", SELF_NAME]);
+    }
+
+    #[test]
+    fn moved_aside_binary_is_restored_only_when_nothing_replaced_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join(SELF_NAME);
+        std::fs::write(&exe, "old").unwrap();
+
+        let moved = move_aside(&exe).unwrap();
+        assert!(!exe.exists());
+        drop(moved);
+        assert_eq!(std::fs::read_to_string(&exe).unwrap(), "old");
+
+        let moved = move_aside(&exe).unwrap();
+        let aside = moved.aside().to_path_buf();
+        std::fs::write(&exe, "new").unwrap();
+        drop(moved);
+        assert_eq!(std::fs::read_to_string(&exe).unwrap(), "new");
+        assert!(aside.exists(), "the old binary stays for prune --orphans");
+    }
+}
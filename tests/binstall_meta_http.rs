@@ -0,0 +1,138 @@
+//! binstall_meta 的网络层测试: wiremock 扮演 registry (`config.json` +
+//! `.crate` 下载), 验证从 `.crate` 里读出 `[package.metadata.binstall]`。
+
+use cargo_fresh::downloader::binstall_meta::{fetch_meta_from, TemplateCtx};
+use cargo_fresh::package::sparse_index::fetch_dl_template;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// 打一个只含 `{name}-{version}/Cargo.toml` 的 `.crate`。
+fn crate_bytes(name: &str, version: &str, manifest: &str) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(
+            &mut header,
+            format!("{name}-{version}/Cargo.toml"),
+            manifest.as_bytes(),
+        )
+        .unwrap();
+    let tarball = builder.into_inner().unwrap();
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    std::io::Write::write_all(&mut gz, &tarball).unwrap();
+    gz.finish().unwrap()
+}
+
+#[tokio::test]
+async fn reads_dl_template_from_registry_config() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/config.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "dl": "https://static.crates.io/crates",
+            "api": "https://crates.io"
+        })))
+        .mount(&server)
+        .await;
+    let client = reqwest::Client::new();
+    assert_eq!(
+        fetch_dl_template(&client, &server.uri()).await.as_deref(),
+        Some("https://static.crates.io/crates")
+    );
+}
+
+#[tokio::test]
+async fn fetches_crate_and_renders_declared_url() {
+    let server = MockServer::start().await;
+    let manifest = r#"
+[package]
+name = "odd-tool"
+version = "2.0.0"
+
+[package.metadata.binstall]
+pkg-url = "{ repo }/releases/download/{ version }/odd_{ target }{ archive-suffix }"
+pkg-fmt = "zip"
+"#;
+    Mock::given(method("GET"))
+        .and(path("/crates/odd-tool/2.0.0/download"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_bytes(crate_bytes("odd-tool", "2.0.0", manifest)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = reqwest::Client::new();
+    let meta = fetch_meta_from(
+        &client,
+        &format!("{}/crates", server.uri()),
+        "odd-tool",
+        "2.0.0",
+    )
+    .await
+    .unwrap()
+    .expect("metadata declared");
+    let cands = meta.candidates(
+        TemplateCtx {
+            name: "odd-tool",
+            version: "2.0.0",
+            repo: Some("https://github.com/o/odd-tool"),
+        },
+        &["x86_64-unknown-linux-gnu".to_string()],
+    );
+    assert_eq!(cands.len(), 1);
+    assert_eq!(
+        cands[0].candidate.url,
+        "https://github.com/o/odd-tool/releases/download/2.0.0/odd_x86_64-unknown-linux-gnu.zip"
+    );
+}
+
+#[tokio::test]
+async fn crate_without_metadata_is_none() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(crate_bytes(
+            "plain",
+            "1.0.0",
+            "[package]\nname = \"plain\"\nversion = \"1.0.0\"\n",
+        )))
+        .mount(&server)
+        .await;
+    let client = reqwest::Client::new();
+    let meta = fetch_meta_from(&client, &server.uri(), "plain", "1.0.0")
+        .await
+        .unwrap();
+    assert_eq!(meta, None);
+}
+
+#[tokio::test]
+async fn oversized_crate_is_an_error() {
+    use cargo_fresh::downloader::binstall_meta::MAX_CRATE_BYTES;
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_bytes(vec![0u8; MAX_CRATE_BYTES as usize + 1]),
+        )
+        .mount(&server)
+        .await;
+    let client = reqwest::Client::new();
+    let err = fetch_meta_from(&client, &server.uri(), "huge", "1.0.0")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("larger than"), "got: {err:#}");
+}
+
+#[tokio::test]
+async fn missing_crate_is_an_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    let client = reqwest::Client::new();
+    assert!(fetch_meta_from(&client, &server.uri(), "nope", "1.0.0")
+        .await
+        .is_err());
+}
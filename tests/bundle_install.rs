@@ -0,0 +1,217 @@
+//! `--from-bundle` 端到端: 用 isolated CARGO_HOME + 本地构造的离线包跑真实二进制,
+//! 验证校验、安装与 `.crates.toml` / `.crates2.json` 更新, 全程不联网。
+
+use assert_cmd::Command;
+use cargo_fresh::downloader::bundle::BundleWriter;
+use cargo_fresh::downloader::fetch::FetchedArchive;
+use cargo_fresh::downloader::resolve::{current_targets, ArchiveFmt};
+use cargo_fresh::downloader::{AcquiredArchive, InstallSpec};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// CARGO_HOME 里装着 mdbook 0.4.0 (bin/mdbook 是旧内容)。
+fn cargo_home_with_old_mdbook() -> tempfile::TempDir {
+    cargo_home_with_old_mdbook_features(&[])
+}
+
+/// 同上, `.crates2.json` 里记录了安装时的 features。
+fn cargo_home_with_old_mdbook_features(features: &[&str]) -> tempfile::TempDir {
+    let home = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(home.path().join("bin")).unwrap();
+    std::fs::write(home.path().join("bin/mdbook"), b"old mdbook").unwrap();
+    let key = "mdbook 0.4.0 (registry+https://github.com/rust-lang/crates.io-index)";
+    std::fs::write(
+        home.path().join(".crates.toml"),
+        format!("[v1]\n\"{key}\" = [\"mdbook\"]\n"),
+    )
+    .unwrap();
+    std::fs::write(
+        home.path().join(".crates2.json"),
+        serde_json::json!({
+            "installs": {
+                key: {
+                    "version_req": null,
+                    "bins": ["mdbook"],
+                    "features": features,
+                    "all_features": false,
+                    "no_default_features": false,
+                    "profile": "release",
+                    "target": "x86_64-unknown-linux-gnu",
+                    "rustc": "rustc 1.0",
+                    "metadata": "0"
+                }
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    home
+}
+
+/// 离线包: mdbook 0.4.52 (已安装, 会升级) + ghost 1.0.0 (未安装, 会跳过)。
+fn write_bundle(root: &Path) {
+    let mut writer = BundleWriter::create(root, current_targets()).unwrap();
+    for name in ["mdbook", "ghost"] {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive_path = temp_dir.path().join(format!("{name}-like.tar.gz"));
+        std::fs::copy(fixture("mdbook-like.tar.gz"), &archive_path).unwrap();
+        let spec = InstallSpec {
+            name: name.into(),
+            version: if name == "mdbook" { "0.4.52" } else { "1.0.0" }.into(),
+            repo_url: None,
+            bins: vec!["mdbook".into()],
+            new_record: None,
+        };
+        let acquired = AcquiredArchive {
+            fetched: FetchedArchive {
+                temp_dir,
+                archive_path,
+                winning_url: format!("https://example.com/{name}.tar.gz"),
+            },
+            fmt: ArchiveFmt::TarGz,
+            bin_paths: Vec::new(),
+        };
+        writer.add(&spec, &acquired).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+fn from_bundle(home: &Path, bundle: &Path) -> assert_cmd::assert::Assert {
+    from_bundle_with_config(home, bundle, "")
+}
+
+/// `config` 写进隔离的 `$XDG_CONFIG_HOME/cargo-fresh/config.toml`。
+fn from_bundle_with_config(home: &Path, bundle: &Path, config: &str) -> assert_cmd::assert::Assert {
+    let config_home = tempfile::tempdir().unwrap();
+    let config_dir = config_home.path().join("cargo-fresh");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), config).unwrap();
+    Command::cargo_bin("cargo-fresh")
+        .unwrap()
+        .env("CARGO_HOME", home)
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("XDG_CACHE_HOME", config_home.path())
+        .args(["--batch", "--format=json", "--from-bundle"])
+        .arg(bundle)
+        .assert()
+}
+
+fn json_stdout(assert: &assert_cmd::assert::Assert) -> serde_json::Value {
+    let out = String::from_utf8_lossy(&assert.get_output().stdout).into_owned();
+    serde_json::from_str(out.trim()).unwrap_or_else(|e| panic!("bad JSON ({e}):\n{out}"))
+}
+
+#[test]
+fn installs_from_directory_bundle_and_updates_metadata() {
+    let home = cargo_home_with_old_mdbook();
+    let bundle = tempfile::tempdir().unwrap();
+    write_bundle(bundle.path());
+
+    let assert = from_bundle(home.path(), bundle.path()).success();
+    let report = json_stdout(&assert);
+    let results = report["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1, "{report}");
+    assert_eq!(results[0]["name"], "mdbook");
+    assert_eq!(results[0]["success"], true);
+    assert_eq!(results[0]["install_method"], "prebuilt");
+
+    let bin = std::fs::read(home.path().join("bin/mdbook")).unwrap();
+    assert!(String::from_utf8_lossy(&bin).contains("mdbook fake"));
+    let toml = std::fs::read_to_string(home.path().join(".crates.toml")).unwrap();
+    assert!(toml.contains("\"mdbook 0.4.52 (registry+"), "{toml}");
+    assert!(
+        !toml.contains("ghost"),
+        "uninstalled packages must not be added: {toml}"
+    );
+    let crates2 = std::fs::read_to_string(home.path().join(".crates2.json")).unwrap();
+    assert!(crates2.contains("mdbook 0.4.52 "), "{crates2}");
+}
+
+#[test]
+fn tampered_archive_is_rejected_and_leaves_install_untouched() {
+    let home = cargo_home_with_old_mdbook();
+    let bundle = tempfile::tempdir().unwrap();
+    write_bundle(bundle.path());
+    let manifest: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(bundle.path().join("manifest.json")).unwrap(),
+    )
+    .unwrap();
+    let archive = manifest["packages"][0]["archive"].as_str().unwrap();
+    std::fs::write(bundle.path().join(archive), b"not the archive").unwrap();
+
+    let assert = from_bundle(home.path(), bundle.path()).code(2);
+    let report = json_stdout(&assert);
+    assert_eq!(report["results"][0]["success"], false);
+    assert_eq!(
+        std::fs::read(home.path().join("bin/mdbook")).unwrap(),
+        b"old mdbook"
+    );
+    let toml = std::fs::read_to_string(home.path().join(".crates.toml")).unwrap();
+    assert!(toml.contains("\"mdbook 0.4.0 (registry+"), "{toml}");
+}
+
+#[test]
+fn tarball_bundle_installs_too() {
+    let home = cargo_home_with_old_mdbook();
+    let staging = tempfile::tempdir().unwrap();
+    write_bundle(staging.path());
+    let out = tempfile::tempdir().unwrap();
+    let tarball = out.path().join("tools.tar.gz");
+    cargo_fresh::downloader::bundle::pack_tarball(staging.path(), &tarball).unwrap();
+
+    from_bundle(home.path(), &tarball).success();
+    let toml = std::fs::read_to_string(home.path().join(".crates.toml")).unwrap();
+    assert!(toml.contains("\"mdbook 0.4.52 (registry+"), "{toml}");
+}
+
+#[test]
+fn custom_features_install_is_skipped_not_replaced() {
+    let home = cargo_home_with_old_mdbook_features(&["search"]);
+    let bundle = tempfile::tempdir().unwrap();
+    write_bundle(bundle.path());
+
+    // 没装任何东西, 更新仍待处理
+    let assert = from_bundle(home.path(), bundle.path()).code(1);
+    let report = json_stdout(&assert);
+    assert!(report["results"].as_array().unwrap().is_empty(), "{report}");
+    let skipped = report["skipped"].as_array().unwrap();
+    assert_eq!(skipped.len(), 1, "{report}");
+    assert_eq!(skipped[0]["name"], "mdbook");
+    assert_eq!(skipped[0]["reason_code"], "source_build_required");
+    assert_eq!(
+        std::fs::read(home.path().join("bin/mdbook")).unwrap(),
+        b"old mdbook"
+    );
+    let toml = std::fs::read_to_string(home.path().join(".crates.toml")).unwrap();
+    assert!(toml.contains("\"mdbook 0.4.0 (registry+"), "{toml}");
+}
+
+#[test]
+fn source_only_and_excluded_packages_are_skipped() {
+    for (config, code, reason_code) in [
+        (
+            "[packages.mdbook]\ninstall = \"source-only\"\n",
+            1,
+            "source_only",
+        ),
+        ("[packages.\"md*\"]\nexclude = true\n", 0, "excluded"),
+    ] {
+        let home = cargo_home_with_old_mdbook();
+        let bundle = tempfile::tempdir().unwrap();
+        write_bundle(bundle.path());
+
+        let assert = from_bundle_with_config(home.path(), bundle.path(), config).code(code);
+        let report = json_stdout(&assert);
+        assert!(report["results"].as_array().unwrap().is_empty(), "{report}");
+        assert_eq!(report["skipped"][0]["reason_code"], reason_code, "{report}");
+        assert_eq!(
+            std::fs::read(home.path().join("bin/mdbook")).unwrap(),
+            b"old mdbook"
+        );
+    }
+}
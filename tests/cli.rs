@@ -40,6 +40,11 @@ fn help_lists_core_flags() {
         "--check-prebuilt",
         "--debug",
         "--jobs",
+        "--limit-rate",
+        "--from-bundle",
+        "--prefer-prebuilt",
+        "--prebuilt-only",
+        "--source-only",
     ] {
         assert!(out.contains(flag), "help missing {flag}\n--- help ---\n{out}");
     }
@@ -211,3 +216,52 @@ fn jobs_flag_rejects_negative() {
         "expected clap to reject -j -1, got: {stderr}"
     );
 }
+
+#[test]
+fn completion_and_man_ignore_a_broken_config() {
+    let xdg = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(xdg.path().join("cargo-fresh")).unwrap();
+    std::fs::write(xdg.path().join("cargo-fresh/config.toml"), "[defaults
+").unwrap();
+    for args in [&["completion", "bash"][..], &["man"]] {
+        bin()
+            .env("XDG_CONFIG_HOME", xdg.path())
+            .args(args)
+            .assert()
+            .success();
+    }
+    // 其余子命令照旧报错
+    bin()
+        .env("XDG_CONFIG_HOME", xdg.path())
+        .args(["config", "show"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn config_show_reports_each_layer() {
+    let cargo_home = tempfile::tempdir().unwrap();
+    let xdg = tempfile::tempdir().unwrap();
+    std::fs::write(
+        cargo_home.path().join("config.toml"),
+        "[fresh.defaults]\nexclude = [\"cargo-fresh\"]\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(xdg.path().join("cargo-fresh")).unwrap();
+    std::fs::write(
+        xdg.path().join("cargo-fresh/config.toml"),
+        "[defaults]\njobs = 8\n",
+    )
+    .unwrap();
+    let assert = bin()
+        .env("CARGO_HOME", cargo_home.path())
+        .env("XDG_CONFIG_HOME", xdg.path())
+        .env("CARGO_FRESH_JOBS", "2")
+        .args(["config", "show"])
+        .assert()
+        .success();
+    let out = String::from_utf8_lossy(&assert.get_output().stdout).into_owned();
+    assert!(out.contains("jobs = 2  # env CARGO_FRESH_JOBS"), "{out}");
+    assert!(out.contains("exclude = [\"cargo-fresh\"]  # "), "{out}");
+    assert!(out.contains("config.toml [fresh]"), "{out}");
+}
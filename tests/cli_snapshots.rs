@@ -15,7 +15,7 @@
 //! 这个数会退化成 `cargo insta accept` 一把过的橡皮图章。
 
 use cargo_fresh::display::{
-    format_status_line, package_transition, StatusStyle,
+    format_status_line, held_line, package_transition, StatusStyle,
 };
 use cargo_fresh::locale::Language;
 use cargo_fresh::models::{PackageInfo, PackageSource, PrebuiltAvailability};
@@ -69,6 +69,30 @@ fn snapshot_updating_line_with_prebuilt() {
     });
 }
 
+/// `--prefer-prebuilt` 退了版本: 被跳过的最新版跟在新版本后面, 预编译尾标照旧在行尾。
+#[test]
+fn snapshot_updating_line_source_only_latest() {
+    settings().bind(|| {
+        let mut p = pkg("ripgrep", Some("14.1.0"), Some("15.1.0"), PackageSource::Crates);
+        p.prebuilt = Some(PrebuiltAvailability::Prebuilt);
+        p.source_only_latest = Some("15.2.0".into());
+        let msg = package_transition(&p, Language::English);
+        insta::assert_snapshot!(format_status_line("Updating", &msg, StatusStyle::Ok));
+    });
+}
+
+/// `cargo fresh hold` 拦下的更新: 不是 Updating, 而是 Held + 要求与可用版本。
+#[test]
+fn snapshot_held_line() {
+    settings().bind(|| {
+        let mut p = pkg("bevy_cli", Some("0.21.3"), Some("0.22.0"), PackageSource::Crates);
+        p.hold = Some("0.21".into());
+        p.held = true;
+        let msg = held_line(&p, Language::English);
+        insta::assert_snapshot!(format_status_line("Held", &msg, StatusStyle::Dim));
+    });
+}
+
 /// git source 的 Skip 行——`[git]` 是稳定 marker,`PackageSource::marker()`
 /// 出来的。pip 脚本可能 grep `Skip \[git\]` 跳过这类包做汇总。
 #[test]
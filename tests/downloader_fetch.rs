@@ -187,3 +187,356 @@ async fn downloading_events_emitted_in_order() {
     assert!(seen_downloading > 0);
     assert!(seen_verifying);
 }
+
+#[tokio::test]
+async fn configured_template_through_mirror_rewrite_verifies_checksum() {
+    use cargo_fresh::config::parse_config;
+    use cargo_fresh::downloader::resolve::custom_candidate_urls;
+    use sha2::Digest;
+
+    // 模板指向 "github.com", [[rewrite]] 把它改写到 wiremock 扮演的制品镜像
+    let server = MockServer::start().await;
+    let config = parse_config(&format!(
+        r#"
+[packages."corp-*"]
+prebuilt-urls = ["https://github.com/corp/{{name}}/releases/download/v{{version}}/{{name}}-{{target}}.{{ext}}"]
+
+[[rewrite]]
+from = "https://github.com/"
+to = "{}/mirror/"
+"#,
+        server.uri()
+    ))
+    .unwrap();
+    let body = b"corp tool archive";
+    let hex: String = sha2::Sha256::digest(body)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    let p = "/mirror/corp/corp-tool/releases/download/v1.0.0/corp-tool-x86_64-unknown-linux-gnu.zip";
+    Mock::given(method("HEAD"))
+        .and(path(p))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(p))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(body.as_ref()))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{p}.sha256")))
+        .respond_with(ResponseTemplate::new(200).set_body_string(hex))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let (templates, _) = config.prebuilt_sources("corp-tool").unwrap();
+    let candidates: Vec<CandidateUrl> = custom_candidate_urls(
+        templates,
+        "corp-tool",
+        "1.0.0",
+        &["x86_64-unknown-linux-gnu".to_string()],
+    )
+    .into_iter()
+    .map(|c| CandidateUrl {
+        url: config.rewrite_url(&c.url),
+        ..c
+    })
+    .collect();
+    assert!(candidates.iter().all(|c| c.url.starts_with(&server.uri())));
+
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let r = fetch(&client(), "corp-tool", &candidates, &tx, cancel)
+        .await
+        .expect("ok");
+    assert!(r.winning_url.ends_with(".zip"), "got {}", r.winning_url);
+    assert_eq!(std::fs::read(&r.archive_path).unwrap(), body);
+}
+
+#[tokio::test]
+async fn second_fetch_is_served_from_cache_without_network() {
+    use cargo_fresh::downloader::cache::ArchiveCache;
+    use cargo_fresh::downloader::fetch::fetch_with_cache;
+
+    let server = MockServer::start().await;
+    // 两次 fetch 各端点只允许被打一次: 第二次必须完全走缓存
+    Mock::given(method("HEAD"))
+        .and(path("/tool.tar.gz"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tool.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"cached-body".as_ref()))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tool.tar.gz.sha256"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ArchiveCache::new(dir.path(), None);
+    let candidates = vec![CandidateUrl {
+        url: format!("{}/tool.tar.gz", server.uri()),
+        archive_fmt: ArchiveFmt::TarGz,
+    }];
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+
+    for _ in 0..2 {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let r = fetch_with_cache(
+            &client(),
+            "tool",
+            "1.0.0",
+            &candidates,
+            &tx,
+            cancel,
+            Some(&cache),
+        )
+        .await
+        .expect("ok");
+        assert!(r.winning_url.ends_with("/tool.tar.gz"));
+        assert!(r.archive_path.ends_with("tool.tar.gz"));
+        assert_eq!(std::fs::read(&r.archive_path).unwrap(), b"cached-body");
+    }
+    let entries = cache.entries().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].size, b"cached-body".len() as u64);
+}
+
+mod resume {
+    //! 断点续传。wiremock 自己发不出"声明了 Content-Length 却只给一半"的响应
+    //! (hyper 会直接拒绝), 所以在它前面架一个 TCP 转发: 前 `cut` 个连接的
+    //! 响应在 body 第 `at` 字节处掐断, 之后的连接原样转发。
+    use super::*;
+    use cargo_fresh::downloader::fetch::{download_resumable, RetryPolicy};
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use wiremock::matchers::header;
+
+    const BODY: &[u8] = b"0123456789abcdefghij";
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(20),
+        }
+    }
+
+    /// 在 `upstream` 前起一个转发端口, 返回其 base URL。
+    async fn cutting_proxy(upstream: &MockServer, cut: usize, at: usize) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let upstream = *upstream.address();
+        let seen = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = listener.accept().await {
+                let n = seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut server = tokio::net::TcpStream::connect(upstream).await.unwrap();
+                    if n >= cut {
+                        let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                        return;
+                    }
+                    // 请求原样转发 (测试里的 GET 没有 body, 读到头部结束即可)
+                    let mut req = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    while !req.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let k = client.read(&mut buf).await.unwrap();
+                        if k == 0 {
+                            return;
+                        }
+                        req.extend_from_slice(&buf[..k]);
+                    }
+                    server.write_all(&req).await.unwrap();
+                    // 响应: 头部完整转发, body 只给前 `at` 字节, 然后断开
+                    let mut resp = Vec::new();
+                    loop {
+                        let k = server.read(&mut buf).await.unwrap();
+                        if k == 0 {
+                            break;
+                        }
+                        resp.extend_from_slice(&buf[..k]);
+                        if let Some(pos) = resp.windows(4).position(|w| w == b"\r\n\r\n") {
+                            if resp.len() >= pos + 4 + at {
+                                resp.truncate(pos + 4 + at);
+                                break;
+                            }
+                        }
+                    }
+                    let _ = client.write_all(&resp).await;
+                    let _ = client.shutdown().await;
+                });
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    async fn run(base: &str) -> (Result<Vec<u8>, DownloaderError>, Vec<(u64, Option<u64>)>) {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("tool.tar.gz");
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let r = download_resumable(
+            &client(),
+            &format!("{base}/tool.tar.gz"),
+            &dest,
+            "tool",
+            &tx,
+            &cancel,
+            fast_retry(),
+        )
+        .await
+        .map(|()| std::fs::read(&dest).unwrap());
+        drop(tx);
+        let mut progress = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            if let ProgressEvent::Downloading { got, total, .. } = ev {
+                progress.push((got, total));
+            }
+        }
+        (r, progress)
+    }
+
+    #[tokio::test]
+    async fn truncated_body_resumes_with_range_from_offset() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/tool.tar.gz"))
+            .and(header("Range", "bytes=8-"))
+            .and(header("If-Range", "\"v1\""))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("Content-Range", "bytes 8-19/20")
+                    .set_body_bytes(&BODY[8..]),
+            )
+            .expect(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tool.tar.gz"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Accept-Ranges", "bytes")
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_bytes(BODY),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = cutting_proxy(&server, 1, 8).await;
+        let (r, progress) = run(&base).await;
+        assert_eq!(r.expect("resumed download"), BODY);
+        // 进度从续传偏移继续, 不回到 0
+        let gots: Vec<u64> = progress.iter().map(|(g, _)| *g).collect();
+        assert!(gots.windows(2).all(|w| w[0] <= w[1]), "progress went backwards: {gots:?}");
+        assert!(gots.contains(&8), "expected progress at the cut offset: {gots:?}");
+        assert_eq!(progress.last(), Some(&(20, Some(20))));
+    }
+
+    #[tokio::test]
+    async fn truncated_body_without_range_support_restarts_from_zero() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/tool.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let base = cutting_proxy(&server, 1, 8).await;
+        let (r, _) = run(&base).await;
+        assert_eq!(r.expect("restarted download"), BODY);
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.iter().all(|r| !r.headers.contains_key("range")));
+    }
+
+    #[tokio::test]
+    async fn changed_file_on_resume_is_rewritten_from_scratch() {
+        // If-Range 不匹配时服务端回 200 整个新文件: 不能拼在旧的前半截后面
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/tool.tar.gz"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Accept-Ranges", "bytes")
+                    .insert_header("ETag", "\"v2\"")
+                    .set_body_bytes(b"brand-new-archive".as_ref()),
+            )
+            .mount(&server)
+            .await;
+
+        let base = cutting_proxy(&server, 1, 8).await;
+        let (r, _) = run(&base).await;
+        assert_eq!(r.expect("download"), b"brand-new-archive");
+    }
+
+    #[tokio::test]
+    async fn repeated_truncation_gives_up_as_interrupted() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/tool.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let base = cutting_proxy(&server, usize::MAX, 8).await;
+        let (r, _) = run(&base).await;
+        assert!(matches!(
+            r.unwrap_err(),
+            DownloaderError::Failed {
+                kind: FailureKind::DownloadInterrupted,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_then_give_up() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/tool.tar.gz"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let (r, _) = run(&server.uri()).await;
+        assert!(matches!(
+            r.unwrap_err(),
+            DownloaderError::Failed {
+                kind: FailureKind::DownloadInterrupted,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/tool.tar.gz"))
+            .respond_with(ResponseTemplate::new(403))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let (r, _) = run(&server.uri()).await;
+        assert!(r.is_err());
+    }
+}
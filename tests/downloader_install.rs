@@ -1,7 +1,9 @@
 //! install.rs 的 tempdir 集成测试——用 isolated CARGO_HOME 验证 atomic
-//! rename + .crates2.json 更新, 不污染真实 ~/.cargo。
+//! rename + .crates2.json 更新 (升级改版本 / 全新安装新建条目), 不污染真实 ~/.cargo。
 
 use cargo_fresh::downloader::install::install_binary;
+use cargo_fresh::models::{InstallOpts, NewInstall};
+use cargo_fresh::package::registry::CRATES_IO_SOURCE_ID;
 use std::io::Write;
 
 #[test]
@@ -38,7 +40,14 @@ fn install_binary_atomically_into_isolated_cargo_home() {
     let prev = std::env::var("CARGO_HOME").ok();
     std::env::set_var("CARGO_HOME", cargo_home.path());
 
-    let dest = install_binary(&src, "rg", "14.1.2").expect("install ok");
+    let dest = install_binary(&src, "ripgrep", "rg", "14.1.2", None).expect("install ok");
+
+    // 全新安装 (`cargo fresh install tokei`): 两个文件都新增条目
+    let fresh = NewInstall {
+        source_id: CRATES_IO_SOURCE_ID.into(),
+        opts: InstallOpts::default(),
+    };
+    install_binary(&src, "tokei", "tokei", "12.1.2", Some(&fresh)).expect("fresh install ok");
 
     // Restore env
     match prev {
@@ -63,12 +72,24 @@ fn install_binary_atomically_into_isolated_cargo_home() {
         "old key should be gone: {keys:?}"
     );
 
+    let tokei =
+        &v["installs"]["tokei 12.1.2 (registry+https://github.com/rust-lang/crates.io-index)"];
+    assert_eq!(tokei["bins"], serde_json::json!(["tokei"]), "got: {body}");
+    assert_eq!(tokei["features"], serde_json::json!([]));
+
     // Verify .crates.toml updated (cargo install --list 数据源)
     let toml_body = std::fs::read_to_string(cargo_home.path().join(".crates.toml")).unwrap();
     assert!(
         toml_body.contains("\"ripgrep 14.1.2 (registry+"),
         "expected .crates.toml to have new version, got: {toml_body}"
     );
+    assert!(
+        toml_body.contains(
+            "\"tokei 12.1.2 (registry+https://github.com/rust-lang/crates.io-index)\" = [\"tokei\"]"
+        ),
+        "expected a new .crates.toml entry, got: {toml_body}"
+    );
+    assert!(cargo_home.path().join("bin").join("tokei").exists());
     assert!(
         !toml_body.contains("\"ripgrep 14.1.1 ("),
         "old .crates.toml entry should be gone: {toml_body}"
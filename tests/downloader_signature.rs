@@ -0,0 +1,314 @@
+//! signature.rs 的 HTTP 集成测试——本地现生成 minisign / cosign 密钥对,
+//! 用 wiremock 把签名文件挂在产物 URL 旁边, 验证三态结局的契约。
+
+use base64::Engine;
+use cargo_fresh::config::PinnedKeys;
+use cargo_fresh::downloader::signature::{check, SignatureOutcome};
+use p256::ecdsa::signature::Signer;
+use p256::pkcs8::EncodePublicKey;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ARCHIVE: &[u8] = b"pretend this is a tar.gz";
+
+fn client() -> reqwest::Client {
+    reqwest::Client::builder().build().unwrap()
+}
+
+fn write_archive(dir: &tempfile::TempDir, body: &[u8]) -> std::path::PathBuf {
+    let p = dir.path().join("x.tar.gz");
+    std::fs::write(&p, body).unwrap();
+    p
+}
+
+/// 返回 (minisign 公钥 base64, 对 ARCHIVE 的 .minisig 全文)。
+fn minisign_pair() -> (String, String) {
+    let kp = minisign::KeyPair::generate_unencrypted_keypair().unwrap();
+    let sig = minisign::sign(
+        Some(&kp.pk),
+        &kp.sk,
+        std::io::Cursor::new(ARCHIVE),
+        None,
+        None,
+    )
+    .unwrap()
+    .into_string();
+    (kp.pk.to_base64(), sig)
+}
+
+/// 返回 (cosign 公钥 PEM, 对 ARCHIVE 的 base64 DER 签名)。
+fn cosign_pair() -> (String, String) {
+    let sk = p256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let pem = sk
+        .verifying_key()
+        .to_public_key_pem(p256::pkcs8::LineEnding::LF)
+        .unwrap();
+    let sig: p256::ecdsa::Signature = sk.sign(ARCHIVE);
+    let b64 = base64::engine::general_purpose::STANDARD.encode(sig.to_der().as_bytes());
+    (pem, b64)
+}
+
+async fn mount_body(server: &MockServer, p: &str, body: String) {
+    Mock::given(method("GET"))
+        .and(path(p))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn minisign_signature_verifies() {
+    let server = MockServer::start().await;
+    let (pk, sig) = minisign_pair();
+    mount_body(&server, "/x.tar.gz.minisig", sig).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, ARCHIVE);
+    let keys = PinnedKeys {
+        minisign: Some(pk),
+        cosign: None,
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome, SignatureOutcome::Verified { suffix: ".minisig" });
+}
+
+#[tokio::test]
+async fn tampered_archive_is_invalid() {
+    let server = MockServer::start().await;
+    let (pk, sig) = minisign_pair();
+    mount_body(&server, "/x.tar.gz.minisig", sig).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, b"something else entirely");
+    let keys = PinnedKeys {
+        minisign: Some(pk),
+        cosign: None,
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    assert!(
+        matches!(
+            outcome,
+            SignatureOutcome::Invalid {
+                suffix: ".minisig",
+                ..
+            }
+        ),
+        "got: {outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn signature_from_other_key_is_invalid() {
+    let server = MockServer::start().await;
+    let (_, sig) = minisign_pair();
+    let (other_pk, _) = minisign_pair();
+    mount_body(&server, "/x.tar.gz.minisig", sig).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, ARCHIVE);
+    let keys = PinnedKeys {
+        minisign: Some(other_pk),
+        cosign: None,
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    assert!(
+        matches!(outcome, SignatureOutcome::Invalid { .. }),
+        "got: {outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn no_signature_file_is_missing() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, ARCHIVE);
+    let (pk, _) = minisign_pair();
+    let (pem, _) = cosign_pair();
+    let keys = PinnedKeys {
+        minisign: Some(pk),
+        cosign: Some(pem),
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome, SignatureOutcome::Missing);
+}
+
+#[tokio::test]
+async fn minisig_without_pinned_minisign_key_is_ignored() {
+    // 只钉了 cosign 公钥: .minisig 即使存在也不请求, 结局只看 cosign 签名
+    let server = MockServer::start().await;
+    let (_, minisig) = minisign_pair();
+    mount_body(&server, "/x.tar.gz.minisig", minisig).await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, ARCHIVE);
+    let (pem, _) = cosign_pair();
+    let keys = PinnedKeys {
+        minisign: None,
+        cosign: Some(pem),
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome, SignatureOutcome::Missing);
+}
+
+#[tokio::test]
+async fn cosign_blob_signature_verifies() {
+    let server = MockServer::start().await;
+    let (pem, sig) = cosign_pair();
+    mount_body(&server, "/x.tar.gz.sig", sig).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, ARCHIVE);
+    let keys = PinnedKeys {
+        minisign: None,
+        cosign: Some(pem),
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome, SignatureOutcome::Verified { suffix: ".sig" });
+}
+
+#[tokio::test]
+async fn sigstore_bundle_verifies() {
+    use sha2::Digest;
+    let server = MockServer::start().await;
+    let (pem, sig) = cosign_pair();
+    let digest = base64::engine::general_purpose::STANDARD.encode(sha2::Sha256::digest(ARCHIVE));
+    let bundle = serde_json::json!({
+        "mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json",
+        "verificationMaterial": { "publicKey": { "hint": "local" } },
+        "messageSignature": {
+            "messageDigest": { "algorithm": "SHA2_256", "digest": digest },
+            "signature": sig,
+        }
+    });
+    Mock::given(method("GET"))
+        .and(path("/x.tar.gz.sig"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    mount_body(&server, "/x.tar.gz.sigstore.json", bundle.to_string()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, ARCHIVE);
+    let keys = PinnedKeys {
+        minisign: None,
+        cosign: Some(pem),
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        outcome,
+        SignatureOutcome::Verified {
+            suffix: ".sigstore.json"
+        }
+    );
+}
+
+#[tokio::test]
+async fn oversized_signature_is_invalid() {
+    use cargo_fresh::downloader::signature::MAX_SIGNATURE_BYTES;
+    let server = MockServer::start().await;
+    mount_body(
+        &server,
+        "/x.tar.gz.minisig",
+        "x".repeat(MAX_SIGNATURE_BYTES + 1),
+    )
+    .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = write_archive(&dir, ARCHIVE);
+    let (pk, _) = minisign_pair();
+    let keys = PinnedKeys {
+        minisign: Some(pk),
+        cosign: None,
+    };
+    let outcome = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &archive,
+        &keys,
+    )
+    .await
+    .unwrap();
+    let SignatureOutcome::Invalid { suffix, detail } = &outcome else {
+        panic!("got {outcome:?}");
+    };
+    assert_eq!(*suffix, ".minisig");
+    assert!(detail.contains("larger than"), "{detail}");
+}
+
+#[tokio::test]
+async fn unreadable_archive_is_an_error_not_a_bad_signature() {
+    let server = MockServer::start().await;
+    let dir = tempfile::tempdir().unwrap();
+    let (pk, _) = minisign_pair();
+    let keys = PinnedKeys {
+        minisign: Some(pk),
+        cosign: None,
+    };
+    let err = check(
+        &client(),
+        &format!("{}/x.tar.gz", server.uri()),
+        &dir.path().join("gone.tar.gz"),
+        &keys,
+    )
+    .await
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("gone.tar.gz"), "got: {err:#}");
+}
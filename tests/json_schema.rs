@@ -5,12 +5,12 @@
 //!
 //! 用 `cargo_fresh::models` 暴露的类型直接构造代表性 fixture，覆盖每一种
 //! `$defs` 形状（updates_available / fresh / skipped / version_check_errors
-//! / results），而不是去跑一次 `cargo fresh` 拿真实输出——同一进程内能稳
+//! / holds / results），而不是去跑一次 `cargo fresh` 拿真实输出——同一进程内能稳
 //! 定生成所有边界场景，CI runner 上不需要任何预装包。
 
 use cargo_fresh::models::{
-    InstallMethod, JsonCheckError, JsonReport, JsonResult, JsonSkipped, JsonSummary,
-    JsonUpdateCandidate, PrebuiltAvailability,
+    InstallMethod, JsonCheckError, JsonHold, JsonReport, JsonResult, JsonSkipped, JsonSummary,
+    JsonUpdateCandidate, PrebuiltAvailability, ShadowedBin, SkipReason,
 };
 use jsonschema::Validator;
 
@@ -49,6 +49,7 @@ fn empty_run_matches_schema() {
         fresh: vec![],
         skipped: vec![],
         version_check_errors: vec![],
+        holds: vec![],
         results: vec![],
         summary: JsonSummary {
             checked: 0,
@@ -82,6 +83,7 @@ fn invalid_payload_is_rejected() {
         fresh: vec![],
         skipped: vec![],
         version_check_errors: vec![],
+        holds: vec![],
         results: vec![],
         summary: JsonSummary {
             checked: 0,
@@ -107,16 +109,21 @@ fn invalid_payload_is_rejected() {
 }
 
 /// 覆盖每一种 `$defs` 形状的"满"快照：
-/// - `updates_available` 含一个 prerelease=false + prebuilt=prebuilt 与
-///   一个 prerelease=true + prebuilt=null
+/// - `updates_available` 含一个 prerelease=false + prebuilt=prebuilt（binary
+///   被 PATH 遮住）与一个 prerelease=true + prebuilt=null
 /// - `fresh` 含一个名字
-/// - `skipped` 覆盖 git/path/unknown 三种 reason_code
+/// - `skipped` 覆盖全部七种 reason_code
 /// - `version_check_errors` 含一个 not_found 一个 unavailable
+/// - `holds` 含一个被拦下的与一个版本查询失败的
 /// - `results` 含一个 success 一个 failure
 /// - `registry_url` 是 Some
 /// - `dry_run` / `include_prerelease` / `aborted` 都翻成 true
 #[test]
 fn full_run_matches_schema() {
+    let shadowed = [ShadowedBin {
+        bin: "rg".into(),
+        path: "/usr/bin/rg".into(),
+    }];
     let report = JsonReport {
         schema_version: 2,
         format: "cargo-fresh-v1",
@@ -132,6 +139,8 @@ fn full_run_matches_schema() {
                 source: "crates",
                 prerelease: false,
                 prebuilt: Some(PrebuiltAvailability::Prebuilt.kind_str()),
+                source_only_latest: Some("14.2.0"),
+                shadowed: &shadowed,
             },
             JsonUpdateCandidate {
                 name: "cargo-fresh",
@@ -140,6 +149,8 @@ fn full_run_matches_schema() {
                 source: "crates",
                 prerelease: true,
                 prebuilt: None,
+                source_only_latest: None,
+                shadowed: &[],
             },
         ],
         fresh: vec!["bat"],
@@ -162,6 +173,30 @@ fn full_run_matches_schema() {
                 reason_code: "unknown_source",
                 reason: "non-crates source: version check skipped",
             },
+            JsonSkipped {
+                name: "big-tool",
+                source: "crates",
+                reason_code: "prebuilt_unavailable",
+                reason: "prebuilt-only policy: no prebuilt binary, not compiling from source",
+            },
+            JsonSkipped {
+                name: "featured-tool",
+                source: "crates",
+                reason_code: SkipReason::SourceBuildRequired.code(),
+                reason: SkipReason::SourceBuildRequired.reason(),
+            },
+            JsonSkipped {
+                name: "built-tool",
+                source: "crates",
+                reason_code: SkipReason::SourceOnly.code(),
+                reason: SkipReason::SourceOnly.reason(),
+            },
+            JsonSkipped {
+                name: "ignored-tool",
+                source: "crates",
+                reason_code: SkipReason::Excluded.code(),
+                reason: SkipReason::Excluded.reason(),
+            },
         ],
         version_check_errors: vec![
             JsonCheckError {
@@ -175,6 +210,22 @@ fn full_run_matches_schema() {
                 error: "sparse index request failed after 1 retry",
             },
         ],
+        holds: vec![
+            JsonHold {
+                name: "bevy_cli",
+                at: "0.21",
+                current: Some("0.21.0"),
+                latest: Some("0.22.1"),
+                held: true,
+            },
+            JsonHold {
+                name: "ghost-crate",
+                at: "1.2.3",
+                current: Some("1.2.3"),
+                latest: None,
+                held: false,
+            },
+        ],
         results: vec![
             JsonResult {
                 name: "ripgrep",
@@ -199,7 +250,7 @@ fn full_run_matches_schema() {
             attempted: 2,
             succeeded: 1,
             failed: 1,
-            skipped: 3,
+            skipped: 7,
             check_errors: 2,
             duration_ms: 1234,
         },
@@ -228,10 +279,13 @@ fn prebuilt_variants_match_schema() {
                 source: "crates",
                 prerelease: false,
                 prebuilt: Some(kind.kind_str()),
+                source_only_latest: None,
+                shadowed: &[],
             }],
             fresh: vec![],
             skipped: vec![],
             version_check_errors: vec![],
+            holds: vec![],
             results: vec![],
             summary: JsonSummary {
                 checked: 1,
@@ -0,0 +1,193 @@
+//! GitLab / Gitea 两个 release API 客户端的 HTTP 集成测试。wiremock 扮演
+//! 平台 API, 验证路径编码、鉴权头和状态码映射与 GitHub 客户端一致。
+
+use cargo_fresh::downloader::release_host::ReleaseApiError;
+use cargo_fresh::downloader::{gitea_api, gitlab_api};
+
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn gitlab_release_json() -> serde_json::Value {
+    serde_json::json!({
+        "tag_name": "v1.0.0",
+        "assets": {
+            "count": 3,
+            "sources": [
+                { "format": "zip", "url": "https://gitlab.com/group/tool/-/archive/v1.0.0/tool-v1.0.0.zip" }
+            ],
+            "links": [
+                {
+                    "name": "tool-1.0.0-x86_64-unknown-linux-gnu.tar.gz",
+                    "url": "https://gitlab.com/group/tool/-/jobs/1/artifacts/raw/tool.tar.gz",
+                    "direct_asset_url": "https://gitlab.com/group/tool/-/releases/v1.0.0/downloads/tool-1.0.0-x86_64-unknown-linux-gnu.tar.gz"
+                },
+                {
+                    "name": "tool-1.0.0-aarch64-apple-darwin.tar.gz",
+                    "url": "https://example.com/tool-1.0.0-aarch64-apple-darwin.tar.gz"
+                }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn gitlab_encodes_project_and_prefers_direct_asset_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/projects/group%2Ftool/releases/v1.0.0"))
+        .and(header("PRIVATE-TOKEN", "glpat-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(gitlab_release_json()))
+        .mount(&server)
+        .await;
+
+    let client = reqwest::Client::new();
+    let assets = gitlab_api::fetch_release_assets(
+        &client,
+        &server.uri(),
+        "group/tool",
+        "v1.0.0",
+        Some("glpat-test"),
+    )
+    .await
+    .expect("ok");
+    // sources[] 是自动生成的源码包, 不算资产
+    assert_eq!(assets.len(), 2);
+    assert_eq!(
+        assets[0].browser_download_url,
+        "https://gitlab.com/group/tool/-/releases/v1.0.0/downloads/tool-1.0.0-x86_64-unknown-linux-gnu.tar.gz"
+    );
+    // 没有 direct_asset_url 时退回 url
+    assert_eq!(
+        assets[1].browser_download_url,
+        "https://example.com/tool-1.0.0-aarch64-apple-darwin.tar.gz"
+    );
+}
+
+#[tokio::test]
+async fn gitlab_maps_404_and_401() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/projects/group%2Fmissing/releases/v1.0.0"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/projects/group%2Fprivate/releases/v1.0.0"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let client = reqwest::Client::new();
+    let missing =
+        gitlab_api::fetch_release_assets(&client, &server.uri(), "group/missing", "v1.0.0", None)
+            .await;
+    assert!(matches!(missing, Err(ReleaseApiError::NotFound)));
+    let private =
+        gitlab_api::fetch_release_assets(&client, &server.uri(), "group/private", "v1.0.0", None)
+            .await;
+    assert!(matches!(private, Err(ReleaseApiError::RateLimited)));
+}
+
+#[tokio::test]
+async fn gitea_fetches_assets_with_token_header() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/tool/releases/tags/v1.0.0"))
+        .and(header("Authorization", "token cb-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "tag_name": "v1.0.0",
+            "assets": [{
+                "name": "tool-v1.0.0-x86_64-unknown-linux-gnu.tar.gz",
+                "browser_download_url": "https://codeberg.org/owner/tool/releases/download/v1.0.0/tool-v1.0.0-x86_64-unknown-linux-gnu.tar.gz"
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = reqwest::Client::new();
+    let assets = gitea_api::fetch_release_assets(
+        &client,
+        &server.uri(),
+        "owner",
+        "tool",
+        "v1.0.0",
+        Some("cb-test"),
+    )
+    .await
+    .expect("ok");
+    assert_eq!(assets.len(), 1);
+    assert_eq!(
+        assets[0].name,
+        "tool-v1.0.0-x86_64-unknown-linux-gnu.tar.gz"
+    );
+}
+
+#[tokio::test]
+async fn gitea_maps_404_and_403() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/missing/releases/tags/v1.0.0"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/limited/releases/tags/v1.0.0"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+
+    let client = reqwest::Client::new();
+    let missing =
+        gitea_api::fetch_release_assets(&client, &server.uri(), "owner", "missing", "v1.0.0", None)
+            .await;
+    assert!(matches!(missing, Err(ReleaseApiError::NotFound)));
+    let limited =
+        gitea_api::fetch_release_assets(&client, &server.uri(), "owner", "limited", "v1.0.0", None)
+            .await;
+    assert!(matches!(limited, Err(ReleaseApiError::RateLimited)));
+}
+
+#[tokio::test]
+async fn github_enterprise_host_uses_configured_api_base() {
+    use cargo_fresh::config::HostConfig;
+    use cargo_fresh::downloader::release_host::{ForgeKind, ReleaseRepo};
+    use std::collections::BTreeMap;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/repos/team/tool/releases/tags/v1.0.0"))
+        .and(header("Authorization", "Bearer ghe-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "tag_name": "v1.0.0",
+            "assets": [{
+                "name": "tool-x86_64-unknown-linux-gnu.tar.gz",
+                "browser_download_url": format!("{}/team/tool/releases/download/v1.0.0/tool-x86_64-unknown-linux-gnu.tar.gz", server.uri())
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    // wiremock 监听 127.0.0.1:<port>; 把它当成一台 GHE 实例配置进 [hosts]
+    let host = server.uri().trim_start_matches("http://").to_string();
+    let mut hosts = BTreeMap::new();
+    hosts.insert(
+        host,
+        HostConfig {
+            api_base: Some(format!("{}/api/v3", server.uri())),
+            ..HostConfig::default()
+        },
+    );
+    let repo = ReleaseRepo::parse_with(&format!("{}/team/tool", server.uri()), &hosts).unwrap();
+    assert_eq!(repo.kind, ForgeKind::GitHub);
+
+    let client = reqwest::Client::new();
+    let assets = repo
+        .list_assets(&client, "v1.0.0", Some("ghe-test"))
+        .await
+        .expect("ok");
+    assert_eq!(assets.len(), 1);
+    assert_eq!(
+        repo.download_url("v1.0.0", &assets[0].name),
+        assets[0].browser_download_url
+    );
+}
@@ -36,3 +36,101 @@ fn empty_env_var_is_ignored() {
     assert_ne!(result.as_deref(), Some(""));
     std::env::remove_var("GITHUB_TOKEN");
 }
+
+#[test]
+#[serial]
+fn gitlab_token_wins_over_gl_token() {
+    use cargo_fresh::downloader::release_host::ForgeKind;
+    use cargo_fresh::downloader::token::discover_for;
+    std::env::set_var("GITLAB_TOKEN", "from-gitlab");
+    std::env::set_var("GL_TOKEN", "from-gl");
+    assert_eq!(
+        discover_for(ForgeKind::GitLab, "gitlab.com", None),
+        (Some("from-gitlab".to_string()), "env:GITLAB_TOKEN")
+    );
+    std::env::remove_var("GITLAB_TOKEN");
+    assert_eq!(
+        discover_for(ForgeKind::GitLab, "gitlab.com", None).0.as_deref(),
+        Some("from-gl")
+    );
+    std::env::remove_var("GL_TOKEN");
+}
+
+#[test]
+#[serial]
+fn self_hosted_gitlab_gets_no_gitlab_com_token() {
+    use cargo_fresh::downloader::release_host::ForgeKind;
+    use cargo_fresh::downloader::token::discover_for;
+    std::env::set_var("GITLAB_TOKEN", "from-gitlab");
+    std::env::set_var("CORP_GITLAB_TOKEN", "from-config");
+    assert_eq!(
+        discover_for(ForgeKind::GitLab, "git.corp.example", None),
+        (None, "none")
+    );
+    // 只有配了 token-env 才发 token
+    assert_eq!(
+        discover_for(ForgeKind::GitLab, "git.corp.example", Some("CORP_GITLAB_TOKEN")),
+        (Some("from-config".to_string()), "config:token-env")
+    );
+    std::env::remove_var("GITLAB_TOKEN");
+    std::env::remove_var("CORP_GITLAB_TOKEN");
+}
+
+#[test]
+#[serial]
+fn codeberg_token_is_preferred_on_codeberg_only() {
+    use cargo_fresh::downloader::release_host::ForgeKind;
+    use cargo_fresh::downloader::token::discover_for;
+    std::env::set_var("CODEBERG_TOKEN", "from-codeberg");
+    std::env::set_var("GITEA_TOKEN", "from-gitea");
+    std::env::remove_var("FORGEJO_TOKEN");
+    assert_eq!(
+        discover_for(ForgeKind::Gitea, "codeberg.org", None).0.as_deref(),
+        Some("from-codeberg")
+    );
+    // 自托管实例既不拿 Codeberg 的 token, 也不拿通用的 GITEA_TOKEN
+    assert_eq!(
+        discover_for(ForgeKind::Gitea, "git.corp.example", None),
+        (None, "none")
+    );
+    std::env::remove_var("CODEBERG_TOKEN");
+    std::env::remove_var("GITEA_TOKEN");
+}
+
+#[test]
+#[serial]
+fn enterprise_hosts_use_enterprise_token_not_github_token() {
+    use cargo_fresh::downloader::release_host::ForgeKind;
+    use cargo_fresh::downloader::token::discover_for;
+    std::env::set_var("GITHUB_TOKEN", "from-github");
+    std::env::set_var("GH_ENTERPRISE_TOKEN", "from-ghe");
+    assert_eq!(
+        discover_for(ForgeKind::GitHub, "ghe.corp.example", None),
+        (Some("from-ghe".to_string()), "env:GH_ENTERPRISE_TOKEN")
+    );
+    std::env::remove_var("GITHUB_TOKEN");
+    std::env::remove_var("GH_ENTERPRISE_TOKEN");
+}
+
+#[test]
+#[serial]
+fn configured_token_env_wins_over_platform_defaults() {
+    use cargo_fresh::downloader::release_host::ForgeKind;
+    use cargo_fresh::downloader::token::discover_for;
+    std::env::set_var("GH_ENTERPRISE_TOKEN", "from-ghe");
+    std::env::set_var("CORP_GHE_TOKEN", "from-config");
+    assert_eq!(
+        discover_for(ForgeKind::GitHub, "ghe.corp.example", Some("CORP_GHE_TOKEN")),
+        (Some("from-config".to_string()), "config:token-env")
+    );
+    // 配置的变量为空时退回平台默认
+    std::env::set_var("CORP_GHE_TOKEN", "");
+    assert_eq!(
+        discover_for(ForgeKind::GitHub, "ghe.corp.example", Some("CORP_GHE_TOKEN"))
+            .0
+            .as_deref(),
+        Some("from-ghe")
+    );
+    std::env::remove_var("GH_ENTERPRISE_TOKEN");
+    std::env::remove_var("CORP_GHE_TOKEN");
+}
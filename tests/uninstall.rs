@@ -0,0 +1,78 @@
+//! package/uninstall.rs 的 tempdir 集成测试——在 isolated CARGO_HOME 里卸载，
+//! 确认 binary 和两个元数据文件里的条目一起删掉，别的包原样保留。
+
+use cargo_fresh::package::uninstall::{preview, recorded_bins, uninstall};
+
+const CRATES_TOML: &str = r#"[v1]
+"cargo-edit 0.12.0 (registry+https://github.com/rust-lang/crates.io-index)" = ["cargo-add", "cargo-rm"]
+"ripgrep 14.1.1 (registry+https://github.com/rust-lang/crates.io-index)" = ["rg"]
+"#;
+
+const CRATES2: &str = r#"{
+    "installs": {
+        "cargo-edit 0.12.0 (registry+https://github.com/rust-lang/crates.io-index)": {
+            "version_req": null,
+            "bins": ["cargo-add", "cargo-rm"],
+            "features": [],
+            "all_features": false,
+            "no_default_features": false,
+            "profile": "release",
+            "target": "x86_64-unknown-linux-gnu",
+            "rustc": "rustc 1.0"
+        },
+        "ripgrep 14.1.1 (registry+https://github.com/rust-lang/crates.io-index)": {
+            "version_req": null,
+            "bins": ["rg"],
+            "features": [],
+            "all_features": false,
+            "no_default_features": false,
+            "profile": "release",
+            "target": "x86_64-unknown-linux-gnu",
+            "rustc": "rustc 1.0"
+        }
+    }
+}"#;
+
+#[test]
+fn uninstall_removes_bins_and_both_records() {
+    let cargo_home = tempfile::tempdir().unwrap();
+    let home = cargo_home.path();
+    let bin = home.join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::write(home.join(".crates.toml"), CRATES_TOML).unwrap();
+    std::fs::write(home.join(".crates2.json"), CRATES2).unwrap();
+    // cargo-rm 已经被手动删掉
+    std::fs::write(bin.join("cargo-add"), "x").unwrap();
+    std::fs::write(bin.join("rg"), "x").unwrap();
+
+    let planned = preview(home, "cargo-edit").unwrap();
+    assert_eq!(planned.removed, [bin.join("cargo-add")]);
+    assert_eq!(planned.missing, ["cargo-rm"]);
+    assert!(
+        bin.join("cargo-add").exists(),
+        "preview must not touch files"
+    );
+
+    let done = uninstall(home, "cargo-edit").unwrap();
+    assert_eq!(done, planned);
+    assert!(!bin.join("cargo-add").exists());
+    assert!(bin.join("rg").exists());
+
+    let toml = std::fs::read_to_string(home.join(".crates.toml")).unwrap();
+    assert!(!toml.contains("cargo-edit"));
+    assert!(toml.contains("ripgrep 14.1.1"));
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(home.join(".crates2.json")).unwrap())
+            .unwrap();
+    let installs = json["installs"].as_object().unwrap();
+    assert_eq!(installs.len(), 1);
+    assert!(installs.keys().all(|k| k.starts_with("ripgrep ")));
+
+    assert_eq!(recorded_bins(home, "cargo-edit").unwrap(), None);
+    assert_eq!(
+        recorded_bins(home, "ripgrep").unwrap(),
+        Some(vec!["rg".to_string()])
+    );
+    let err = uninstall(home, "cargo-edit").unwrap_err();
+    assert!(err.to_string().contains("not installed"));
+}